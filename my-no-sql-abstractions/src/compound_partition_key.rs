@@ -0,0 +1,130 @@
+const SEPARATOR: char = '|';
+const NAME_VALUE_SEPARATOR: char = '=';
+const ESCAPE: char = '\\';
+
+// Builds partition key from several fields as name=value|name=value.
+// Separators and escape symbol inside names and values are escaped with '\'
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CompoundPartitionKey {
+    value: String,
+}
+
+impl CompoundPartitionKey {
+    pub fn new() -> Self {
+        Self {
+            value: String::new(),
+        }
+    }
+
+    pub fn add(mut self, name: &str, value: impl AsRef<str>) -> Self {
+        if self.value.len() > 0 {
+            self.value.push(SEPARATOR);
+        }
+
+        write_escaped(&mut self.value, name);
+        self.value.push(NAME_VALUE_SEPARATOR);
+        write_escaped(&mut self.value, value.as_ref());
+        self
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.value.as_str()
+    }
+
+    pub fn build(self) -> String {
+        self.value
+    }
+
+    pub fn parse(src: &str) -> Option<Vec<(String, String)>> {
+        let mut result = Vec::new();
+
+        if src.len() == 0 {
+            return Some(result);
+        }
+
+        let mut name = None;
+        let mut current = String::new();
+        let mut chars = src.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                ESCAPE => current.push(chars.next()?),
+                NAME_VALUE_SEPARATOR => {
+                    if name.is_some() {
+                        return None;
+                    }
+                    name = Some(std::mem::take(&mut current));
+                }
+                SEPARATOR => {
+                    result.push((name.take()?, std::mem::take(&mut current)));
+                }
+                _ => current.push(c),
+            }
+        }
+
+        result.push((name?, current));
+
+        Some(result)
+    }
+}
+
+fn write_escaped(dest: &mut String, src: &str) {
+    for c in src.chars() {
+        if c == SEPARATOR || c == NAME_VALUE_SEPARATOR || c == ESCAPE {
+            dest.push(ESCAPE);
+        }
+        dest.push(c);
+    }
+}
+
+impl From<CompoundPartitionKey> for String {
+    fn from(src: CompoundPartitionKey) -> Self {
+        src.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CompoundPartitionKey;
+
+    #[test]
+    fn test_build() {
+        let result = CompoundPartitionKey::new()
+            .add("tenant", "acme")
+            .add("region", "eu")
+            .build();
+
+        assert_eq!(result, "tenant=acme|region=eu");
+    }
+
+    #[test]
+    fn test_separator_inside_value_does_not_collide() {
+        let a = CompoundPartitionKey::new()
+            .add("tenant", "a|region=b")
+            .add("region", "c")
+            .build();
+
+        let b = CompoundPartitionKey::new()
+            .add("tenant", "a")
+            .add("region", "b|region=c")
+            .build();
+
+        assert_ne!(a, b);
+
+        assert_eq!(
+            CompoundPartitionKey::parse(a.as_str()).unwrap(),
+            vec![
+                ("tenant".to_string(), "a|region=b".to_string()),
+                ("region".to_string(), "c".to_string())
+            ]
+        );
+
+        assert_eq!(
+            CompoundPartitionKey::parse(b.as_str()).unwrap(),
+            vec![
+                ("tenant".to_string(), "a".to_string()),
+                ("region".to_string(), "b|region=c".to_string())
+            ]
+        );
+    }
+}
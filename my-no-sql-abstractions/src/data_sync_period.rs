@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 #[derive(Debug, Clone, Copy)]
 pub enum DataSynchronizationPeriod {
     Immediately,
@@ -8,3 +10,107 @@ pub enum DataSynchronizationPeriod {
     Min1,
     Asap,
 }
+
+impl DataSynchronizationPeriod {
+    pub fn as_duration(&self) -> Option<Duration> {
+        match self {
+            Self::Immediately => None,
+            Self::Sec1 => Some(Duration::from_secs(1)),
+            Self::Sec5 => Some(Duration::from_secs(5)),
+            Self::Sec15 => Some(Duration::from_secs(15)),
+            Self::Sec30 => Some(Duration::from_secs(30)),
+            Self::Min1 => Some(Duration::from_secs(60)),
+            Self::Asap => None,
+        }
+    }
+
+    pub fn from_duration(duration: Duration) -> Self {
+        if duration.is_zero() {
+            return Self::Immediately;
+        }
+
+        let mut result = Self::Sec1;
+        let mut best_diff = u128::MAX;
+
+        for period in [Self::Sec1, Self::Sec5, Self::Sec15, Self::Sec30, Self::Min1] {
+            let period_micros = period.as_duration().unwrap().as_micros();
+            let diff = period_micros.abs_diff(duration.as_micros());
+
+            if diff < best_diff {
+                best_diff = diff;
+                result = period;
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::DataSynchronizationPeriod;
+
+    #[test]
+    fn test_as_duration() {
+        assert!(DataSynchronizationPeriod::Immediately
+            .as_duration()
+            .is_none());
+        assert!(DataSynchronizationPeriod::Asap.as_duration().is_none());
+
+        assert_eq!(
+            DataSynchronizationPeriod::Sec1.as_duration().unwrap(),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            DataSynchronizationPeriod::Sec5.as_duration().unwrap(),
+            Duration::from_secs(5)
+        );
+        assert_eq!(
+            DataSynchronizationPeriod::Sec15.as_duration().unwrap(),
+            Duration::from_secs(15)
+        );
+        assert_eq!(
+            DataSynchronizationPeriod::Sec30.as_duration().unwrap(),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            DataSynchronizationPeriod::Min1.as_duration().unwrap(),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn test_from_duration_picks_nearest() {
+        let cases = [
+            (Duration::ZERO, "Immediately"),
+            (Duration::from_millis(300), "Sec1"),
+            (Duration::from_secs(1), "Sec1"),
+            (Duration::from_secs(4), "Sec5"),
+            (Duration::from_secs(12), "Sec15"),
+            (Duration::from_secs(25), "Sec30"),
+            (Duration::from_secs(50), "Min1"),
+            (Duration::from_secs(3600), "Min1"),
+        ];
+
+        for (duration, expected) in cases {
+            let result = DataSynchronizationPeriod::from_duration(duration);
+            assert_eq!(format!("{:?}", result), expected, "{:?}", duration);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for period in [
+            DataSynchronizationPeriod::Sec1,
+            DataSynchronizationPeriod::Sec5,
+            DataSynchronizationPeriod::Sec15,
+            DataSynchronizationPeriod::Sec30,
+            DataSynchronizationPeriod::Min1,
+        ] {
+            let result = DataSynchronizationPeriod::from_duration(period.as_duration().unwrap());
+            assert_eq!(format!("{:?}", result), format!("{:?}", period));
+        }
+    }
+}
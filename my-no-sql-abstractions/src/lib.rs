@@ -1,4 +1,6 @@
+mod compound_partition_key;
 mod data_sync_period;
+pub use compound_partition_key::CompoundPartitionKey;
 mod my_no_sql_entity;
 pub use data_sync_period::DataSynchronizationPeriod;
 pub use my_no_sql_entity::*;
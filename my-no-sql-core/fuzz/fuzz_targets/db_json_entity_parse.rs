@@ -0,0 +1,34 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use my_no_sql_core::db_json_entity::{DbJsonEntity, JsonTimeStamp};
+
+fuzz_target!(|data: &[u8]| {
+    let now = JsonTimeStamp::now();
+
+    if let Ok(entity) = DbJsonEntity::parse(data, &now) {
+        let _ = entity.get_partition_key();
+        let _ = entity.get_row_key();
+        let _ = entity.get_expires();
+        let _ = entity.get_time_stamp();
+    }
+
+    if let Ok(entity) = DbJsonEntity::from_slice(data) {
+        // Every position we got must point inside the source
+        for position in [&entity.partition_key, &entity.row_key]
+            .into_iter()
+            .chain(entity.time_stamp.as_ref())
+            .chain(entity.expires.as_ref())
+        {
+            assert!(position.key.start <= position.key.end);
+            assert!(position.value.start <= position.value.end);
+            assert!(position.value.end <= data.len());
+        }
+    }
+
+    if let Ok(db_row) = DbJsonEntity::try_parse_into_db_row(data.into(), &now) {
+        let _ = db_row.get_partition_key();
+        let _ = db_row.get_row_key();
+        let _ = db_row.to_vec();
+    }
+});
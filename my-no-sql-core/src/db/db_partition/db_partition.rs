@@ -4,7 +4,7 @@ use my_json::json_writer::JsonObject;
 use rust_extensions::date_time::AtomicDateTimeAsMicroseconds;
 use rust_extensions::sorted_vec::EntityWithStrKey;
 
-use crate::db::{DbRow, RowKeyParameter};
+use crate::db::{AvgSize, DbRow, RowKeyParameter};
 
 use std::sync::Arc;
 
@@ -43,13 +43,59 @@ impl DbPartition {
         }
     }
 
+    // Partition TTL cascades to the rows: once the partition is expired
+    // every row of it is expired regardless of its own Expires
+    #[cfg(feature = "master-node")]
+    pub fn is_expired(&self, now: rust_extensions::date_time::DateTimeAsMicroseconds) -> bool {
+        match self.expires {
+            Some(expires) => expires.unix_microseconds <= now.unix_microseconds,
+            None => false,
+        }
+    }
+
     #[cfg(feature = "master-node")]
     pub fn get_rows_to_expire(
         &self,
         now: rust_extensions::date_time::DateTimeAsMicroseconds,
     ) -> Vec<Arc<DbRow>> {
+        if self.is_expired(now) {
+            return self.get_all_rows_cloned();
+        }
+
         self.rows.get_rows_to_expire(now)
     }
+    #[cfg(feature = "master-node")]
+    pub fn drain_expired(
+        &mut self,
+        now: rust_extensions::date_time::DateTimeAsMicroseconds,
+    ) -> Vec<Arc<DbRow>> {
+        let rows_to_expire = self.get_rows_to_expire(now);
+
+        let mut result = Vec::with_capacity(rows_to_expire.len());
+
+        for db_row in rows_to_expire {
+            if let Some(removed_item) = self.remove_row(db_row.get_row_key()) {
+                result.push(removed_item);
+            }
+        }
+
+        result
+    }
+
+    #[cfg(feature = "master-node")]
+    pub fn row_ages(
+        &self,
+        now: rust_extensions::date_time::DateTimeAsMicroseconds,
+    ) -> Vec<std::time::Duration> {
+        self.rows
+            .get_all()
+            .map(|db_row| {
+                let age = now.unix_microseconds - db_row.get_time_stamp_value().unix_microseconds;
+                std::time::Duration::from_micros(age.max(0) as u64)
+            })
+            .collect()
+    }
+
     #[cfg(feature = "master-node")]
     pub fn get_expiration_index_owned(
         &self,
@@ -68,6 +114,14 @@ impl DbPartition {
         return self.rows.len();
     }
 
+    pub fn compact(&mut self) {
+        self.rows.compact();
+    }
+
+    pub fn estimated_capacity_overhead_bytes(&self) -> usize {
+        self.rows.estimated_capacity_overhead_bytes()
+    }
+
     #[inline]
     pub fn insert_row(&mut self, db_row: Arc<DbRow>) -> bool {
         if self.rows.has_db_row(db_row.get_row_key()) {
@@ -93,13 +147,36 @@ impl DbPartition {
 
     #[inline]
     pub fn insert_or_replace_rows_bulk(&mut self, db_rows: &[Arc<DbRow>]) -> Vec<Arc<DbRow>> {
+        self.insert_or_replace_rows(db_rows, None)
+    }
+
+    // avg_size of the table is updated in the same pass, so the batch is iterated only once
+    pub fn insert_or_replace_rows_bulk_with_avg_size(
+        &mut self,
+        db_rows: &[Arc<DbRow>],
+        avg_size: &mut AvgSize,
+    ) -> Vec<Arc<DbRow>> {
+        self.insert_or_replace_rows(db_rows, Some(avg_size))
+    }
+
+    fn insert_or_replace_rows(
+        &mut self,
+        db_rows: &[Arc<DbRow>],
+        mut avg_size: Option<&mut AvgSize>,
+    ) -> Vec<Arc<DbRow>> {
         let mut result = Vec::new();
 
         for db_row in db_rows {
             self.content_size += db_row.get_src_as_slice().len();
+            if let Some(avg_size) = avg_size.as_deref_mut() {
+                avg_size.add(db_row);
+            }
 
             if let Some(removed_item) = self.rows.insert(db_row.clone()) {
                 self.content_size -= removed_item.get_src_as_slice().len();
+                if let Some(avg_size) = avg_size.as_deref_mut() {
+                    avg_size.remove(&removed_item);
+                }
                 result.push(removed_item);
             }
         }
@@ -149,6 +226,14 @@ impl DbPartition {
         self.rows.rows_with_expiration_index_len()
     }
 
+    #[cfg(feature = "master-node")]
+    pub fn count_rows_to_expire(
+        &self,
+        moment: rust_extensions::date_time::DateTimeAsMicroseconds,
+    ) -> usize {
+        self.rows.count_rows_to_expire(moment)
+    }
+
     pub fn get_row(&self, row_key: &str) -> Option<&Arc<DbRow>> {
         let result = self.rows.get(row_key);
         result
@@ -215,3 +300,136 @@ impl JsonObject for &'_ DbPartition {
         dest.push(b']');
     }
 }
+
+#[cfg(feature = "master-node")]
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+    use crate::{
+        db::{AvgSize, DbRow},
+        db_json_entity::{DbJsonEntity, JsonTimeStamp},
+    };
+
+    use super::DbPartition;
+
+    fn create_rows(amount: usize) -> Vec<Arc<DbRow>> {
+        let time_stamp = JsonTimeStamp::now();
+
+        (0..amount)
+            .map(|i| {
+                let test_json = format!(r#"{{"PartitionKey": "test", "RowKey": "{}"}}"#, i);
+                let db_row =
+                    DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &time_stamp)
+                        .unwrap();
+                Arc::new(db_row)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bulk_insert_updates_avg_size() {
+        let db_rows = create_rows(3);
+
+        let mut db_partition = DbPartition::new("test".to_string());
+        let mut avg_size = AvgSize::new();
+
+        db_partition.insert_or_replace_rows_bulk_with_avg_size(&db_rows, &mut avg_size);
+        let replaced =
+            db_partition.insert_or_replace_rows_bulk_with_avg_size(&db_rows[..1], &mut avg_size);
+
+        assert_eq!(replaced.len(), 1);
+        assert_eq!(avg_size.count, 3);
+        assert_eq!(avg_size.total_size, db_partition.get_content_size());
+    }
+
+    // Single pass has to end up with the same avg_size as adding the batch and
+    // removing the replaced rows around insert_or_replace_rows_bulk
+    #[test]
+    fn test_bulk_insert_avg_size_matches_separate_passes() {
+        let db_rows = create_rows(10);
+
+        let mut single_pass_partition = DbPartition::new("test".to_string());
+        let mut single_pass = AvgSize::new();
+        single_pass_partition.insert_or_replace_rows_bulk_with_avg_size(&db_rows, &mut single_pass);
+        single_pass_partition
+            .insert_or_replace_rows_bulk_with_avg_size(&db_rows[..5], &mut single_pass);
+
+        let mut separate_passes_partition = DbPartition::new("test".to_string());
+        let mut separate_passes = AvgSize::new();
+        for batch in [&db_rows[..], &db_rows[..5]] {
+            for db_row in batch {
+                separate_passes.add(db_row);
+            }
+
+            for replaced in separate_passes_partition.insert_or_replace_rows_bulk(batch) {
+                separate_passes.remove(&replaced);
+            }
+        }
+
+        assert_eq!(single_pass.count, separate_passes.count);
+        assert_eq!(single_pass.total_size, separate_passes.total_size);
+        assert_eq!(
+            single_pass_partition.get_content_size(),
+            separate_passes_partition.get_content_size()
+        );
+    }
+
+    #[test]
+    fn test_row_ages() {
+        let mut db_partition = DbPartition::new("test".to_string());
+
+        for (row_key, seconds) in [("1", 10), ("2", 12)] {
+            let test_json = format!(r#"{{"PartitionKey": "test", "RowKey": "{}"}}"#, row_key);
+            let time_stamp =
+                JsonTimeStamp::from_date_time(DateTimeAsMicroseconds::new(seconds * 1_000_000));
+
+            let db_row =
+                DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &time_stamp).unwrap();
+
+            db_partition.insert_row(Arc::new(db_row));
+        }
+
+        let ages = db_partition.row_ages(DateTimeAsMicroseconds::new(15_000_000));
+
+        assert_eq!(ages, vec![Duration::from_secs(5), Duration::from_secs(3)]);
+    }
+
+    #[test]
+    fn test_drain_expired() {
+        let mut db_partition = DbPartition::new("test".to_string());
+
+        for (row_key, expires) in [
+            ("1", Some("2019-01-01T00:00:00")),
+            ("2", Some("2019-01-03T00:00:00")),
+            ("3", None),
+        ] {
+            let test_json = match expires {
+                Some(expires) => format!(
+                    r#"{{"PartitionKey": "test", "RowKey": "{}", "Expires": "{}"}}"#,
+                    row_key, expires
+                ),
+                None => format!(r#"{{"PartitionKey": "test", "RowKey": "{}"}}"#, row_key),
+            };
+
+            let db_row =
+                DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &JsonTimeStamp::now())
+                    .unwrap();
+
+            db_partition.insert_row(Arc::new(db_row));
+        }
+
+        let now = DateTimeAsMicroseconds::from_str("2019-01-02T00:00:00").unwrap();
+
+        let drained = db_partition.drain_expired(now);
+
+        assert_eq!(drained.len(), 1);
+        assert_eq!(drained[0].get_row_key(), "1");
+        assert_eq!(db_partition.get_rows_amount(), 2);
+        assert!(db_partition.get_row("1").is_none());
+
+        assert!(db_partition.drain_expired(now).is_empty());
+    }
+}
@@ -7,6 +7,8 @@ use crate::db::DbRow;
 
 pub struct DbRowsContainer {
     data: SortedVecOfArcWithStrKey<DbRow>,
+    // Vector does not give memory back on remove, so capacity is at least the peak amount of rows
+    peak_len: usize,
 
     #[cfg(feature = "master-node")]
     rows_with_expiration_index: crate::ExpirationIndexContainer<Arc<DbRow>>,
@@ -16,6 +18,7 @@ impl DbRowsContainer {
     pub fn new() -> Self {
         Self {
             data: SortedVecOfArcWithStrKey::new(),
+            peak_len: 0,
             #[cfg(feature = "master-node")]
             rows_with_expiration_index: crate::ExpirationIndexContainer::new(),
         }
@@ -29,6 +32,12 @@ impl DbRowsContainer {
     pub fn rows_with_expiration_index_len(&self) -> usize {
         self.rows_with_expiration_index.len()
     }
+    #[cfg(feature = "master-node")]
+    pub fn count_rows_to_expire(&self, moment: DateTimeAsMicroseconds) -> usize {
+        self.rows_with_expiration_index
+            .count_items_to_expire(moment)
+    }
+
     #[cfg(feature = "master-node")]
     pub fn get_rows_to_expire(&self, now: DateTimeAsMicroseconds) -> Vec<Arc<DbRow>> {
         self.rows_with_expiration_index
@@ -67,15 +76,19 @@ impl DbRowsContainer {
         Some(by_last_read_access)
     }
 
+    // Data is written before the index and the replaced row leaves the index before the new one is added.
+    // Index removes items by row key, so the other order would drop the new row when both rows expire at the same moment
     pub fn insert(&mut self, db_row: Arc<DbRow>) -> Option<Arc<DbRow>> {
-        #[cfg(feature = "master-node")]
-        self.rows_with_expiration_index.add(&db_row);
-
-        let (_, removed_db_row) = self.data.insert_or_replace(db_row);
+        let (_, removed_db_row) = self.data.insert_or_replace(db_row.clone());
+        self.peak_len = self.peak_len.max(self.data.len());
 
         #[cfg(feature = "master-node")]
-        if let Some(removed_db_row) = &removed_db_row {
-            self.rows_with_expiration_index.remove(removed_db_row);
+        {
+            if let Some(removed_db_row) = &removed_db_row {
+                self.rows_with_expiration_index.remove(removed_db_row);
+            }
+
+            self.rows_with_expiration_index.add(&db_row);
         }
 
         removed_db_row
@@ -92,6 +105,29 @@ impl DbRowsContainer {
         result
     }
 
+    pub fn compact(&mut self) {
+        let mut data = SortedVecOfArcWithStrKey::new_with_capacity(self.data.len());
+
+        for db_row in self.data.iter() {
+            data.insert_or_replace(db_row.clone());
+        }
+
+        self.data = data;
+        self.peak_len = self.data.len();
+
+        #[cfg(feature = "master-node")]
+        self.rows_with_expiration_index.shrink_to_fit();
+    }
+
+    pub fn estimated_capacity_overhead_bytes(&self) -> usize {
+        let result = (self.peak_len - self.data.len()) * std::mem::size_of::<Arc<DbRow>>();
+
+        #[cfg(feature = "master-node")]
+        let result = result + self.rows_with_expiration_index.capacity_overhead_bytes();
+
+        result
+    }
+
     pub fn get(&self, row_key: &str) -> Option<&Arc<DbRow>> {
         self.data.get(row_key)
     }
@@ -179,6 +215,31 @@ mod tests {
         assert_eq!(1, db_rows.rows_with_expiration_index.len())
     }
 
+    #[test]
+    fn test_replace_row_with_the_same_expiration() {
+        let test_json = r#"{
+            "PartitionKey": "test",
+            "RowKey": "test",
+            "Expires": "2019-01-01T00:00:00"
+        }"#;
+
+        let time_stamp = JsonTimeStamp::now();
+
+        let mut db_rows = DbRowsContainer::new();
+
+        for _ in 0..2 {
+            let db_row =
+                DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &time_stamp).unwrap();
+            db_rows.insert(Arc::new(db_row));
+        }
+
+        assert_eq!(1, db_rows.rows_with_expiration_index.len());
+        assert!(db_rows.rows_with_expiration_index.validate().is_ok());
+
+        let expiration = DateTimeAsMicroseconds::from_str("2019-01-01T00:00:00").unwrap();
+        assert_eq!(1, db_rows.get_rows_to_expire(expiration).len());
+    }
+
     #[test]
     fn test_that_index_does_not_appear_since_we_do_not_have_expiration() {
         let test_json = r#"{
@@ -438,4 +499,35 @@ mod tests {
 
         assert_eq!("test1", db_rows_to_gc.get(0).unwrap().get_row_key());
     }
+
+    #[test]
+    fn test_compact_gives_back_capacity() {
+        let mut db_rows = DbRowsContainer::new();
+
+        for i in 0..10 {
+            let test_json = format!(r#"{{"PartitionKey":"test","RowKey":"test{}"}}"#, i);
+            let db_row =
+                DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &JsonTimeStamp::now())
+                    .unwrap();
+            db_rows.insert(Arc::new(db_row));
+        }
+
+        assert_eq!(db_rows.estimated_capacity_overhead_bytes(), 0);
+
+        for i in 0..8 {
+            db_rows.remove(format!("test{}", i).as_str());
+        }
+
+        assert_eq!(
+            db_rows.estimated_capacity_overhead_bytes(),
+            8 * std::mem::size_of::<Arc<DbRow>>()
+        );
+
+        db_rows.compact();
+
+        assert_eq!(db_rows.estimated_capacity_overhead_bytes(), 0);
+        assert_eq!(db_rows.len(), 2);
+        assert!(db_rows.has_db_row("test8"));
+        assert!(db_rows.has_db_row("test9"));
+    }
 }
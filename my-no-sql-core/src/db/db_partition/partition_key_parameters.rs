@@ -19,3 +19,17 @@ impl PartitionKeyParameter for String {
         PartitionKey::new(self.clone())
     }
 }
+
+impl PartitionKeyParameter for my_no_sql_abstractions::CompoundPartitionKey {
+    fn as_str(&self) -> &str {
+        self.as_str()
+    }
+
+    fn into_partition_key(self) -> PartitionKey {
+        PartitionKey::new(self.build())
+    }
+
+    fn to_partition_key(&self) -> PartitionKey {
+        PartitionKey::new(self.as_str().to_string())
+    }
+}
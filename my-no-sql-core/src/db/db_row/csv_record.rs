@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use my_json::json_reader::JsonFirstLineReader;
+use rust_extensions::array_of_bytes_iterator::SliceIterator;
+
+use super::DbRow;
+
+impl DbRow {
+    // Values of the top level fields in the order of columns. Missing or null field gives an empty value.
+    // Strings are unescaped, other values are kept as json. Fields are read from the written json,
+    // so Expires reflects the current value
+    pub fn to_csv_record(&self, columns: &[String]) -> Vec<String> {
+        let mut fields = HashMap::new();
+
+        for (name, value) in read_top_level_fields(self.to_vec().as_slice()) {
+            fields.insert(name, value);
+        }
+
+        columns
+            .iter()
+            .map(|column| fields.get(column.as_str()).cloned().unwrap_or_default())
+            .collect()
+    }
+
+    pub fn get_top_level_field_names(&self) -> Vec<String> {
+        read_top_level_fields(self.to_vec().as_slice())
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+// Scanning stops at the first malformed field
+fn read_top_level_fields(raw: &[u8]) -> Vec<(String, String)> {
+    let mut json_first_line_reader = JsonFirstLineReader::new(SliceIterator::new(raw));
+
+    let mut result = Vec::new();
+
+    while let Some(line) = json_first_line_reader.get_next() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+
+        let line_ref = line.as_ref(&json_first_line_reader);
+
+        let name = match line_ref.name.as_unescaped_name() {
+            Ok(name) => name.to_string(),
+            Err(_) => break,
+        };
+
+        result.push((name, value_to_csv(line_ref.value.as_bytes())));
+    }
+
+    result
+}
+
+fn value_to_csv(value: &[u8]) -> String {
+    match value.first() {
+        Some(b'"') => serde_json::from_slice::<String>(value).unwrap_or_default(),
+        _ => {
+            if value == b"null" {
+                return String::new();
+            }
+
+            String::from_utf8_lossy(value).to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db_json_entity::{DbJsonEntity, JsonTimeStamp};
+
+    #[test]
+    fn test_to_csv_record() {
+        let json = r#"{"PartitionKey":"Pk","RowKey":"Rk","Name":"A \"quoted\" name","Amount":12.5,"Tags":[1,2],"Comment":null}"#;
+
+        let db_row =
+            DbJsonEntity::parse_into_db_row(json.as_bytes().into(), &JsonTimeStamp::now()).unwrap();
+
+        let columns: Vec<String> = ["RowKey", "Name", "Missing", "Amount", "Tags", "Comment"]
+            .into_iter()
+            .map(|itm| itm.to_string())
+            .collect();
+
+        assert_eq!(
+            db_row.to_csv_record(&columns),
+            vec!["Rk", "A \"quoted\" name", "", "12.5", "[1,2]", ""]
+        );
+    }
+
+    #[cfg(feature = "master-node")]
+    #[test]
+    fn test_to_csv_record_reads_updated_expires() {
+        use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+        let json = r#"{"PartitionKey":"Pk","RowKey":"Rk","Expires":"2019-01-01T00:00:00"}"#;
+
+        let db_row =
+            DbJsonEntity::parse_into_db_row(json.as_bytes().into(), &JsonTimeStamp::now()).unwrap();
+
+        let new_expires = DateTimeAsMicroseconds::from_str("2020-01-02T01:02:03").unwrap();
+        db_row.update_expires(new_expires.into());
+
+        let columns = vec!["Expires".to_string()];
+
+        assert_eq!(
+            db_row.to_csv_record(&columns),
+            vec![new_expires.to_rfc3339()[..19].to_string()]
+        );
+
+        db_row.update_expires(None);
+
+        assert_eq!(db_row.to_csv_record(&columns), vec![""]);
+        assert_eq!(
+            db_row.get_top_level_field_names(),
+            vec!["PartitionKey", "RowKey"]
+        );
+    }
+}
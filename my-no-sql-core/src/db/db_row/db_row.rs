@@ -8,7 +8,7 @@ use rust_extensions::date_time::DateTimeAsMicroseconds;
 use rust_extensions::sorted_vec::EntityWithStrKey;
 
 use crate::db::PartitionKeyParameter;
-use crate::db_json_entity::DbJsonEntity;
+use crate::db_json_entity::{DbEntityParseFail, DbJsonEntity};
 
 use super::RowKeyParameter;
 
@@ -23,11 +23,21 @@ pub struct DbRow {
     #[cfg(feature = "master-node")]
     pub time_stamp: crate::db_json_entity::KeyValueContentPosition,
     #[cfg(feature = "master-node")]
+    time_stamp_value: DateTimeAsMicroseconds,
+    #[cfg(feature = "master-node")]
     last_read_access: AtomicDateTimeAsMicroseconds,
 }
 
 impl DbRow {
     pub fn new(db_json_entity: DbJsonEntity, raw: Vec<u8>) -> Self {
+        match Self::try_new(db_json_entity, raw) {
+            Ok(result) => result,
+            Err(err) => panic!("Can not create DbRow. Err: {:?}", err),
+        }
+    }
+
+    // Same as new, but malformed entity is reported as an error instead of panic
+    pub fn try_new(db_json_entity: DbJsonEntity, raw: Vec<u8>) -> Result<Self, DbEntityParseFail> {
         #[cfg(feature = "debug_db_row")]
         println!(
             "Created DbRow: PK:{}. RK:{}. Expires{:?}",
@@ -37,18 +47,28 @@ impl DbRow {
         );
 
         #[cfg(feature = "master-node")]
-        let time_stamp = db_json_entity.time_stamp.unwrap();
+        let time_stamp = match db_json_entity.time_stamp {
+            Some(time_stamp) => time_stamp,
+            None => return Err(DbEntityParseFail::TimeStampIsRequired),
+        };
         #[cfg(feature = "master-node")]
-        let time_stamp_value =
-            DateTimeAsMicroseconds::from_str(time_stamp.value.get_str_value(&raw)).unwrap();
+        let time_stamp_value = {
+            let value = time_stamp.value.get_str_value(&raw);
+            match DateTimeAsMicroseconds::from_str(value) {
+                Some(time_stamp_value) => time_stamp_value,
+                None => return Err(DbEntityParseFail::InvalidTimeStamp(value.to_string())),
+            }
+        };
 
-        Self {
+        let result = Self {
             raw,
             partition_key: db_json_entity.partition_key.value,
             row_key: db_json_entity.row_key.value,
             #[cfg(feature = "master-node")]
             time_stamp: time_stamp.value,
             #[cfg(feature = "master-node")]
+            time_stamp_value,
+            #[cfg(feature = "master-node")]
             expires_value: if let Some(expires_value) = db_json_entity.expires_value {
                 AtomicDateTimeAsMicroseconds::new(expires_value.unix_microseconds)
             } else {
@@ -58,7 +78,9 @@ impl DbRow {
             expires: db_json_entity.expires,
             #[cfg(feature = "master-node")]
             last_read_access: AtomicDateTimeAsMicroseconds::new(time_stamp_value.unix_microseconds),
-        }
+        };
+
+        Ok(result)
     }
 
     pub fn get_partition_key(&self) -> &str {
@@ -69,13 +91,29 @@ impl DbRow {
         self.row_key.get_str_value(&self.raw)
     }
 
+    #[cfg(feature = "master-node")]
     pub fn get_time_stamp(&self) -> &str {
-        self.row_key.get_str_value(&self.raw)
+        self.time_stamp.get_str_value(&self.raw)
     }
+
+    // Position of TimeStamp is kept only by master node, so here it is looked up in the content
+    #[cfg(not(feature = "master-node"))]
+    pub fn get_time_stamp(&self) -> &str {
+        match DbJsonEntity::from_slice(&self.raw) {
+            Ok(db_json_entity) => db_json_entity.get_time_stamp(&self.raw).unwrap_or_default(),
+            Err(_) => "",
+        }
+    }
+
     pub fn get_src_as_slice(&self) -> &[u8] {
         self.raw.as_slice()
     }
 
+    #[cfg(feature = "master-node")]
+    pub fn get_time_stamp_value(&self) -> DateTimeAsMicroseconds {
+        self.time_stamp_value
+    }
+
     #[cfg(feature = "master-node")]
     pub fn update_last_read_access(
         &self,
@@ -120,7 +158,7 @@ impl DbRow {
         if expires_value.is_none() {
             if let Some(expires) = &self.expires {
                 if let Some(before_separator) =
-                    find_json_separator_before(&self.raw, expires.key.start - 1)
+                    find_json_separator_before(&self.raw, expires.key.start.saturating_sub(1))
                 {
                     out.extend_from_slice(&self.raw[..before_separator]);
                     out.extend_from_slice(&self.raw[expires.value.end..]);
@@ -209,7 +247,7 @@ fn inject_expires(out: &mut Vec<u8>, expires_value: DateTimeAsMicroseconds) {
 fn find_json_separator_before(src: &[u8], pos: usize) -> Option<usize> {
     let mut i = pos;
     while i > 0 {
-        let b = src[i];
+        let b = *src.get(i)?;
 
         if b <= 32 {
             i -= 1;
@@ -228,8 +266,8 @@ fn find_json_separator_before(src: &[u8], pos: usize) -> Option<usize> {
 #[cfg(feature = "master-node")]
 fn find_json_separator_after(src: &[u8], pos: usize) -> Option<usize> {
     let mut i = pos;
-    while i < src.len() {
-        let b = src[i];
+    while let Some(b) = src.get(i) {
+        let b = *b;
 
         if b <= 32 {
             i += 1;
@@ -0,0 +1,92 @@
+use my_json::json_reader::{array_iterator::JsonArrayIterator, JsonFirstLineReader};
+use rust_extensions::array_of_bytes_iterator::SliceIterator;
+
+use super::DbRow;
+
+impl DbRow {
+    // Supports subset of JSON Pointer (RFC 6901): /a/b/0/c.
+    // Returns the raw (not unescaped) content of the string value or None if the value is missing or not a string
+    pub fn get_field_by_pointer(&self, pointer: &str) -> Option<&str> {
+        get_str_by_pointer(self.get_src_as_slice(), pointer)
+    }
+}
+
+fn get_str_by_pointer<'s>(raw: &'s [u8], pointer: &str) -> Option<&'s str> {
+    if !pointer.starts_with('/') {
+        return None;
+    }
+
+    let mut value = raw;
+
+    for segment in pointer[1..].split('/') {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+
+        value = match *value.first()? {
+            b'{' => get_object_value(value, segment.as_str())?,
+            b'[' => get_array_value(value, segment.parse().ok()?)?,
+            _ => return None,
+        };
+    }
+
+    if value.len() < 2 || value[0] != b'"' {
+        return None;
+    }
+
+    std::str::from_utf8(&value[1..value.len() - 1]).ok()
+}
+
+fn get_object_value<'s>(raw: &'s [u8], key: &str) -> Option<&'s [u8]> {
+    let mut json_first_line_reader = JsonFirstLineReader::new(SliceIterator::new(raw));
+
+    while let Some(line) = json_first_line_reader.get_next() {
+        let line = line.ok()?;
+
+        if line.name.as_unescaped_name(&json_first_line_reader).ok()? == key {
+            return Some(&raw[line.value.start..line.value.end]);
+        }
+    }
+
+    None
+}
+
+fn get_array_value<'s>(raw: &'s [u8], index: usize) -> Option<&'s [u8]> {
+    let mut json_array_iterator = JsonArrayIterator::new(SliceIterator::new(raw)).ok()?;
+    let mut item_index = 0;
+
+    while let Some(item) = json_array_iterator.get_next() {
+        let item = item.ok()?;
+
+        if item_index == index {
+            return Some(&raw[item.start..item.end]);
+        }
+
+        item_index += 1;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db_json_entity::{DbJsonEntity, JsonTimeStamp};
+
+    #[test]
+    fn test_get_field_by_pointer() {
+        let test_json = r#"{"PartitionKey":"Pk","RowKey":"Rk","A":{"B":[{"C":"1"},{"C":"2","D":{"E":"deep"}}],"N":5},"a/b":"slash","q\"k":"quoted"}"#;
+
+        let db_row =
+            DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &JsonTimeStamp::now())
+                .unwrap();
+
+        assert_eq!(db_row.get_field_by_pointer("/RowKey"), Some("Rk"));
+        assert_eq!(db_row.get_field_by_pointer("/A/B/0/C"), Some("1"));
+        assert_eq!(db_row.get_field_by_pointer("/A/B/1/D/E"), Some("deep"));
+        assert_eq!(db_row.get_field_by_pointer("/a~1b"), Some("slash"));
+        assert_eq!(db_row.get_field_by_pointer("/q\"k"), Some("quoted"));
+
+        assert_eq!(db_row.get_field_by_pointer("/A/N"), None);
+        assert_eq!(db_row.get_field_by_pointer("/A/B/2/C"), None);
+        assert_eq!(db_row.get_field_by_pointer("/A/Missing"), None);
+        assert_eq!(db_row.get_field_by_pointer("A"), None);
+    }
+}
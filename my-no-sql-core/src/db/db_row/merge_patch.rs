@@ -0,0 +1,159 @@
+use serde_json::{Map, Value};
+
+use crate::db_json_entity::{DbEntityParseFail, DbJsonEntity};
+
+use super::DbRow;
+
+#[derive(Debug)]
+pub enum MergePatchError {
+    InvalidJson(String),
+    PatchIsNotAnObject,
+    PartitionKeyCanNotBeChanged,
+    RowKeyCanNotBeChanged,
+    DbEntityParseFail(DbEntityParseFail),
+}
+
+impl From<DbEntityParseFail> for MergePatchError {
+    fn from(src: DbEntityParseFail) -> Self {
+        Self::DbEntityParseFail(src)
+    }
+}
+
+impl DbRow {
+    // RFC 7386 JSON Merge Patch. TimeStamp of the row is kept as is.
+    pub fn apply_merge_patch(&self, patch: &[u8]) -> Result<DbRow, MergePatchError> {
+        let patch: Value = serde_json::from_slice(patch)
+            .map_err(|err| MergePatchError::InvalidJson(err.to_string()))?;
+
+        let mut patch = match patch {
+            Value::Object(patch) => patch,
+            _ => return Err(MergePatchError::PatchIsNotAnObject),
+        };
+
+        check_key_is_not_changed(
+            &patch,
+            crate::db_json_entity::consts::PARTITION_KEY,
+            self.get_partition_key(),
+            MergePatchError::PartitionKeyCanNotBeChanged,
+        )?;
+
+        check_key_is_not_changed(
+            &patch,
+            crate::db_json_entity::consts::ROW_KEY,
+            self.get_row_key(),
+            MergePatchError::RowKeyCanNotBeChanged,
+        )?;
+
+        patch.retain(|key, _| {
+            !rust_extensions::str_utils::compare_strings_case_insensitive(
+                key,
+                crate::db_json_entity::consts::TIME_STAMP_LOWER_CASE,
+            )
+        });
+
+        let mut target: Value = serde_json::from_slice(self.to_vec().as_slice())
+            .map_err(|err| MergePatchError::InvalidJson(err.to_string()))?;
+
+        merge_patch(&mut target, Value::Object(patch));
+
+        let raw = serde_json::to_vec(&target)
+            .map_err(|err| MergePatchError::InvalidJson(err.to_string()))?;
+
+        let result = DbJsonEntity::restore_into_db_row(raw.as_slice().into())?;
+
+        Ok(result)
+    }
+}
+
+fn check_key_is_not_changed(
+    patch: &Map<String, Value>,
+    key: &str,
+    current_value: &str,
+    err: MergePatchError,
+) -> Result<(), MergePatchError> {
+    if let Some(value) = patch.get(key) {
+        if value.as_str() != Some(current_value) {
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+fn merge_patch(target: &mut Value, patch: Value) {
+    let patch = match patch {
+        Value::Object(patch) => patch,
+        patch => {
+            *target = patch;
+            return;
+        }
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+
+    let target = target.as_object_mut().unwrap();
+
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key.as_str());
+            continue;
+        }
+
+        merge_patch(target.entry(key).or_insert(Value::Null), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db_json_entity::{DbJsonEntity, JsonTimeStamp};
+
+    use super::MergePatchError;
+
+    #[test]
+    fn test_merge_patch() {
+        let test_json = r#"{"PartitionKey":"Pk","RowKey":"Rk","A":1,"B":{"C":2,"D":3},"E":[1,2]}"#;
+
+        let db_row =
+            DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &JsonTimeStamp::now())
+                .unwrap();
+
+        let patch = r#"{"A":null,"B":{"C":5},"E":[3],"F":"New"}"#;
+
+        let result = db_row.apply_merge_patch(patch.as_bytes()).unwrap();
+
+        assert_eq!(result.get_partition_key(), "Pk");
+        assert_eq!(result.get_row_key(), "Rk");
+
+        let result: serde_json::Value =
+            serde_json::from_slice(result.get_src_as_slice()).unwrap();
+
+        assert!(result.get("A").is_none());
+        assert_eq!(result["B"]["C"], 5);
+        assert_eq!(result["B"]["D"], 3);
+        assert_eq!(result["E"], serde_json::json!([3]));
+        assert_eq!(result["F"], "New");
+    }
+
+    #[test]
+    fn test_merge_patch_can_not_change_keys() {
+        let test_json = r#"{"PartitionKey":"Pk","RowKey":"Rk"}"#;
+
+        let db_row =
+            DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &JsonTimeStamp::now())
+                .unwrap();
+
+        let result = db_row.apply_merge_patch(r#"{"PartitionKey":"Pk2"}"#.as_bytes());
+        assert!(matches!(
+            result,
+            Err(MergePatchError::PartitionKeyCanNotBeChanged)
+        ));
+
+        let result = db_row.apply_merge_patch(r#"{"RowKey":null}"#.as_bytes());
+        assert!(matches!(result, Err(MergePatchError::RowKeyCanNotBeChanged)));
+
+        let result = db_row.apply_merge_patch(r#"{"PartitionKey":"Pk","A":1}"#.as_bytes());
+        assert!(result.is_ok());
+    }
+}
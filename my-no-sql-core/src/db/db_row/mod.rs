@@ -5,3 +5,7 @@ mod row_key_parameter;
 #[cfg(feature = "master-node")]
 mod test_expires_update;
 pub use row_key_parameter::*;
+mod json_pointer;
+mod merge_patch;
+pub use merge_patch::*;
+mod csv_record;
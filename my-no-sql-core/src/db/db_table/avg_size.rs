@@ -18,6 +18,22 @@ impl AvgSize {
         self.count += 1;
     }
 
+    pub fn remove(&mut self, db_row: &DbRow) {
+        if self.count == 0 {
+            return;
+        }
+
+        self.total_size = self
+            .total_size
+            .saturating_sub(db_row.get_src_as_slice().len());
+        self.count -= 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.total_size = 0;
+        self.count = 0;
+    }
+
     pub fn get(&self) -> usize {
         if self.count == 0 {
             return 0;
@@ -26,3 +42,53 @@ impl AvgSize {
         self.total_size / self.count
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::db_json_entity::{DbJsonEntity, JsonTimeStamp};
+
+    use super::AvgSize;
+
+    #[test]
+    fn test_avg_size_tracks_remaining_rows() {
+        let now = JsonTimeStamp::now();
+
+        let db_rows: Vec<_> = ["a", "bbbbbbbbbb", "cccccccccccccccccccc"]
+            .into_iter()
+            .map(|value| {
+                let test_json = format!(
+                    r#"{{"PartitionKey":"Pk","RowKey":"Rk","Value":"{}"}}"#,
+                    value
+                );
+                DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &now).unwrap()
+            })
+            .collect();
+
+        let mut avg_size = AvgSize::new();
+
+        for db_row in db_rows.iter() {
+            avg_size.add(db_row);
+        }
+
+        let total: usize = db_rows
+            .iter()
+            .map(|db_row| db_row.get_src_as_slice().len())
+            .sum();
+        assert_eq!(avg_size.get(), total / 3);
+
+        avg_size.remove(&db_rows[2]);
+        assert_eq!(
+            avg_size.get(),
+            (db_rows[0].get_src_as_slice().len() + db_rows[1].get_src_as_slice().len()) / 2
+        );
+
+        avg_size.remove(&db_rows[1]);
+        assert_eq!(avg_size.get(), db_rows[0].get_src_as_slice().len());
+
+        avg_size.remove(&db_rows[0]);
+        assert_eq!(avg_size.get(), 0);
+
+        avg_size.remove(&db_rows[0]);
+        assert_eq!(avg_size.count, 0);
+    }
+}
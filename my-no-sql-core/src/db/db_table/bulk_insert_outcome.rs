@@ -0,0 +1,14 @@
+use std::sync::Arc;
+
+use crate::db::DbRow;
+
+pub struct BulkInsertOutcome {
+    pub inserted: usize,
+    pub replaced: Vec<Arc<DbRow>>,
+}
+
+impl BulkInsertOutcome {
+    pub fn get_replaced_amount(&self) -> usize {
+        self.replaced.len()
+    }
+}
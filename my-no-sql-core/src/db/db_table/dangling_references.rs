@@ -0,0 +1,72 @@
+use crate::db::PartitionKey;
+
+use super::DbTable;
+
+impl DbTable {
+    // Offline consistency check. Field of each row is treated as a row key of the target table.
+    // Rows without the field or with not string value are not references and are skipped
+    pub fn find_dangling_references(
+        &self,
+        field: &str,
+        target_table: &DbTable,
+    ) -> Vec<(PartitionKey, String)> {
+        let pointer = format!("/{}", field.replace('~', "~0").replace('/', "~1"));
+
+        let mut result = Vec::new();
+
+        for db_partition in self.partitions.get_partitions() {
+            for db_row in db_partition.get_all_rows() {
+                let foreign_row_key = match db_row.get_field_by_pointer(pointer.as_str()) {
+                    Some(foreign_row_key) => foreign_row_key,
+                    None => continue,
+                };
+
+                let exists = target_table
+                    .get_by_row_key(foreign_row_key, None, Some(1))
+                    .next()
+                    .is_some();
+
+                if !exists {
+                    result.push((
+                        db_partition.partition_key.clone(),
+                        db_row.get_row_key().to_string(),
+                    ));
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(feature = "master-node")]
+#[cfg(test)]
+mod tests {
+    use crate::db::{db_table::test_utils::insert, DbTable, DbTableAttributes};
+
+    #[test]
+    fn test_find_dangling_references() {
+        let mut clients = DbTable::new("clients".to_string(), DbTableAttributes::create_default());
+        insert(
+            &mut clients,
+            r#"{"PartitionKey": "c", "RowKey": "client1"}"#,
+        );
+
+        let mut orders = DbTable::new("orders".to_string(), DbTableAttributes::create_default());
+        insert(
+            &mut orders,
+            r#"{"PartitionKey": "o", "RowKey": "order1", "ClientId": "client1"}"#,
+        );
+        insert(
+            &mut orders,
+            r#"{"PartitionKey": "o", "RowKey": "order2", "ClientId": "client2"}"#,
+        );
+        insert(&mut orders, r#"{"PartitionKey": "o", "RowKey": "order3"}"#);
+
+        let result = orders.find_dangling_references("ClientId", &clients);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].0.as_str(), "o");
+        assert_eq!(result[0].1, "order2");
+    }
+}
@@ -14,6 +14,8 @@ pub struct PartitionToGc {
 
 pub struct DbPartitionsContainer {
     partitions: SortedVecWithStrKey<DbPartition>,
+    // Vector does not give memory back on remove, so capacity is at least the peak amount of partitions
+    peak_len: usize,
     #[cfg(feature = "master-node")]
     partitions_to_expire_index:
         crate::ExpirationIndexContainer<super::DbPartitionExpirationIndexOwned>,
@@ -23,6 +25,7 @@ impl DbPartitionsContainer {
     pub fn new() -> Self {
         Self {
             partitions: SortedVecWithStrKey::new(),
+            peak_len: 0,
             #[cfg(feature = "master-node")]
             partitions_to_expire_index: crate::ExpirationIndexContainer::new(),
         }
@@ -45,6 +48,32 @@ impl DbPartitionsContainer {
             .get_items_to_expire(now, |itm| itm.partition_key.clone())
     }
 
+    #[cfg(feature = "master-node")]
+    pub fn count_partitions_to_expire(&self, moment: DateTimeAsMicroseconds) -> usize {
+        self.partitions_to_expire_index
+            .count_items_to_expire(moment)
+    }
+
+    // Returns false if there is no such partition
+    #[cfg(feature = "master-node")]
+    pub fn update_partition_expiration(
+        &mut self,
+        partition_key: &str,
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> bool {
+        let db_partition = match self.partitions.get_mut(partition_key) {
+            Some(db_partition) => db_partition,
+            None => return false,
+        };
+
+        let old_expires = std::mem::replace(&mut db_partition.expires, expires);
+
+        self.partitions_to_expire_index
+            .update(old_expires, &*db_partition);
+
+        true
+    }
+
     pub fn add_partition_if_not_exists(
         &mut self,
         partition_key: &impl PartitionKeyParameter,
@@ -59,6 +88,8 @@ impl DbPartitionsContainer {
             rust_extensions::sorted_vec::InsertIfNotExists::Exists(index) => index,
         };
 
+        self.peak_len = self.peak_len.max(self.partitions.len());
+
         self.partitions.get_by_index_mut(index).unwrap()
     }
 
@@ -74,15 +105,22 @@ impl DbPartitionsContainer {
         self.partitions.contains(partition_key)
     }
 
+    // Same order as DbRowsContainer::insert: data first, then the replaced partition leaves the index
+    // before the new one is added
     pub fn insert(&mut self, db_partition: DbPartition) {
         #[cfg(feature = "master-node")]
-        self.partitions_to_expire_index.add(&db_partition);
+        let expiration_index_item = db_partition.get_expiration_index_owned();
 
         let (_, _removed_partition) = self.partitions.insert_or_replace(db_partition);
+        self.peak_len = self.peak_len.max(self.partitions.len());
 
         #[cfg(feature = "master-node")]
-        if let Some(removed_partition) = _removed_partition {
-            self.partitions_to_expire_index.remove(&removed_partition);
+        {
+            if let Some(removed_partition) = _removed_partition {
+                self.partitions_to_expire_index.remove(&removed_partition);
+            }
+
+            self.partitions_to_expire_index.add(&expiration_index_item);
         }
     }
 
@@ -104,12 +142,60 @@ impl DbPartitionsContainer {
         let mut result = SortedVecWithStrKey::new();
 
         std::mem::swap(&mut result, &mut self.partitions);
+        // Old vector leaves with the result, so nothing is allocated any more
+        self.peak_len = 0;
         #[cfg(feature = "master-node")]
         self.partitions_to_expire_index.clear();
 
         Some(result)
     }
 
+    // Moves partitions into the vector of the exact size and shrinks every partition
+    pub fn compact(&mut self) {
+        let mut partition_keys = Vec::with_capacity(self.partitions.len());
+
+        for db_partition in self.partitions.iter() {
+            partition_keys.push(db_partition.partition_key.clone());
+        }
+
+        // Removing from the end does not shift the rest of the partitions
+        let mut removed = Vec::with_capacity(partition_keys.len());
+        for partition_key in partition_keys.iter().rev() {
+            if let Some(db_partition) = self.partitions.remove(partition_key.as_str()) {
+                removed.push(db_partition);
+            }
+        }
+
+        let mut partitions = SortedVecWithStrKey::new_with_capacity(removed.len());
+        for mut db_partition in removed.into_iter().rev() {
+            db_partition.compact();
+            partitions.insert_or_replace(db_partition);
+        }
+
+        self.partitions = partitions;
+        self.peak_len = self.partitions.len();
+
+        #[cfg(feature = "master-node")]
+        self.partitions_to_expire_index.shrink_to_fit();
+    }
+
+    // Lower bound of the unused memory. See DbTable::estimated_capacity_overhead_bytes
+    pub fn estimated_capacity_overhead_bytes(&self) -> usize {
+        let mut result =
+            (self.peak_len - self.partitions.len()) * std::mem::size_of::<DbPartition>();
+
+        for db_partition in self.partitions.iter() {
+            result += db_partition.estimated_capacity_overhead_bytes();
+        }
+
+        #[cfg(feature = "master-node")]
+        {
+            result += self.partitions_to_expire_index.capacity_overhead_bytes();
+        }
+
+        result
+    }
+
     #[cfg(feature = "master-node")]
     pub fn get_partitions_to_gc_by_max_amount(
         &self,
@@ -8,7 +8,9 @@ use crate::db::{DbPartition, DbRow, PartitionKey, PartitionKeyParameter, RowKeyP
 
 #[cfg(feature = "master-node")]
 use super::DbTableAttributes;
-use super::{AllDbRowsIterator, AvgSize, ByRowKeyIterator, DbPartitionsContainer};
+use super::{
+    AllDbRowsIterator, AvgSize, BulkInsertOutcome, ByRowKeyIterator, DbPartitionsContainer,
+};
 
 pub struct DbTable {
     pub name: String,
@@ -77,6 +79,19 @@ impl DbTable {
         result
     }
 
+    // Gives back the memory which is left after heavy churn. Worth to call
+    // if estimated_capacity_overhead_bytes is big comparing to get_table_size
+    pub fn compact(&mut self) {
+        self.partitions.compact();
+    }
+
+    // Sorted vectors do not expose their capacity, so the unused part of them is estimated
+    // by the peak amount of items since the last compact. It is a lower bound: growth of
+    // the vector above the peak is not counted. Expiration indexes are counted exactly
+    pub fn estimated_capacity_overhead_bytes(&self) -> usize {
+        self.partitions.estimated_capacity_overhead_bytes()
+    }
+
     pub fn get_table_size(&self) -> usize {
         let mut result = 0;
         for db_partition in self.partitions.get_partitions() {
@@ -121,12 +136,17 @@ impl DbTable {
         db_row: &Arc<DbRow>,
         #[cfg(feature = "master-node")] set_last_write_moment: Option<DateTimeAsMicroseconds>,
     ) -> (PartitionKey, Option<Arc<DbRow>>) {
-        self.avg_size.add(db_row);
-
+        // Partition is written before the table level stats, the same way as insert_row does
         let db_partition = self.partitions.add_partition_if_not_exists(db_row);
 
         let removed_db_row = db_partition.insert_or_replace_row(db_row.clone());
 
+        self.avg_size.add(db_row);
+
+        if let Some(removed_db_row) = &removed_db_row {
+            self.avg_size.remove(removed_db_row);
+        }
+
         #[cfg(feature = "master-node")]
         if let Some(set_last_write_moment) = set_last_write_moment {
             self.last_write_moment = set_last_write_moment;
@@ -142,11 +162,13 @@ impl DbTable {
         db_row: &Arc<DbRow>,
         #[cfg(feature = "master-node")] set_last_write_moment: Option<DateTimeAsMicroseconds>,
     ) -> Option<PartitionKey> {
-        self.avg_size.add(db_row);
-
         let db_partition = self.partitions.add_partition_if_not_exists(db_row);
 
         let result = db_partition.insert_row(db_row.clone());
+
+        if result {
+            self.avg_size.add(db_row);
+        }
         #[cfg(feature = "master-node")]
         if result {
             if let Some(set_last_write_moment) = set_last_write_moment {
@@ -168,26 +190,71 @@ impl DbTable {
         db_rows: &[Arc<DbRow>],
         #[cfg(feature = "master-node")] set_last_write_moment: Option<DateTimeAsMicroseconds>,
     ) -> (PartitionKey, Vec<Arc<DbRow>>) {
-        for db_row in db_rows {
-            self.avg_size.add(db_row);
-        }
+        let (partition_key, outcome) = self.bulk_insert_or_replace_with_outcome(
+            partition_key,
+            db_rows,
+            #[cfg(feature = "master-node")]
+            set_last_write_moment,
+        );
+
+        (partition_key, outcome.replaced)
+    }
 
+    pub fn bulk_insert_or_replace_with_outcome(
+        &mut self,
+        partition_key: &impl PartitionKeyParameter,
+        db_rows: &[Arc<DbRow>],
+        #[cfg(feature = "master-node")] set_last_write_moment: Option<DateTimeAsMicroseconds>,
+    ) -> (PartitionKey, BulkInsertOutcome) {
         let db_partition = self.partitions.add_partition_if_not_exists(partition_key);
 
-        let result = db_partition.insert_or_replace_rows_bulk(db_rows);
+        let rows_before = db_partition.get_rows_amount();
+
+        let replaced =
+            db_partition.insert_or_replace_rows_bulk_with_avg_size(db_rows, &mut self.avg_size);
+
         #[cfg(feature = "master-node")]
         if let Some(set_last_write_moment) = set_last_write_moment {
             self.last_write_moment = set_last_write_moment;
             db_partition.last_write_moment = set_last_write_moment;
         }
 
-        (db_partition.partition_key.clone(), result)
+        let outcome = BulkInsertOutcome {
+            inserted: db_partition.get_rows_amount() - rows_before,
+            replaced,
+        };
+
+        (db_partition.partition_key.clone(), outcome)
     }
 
     #[inline]
     pub fn init_partition(&mut self, db_partition: DbPartition) {
+        if let Some(removed_partition) = self.partitions.remove(db_partition.partition_key.as_str())
+        {
+            self.remove_from_avg_size(&removed_partition);
+        }
+
+        for db_row in db_partition.get_all_rows() {
+            self.avg_size.add(db_row);
+        }
+
         self.partitions.insert(db_partition);
     }
+
+    // Same as init_partition, but partition which is already in the table is not replaced.
+    // Rejected partition is given back, so rows are not lost on a duplicate during the restore
+    pub fn restore_partition(&mut self, db_partition: DbPartition) -> Result<(), DbPartition> {
+        if self
+            .partitions
+            .get(db_partition.partition_key.as_str())
+            .is_some()
+        {
+            return Err(db_partition);
+        }
+
+        self.init_partition(db_partition);
+        Ok(())
+    }
 }
 
 /// Delete Operations
@@ -206,6 +273,7 @@ impl DbTable {
             let db_partition = self.partitions.get_mut(partition_key.as_str())?;
 
             let removed_row = db_partition.remove_row(row_key.as_str())?;
+            self.avg_size.remove(&removed_row);
             #[cfg(feature = "master-node")]
             if let Some(set_last_write_moment) = set_last_write_moment {
                 self.last_write_moment = DateTimeAsMicroseconds::now();
@@ -238,6 +306,10 @@ impl DbTable {
 
             let removed_rows = db_partition.remove_rows_bulk(row_keys)?;
 
+            for removed_row in removed_rows.iter() {
+                self.avg_size.remove(removed_row);
+            }
+
             #[cfg(feature = "master-node")]
             if let Some(set_last_write_moment) = set_last_write_moment {
                 self.last_write_moment = DateTimeAsMicroseconds::now();
@@ -266,6 +338,10 @@ impl DbTable {
     ) -> Option<DbPartition> {
         let removed_partition = self.partitions.remove(partition_key.as_str());
 
+        if let Some(removed_partition) = &removed_partition {
+            self.remove_from_avg_size(removed_partition);
+        }
+
         #[cfg(feature = "master-node")]
         if removed_partition.is_some() {
             if let Some(set_last_write_moment) = set_last_write_moment {
@@ -277,6 +353,13 @@ impl DbTable {
     }
 
     pub fn clear_table(&mut self) -> Option<SortedVecWithStrKey<DbPartition>> {
+        self.avg_size.clear();
         self.partitions.clear()
     }
+
+    fn remove_from_avg_size(&mut self, db_partition: &DbPartition) {
+        for db_row in db_partition.get_all_rows() {
+            self.avg_size.remove(db_row);
+        }
+    }
 }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use rust_extensions::{
     date_time::DateTimeAsMicroseconds,
     sorted_vec::{EntityWithStrKey, SortedVecWithStrKey},
@@ -7,6 +9,13 @@ use crate::db::PartitionKey;
 
 use super::{AvgSize, DataToGc, DbPartitionsContainer, DbTable, DbTableAttributes};
 
+// Partitions and rows are counted separately: a row of an expiring partition is not counted as a row
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExpirationBucket {
+    pub partitions: usize,
+    pub rows: usize,
+}
+
 pub struct PartitionLastWriteMoment {
     pub partition_key: PartitionKey,
     pub last_write_moment: DateTimeAsMicroseconds,
@@ -39,6 +48,51 @@ impl DbTable {
         result
     }
 
+    // Amount of partitions and rows which expire within each of the buckets from now.
+    // Buckets are cumulative: items which expire within a minute are counted in the hour bucket too.
+    // Items which are already expired but not collected yet are counted in every bucket
+    pub fn expiration_histogram(
+        &self,
+        now: DateTimeAsMicroseconds,
+        buckets: &[Duration],
+    ) -> Vec<ExpirationBucket> {
+        let mut result = Vec::with_capacity(buckets.len());
+
+        for bucket in buckets {
+            let moment =
+                DateTimeAsMicroseconds::new(now.unix_microseconds + bucket.as_micros() as i64);
+
+            let mut item = ExpirationBucket {
+                partitions: self.partitions.count_partitions_to_expire(moment),
+                rows: 0,
+            };
+
+            for db_partition in self.partitions.get_partitions() {
+                item.rows += db_partition.count_rows_to_expire(moment);
+            }
+
+            result.push(item);
+        }
+
+        result
+    }
+
+    // Big spread between the oldest and the newest write moments means some partitions are not updated.
+    // None if the table has no partitions
+    pub fn oldest_partition_write_moment(&self) -> Option<DateTimeAsMicroseconds> {
+        self.partitions
+            .get_partitions()
+            .map(|db_partition| db_partition.last_write_moment)
+            .min_by_key(|moment| moment.unix_microseconds)
+    }
+
+    pub fn newest_partition_write_moment(&self) -> Option<DateTimeAsMicroseconds> {
+        self.partitions
+            .get_partitions()
+            .map(|db_partition| db_partition.last_write_moment)
+            .max_by_key(|moment| moment.unix_microseconds)
+    }
+
     pub fn get_partitions_last_write_moment(
         &self,
     ) -> SortedVecWithStrKey<PartitionLastWriteMoment> {
@@ -54,10 +108,33 @@ impl DbTable {
         result
     }
 
+    // Rows of the partition expire together with the partition
+    pub fn update_partition_expiration(
+        &mut self,
+        partition_key: &str,
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> bool {
+        self.partitions
+            .update_partition_expiration(partition_key, expires)
+    }
+
     pub fn get_data_to_gc(&self, now: DateTimeAsMicroseconds) -> DataToGc {
+        self.compile_data_to_gc(&self.attributes, now)
+    }
+
+    // Data which GC would remove if the table had the proposed attributes. Nothing is changed
+    pub fn preview_gc(&self, proposed: DbTableAttributes, now: DateTimeAsMicroseconds) -> DataToGc {
+        self.compile_data_to_gc(&proposed, now)
+    }
+
+    fn compile_data_to_gc(
+        &self,
+        attributes: &DbTableAttributes,
+        now: DateTimeAsMicroseconds,
+    ) -> DataToGc {
         let mut result = DataToGc::new();
 
-        if let Some(max_partitions_amount) = self.attributes.max_partitions_amount {
+        if let Some(max_partitions_amount) = attributes.max_partitions_amount {
             if let Some(partitions_to_expire) = self
                 .partitions
                 .get_partitions_to_gc_by_max_amount(max_partitions_amount)
@@ -78,6 +155,12 @@ impl DbTable {
                 continue;
             }
 
+            // Expired partition is GCed as a whole instead of row by row
+            if db_partition.is_expired(now) {
+                result.add_partition_to_expire(db_partition.partition_key.clone());
+                continue;
+            }
+
             let rows_to_expire = db_partition.get_rows_to_expire(now);
 
             if rows_to_expire.len() > 0 {
@@ -85,7 +168,7 @@ impl DbTable {
             }
 
             //Find DBRows to GC by max amount
-            if let Some(max_rows_per_partition) = self.attributes.max_rows_per_partition_amount {
+            if let Some(max_rows_per_partition) = attributes.max_rows_per_partition_amount {
                 if let Some(rows_to_gc) = db_partition
                     .rows
                     .get_rows_to_gc_by_max_amount(max_rows_per_partition)
@@ -170,4 +253,208 @@ mod tests {
         assert_eq!(db_table.get_table_size(), db_row2.get_src_as_slice().len());
         assert_eq!(db_table.get_partitions_amount(), 1);
     }
+
+    #[test]
+    fn test_bulk_insert_or_replace_with_outcome() {
+        let mut db_table = DbTable::new(
+            "test-table".to_string(),
+            DbTableAttributes::create_default(),
+        );
+
+        let now = JsonTimeStamp::now();
+
+        let test_json = r#"{"PartitionKey": "test", "RowKey": "test1"}"#;
+        let db_row = DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &now).unwrap();
+
+        db_table.insert_row(&Arc::new(db_row), None);
+
+        let mut db_rows = Vec::new();
+
+        for row_key in ["test1", "test2", "test3"] {
+            let test_json = format!(r#"{{"PartitionKey": "test", "RowKey": "{}"}}"#, row_key);
+            let db_row =
+                DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &now).unwrap();
+            db_rows.push(Arc::new(db_row));
+        }
+
+        let (partition_key, outcome) =
+            db_table.bulk_insert_or_replace_with_outcome(&"test".to_string(), &db_rows, None);
+
+        assert_eq!(partition_key.as_str(), "test");
+        assert_eq!(outcome.inserted, 2);
+        assert_eq!(outcome.get_replaced_amount(), 1);
+        assert_eq!(outcome.replaced[0].get_row_key(), "test1");
+    }
+
+    #[test]
+    fn test_partition_ttl_cascades_to_rows() {
+        let mut db_table = DbTable::new(
+            "test-table".to_string(),
+            DbTableAttributes::create_default(),
+        );
+
+        let now = JsonTimeStamp::now();
+
+        for row_key in ["test1", "test2"] {
+            let test_json = format!(
+                r#"{{"PartitionKey": "test", "RowKey": "{}", "Expires": "2030-01-01T00:00:10"}}"#,
+                row_key
+            );
+            let db_row =
+                DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &now).unwrap();
+            db_table.insert_row(&Arc::new(db_row), None);
+        }
+
+        let partition_expires = DateTimeAsMicroseconds::from_str("2030-01-01T00:00:05").unwrap();
+        assert!(db_table.update_partition_expiration("test", Some(partition_expires)));
+
+        let before = DateTimeAsMicroseconds::from_str("2030-01-01T00:00:04").unwrap();
+        assert!(!db_table.get_data_to_gc(before).has_data_to_gc());
+
+        let data_to_gc = db_table.get_data_to_gc(partition_expires);
+        assert!(data_to_gc.has_partition_to_gc("test"));
+        assert_eq!(data_to_gc.db_rows.len(), 0);
+
+        let db_partition = db_table.get_partition("test").unwrap();
+        assert_eq!(db_partition.get_rows_to_expire(partition_expires).len(), 2);
+    }
+
+    #[test]
+    fn test_preview_gc() {
+        let mut db_table = DbTable::new(
+            "test-table".to_string(),
+            DbTableAttributes::create_default(),
+        );
+
+        let now = JsonTimeStamp::now();
+
+        for row_key in ["test1", "test2", "test3"] {
+            let test_json = format!(r#"{{"PartitionKey": "test", "RowKey": "{}"}}"#, row_key);
+            let db_row =
+                DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &now).unwrap();
+            db_table.insert_row(&Arc::new(db_row), None);
+        }
+
+        assert!(!db_table.get_data_to_gc(now.date_time).has_data_to_gc());
+
+        let mut proposed = DbTableAttributes::create_default();
+        proposed.max_rows_per_partition_amount = Some(1);
+
+        let data_to_gc = db_table.preview_gc(proposed, now.date_time);
+        let rows_to_gc = data_to_gc.db_rows.get("test").unwrap();
+        assert_eq!(rows_to_gc.rows.len(), 2);
+
+        assert_eq!(db_table.get_rows_amount(), 3);
+        assert!(db_table.attributes.max_rows_per_partition_amount.is_none());
+    }
+
+    #[test]
+    fn test_expiration_histogram() {
+        let mut db_table = DbTable::new(
+            "test-table".to_string(),
+            DbTableAttributes::create_default(),
+        );
+
+        let now = JsonTimeStamp::now();
+
+        for (row_key, expires) in [
+            ("test1", "2030-01-01T00:00:30"),
+            ("test2", "2030-01-01T00:30:00"),
+            ("test3", "2030-01-02T00:00:00"),
+        ] {
+            let test_json = format!(
+                r#"{{"PartitionKey": "test", "RowKey": "{}", "Expires": "{}"}}"#,
+                row_key, expires
+            );
+            let db_row =
+                DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &now).unwrap();
+            db_table.insert_row(&Arc::new(db_row), None);
+        }
+
+        let partition_expires = DateTimeAsMicroseconds::from_str("2030-01-01T00:00:45").unwrap();
+        assert!(db_table.update_partition_expiration("test", Some(partition_expires)));
+
+        let histogram = db_table.expiration_histogram(
+            DateTimeAsMicroseconds::from_str("2030-01-01T00:00:00").unwrap(),
+            &[
+                Duration::from_secs(60),
+                Duration::from_secs(60 * 60),
+                Duration::from_secs(24 * 60 * 60),
+            ],
+        );
+
+        let partitions: Vec<usize> = histogram.iter().map(|item| item.partitions).collect();
+        let rows: Vec<usize> = histogram.iter().map(|item| item.rows).collect();
+
+        assert_eq!(partitions, vec![1, 1, 1]);
+        assert_eq!(rows, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_oldest_and_newest_partition_write_moments() {
+        let mut db_table = DbTable::new(
+            "test-table".to_string(),
+            DbTableAttributes::create_default(),
+        );
+
+        assert!(db_table.oldest_partition_write_moment().is_none());
+        assert!(db_table.newest_partition_write_moment().is_none());
+
+        let now = JsonTimeStamp::now();
+
+        for (partition_key, moment) in [("pk1", 20), ("pk2", 10), ("pk3", 30)] {
+            let test_json = format!(r#"{{"PartitionKey": "{}", "RowKey": "rk"}}"#, partition_key);
+            let db_row =
+                DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &now).unwrap();
+            db_table.insert_row(&Arc::new(db_row), Some(DateTimeAsMicroseconds::new(moment)));
+        }
+
+        assert_eq!(
+            db_table
+                .oldest_partition_write_moment()
+                .unwrap()
+                .unix_microseconds,
+            10
+        );
+        assert_eq!(
+            db_table
+                .newest_partition_write_moment()
+                .unwrap()
+                .unix_microseconds,
+            30
+        );
+    }
+
+    #[test]
+    fn test_restore_duplicate_partition_is_rejected() {
+        let mut db_table = DbTable::new(
+            "test-table".to_string(),
+            DbTableAttributes::create_default(),
+        );
+
+        let now = JsonTimeStamp::now();
+
+        let create_partition = |row_key: &str| {
+            let test_json = format!(r#"{{"PartitionKey": "test", "RowKey": "{}"}}"#, row_key);
+            let db_row =
+                DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &now).unwrap();
+
+            let mut db_partition = crate::db::DbPartition::new("test".to_string());
+            db_partition.insert_row(Arc::new(db_row));
+            db_partition
+        };
+
+        assert!(db_table
+            .restore_partition(create_partition("test1"))
+            .is_ok());
+
+        let rejected = db_table
+            .restore_partition(create_partition("test2"))
+            .unwrap_err();
+        assert!(rejected.get_row("test2").is_some());
+
+        let db_partition = db_table.get_partition("test").unwrap();
+        assert_eq!(db_partition.get_rows_amount(), 1);
+        assert!(db_partition.get_row("test1").is_some());
+    }
 }
@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+
+use super::DbTable;
+
+impl DbTable {
+    // Union of the top level field names of the first sample rows.
+    // Columns go in the order they are met for the first time
+    pub fn infer_columns(&self, sample: usize) -> Vec<String> {
+        let mut result = Vec::new();
+        let mut met = HashSet::new();
+
+        for (_, db_row) in self.get_all_rows(None, Some(sample)) {
+            for name in db_row.get_top_level_field_names() {
+                if met.insert(name.clone()) {
+                    result.push(name);
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(feature = "master-node")]
+#[cfg(test)]
+mod tests {
+    use crate::db::{db_table::test_utils::insert, DbTable, DbTableAttributes};
+
+    #[test]
+    fn test_infer_columns() {
+        let mut db_table = DbTable::new("test".to_string(), DbTableAttributes::create_default());
+        insert(
+            &mut db_table,
+            r#"{"PartitionKey": "pk", "RowKey": "rk1", "Name": "a"}"#,
+        );
+        insert(
+            &mut db_table,
+            r#"{"PartitionKey": "pk", "RowKey": "rk2", "Amount": 1}"#,
+        );
+
+        let columns = db_table.infer_columns(10);
+
+        assert_eq!(
+            columns,
+            vec!["PartitionKey", "RowKey", "TimeStamp", "Name", "Amount"]
+        );
+
+        let columns = db_table.infer_columns(1);
+        assert!(!columns.contains(&"Amount".to_string()));
+    }
+}
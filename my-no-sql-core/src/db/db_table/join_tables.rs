@@ -0,0 +1,188 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::db::DbRow;
+
+use super::{AllDbRowsIterator, DbTable};
+
+// Hash join of two tables by the keys extracted from the rows. Lookup is built from the smaller
+// table and the larger one is streamed. Rows without the key are not joined
+pub fn join_tables<'a, TKeyOfA, TKeyOfB>(
+    a: &'a DbTable,
+    b: &'a DbTable,
+    key_of_a: TKeyOfA,
+    key_of_b: TKeyOfB,
+) -> impl Iterator<Item = (&'a Arc<DbRow>, &'a Arc<DbRow>)> + 'a
+where
+    TKeyOfA: Fn(&DbRow) -> Option<&str> + 'a,
+    TKeyOfB: Fn(&DbRow) -> Option<&str> + 'a,
+{
+    let result: Box<dyn Iterator<Item = (&'a Arc<DbRow>, &'a Arc<DbRow>)> + 'a> =
+        if a.get_rows_amount() <= b.get_rows_amount() {
+            let lookup = build_lookup(a, key_of_a);
+
+            Box::new(HashJoinIterator::new(lookup, b, key_of_b))
+        } else {
+            let lookup = build_lookup(b, key_of_b);
+
+            let rows = HashJoinIterator::new(lookup, a, key_of_a);
+
+            Box::new(rows.map(|(b_row, a_row)| (a_row, b_row)))
+        };
+
+    result
+}
+
+fn build_lookup<'a>(
+    db_table: &'a DbTable,
+    key_of: impl Fn(&DbRow) -> Option<&str>,
+) -> HashMap<&'a str, Vec<&'a Arc<DbRow>>> {
+    let mut result: HashMap<&'a str, Vec<&'a Arc<DbRow>>> = HashMap::new();
+
+    for (_, db_row) in db_table.get_all_rows(None, None) {
+        if let Some(key) = key_of(db_row.as_ref()) {
+            result.entry(key).or_default().push(db_row);
+        }
+    }
+
+    result
+}
+
+fn get_matches<'l, 'a>(
+    lookup: &'l HashMap<&'a str, Vec<&'a Arc<DbRow>>>,
+    key: &str,
+) -> &'l [&'a Arc<DbRow>] {
+    match lookup.get(key) {
+        Some(db_rows) => db_rows.as_slice(),
+        None => &[],
+    }
+}
+
+// Streams the probe table and yields (lookup row, probe row) pairs. Keeps the key of the current
+// probe row and the position in its matches, so the lookup is never copied
+struct HashJoinIterator<'a, TKeyOf: Fn(&DbRow) -> Option<&str>> {
+    lookup: HashMap<&'a str, Vec<&'a Arc<DbRow>>>,
+    probe_rows: AllDbRowsIterator<'a>,
+    key_of: TKeyOf,
+    current: Option<(&'a Arc<DbRow>, &'a str)>,
+    index: usize,
+}
+
+impl<'a, TKeyOf: Fn(&DbRow) -> Option<&str>> HashJoinIterator<'a, TKeyOf> {
+    fn new(
+        lookup: HashMap<&'a str, Vec<&'a Arc<DbRow>>>,
+        probe_table: &'a DbTable,
+        key_of: TKeyOf,
+    ) -> Self {
+        Self {
+            lookup,
+            probe_rows: probe_table.get_all_rows(None, None),
+            key_of,
+            current: None,
+            index: 0,
+        }
+    }
+}
+
+impl<'a, TKeyOf: Fn(&DbRow) -> Option<&str>> Iterator for HashJoinIterator<'a, TKeyOf> {
+    type Item = (&'a Arc<DbRow>, &'a Arc<DbRow>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((probe_row, key)) = self.current {
+                if let Some(lookup_row) = get_matches(&self.lookup, key).get(self.index).copied() {
+                    self.index += 1;
+                    return Some((lookup_row, probe_row));
+                }
+
+                self.current = None;
+            }
+
+            let (_, probe_row) = self.probe_rows.next()?;
+
+            if let Some(key) = (self.key_of)(probe_row.as_ref()) {
+                self.current = Some((probe_row, key));
+                self.index = 0;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "master-node")]
+#[cfg(test)]
+mod tests {
+    use crate::db::{db_table::test_utils::insert, DbTable, DbTableAttributes};
+
+    #[test]
+    fn test_join_tables() {
+        let mut clients = DbTable::new("clients".to_string(), DbTableAttributes::create_default());
+        insert(
+            &mut clients,
+            r#"{"PartitionKey": "c", "RowKey": "client1"}"#,
+        );
+        insert(
+            &mut clients,
+            r#"{"PartitionKey": "c", "RowKey": "client2"}"#,
+        );
+
+        let mut orders = DbTable::new("orders".to_string(), DbTableAttributes::create_default());
+        for (order_id, client_id) in [("o1", "client1"), ("o2", "client1"), ("o3", "client3")] {
+            insert(
+                &mut orders,
+                format!(
+                    r#"{{"PartitionKey": "o", "RowKey": "{}", "ClientId": "{}"}}"#,
+                    order_id, client_id
+                )
+                .as_str(),
+            );
+        }
+
+        let mut result: Vec<(&str, &str)> = super::join_tables(
+            &orders,
+            &clients,
+            |db_row| db_row.get_field_by_pointer("/ClientId"),
+            |db_row| Some(db_row.get_row_key()),
+        )
+        .map(|(order, client)| (order.get_row_key(), client.get_row_key()))
+        .collect();
+
+        result.sort();
+
+        assert_eq!(result, vec![("o1", "client1"), ("o2", "client1")]);
+    }
+
+    #[test]
+    fn test_join_tables_with_several_matches_in_lookup() {
+        let mut clients = DbTable::new("clients".to_string(), DbTableAttributes::create_default());
+        for client_id in ["client1", "client2", "client3", "client4"] {
+            insert(
+                &mut clients,
+                format!(r#"{{"PartitionKey": "c", "RowKey": "{}"}}"#, client_id).as_str(),
+            );
+        }
+
+        let mut orders = DbTable::new("orders".to_string(), DbTableAttributes::create_default());
+        for (order_id, client_id) in [("o1", "client1"), ("o2", "client1"), ("o3", "client5")] {
+            insert(
+                &mut orders,
+                format!(
+                    r#"{{"PartitionKey": "o", "RowKey": "{}", "ClientId": "{}"}}"#,
+                    order_id, client_id
+                )
+                .as_str(),
+            );
+        }
+
+        let mut result: Vec<(&str, &str)> = super::join_tables(
+            &orders,
+            &clients,
+            |db_row| db_row.get_field_by_pointer("/ClientId"),
+            |db_row| Some(db_row.get_row_key()),
+        )
+        .map(|(order, client)| (order.get_row_key(), client.get_row_key()))
+        .collect();
+
+        result.sort();
+
+        assert_eq!(result, vec![("o1", "client1"), ("o2", "client1")]);
+    }
+}
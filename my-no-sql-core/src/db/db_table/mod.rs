@@ -27,3 +27,16 @@ mod all_db_rows_iterator;
 pub use all_db_rows_iterator::*;
 mod by_row_key_iterator;
 pub use by_row_key_iterator::*;
+mod bulk_insert_outcome;
+pub use bulk_insert_outcome::*;
+mod dangling_references;
+mod join_tables;
+pub use join_tables::*;
+mod infer_columns;
+mod move_row;
+mod restore_from_ndjson;
+pub use restore_from_ndjson::*;
+
+#[cfg(feature = "master-node")]
+#[cfg(test)]
+mod test_utils;
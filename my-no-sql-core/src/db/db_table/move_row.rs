@@ -0,0 +1,118 @@
+#[cfg(feature = "master-node")]
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+use std::sync::Arc;
+
+use crate::{
+    db::DbRow,
+    db_json_entity::{DbEntityParseFail, DbJsonEntity},
+};
+
+use super::DbTable;
+
+impl DbTable {
+    // Re-keys the row into another partition in one operation. PartitionKey is rewritten inside the row content.
+    // Nothing is changed and None is returned if there is no such row
+    // or the destination partition already has a row with the same RowKey
+    pub fn move_row(
+        &mut self,
+        from_partition_key: &str,
+        to_partition_key: &str,
+        row_key: &str,
+        #[cfg(feature = "master-node")] set_last_write_moment: Option<DateTimeAsMicroseconds>,
+    ) -> Option<Arc<DbRow>> {
+        let db_row = self
+            .partitions
+            .get(from_partition_key)?
+            .get_row_and_clone(row_key)?;
+
+        if from_partition_key == to_partition_key {
+            return Some(db_row);
+        }
+
+        if let Some(db_partition) = self.partitions.get(to_partition_key) {
+            if db_partition.get_row(row_key).is_some() {
+                return None;
+            }
+        }
+
+        let moved_db_row = Arc::new(rewrite_partition_key(&db_row, to_partition_key).ok()?);
+
+        self.remove_row(
+            &from_partition_key.to_string(),
+            &row_key.to_string(),
+            true,
+            #[cfg(feature = "master-node")]
+            set_last_write_moment,
+        )?;
+
+        self.insert_row(
+            &moved_db_row,
+            #[cfg(feature = "master-node")]
+            set_last_write_moment,
+        );
+
+        Some(moved_db_row)
+    }
+}
+
+fn rewrite_partition_key(db_row: &DbRow, partition_key: &str) -> Result<DbRow, DbEntityParseFail> {
+    // to_vec gives the content with the current Expires of the row
+    let raw = db_row.to_vec();
+    let db_json_entity = DbJsonEntity::from_slice(raw.as_slice())?;
+
+    let value = &db_json_entity.partition_key.value;
+
+    let mut result = Vec::with_capacity(raw.len() + partition_key.len());
+    result.extend_from_slice(&raw[..value.start]);
+    result.push(b'"');
+    result.extend_from_slice(
+        my_json::json_string_value::escape_json_string_value(partition_key)
+            .as_str()
+            .as_bytes(),
+    );
+    result.push(b'"');
+    result.extend_from_slice(&raw[value.end..]);
+
+    let db_json_entity = DbJsonEntity::from_slice(result.as_slice())?;
+    DbRow::try_new(db_json_entity, result)
+}
+
+#[cfg(feature = "master-node")]
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        db::{DbTable, DbTableAttributes},
+        db_json_entity::{DbJsonEntity, JsonTimeStamp},
+    };
+
+    #[test]
+    fn test_move_row() {
+        let mut db_table = DbTable::new("test".to_string(), DbTableAttributes::create_default());
+
+        let now = JsonTimeStamp::now();
+        let test_json = r#"{"PartitionKey": "from", "RowKey": "rk", "Expires": "2030-01-01T00:00:00", "Value": 1}"#;
+        let db_row = DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &now).unwrap();
+        db_table.insert_row(&Arc::new(db_row), None);
+
+        let moved = db_table.move_row("from", "to", "rk", None).unwrap();
+
+        assert_eq!(moved.get_partition_key(), "to");
+        assert_eq!(moved.get_row_key(), "rk");
+        assert!(db_table.get_partition("from").is_none());
+        assert!(db_table
+            .get_partition("to")
+            .unwrap()
+            .get_row("rk")
+            .is_some());
+        assert_eq!(db_table.get_expiration_index_rows_amount(), 1);
+        assert_eq!(db_table.get_table_size(), moved.get_src_as_slice().len());
+
+        let value: serde_json::Value = serde_json::from_slice(&moved.to_vec()).unwrap();
+        assert_eq!(value["PartitionKey"], "to");
+        assert_eq!(value["Value"], 1);
+
+        assert!(db_table.move_row("from", "to", "rk", None).is_none());
+    }
+}
@@ -0,0 +1,206 @@
+use std::{io::BufRead, sync::Arc};
+
+use my_json::json_reader::JsonFirstLineReader;
+use rust_extensions::array_of_bytes_iterator::SliceIterator;
+#[cfg(feature = "master-node")]
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+use crate::{
+    db::DbRow,
+    db_json_entity::{DbEntityParseFail, DbJsonEntity, JsonTimeStamp},
+};
+
+use super::DbTable;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampPolicy {
+    // TimeStamp of the backup is kept. Rows without it are stamped with now
+    KeepStored,
+    Restamp,
+}
+
+#[derive(Debug, Default)]
+pub struct RestoreStats {
+    pub restored: usize,
+    pub replaced: usize,
+    pub failed: usize,
+    // Line number starts from 1
+    pub first_error: Option<(usize, DbEntityParseFail)>,
+}
+
+impl DbTable {
+    // Loads rows written one json object per line. Empty lines are skipped.
+    // Rows which can not be parsed are skipped as well and are counted as failed
+    pub fn restore_from_ndjson(
+        &mut self,
+        mut reader: impl BufRead,
+        timestamp_policy: TimestampPolicy,
+    ) -> Result<RestoreStats, std::io::Error> {
+        let now = JsonTimeStamp::now();
+        #[cfg(feature = "master-node")]
+        let set_last_write_moment = Some(DateTimeAsMicroseconds::now());
+
+        let mut stats = RestoreStats::default();
+        let mut line = Vec::new();
+        let mut line_no = 0;
+
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+
+            line_no += 1;
+
+            let json = trim_line(&line);
+
+            if json.is_empty() {
+                continue;
+            }
+
+            let db_row = match parse_line(json, &now, timestamp_policy) {
+                Ok(db_row) => db_row,
+                Err(err) => {
+                    stats.failed += 1;
+
+                    if stats.first_error.is_none() {
+                        stats.first_error = Some((line_no, err));
+                    }
+
+                    continue;
+                }
+            };
+
+            let (_, replaced) = self.insert_or_replace_row(
+                &Arc::new(db_row),
+                #[cfg(feature = "master-node")]
+                set_last_write_moment,
+            );
+
+            stats.restored += 1;
+
+            if replaced.is_some() {
+                stats.replaced += 1;
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+fn parse_line(
+    json: &[u8],
+    now: &JsonTimeStamp,
+    timestamp_policy: TimestampPolicy,
+) -> Result<DbRow, DbEntityParseFail> {
+    let json_first_line_reader = JsonFirstLineReader::new(SliceIterator::new(json));
+
+    match timestamp_policy {
+        TimestampPolicy::KeepStored => {
+            DbJsonEntity::try_parse_into_db_row_keeping_time_stamp(json_first_line_reader, now)
+        }
+        TimestampPolicy::Restamp => {
+            DbJsonEntity::try_parse_into_db_row(json_first_line_reader, now)
+        }
+    }
+}
+
+fn trim_line(line: &[u8]) -> &[u8] {
+    let start = line
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(line.len());
+
+    let end = line
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map(|pos| pos + 1)
+        .unwrap_or(start);
+
+    &line[start..end]
+}
+
+#[cfg(feature = "master-node")]
+#[cfg(test)]
+mod tests {
+    use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+    use crate::db::{DbTable, DbTableAttributes};
+
+    use super::TimestampPolicy;
+
+    const BACKUP: &str = r#"{"PartitionKey": "pk", "RowKey": "rk1", "TimeStamp": "2020-01-01T00:00:00", "Value": 1}
+
+{"PartitionKey": "pk", "RowKey": "rk2", "TimeStamp": "2020-01-01T00:00:00", "Value": 2}
+{"RowKey": "rk3"}
+{"PartitionKey": "pk", "RowKey": "rk1", "TimeStamp": "2020-01-01T00:00:00", "Value": 3}
+"#;
+
+    #[test]
+    fn test_restore_keeps_stored_time_stamp() {
+        let mut db_table = DbTable::new("test".to_string(), DbTableAttributes::create_default());
+
+        let stats = db_table
+            .restore_from_ndjson(BACKUP.as_bytes(), TimestampPolicy::KeepStored)
+            .unwrap();
+
+        assert_eq!(stats.restored, 3);
+        assert_eq!(stats.replaced, 1);
+        assert_eq!(stats.failed, 1);
+        assert_eq!(stats.first_error.unwrap().0, 4);
+
+        assert_eq!(db_table.get_rows_amount(), 2);
+
+        let db_row = db_table
+            .get_partition("pk")
+            .unwrap()
+            .get_row("rk1")
+            .unwrap();
+        assert_eq!(
+            db_row.get_time_stamp_value().unix_microseconds,
+            DateTimeAsMicroseconds::from_str("2020-01-01T00:00:00")
+                .unwrap()
+                .unix_microseconds
+        );
+
+        let value: serde_json::Value = serde_json::from_slice(&db_row.to_vec()).unwrap();
+        assert_eq!(value["Value"], 3);
+    }
+
+    #[test]
+    fn test_restore_stamps_rows_without_time_stamp() {
+        let mut db_table = DbTable::new("test".to_string(), DbTableAttributes::create_default());
+
+        let started = DateTimeAsMicroseconds::now();
+
+        db_table
+            .restore_from_ndjson(
+                r#"{"PartitionKey": "pk", "RowKey": "rk"}"#.as_bytes(),
+                TimestampPolicy::KeepStored,
+            )
+            .unwrap();
+
+        let db_row = db_table.get_partition("pk").unwrap().get_row("rk").unwrap();
+        assert!(db_row.get_time_stamp_value().unix_microseconds >= started.unix_microseconds);
+    }
+
+    #[test]
+    fn test_restore_restamps() {
+        let mut db_table = DbTable::new("test".to_string(), DbTableAttributes::create_default());
+
+        let started = DateTimeAsMicroseconds::now();
+
+        let stats = db_table
+            .restore_from_ndjson(BACKUP.as_bytes(), TimestampPolicy::Restamp)
+            .unwrap();
+
+        assert_eq!(stats.restored, 3);
+
+        let db_row = db_table
+            .get_partition("pk")
+            .unwrap()
+            .get_row("rk2")
+            .unwrap();
+        assert!(db_row.get_time_stamp_value().unix_microseconds >= started.unix_microseconds);
+    }
+}
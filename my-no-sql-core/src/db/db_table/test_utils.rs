@@ -0,0 +1,11 @@
+use std::sync::Arc;
+
+use crate::db_json_entity::{DbJsonEntity, JsonTimeStamp};
+
+use super::DbTable;
+
+pub fn insert(db_table: &mut DbTable, json: &str) {
+    let now = JsonTimeStamp::now();
+    let db_row = DbJsonEntity::parse_into_db_row(json.as_bytes().into(), &now).unwrap();
+    db_table.insert_row(&Arc::new(db_row), None);
+}
@@ -4,3 +4,7 @@ pub const ROW_KEY: &str = "RowKey";
 pub const TIME_STAMP: &str = "TimeStamp";
 pub const TIME_STAMP_LOWER_CASE: &str = "timestamp";
 pub const EXPIRES: &str = "Expires";
+// Seconds from the moment the row is written. Resolved into Expires using the server time
+pub const RELATIVE_EXPIRES: &str = "RelativeExpires";
+
+pub const DEFAULT_MAX_KEY_LEN: usize = 1024;
@@ -30,6 +30,16 @@ impl DbJsonEntity {
     pub fn new(
         mut json_first_line_reader: JsonFirstLineReader<SliceIterator>,
     ) -> Result<Self, DbEntityParseFail> {
+        let src = json_first_line_reader.get_src_slice();
+        let json_start = get_json_start_offset(src);
+
+        if json_start > 0 {
+            let slice_iterator = SliceIterator::new(&src[json_start..]);
+            let mut result = Self::new(JsonFirstLineReader::new(slice_iterator))?;
+            result.shift_positions(json_start);
+            return Ok(result);
+        }
+
         let mut partition_key = None;
         let mut row_key = None;
         let mut expires = None;
@@ -78,20 +88,35 @@ impl DbJsonEntity {
             return Err(DbEntityParseFail::PartitionKeyIsTooLong);
         }
 
+        check_value(super::consts::PARTITION_KEY, &partition_key, raw)?;
+
         if partition_key.value.is_null(raw) {
             return Err(DbEntityParseFail::FieldPartitionKeyCanNotBeNull);
         }
 
+        if !partition_key.value.is_string(raw) {
+            return Err(DbEntityParseFail::FieldPartitionKeyMustBeString);
+        }
+
         if row_key.is_none() {
             return Err(DbEntityParseFail::FieldRowKeyIsRequired);
         }
 
         let row_key = row_key.unwrap();
 
+        check_value(super::consts::ROW_KEY, &row_key, raw)?;
+
         if row_key.value.is_null(raw) {
             return Err(DbEntityParseFail::FieldRowKeyCanNotBeNull);
         }
 
+        if !row_key.value.is_string(raw) {
+            return Err(DbEntityParseFail::FieldRowKeyMustBeString);
+        }
+
+        check_optional_value(super::consts::TIME_STAMP, time_stamp.as_ref(), raw)?;
+        check_optional_value(super::consts::EXPIRES, expires.as_ref(), raw)?;
+
         let result = Self {
             partition_key,
             row_key,
@@ -118,14 +143,90 @@ impl DbJsonEntity {
     }
 
     pub fn parse_into_db_row(
-        mut json_first_line_reader: JsonFirstLineReader<SliceIterator>,
+        json_first_line_reader: JsonFirstLineReader<SliceIterator>,
+        now: &JsonTimeStamp,
+    ) -> Result<DbRow, DbEntityParseFail> {
+        Self::parse_into_db_row_with_max_key_len(
+            json_first_line_reader,
+            now,
+            super::consts::DEFAULT_MAX_KEY_LEN,
+        )
+    }
+
+    pub fn parse_into_db_row_with_max_key_len(
+        json_first_line_reader: JsonFirstLineReader<SliceIterator>,
+        now: &JsonTimeStamp,
+        max_key_len: usize,
+    ) -> Result<DbRow, DbEntityParseFail> {
+        let (db_json_entity, raw) =
+            Self::compile_db_row_content(json_first_line_reader, now, max_key_len, false)?;
+        Ok(DbRow::new(db_json_entity, raw))
+    }
+
+    // Malformed input never panics here. Prefer it for the input which comes from outside
+    pub fn try_parse_into_db_row(
+        json_first_line_reader: JsonFirstLineReader<SliceIterator>,
+        now: &JsonTimeStamp,
+    ) -> Result<DbRow, DbEntityParseFail> {
+        Self::try_parse_into_db_row_with_max_key_len(
+            json_first_line_reader,
+            now,
+            super::consts::DEFAULT_MAX_KEY_LEN,
+        )
+    }
+
+    pub fn try_parse_into_db_row_with_max_key_len(
+        json_first_line_reader: JsonFirstLineReader<SliceIterator>,
+        now: &JsonTimeStamp,
+        max_key_len: usize,
+    ) -> Result<DbRow, DbEntityParseFail> {
+        let (db_json_entity, raw) =
+            Self::compile_db_row_content(json_first_line_reader, now, max_key_len, false)?;
+        DbRow::try_new(db_json_entity, raw)
+    }
+
+    // Same as try_parse_into_db_row, but TimeStamp of the content is kept.
+    // Rows without it are stamped with now
+    pub fn try_parse_into_db_row_keeping_time_stamp(
+        json_first_line_reader: JsonFirstLineReader<SliceIterator>,
         now: &JsonTimeStamp,
     ) -> Result<DbRow, DbEntityParseFail> {
+        let (db_json_entity, raw) = Self::compile_db_row_content(
+            json_first_line_reader,
+            now,
+            super::consts::DEFAULT_MAX_KEY_LEN,
+            true,
+        )?;
+        DbRow::try_new(db_json_entity, raw)
+    }
+
+    fn compile_db_row_content(
+        mut json_first_line_reader: JsonFirstLineReader<SliceIterator>,
+        now: &JsonTimeStamp,
+        max_key_len: usize,
+        keep_time_stamp: bool,
+    ) -> Result<(Self, Vec<u8>), DbEntityParseFail> {
+        let src = json_first_line_reader.get_src_slice();
+        let json_start = get_json_start_offset(src);
+
+        if json_start > 0 {
+            let slice_iterator = SliceIterator::new(&src[json_start..]);
+            return Self::compile_db_row_content(
+                JsonFirstLineReader::new(slice_iterator),
+                now,
+                max_key_len,
+                keep_time_stamp,
+            );
+        }
+
         let mut partition_key = None;
         let mut row_key = None;
         let mut expires = None;
         let mut time_stamp = None;
         let mut expires_value = None;
+        let mut expires_line = None;
+        let mut relative_expires = None;
+        let mut stored_time_stamp = None;
 
         let mut raw = DbRowContentCompiler::new(json_first_line_reader.get_src_slice().len());
 
@@ -134,7 +235,7 @@ impl DbJsonEntity {
 
             let line_ref = line.as_ref(&json_first_line_reader);
 
-            let name = line_ref.name.as_unescaped_name().unwrap();
+            let name = line_ref.name.as_unescaped_name()?;
             match name {
                 super::consts::PARTITION_KEY => {
                     partition_key = Some(raw.append(line_ref));
@@ -142,20 +243,39 @@ impl DbJsonEntity {
 
                 super::consts::ROW_KEY => {
                     row_key = Some(raw.append(line_ref));
-                    time_stamp = raw
-                        .append_str_value(super::consts::TIME_STAMP, now.as_str())
-                        .into();
+
+                    if !keep_time_stamp {
+                        time_stamp = raw
+                            .append_str_value(super::consts::TIME_STAMP, now.as_str())
+                            .into();
+                    }
                 }
                 super::consts::EXPIRES => {
                     expires_value = line.value.as_date_time(&json_first_line_reader);
-                    expires = Some(raw.append(line_ref));
+                    expires_line = Some(line);
+                }
+                super::consts::RELATIVE_EXPIRES => {
+                    relative_expires = parse_relative_expires(line_ref.value.as_bytes())?;
+
+                    if let Some(value) = relative_expires {
+                        if value < 0 {
+                            return Err(DbEntityParseFail::RelativeExpiresCanNotBeNegative);
+                        }
+                    }
+                }
+                super::consts::TIME_STAMP => {
+                    if keep_time_stamp {
+                        stored_time_stamp = line.value.as_date_time(&json_first_line_reader);
+                    }
                 }
-                super::consts::TIME_STAMP => {}
                 _ => {
                     if rust_extensions::str_utils::compare_strings_case_insensitive(
                         name,
                         super::consts::TIME_STAMP_LOWER_CASE,
                     ) {
+                        if keep_time_stamp {
+                            stored_time_stamp = line.value.as_date_time(&json_first_line_reader);
+                        }
                     } else {
                         raw.append(line_ref);
                     }
@@ -163,6 +283,28 @@ impl DbJsonEntity {
             }
         }
 
+        if keep_time_stamp {
+            let stored_time_stamp = stored_time_stamp.map(JsonTimeStamp::from_date_time);
+            let value = stored_time_stamp.as_ref().unwrap_or(now);
+            time_stamp = raw
+                .append_str_value(super::consts::TIME_STAMP, value.as_str())
+                .into();
+        }
+
+        // RelativeExpires wins over Expires, so producer clock does not affect the TTL
+        if let Some(relative_expires) = relative_expires {
+            let value = DateTimeAsMicroseconds::new(
+                now.date_time
+                    .unix_microseconds
+                    .saturating_add(relative_expires.saturating_mul(1_000_000)),
+            );
+            let value_as_json = JsonTimeStamp::from_date_time(value);
+            expires = Some(raw.append_str_value(super::consts::EXPIRES, value_as_json.as_str()));
+            expires_value = Some(value);
+        } else if let Some(expires_line) = expires_line {
+            expires = Some(raw.append(expires_line.as_ref(&json_first_line_reader)));
+        }
+
         if partition_key.is_none() {
             return Err(DbEntityParseFail::FieldPartitionKeyIsRequired);
         }
@@ -173,26 +315,46 @@ impl DbJsonEntity {
             return Err(DbEntityParseFail::PartitionKeyIsTooLong);
         }
 
-        if partition_key
-            .value
-            .is_null(json_first_line_reader.get_src_slice())
-        {
+        // Positions point into the compiled content, not into the source slice
+        let raw = raw.into_vec();
+
+        check_value(super::consts::PARTITION_KEY, &partition_key, &raw)?;
+
+        if partition_key.value.is_null(&raw) {
             return Err(DbEntityParseFail::FieldPartitionKeyCanNotBeNull);
         }
 
+        if !partition_key.value.is_string(&raw) {
+            return Err(DbEntityParseFail::FieldPartitionKeyMustBeString);
+        }
+
         if row_key.is_none() {
             return Err(DbEntityParseFail::FieldRowKeyIsRequired);
         }
 
         let row_key = row_key.unwrap();
 
-        if row_key
-            .value
-            .is_null(json_first_line_reader.get_src_slice())
-        {
+        check_value(super::consts::ROW_KEY, &row_key, &raw)?;
+
+        if row_key.value.is_null(&raw) {
             return Err(DbEntityParseFail::FieldRowKeyCanNotBeNull);
         }
 
+        if !row_key.value.is_string(&raw) {
+            return Err(DbEntityParseFail::FieldRowKeyMustBeString);
+        }
+
+        if is_key_too_long(&partition_key, max_key_len) {
+            return Err(DbEntityParseFail::PartitionKeyIsTooLong);
+        }
+
+        if is_key_too_long(&row_key, max_key_len) {
+            return Err(DbEntityParseFail::RowKeyIsTooLong);
+        }
+
+        check_optional_value(super::consts::TIME_STAMP, time_stamp.as_ref(), &raw)?;
+        check_optional_value(super::consts::EXPIRES, expires.as_ref(), &raw)?;
+
         let db_json_entity = Self {
             partition_key,
             row_key,
@@ -201,9 +363,20 @@ impl DbJsonEntity {
             expires_value,
         };
 
-        let result = DbRow::new(db_json_entity, raw.into_vec());
+        Ok((db_json_entity, raw))
+    }
+
+    fn shift_positions(&mut self, offset: usize) {
+        shift_position(&mut self.partition_key, offset);
+        shift_position(&mut self.row_key, offset);
 
-        Ok(result)
+        if let Some(time_stamp) = self.time_stamp.as_mut() {
+            shift_position(time_stamp, offset);
+        }
+
+        if let Some(expires) = self.expires.as_mut() {
+            shift_position(expires, offset);
+        }
     }
 
     pub fn get_partition_key<'s>(&self, raw: &'s [u8]) -> &'s str {
@@ -384,6 +557,73 @@ impl DbJsonEntity {
     }
 }
 
+const UTF8_BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+
+// Some producers send the json with UTF-8 BOM or whitespaces before the opening bracket
+fn get_json_start_offset(src: &[u8]) -> usize {
+    let mut result = if src.starts_with(&UTF8_BOM) {
+        UTF8_BOM.len()
+    } else {
+        0
+    };
+
+    while result < src.len() && src[result].is_ascii_whitespace() {
+        result += 1;
+    }
+
+    result
+}
+
+// Accepts seconds as a json number or as a string with a number. Null means there is no RelativeExpires
+fn parse_relative_expires(value: &[u8]) -> Result<Option<i64>, DbEntityParseFail> {
+    let value = String::from_utf8_lossy(value);
+
+    if value == "null" {
+        return Ok(None);
+    }
+
+    match value.trim_matches('"').parse::<i64>() {
+        Ok(value) => Ok(Some(value)),
+        Err(_) => Err(DbEntityParseFail::InvalidRelativeExpires(value.to_string())),
+    }
+}
+
+fn shift_position(position: &mut JsonKeyValuePosition, offset: usize) {
+    position.key.start += offset;
+    position.key.end += offset;
+    position.value.start += offset;
+    position.value.end += offset;
+}
+
+// Getters slice the content by the positions as is, so it is checked once when the entity is parsed
+fn check_value(
+    field_name: &'static str,
+    position: &JsonKeyValuePosition,
+    raw: &[u8],
+) -> Result<(), DbEntityParseFail> {
+    if !position.value.is_valid(raw) {
+        return Err(DbEntityParseFail::InvalidFieldValue(field_name));
+    }
+
+    Ok(())
+}
+
+fn check_optional_value(
+    field_name: &'static str,
+    position: Option<&JsonKeyValuePosition>,
+    raw: &[u8],
+) -> Result<(), DbEntityParseFail> {
+    match position {
+        Some(position) => check_value(field_name, position, raw),
+        None => Ok(()),
+    }
+}
+
+// Value position includes the surrounding double quotes
+fn is_key_too_long(position: &JsonKeyValuePosition, max_len: usize) -> bool {
+    position.value.len().saturating_sub(2) > max_len
+}
+
 fn replace_timestamp(
     raw: &mut Vec<u8>,
     time_stamp_position: &JsonKeyValuePosition,
@@ -649,4 +889,241 @@ mod tests {
                 .unix_microseconds
         );
     }
+
+    #[test]
+    fn test_partition_key_longer_than_max_key_len_is_rejected() {
+        let partition_key = "a".repeat(65);
+        let test_json = format!(r#"{{"PartitionKey":"{}","RowKey":"Rk"}}"#, partition_key);
+
+        let result = DbJsonEntity::parse_into_db_row_with_max_key_len(
+            test_json.as_bytes().into(),
+            &JsonTimeStamp::now(),
+            64,
+        );
+
+        assert!(matches!(
+            result,
+            Err(DbEntityParseFail::PartitionKeyIsTooLong)
+        ));
+    }
+
+    #[test]
+    fn test_row_key_longer_than_max_key_len_is_rejected() {
+        let row_key = "r".repeat(65);
+        let test_json = format!(r#"{{"PartitionKey":"Pk","RowKey":"{}"}}"#, row_key);
+
+        let result = DbJsonEntity::parse_into_db_row_with_max_key_len(
+            test_json.as_bytes().into(),
+            &JsonTimeStamp::now(),
+            64,
+        );
+
+        assert!(matches!(result, Err(DbEntityParseFail::RowKeyIsTooLong)));
+    }
+
+    #[test]
+    fn test_keys_within_max_key_len_are_accepted() {
+        let partition_key = "a".repeat(64);
+        let test_json = format!(r#"{{"PartitionKey":"{}","RowKey":"Rk"}}"#, partition_key);
+
+        let db_row = DbJsonEntity::parse_into_db_row_with_max_key_len(
+            test_json.as_bytes().into(),
+            &JsonTimeStamp::now(),
+            64,
+        )
+        .unwrap();
+
+        assert_eq!(db_row.get_partition_key(), partition_key.as_str());
+        assert_eq!(db_row.get_row_key(), "Rk");
+    }
+
+    #[test]
+    fn test_parse_json_with_bom() {
+        let mut test_json = vec![0xEF, 0xBB, 0xBF];
+        test_json.extend_from_slice(r#"{"PartitionKey":"Pk","RowKey":"Rk"}"#.as_bytes());
+
+        let db_row =
+            DbJsonEntity::parse_into_db_row(test_json.as_slice().into(), &JsonTimeStamp::now())
+                .unwrap();
+
+        assert_eq!(db_row.get_partition_key(), "Pk");
+        assert_eq!(db_row.get_row_key(), "Rk");
+
+        let db_json_entity = DbJsonEntity::from_slice(test_json.as_slice()).unwrap();
+
+        assert_eq!(db_json_entity.get_partition_key(&test_json), "Pk");
+        assert_eq!(db_json_entity.get_row_key(&test_json), "Rk");
+    }
+
+    #[test]
+    fn test_relative_expires_is_resolved_with_server_time() {
+        let test_json = r#"{"PartitionKey":"Pk","RowKey":"Rk","Expires":"2000-01-01T00:00:00","RelativeExpires":60}"#;
+
+        let now = JsonTimeStamp::from_date_time(DateTimeAsMicroseconds::new(1_700_000_000_000_000));
+
+        let db_row = DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &now).unwrap();
+
+        let db_json_entity = DbJsonEntity::from_slice(db_row.get_src_as_slice()).unwrap();
+
+        assert_eq!(
+            db_json_entity.expires_value.unwrap().unix_microseconds,
+            1_700_000_060_000_000
+        );
+
+        let raw = std::str::from_utf8(db_row.get_src_as_slice()).unwrap();
+        assert!(!raw.contains("RelativeExpires"));
+        assert!(!raw.contains("2000-01-01"));
+    }
+
+    #[test]
+    fn test_stored_time_stamp_is_kept() {
+        let test_json = r#"{"PartitionKey":"Pk","TimeStamp":"2020-01-01T00:00:00","RowKey":"Rk"}"#;
+
+        let db_row = DbJsonEntity::try_parse_into_db_row_keeping_time_stamp(
+            test_json.as_bytes().into(),
+            &JsonTimeStamp::now(),
+        )
+        .unwrap();
+
+        assert!(db_row.get_time_stamp().starts_with("2020-01-01T00:00:00"));
+        assert_eq!(db_row.get_row_key(), "Rk");
+    }
+
+    #[test]
+    fn test_negative_relative_expires_is_rejected() {
+        let test_json = r#"{"PartitionKey":"Pk","RowKey":"Rk","RelativeExpires":-60}"#;
+
+        let result =
+            DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &JsonTimeStamp::now());
+
+        assert!(matches!(
+            result,
+            Err(DbEntityParseFail::RelativeExpiresCanNotBeNegative)
+        ));
+    }
+
+    #[test]
+    fn test_not_a_number_relative_expires_is_rejected() {
+        for value in [r#""abc""#, "1.5", "true"] {
+            let test_json = format!(
+                r#"{{"PartitionKey":"Pk","RowKey":"Rk","Expires":"2030-01-01T00:00:00","RelativeExpires":{}}}"#,
+                value
+            );
+
+            let result =
+                DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &JsonTimeStamp::now());
+
+            assert!(matches!(
+                result,
+                Err(DbEntityParseFail::InvalidRelativeExpires(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_expires_is_kept_without_relative_expires() {
+        let test_json = r#"{"PartitionKey":"Pk","Expires":"2030-01-01T00:00:00","RowKey":"Rk"}"#;
+
+        let db_row =
+            DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &JsonTimeStamp::now())
+                .unwrap();
+
+        let db_json_entity = DbJsonEntity::from_slice(db_row.get_src_as_slice()).unwrap();
+
+        assert_eq!(
+            db_json_entity.get_expires(db_row.get_src_as_slice()),
+            Some("2030-01-01T00:00:00")
+        );
+    }
+
+    #[test]
+    fn test_parse_json_with_leading_whitespaces() {
+        let test_json = " \r\n\t {\"PartitionKey\":\"Pk\",\"RowKey\":\"Rk\"}";
+
+        let db_row =
+            DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &JsonTimeStamp::now())
+                .unwrap();
+
+        assert_eq!(db_row.get_partition_key(), "Pk");
+        assert_eq!(db_row.get_row_key(), "Rk");
+
+        let db_json_entity = DbJsonEntity::from_slice(test_json.as_bytes()).unwrap();
+
+        assert_eq!(db_json_entity.get_partition_key(test_json.as_bytes()), "Pk");
+        assert_eq!(db_json_entity.get_row_key(test_json.as_bytes()), "Rk");
+    }
+
+    #[test]
+    fn test_try_parse_into_db_row() {
+        let test_json = r#"{"PartitionKey":"Pk","RowKey":"Rk"}"#;
+
+        let db_row =
+            DbJsonEntity::try_parse_into_db_row(test_json.as_bytes().into(), &JsonTimeStamp::now())
+                .unwrap();
+
+        assert_eq!(db_row.get_partition_key(), "Pk");
+        assert_eq!(db_row.get_row_key(), "Rk");
+
+        let test_json = r#"{"PartitionKey":"Pk"}"#;
+
+        let result =
+            DbJsonEntity::try_parse_into_db_row(test_json.as_bytes().into(), &JsonTimeStamp::now());
+
+        assert!(matches!(
+            result,
+            Err(DbEntityParseFail::FieldRowKeyIsRequired)
+        ));
+    }
+
+    #[test]
+    fn test_malformed_json_is_rejected_without_panic() {
+        let now = JsonTimeStamp::now();
+
+        let cases: [&[u8]; 8] = [
+            b"",
+            b"{",
+            b"}",
+            b"{\"PartitionKey\":1,\"RowKey\":\"Rk\"}",
+            b"{\"PartitionKey\":\"Pk\",\"RowKey\":[]}",
+            b"{\"PartitionKey\":\"Pk\",\"RowKey\":\"Rk\",\"RelativeExpires\":-9223372036854775808}",
+            b"{\"PartitionKey\":\"\xFF\",\"RowKey\":\"Rk\"}",
+            &[0xEF, 0xBB, 0xBF],
+        ];
+
+        for case in cases {
+            if let Ok(entity) = DbJsonEntity::parse(case, &now) {
+                let _ = entity.get_partition_key();
+                let _ = entity.get_row_key();
+            }
+
+            if let Ok(db_row) = DbJsonEntity::try_parse_into_db_row(case.into(), &now) {
+                let _ = db_row.to_vec();
+            }
+        }
+
+        let result = DbJsonEntity::from_slice(b"{\"PartitionKey\":1,\"RowKey\":\"Rk\"}");
+
+        assert!(matches!(
+            result,
+            Err(DbEntityParseFail::FieldPartitionKeyMustBeString)
+        ));
+    }
+
+    #[test]
+    fn test_key_which_is_not_utf8_is_rejected() {
+        let src = b"{\"PartitionKey\":\"\xFF\",\"RowKey\":\"Rk\"}";
+
+        let result = DbJsonEntity::from_slice(src);
+        assert!(matches!(
+            result,
+            Err(DbEntityParseFail::InvalidFieldValue(field_name)) if field_name == "PartitionKey"
+        ));
+
+        let result =
+            DbJsonEntity::try_parse_into_db_row(src.as_slice().into(), &JsonTimeStamp::now());
+        assert!(matches!(
+            result,
+            Err(DbEntityParseFail::InvalidFieldValue(field_name)) if field_name == "PartitionKey"
+        ));
+    }
 }
@@ -6,8 +6,18 @@ pub enum DbEntityParseFail {
     FieldRowKeyIsRequired,
     FieldPartitionKeyCanNotBeNull,
     FieldRowKeyCanNotBeNull,
+    FieldPartitionKeyMustBeString,
+    FieldRowKeyMustBeString,
     JsonParseError(JsonParseError),
     PartitionKeyIsTooLong,
+    RowKeyIsTooLong,
+    RelativeExpiresCanNotBeNegative,
+    // RelativeExpires is not a whole number of seconds
+    InvalidRelativeExpires(String),
+    TimeStampIsRequired,
+    InvalidTimeStamp(String),
+    // Value is out of the content or is not utf8
+    InvalidFieldValue(&'static str),
 }
 
 impl From<JsonParseError> for DbEntityParseFail {
@@ -8,17 +8,38 @@ pub struct KeyValueContentPosition {
 
 impl KeyValueContentPosition {
     pub fn len(&self) -> usize {
-        self.end - self.start
+        self.end.saturating_sub(self.start)
     }
 
+    // Content is checked by is_valid when the entity is parsed
     pub fn get_value<'s>(&self, raw: &'s [u8]) -> &'s str {
         std::str::from_utf8(&raw[self.start..self.end]).unwrap()
     }
 
     pub fn get_str_value<'s>(&self, raw: &'s [u8]) -> &'s str {
+        if self.len() < 2 {
+            return "";
+        }
+
         std::str::from_utf8(&raw[self.start + 1..self.end - 1]).unwrap()
     }
 
+    // Json value starts and ends with ascii, so the content without the quotes stays utf8 as well
+    pub fn is_valid(&self, raw: &[u8]) -> bool {
+        match raw.get(self.start..self.end) {
+            Some(value) => std::str::from_utf8(value).is_ok(),
+            None => false,
+        }
+    }
+
+    pub fn is_string(&self, raw: &[u8]) -> bool {
+        if self.len() < 2 {
+            return false;
+        }
+
+        raw.get(self.start) == Some(&b'"') && raw.get(self.end - 1) == Some(&b'"')
+    }
+
     pub fn is_null(&self, raw: &[u8]) -> bool {
         self.get_value(raw) == "null"
     }
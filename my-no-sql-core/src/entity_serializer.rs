@@ -5,18 +5,114 @@ use serde::{de::DeserializeOwned, Serialize};
 
 use crate::db_json_entity::DbJsonEntity;
 
+// Json library entities are serialized and deserialized with. Output has to stay json compatible,
+// since the server and the readers parse it as json
+pub trait SerializerBackend {
+    fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, String>;
+    fn deserialize<T: DeserializeOwned>(data: &[u8]) -> Result<T, String>;
+}
+
+pub struct SerdeJsonBackend;
+
+impl SerializerBackend for SerdeJsonBackend {
+    fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+        serde_json::to_vec(value).map_err(|err| err.to_string())
+    }
+
+    fn deserialize<T: DeserializeOwned>(data: &[u8]) -> Result<T, String> {
+        serde_json::from_slice(data).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(feature = "simd-json")]
+pub struct SimdJsonBackend;
+
+#[cfg(feature = "simd-json")]
+impl SerializerBackend for SimdJsonBackend {
+    fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+        simd_json::serde::to_vec(value).map_err(|err| err.to_string())
+    }
+
+    // simd-json parses in place, so the data is copied into the buffer it is allowed to modify
+    fn deserialize<T: DeserializeOwned>(data: &[u8]) -> Result<T, String> {
+        let mut buffer = data.to_vec();
+        simd_json::serde::from_slice(buffer.as_mut_slice()).map_err(|err| err.to_string())
+    }
+}
+
 pub fn serialize<TMyNoSqlEntity>(entity: &TMyNoSqlEntity) -> Vec<u8>
 where
     TMyNoSqlEntity: MyNoSqlEntity + Serialize,
 {
-    serde_json::to_vec(&entity).unwrap()
+    serialize_with::<SerdeJsonBackend, TMyNoSqlEntity>(entity)
+}
+
+// Backend is picked by the entity type: MyNoSqlEntitySerializer of the entity calls it
+// with the backend of the table. #[my_no_sql_entity("table", simd_json)] generates such impl
+pub fn serialize_with<TBackend, TMyNoSqlEntity>(entity: &TMyNoSqlEntity) -> Vec<u8>
+where
+    TBackend: SerializerBackend,
+    TMyNoSqlEntity: MyNoSqlEntity + Serialize,
+{
+    TBackend::serialize(entity).unwrap()
+}
+
+pub fn serialize_canonical<TMyNoSqlEntity>(entity: &TMyNoSqlEntity) -> Vec<u8>
+where
+    TMyNoSqlEntity: MyNoSqlEntity + Serialize,
+{
+    let value = serde_json::to_value(entity).unwrap();
+    let mut result = Vec::new();
+    write_canonical(&value, &mut result);
+    result
+}
+
+fn write_canonical(value: &serde_json::Value, out: &mut Vec<u8>) {
+    match value {
+        serde_json::Value::Object(object) => {
+            let mut keys: Vec<&String> = object.keys().collect();
+            keys.sort();
+
+            out.push(b'{');
+            for (index, key) in keys.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+
+                serde_json::to_writer(&mut *out, key).unwrap();
+                out.push(b':');
+                write_canonical(&object[key.as_str()], out);
+            }
+            out.push(b'}');
+        }
+        serde_json::Value::Array(array) => {
+            out.push(b'[');
+            for (index, item) in array.iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+
+                write_canonical(item, out);
+            }
+            out.push(b']');
+        }
+        _ => serde_json::to_writer(&mut *out, value).unwrap(),
+    }
 }
 
 pub fn deserialize<TMyNoSqlEntity>(data: &[u8]) -> Result<TMyNoSqlEntity, String>
 where
     TMyNoSqlEntity: MyNoSqlEntity + DeserializeOwned,
 {
-    let parse_result: Result<TMyNoSqlEntity, _> = serde_json::from_slice(&data);
+    deserialize_with::<SerdeJsonBackend, TMyNoSqlEntity>(data)
+}
+
+pub fn deserialize_with<TBackend, TMyNoSqlEntity>(data: &[u8]) -> Result<TMyNoSqlEntity, String>
+where
+    TBackend: SerializerBackend,
+    TMyNoSqlEntity: MyNoSqlEntity + DeserializeOwned,
+{
+    let parse_result: Result<TMyNoSqlEntity, _> = TBackend::deserialize(data);
 
     match parse_result {
         Ok(el) => return Ok(el),
@@ -117,4 +213,160 @@ mod tests {
             dest
         );
     }
+
+    mod backends {
+        use my_no_sql_abstractions::MyNoSqlEntity;
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        pub struct TestEntity {
+            #[serde(rename = "PartitionKey")]
+            pub partition_key: String,
+            #[serde(rename = "RowKey")]
+            pub row_key: String,
+            pub value: f64,
+            pub text: String,
+            pub items: Vec<i64>,
+        }
+
+        impl MyNoSqlEntity for TestEntity {
+            const TABLE_NAME: &'static str = "test";
+            const LAZY_DESERIALIZATION: bool = false;
+            fn get_partition_key(&self) -> &str {
+                &self.partition_key
+            }
+            fn get_row_key(&self) -> &str {
+                &self.row_key
+            }
+            fn get_time_stamp(&self) -> i64 {
+                0
+            }
+        }
+
+        pub fn create_entity() -> TestEntity {
+            TestEntity {
+                partition_key: "PK".to_string(),
+                row_key: "RK".to_string(),
+                value: 1.5,
+                text: "Quote \" and unicode ü".to_string(),
+                items: vec![-1, 0, i64::MAX],
+            }
+        }
+
+        #[test]
+        fn test_default_backend_roundtrip() {
+            let entity = create_entity();
+
+            let serialized = crate::entity_serializer::serialize(&entity);
+            let deserialized: TestEntity =
+                crate::entity_serializer::deserialize(&serialized).unwrap();
+
+            assert_eq!(entity, deserialized);
+        }
+
+        #[cfg(feature = "simd-json")]
+        #[test]
+        fn test_simd_json_parity_with_serde_json() {
+            use crate::entity_serializer::{
+                deserialize_with, serialize_with, SerdeJsonBackend, SimdJsonBackend,
+            };
+
+            let entity = create_entity();
+
+            let by_serde_json = serialize_with::<SerdeJsonBackend, _>(&entity);
+            let by_simd_json = serialize_with::<SimdJsonBackend, _>(&entity);
+
+            let as_value_serde: serde_json::Value = serde_json::from_slice(&by_serde_json).unwrap();
+            let as_value_simd: serde_json::Value = serde_json::from_slice(&by_simd_json).unwrap();
+            assert_eq!(as_value_serde, as_value_simd);
+
+            let from_serde: TestEntity =
+                deserialize_with::<SimdJsonBackend, _>(&by_serde_json).unwrap();
+            let from_simd: TestEntity =
+                deserialize_with::<SerdeJsonBackend, _>(&by_simd_json).unwrap();
+
+            assert_eq!(from_serde, entity);
+            assert_eq!(from_simd, entity);
+        }
+    }
+
+    #[test]
+    fn test_serialize_canonical_does_not_depend_on_fields_order() {
+        use my_no_sql_abstractions::MyNoSqlEntity;
+        use serde::Serialize;
+        use std::collections::HashMap;
+
+        #[derive(Serialize)]
+        struct EntityA {
+            #[serde(rename = "PartitionKey")]
+            partition_key: String,
+            #[serde(rename = "RowKey")]
+            row_key: String,
+            value: i32,
+            tags: HashMap<String, i32>,
+        }
+
+        #[derive(Serialize)]
+        struct EntityB {
+            tags: HashMap<String, i32>,
+            value: i32,
+            #[serde(rename = "RowKey")]
+            row_key: String,
+            #[serde(rename = "PartitionKey")]
+            partition_key: String,
+        }
+
+        impl MyNoSqlEntity for EntityA {
+            const TABLE_NAME: &'static str = "test";
+            const LAZY_DESERIALIZATION: bool = false;
+            fn get_partition_key(&self) -> &str {
+                &self.partition_key
+            }
+            fn get_row_key(&self) -> &str {
+                &self.row_key
+            }
+            fn get_time_stamp(&self) -> i64 {
+                0
+            }
+        }
+
+        impl MyNoSqlEntity for EntityB {
+            const TABLE_NAME: &'static str = "test";
+            const LAZY_DESERIALIZATION: bool = false;
+            fn get_partition_key(&self) -> &str {
+                &self.partition_key
+            }
+            fn get_row_key(&self) -> &str {
+                &self.row_key
+            }
+            fn get_time_stamp(&self) -> i64 {
+                0
+            }
+        }
+
+        let mut tags = HashMap::new();
+        for i in 0..16 {
+            tags.insert(format!("tag{}", i), i);
+        }
+
+        let a = EntityA {
+            partition_key: "PK".to_string(),
+            row_key: "RK".to_string(),
+            value: 5,
+            tags: tags.clone(),
+        };
+
+        let b = EntityB {
+            partition_key: "PK".to_string(),
+            row_key: "RK".to_string(),
+            value: 5,
+            tags,
+        };
+
+        let a = super::serialize_canonical(&a);
+        let b = super::serialize_canonical(&b);
+
+        assert_eq!(a, b);
+        assert!(a.starts_with(br#"{"PartitionKey":"PK","RowKey":"RK","tags":{"tag0":0,"#));
+    }
 }
@@ -27,6 +27,13 @@ impl<TOwnedType: Clone + ExpirationIndex<TOwnedType>> ExpirationIndexItem<TOwned
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexInconsistency {
+    AmountMismatch { amount: usize, items_in_index: usize },
+    NotSorted { position: usize },
+    EmptyMoment { moment: i64 },
+}
+
 pub struct ExpirationIndexContainer<TOwnedType: Clone + ExpirationIndex<TOwnedType>> {
     index: Vec<ExpirationIndexItem<TOwnedType>>,
     amount: usize,
@@ -69,6 +76,8 @@ impl<TOwnedType: Clone + ExpirationIndex<TOwnedType>> ExpirationIndexContainer<T
         }
 
         self.amount += 1;
+
+        self.validate_in_debug();
     }
 
     pub fn update(
@@ -117,6 +126,8 @@ impl<TOwnedType: Clone + ExpirationIndex<TOwnedType>> ExpirationIndexContainer<T
         }
 
         self.amount -= 1;
+
+        self.validate_in_debug();
     }
 
     pub fn get_items_to_expire<TResult>(
@@ -138,6 +149,15 @@ impl<TOwnedType: Clone + ExpirationIndex<TOwnedType>> ExpirationIndexContainer<T
         result
     }
 
+    // Amount of items which expire at or before the moment
+    pub fn count_items_to_expire(&self, moment: DateTimeAsMicroseconds) -> usize {
+        let to = self
+            .index
+            .partition_point(|itm| itm.moment.unix_microseconds <= moment.unix_microseconds);
+
+        self.index[..to].iter().map(|itm| itm.items.len()).sum()
+    }
+
     pub fn has_data_with_expiration_moment(
         &self,
         expiration_moment: DateTimeAsMicroseconds,
@@ -151,7 +171,68 @@ impl<TOwnedType: Clone + ExpirationIndex<TOwnedType>> ExpirationIndexContainer<T
 
     pub fn clear(&mut self) {
         self.index.clear();
+        self.amount = 0;
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        for expiration_item in self.index.iter_mut() {
+            expiration_item.items.shrink_to_fit();
+        }
+
+        self.index.shrink_to_fit();
+    }
+
+    pub fn capacity_overhead_bytes(&self) -> usize {
+        let mut result = (self.index.capacity() - self.index.len())
+            * std::mem::size_of::<ExpirationIndexItem<TOwnedType>>();
+
+        for expiration_item in self.index.iter() {
+            result += (expiration_item.items.capacity() - expiration_item.items.len())
+                * std::mem::size_of::<TOwnedType>();
+        }
+
+        result
+    }
+
+    pub fn validate(&self) -> Result<(), IndexInconsistency> {
+        let mut items_in_index = 0;
+
+        for (position, expiration_item) in self.index.iter().enumerate() {
+            if expiration_item.items.is_empty() {
+                return Err(IndexInconsistency::EmptyMoment {
+                    moment: expiration_item.moment.unix_microseconds,
+                });
+            }
+
+            if position > 0 {
+                let prev_moment = self.index[position - 1].moment.unix_microseconds;
+                if prev_moment >= expiration_item.moment.unix_microseconds {
+                    return Err(IndexInconsistency::NotSorted { position });
+                }
+            }
+
+            items_in_index += expiration_item.items.len();
+        }
+
+        if items_in_index != self.amount {
+            return Err(IndexInconsistency::AmountMismatch {
+                amount: self.amount,
+                items_in_index,
+            });
+        }
+
+        Ok(())
+    }
+
+    #[cfg(all(debug_assertions, feature = "debug_expiration_index"))]
+    fn validate_in_debug(&self) {
+        if let Err(err) = self.validate() {
+            panic!("Expiration index is inconsistent: {:?}", err);
+        }
     }
+
+    #[cfg(not(all(debug_assertions, feature = "debug_expiration_index")))]
+    fn validate_in_debug(&self) {}
 }
 
 #[cfg(test)]
@@ -218,6 +299,76 @@ mod tests {
                     .map(|itm| itm.moment.unix_microseconds)
                     .collect::<Vec<_>>()
             );
+
+            assert!(index.validate().is_ok());
+        }
+
+        #[test]
+        fn test_count_items_to_expire() {
+            let mut index = ExpirationIndexContainer::new();
+
+            for (key, expires) in [("1", 10), ("2", 20), ("3", 20), ("4", 30)] {
+                index.add(&TestExpirationItem {
+                    key: key.to_string(),
+                    expires: DateTimeAsMicroseconds::new(expires).into(),
+                });
+            }
+
+            let count = |moment| index.count_items_to_expire(DateTimeAsMicroseconds::new(moment));
+
+            assert_eq!(count(5), 0);
+            assert_eq!(count(20), 3);
+            assert_eq!(count(25), 3);
+            assert_eq!(count(30), 4);
+        }
+
+        #[test]
+        fn test_validate_after_update_remove_and_clear() {
+            let mut index = ExpirationIndexContainer::new();
+
+            let mut item = TestExpirationItem {
+                key: "1".to_string(),
+                expires: DateTimeAsMicroseconds::new(1).into(),
+            };
+
+            index.add(&item);
+
+            let old_expires = item.expires;
+            item.expires = DateTimeAsMicroseconds::new(3).into();
+            index.update(old_expires, &item);
+
+            assert_eq!(index.len(), 1);
+            assert!(index.validate().is_ok());
+
+            index.remove(&item);
+            assert_eq!(index.len(), 0);
+            assert!(index.validate().is_ok());
+
+            index.add(&item);
+            index.clear();
+            assert_eq!(index.len(), 0);
+            assert!(index.validate().is_ok());
+        }
+
+        #[test]
+        fn test_validate_detects_amount_mismatch() {
+            let mut index = ExpirationIndexContainer::new();
+
+            let item = TestExpirationItem {
+                key: "1".to_string(),
+                expires: DateTimeAsMicroseconds::new(1).into(),
+            };
+
+            index.add(&item);
+            index.amount = 2;
+
+            assert_eq!(
+                index.validate(),
+                Err(crate::IndexInconsistency::AmountMismatch {
+                    amount: 2,
+                    items_in_index: 1
+                })
+            );
         }
     }
 }
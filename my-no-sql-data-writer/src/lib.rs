@@ -1,2 +1,4 @@
 mod my_no_sql_data_writer;
 pub use my_no_sql_data_writer::*;
+#[cfg(test)]
+mod test_fixtures;
@@ -0,0 +1,156 @@
+use my_no_sql_abstractions::MyNoSqlEntitySerializer;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggOp {
+    Sum,
+    Min,
+    Max,
+    Avg,
+    Count,
+}
+
+impl AggOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AggOp::Sum => "sum",
+            AggOp::Min => "min",
+            AggOp::Max => "max",
+            AggOp::Avg => "avg",
+            AggOp::Count => "count",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AggregateHttpContract {
+    #[serde(rename = "value")]
+    pub value: f64,
+}
+
+// Client side aggregation. Used if server does not support aggregation endpoint.
+// Rows without the field or with not numeric value are skipped
+pub struct Aggregator {
+    op: AggOp,
+    count: usize,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Aggregator {
+    pub fn new(op: AggOp) -> Self {
+        Self {
+            op,
+            count: 0,
+            sum: 0.0,
+            min: f64::MAX,
+            max: f64::MIN,
+        }
+    }
+
+    pub fn add_entity<TEntity: MyNoSqlEntitySerializer>(&mut self, entity: &TEntity, field: &str) {
+        let json: serde_json::Value =
+            match serde_json::from_slice(entity.serialize_entity().as_slice()) {
+                Ok(json) => json,
+                Err(_) => return,
+            };
+
+        if let Some(value) = get_numeric_value(&json, field) {
+            self.add(value);
+        }
+    }
+
+    pub fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    // Min, Max and Avg of no values are NaN
+    pub fn get_result(&self) -> f64 {
+        if self.count == 0 {
+            return match self.op {
+                AggOp::Sum | AggOp::Count => 0.0,
+                AggOp::Min | AggOp::Max | AggOp::Avg => f64::NAN,
+            };
+        }
+
+        match self.op {
+            AggOp::Sum => self.sum,
+            AggOp::Min => self.min,
+            AggOp::Max => self.max,
+            AggOp::Avg => self.sum / self.count as f64,
+            AggOp::Count => self.count as f64,
+        }
+    }
+}
+
+pub fn aggregate_entities<'s, TEntity: MyNoSqlEntitySerializer + 's>(
+    entities: impl Iterator<Item = &'s TEntity>,
+    field: &str,
+    op: AggOp,
+) -> f64 {
+    let mut aggregator = Aggregator::new(op);
+
+    for entity in entities {
+        aggregator.add_entity(entity, field);
+    }
+
+    aggregator.get_result()
+}
+
+fn get_numeric_value(json: &serde_json::Value, field: &str) -> Option<f64> {
+    match json.get(field)? {
+        serde_json::Value::Number(value) => value.as_f64(),
+        serde_json::Value::String(value) => value.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AggOp, Aggregator};
+
+    fn aggregate(op: AggOp, values: &[f64]) -> f64 {
+        let mut aggregator = Aggregator::new(op);
+
+        for value in values {
+            aggregator.add(*value);
+        }
+
+        aggregator.get_result()
+    }
+
+    #[test]
+    fn test_aggregation() {
+        let values = [3.0, -1.0, 4.0];
+
+        assert_eq!(aggregate(AggOp::Sum, &values), 6.0);
+        assert_eq!(aggregate(AggOp::Min, &values), -1.0);
+        assert_eq!(aggregate(AggOp::Max, &values), 4.0);
+        assert_eq!(aggregate(AggOp::Avg, &values), 2.0);
+        assert_eq!(aggregate(AggOp::Count, &values), 3.0);
+    }
+
+    #[test]
+    fn test_aggregation_of_no_values() {
+        assert_eq!(aggregate(AggOp::Sum, &[]), 0.0);
+        assert_eq!(aggregate(AggOp::Count, &[]), 0.0);
+        assert!(aggregate(AggOp::Avg, &[]).is_nan());
+        assert!(aggregate(AggOp::Min, &[]).is_nan());
+    }
+
+    #[test]
+    fn test_numeric_value_is_read_from_number_or_string() {
+        let json: serde_json::Value =
+            serde_json::from_str(r#"{"a":1.5,"b":"2","c":"x","d":null}"#).unwrap();
+
+        assert_eq!(super::get_numeric_value(&json, "a"), Some(1.5));
+        assert_eq!(super::get_numeric_value(&json, "b"), Some(2.0));
+        assert_eq!(super::get_numeric_value(&json, "c"), None);
+        assert_eq!(super::get_numeric_value(&json, "d"), None);
+        assert_eq!(super::get_numeric_value(&json, "e"), None);
+    }
+}
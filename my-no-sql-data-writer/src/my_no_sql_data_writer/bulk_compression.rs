@@ -0,0 +1,136 @@
+use std::io::Write;
+
+use flate2::{write::GzEncoder, Compression};
+use flurl::FlUrl;
+
+pub const DEFAULT_BULK_COMPRESSION_MIN_SIZE: usize = 64 * 1024;
+
+// Bulk bodies of at least min_size bytes are sent gzipped.
+// Smaller ones are sent as they are, since compressing them is not worth the cpu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BulkCompression {
+    pub min_size: usize,
+}
+
+impl Default for BulkCompression {
+    fn default() -> Self {
+        Self {
+            min_size: DEFAULT_BULK_COMPRESSION_MIN_SIZE,
+        }
+    }
+}
+
+const CONTENT_ENCODING_HEADER: &str = "Content-Encoding";
+const GZIP_CONTENT_ENCODING: &str = "gzip";
+
+impl BulkCompression {
+    pub fn apply(
+        compression: Option<BulkCompression>,
+        flurl: FlUrl,
+        body: Option<Vec<u8>>,
+    ) -> (FlUrl, Option<Vec<u8>>) {
+        match Self::compress(compression, body) {
+            (body, Some(content_encoding)) => (
+                flurl.with_header(CONTENT_ENCODING_HEADER, content_encoding),
+                body,
+            ),
+            (body, None) => (flurl, body),
+        }
+    }
+
+    // Body as it is sent and the Content-Encoding it is sent with
+    pub fn compress(
+        compression: Option<BulkCompression>,
+        body: Option<Vec<u8>>,
+    ) -> (Option<Vec<u8>>, Option<&'static str>) {
+        let (compression, body) = match (compression, body) {
+            (Some(compression), Some(body)) => (compression, body),
+            (_, body) => return (body, None),
+        };
+
+        if body.len() < compression.min_size {
+            return (Some(body), None);
+        }
+
+        match gzip(&body) {
+            Some(compressed) => (Some(compressed), Some(GZIP_CONTENT_ENCODING)),
+            None => (Some(body), None),
+        }
+    }
+}
+
+fn gzip(src: &[u8]) -> Option<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::with_capacity(src.len() / 4), Compression::fast());
+    encoder.write_all(src).ok()?;
+    encoder.finish().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+
+    use super::{gzip, BulkCompression, GZIP_CONTENT_ENCODING};
+
+    fn gunzip(src: &[u8]) -> String {
+        let mut decompressed = String::new();
+        GzDecoder::new(src)
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        decompressed
+    }
+
+    #[test]
+    fn test_gzip() {
+        let src = r#"[{"PartitionKey":"pk","RowKey":"rk"}]"#.repeat(100);
+
+        let compressed = gzip(src.as_bytes()).unwrap();
+        assert!(compressed.len() < src.len());
+
+        assert_eq!(gunzip(compressed.as_slice()), src);
+    }
+
+    #[test]
+    fn test_body_below_min_size_is_sent_as_is() {
+        let src = r#"[{"PartitionKey":"pk","RowKey":"rk"}]"#.repeat(100);
+        let compression = BulkCompression {
+            min_size: src.len() + 1,
+        };
+
+        let (body, content_encoding) =
+            BulkCompression::compress(Some(compression), Some(src.as_bytes().to_vec()));
+
+        assert_eq!(body.unwrap(), src.as_bytes());
+        assert_eq!(content_encoding, None);
+    }
+
+    #[test]
+    fn test_body_of_min_size_is_gzipped() {
+        let src = r#"[{"PartitionKey":"pk","RowKey":"rk"}]"#.repeat(100);
+        let compression = BulkCompression {
+            min_size: src.len(),
+        };
+
+        let (body, content_encoding) =
+            BulkCompression::compress(Some(compression), Some(src.as_bytes().to_vec()));
+
+        assert_eq!(content_encoding, Some(GZIP_CONTENT_ENCODING));
+        assert_eq!(gunzip(body.unwrap().as_slice()), src);
+    }
+
+    #[test]
+    fn test_no_compression() {
+        let src = r#"[{"PartitionKey":"pk","RowKey":"rk"}]"#.repeat(100);
+
+        let (body, content_encoding) =
+            BulkCompression::compress(None, Some(src.as_bytes().to_vec()));
+        assert_eq!(body.unwrap(), src.as_bytes());
+        assert_eq!(content_encoding, None);
+
+        let (body, content_encoding) =
+            BulkCompression::compress(Some(BulkCompression { min_size: 0 }), None);
+        assert!(body.is_none());
+        assert_eq!(content_encoding, None);
+    }
+}
@@ -0,0 +1,232 @@
+use std::{collections::BTreeMap, future::Future};
+
+use my_no_sql_abstractions::MyNoSqlEntity;
+
+use crate::{DataWriterError, WriteNotification, WriteNotificationCallback};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkMode {
+    // Stops at the first failed partition and returns its error
+    FailFast,
+    // Writes all the partitions and reports the result of each one
+    ContinueOnError,
+}
+
+pub type PartitionBulkResult = (String, Result<(), DataWriterError>);
+
+pub fn group_by_partition<TEntity: MyNoSqlEntity>(
+    entities: &[TEntity],
+) -> BTreeMap<&str, Vec<&TEntity>> {
+    let mut result: BTreeMap<&str, Vec<&TEntity>> = BTreeMap::new();
+
+    for entity in entities {
+        result
+            .entry(entity.get_partition_key())
+            .or_default()
+            .push(entity);
+    }
+
+    result
+}
+
+// Writes every partition of the groups with its own write_partition call.
+// Entities of the written partition are notified one by one
+pub async fn write_by_partitions<'g, 's: 'g, TEntity: MyNoSqlEntity, TFuture>(
+    groups: &'g BTreeMap<&'s str, Vec<&'s TEntity>>,
+    mode: BulkMode,
+    write_notification: Option<&WriteNotificationCallback<TEntity>>,
+    write_partition: impl Fn(&'g [&'s TEntity]) -> TFuture,
+) -> Result<Vec<PartitionBulkResult>, DataWriterError>
+where
+    TFuture: Future<Output = Result<(), DataWriterError>>,
+{
+    let mut results = BulkResults::new(mode);
+
+    for (partition_key, entities) in groups {
+        let result = write_partition(entities.as_slice()).await;
+
+        if result.is_ok() {
+            if let Some(callback) = write_notification {
+                for entity in entities.iter().copied() {
+                    callback(WriteNotification::Upserted(entity));
+                }
+            }
+        }
+
+        if !results.add(partition_key, result) {
+            break;
+        }
+    }
+
+    results.into_result()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BulkStats {
+    pub batches: usize,
+    pub entities: usize,
+}
+
+impl BulkStats {
+    pub fn add_batch(&mut self, entities: usize) {
+        self.batches += 1;
+        self.entities += entities;
+    }
+}
+
+pub struct BulkResults {
+    mode: BulkMode,
+    results: Vec<PartitionBulkResult>,
+}
+
+impl BulkResults {
+    pub fn new(mode: BulkMode) -> Self {
+        Self {
+            mode,
+            results: Vec::new(),
+        }
+    }
+
+    // Returns false if the rest of the partitions must not be written
+    pub fn add(&mut self, partition_key: &str, result: Result<(), DataWriterError>) -> bool {
+        let failed = result.is_err();
+        self.results.push((partition_key.to_string(), result));
+        !(failed && self.mode == BulkMode::FailFast)
+    }
+
+    pub fn into_result(mut self) -> Result<Vec<PartitionBulkResult>, DataWriterError> {
+        if self.mode == BulkMode::FailFast {
+            if let Some((_, Err(_))) = self.results.last() {
+                let (_, err) = self.results.pop().unwrap();
+                return Err(err.unwrap_err());
+            }
+        }
+
+        Ok(self.results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{BulkMode, BulkResults, BulkStats};
+    use crate::{test_fixtures::TestEntity, DataWriterError, WriteNotification};
+
+    fn create_entities() -> Vec<TestEntity> {
+        ["pk1", "pk2", "pk1", "pk3"]
+            .iter()
+            .enumerate()
+            .map(|(i, partition_key)| TestEntity {
+                partition_key: partition_key.to_string(),
+                row_key: i.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_write_by_partitions_notifies_written_partitions() {
+        let entities = create_entities();
+        let groups = super::group_by_partition(&entities);
+
+        let notified = Arc::new(Mutex::new(Vec::new()));
+        let notified_by_callback = notified.clone();
+        let callback: crate::WriteNotificationCallback<TestEntity> =
+            Arc::new(move |notification| {
+                if let WriteNotification::Upserted(entity) = notification {
+                    notified_by_callback
+                        .lock()
+                        .unwrap()
+                        .push(entity.row_key.clone());
+                }
+            });
+
+        let results = super::write_by_partitions(
+            &groups,
+            BulkMode::ContinueOnError,
+            Some(&callback),
+            |entities| async move {
+                if entities[0].partition_key == "pk2" {
+                    return Err(DataWriterError::Error("fail".to_string()));
+                }
+
+                Ok(())
+            },
+        )
+        .await
+        .unwrap();
+
+        let partition_keys: Vec<&str> = results.iter().map(|(pk, _)| pk.as_str()).collect();
+        assert_eq!(partition_keys, vec!["pk1", "pk2", "pk3"]);
+        assert!(results[1].1.is_err());
+
+        assert_eq!(*notified.lock().unwrap(), vec!["0", "2", "3"]);
+    }
+
+    #[tokio::test]
+    async fn test_write_by_partitions_fail_fast() {
+        let entities = create_entities();
+        let groups = super::group_by_partition(&entities);
+
+        let written = Mutex::new(Vec::new());
+
+        let result = super::write_by_partitions(&groups, BulkMode::FailFast, None, |entities| {
+            written
+                .lock()
+                .unwrap()
+                .push(entities[0].partition_key.clone());
+            async move {
+                if entities[0].partition_key == "pk2" {
+                    return Err(DataWriterError::Error("fail".to_string()));
+                }
+
+                Ok(())
+            }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(*written.lock().unwrap(), vec!["pk1", "pk2"]);
+    }
+
+    #[test]
+    fn test_fail_fast_stops_and_returns_error() {
+        let mut results = BulkResults::new(BulkMode::FailFast);
+
+        assert!(results.add("pk1", Ok(())));
+        assert!(!results.add("pk2", Err(DataWriterError::Error("fail".to_string()))));
+
+        match results.into_result() {
+            Err(DataWriterError::Error(message)) => assert_eq!(message, "fail"),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_continue_on_error_reports_every_partition() {
+        let mut results = BulkResults::new(BulkMode::ContinueOnError);
+
+        assert!(results.add("pk1", Err(DataWriterError::Error("fail".to_string()))));
+        assert!(results.add("pk2", Ok(())));
+
+        let results = results.into_result().unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "pk1");
+        assert!(results[0].1.is_err());
+        assert_eq!(results[1].0, "pk2");
+        assert!(results[1].1.is_ok());
+    }
+
+    #[test]
+    fn test_bulk_stats() {
+        let mut stats = BulkStats::default();
+
+        stats.add_batch(3);
+        stats.add_batch(1);
+
+        assert_eq!(stats.batches, 2);
+        assert_eq!(stats.entities, 4);
+    }
+}
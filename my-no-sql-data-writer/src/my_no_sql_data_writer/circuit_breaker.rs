@@ -0,0 +1,182 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use super::DataWriterError;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CircuitBreakerConfig {
+    pub failures_threshold: usize,
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failures_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+struct CircuitBreakerState {
+    consecutive_failures: usize,
+    opened_at: Option<Instant>,
+    probe_started: Option<Instant>,
+}
+
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Mutex<CircuitBreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(CircuitBreakerState {
+                consecutive_failures: 0,
+                opened_at: None,
+                probe_started: None,
+            }),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.lock().unwrap().opened_at.is_some()
+    }
+
+    // Fails fast while the circuit is open. Once cooldown is passed a single probe request is let through;
+    // if the probe hangs for longer than cooldown - the next one is allowed
+    pub fn check(&self) -> Result<(), DataWriterError> {
+        self.check_at(Instant::now())
+    }
+
+    fn check_at(&self, now: Instant) -> Result<(), DataWriterError> {
+        let mut state = self.state.lock().unwrap();
+
+        let opened_at = match state.opened_at {
+            Some(opened_at) => opened_at,
+            None => return Ok(()),
+        };
+
+        if now.duration_since(opened_at) < self.config.cooldown {
+            return Err(DataWriterError::CircuitOpen);
+        }
+
+        if let Some(probe_started) = state.probe_started {
+            if now.duration_since(probe_started) < self.config.cooldown {
+                return Err(DataWriterError::CircuitOpen);
+            }
+        }
+
+        state.probe_started = Some(now);
+        Ok(())
+    }
+
+    pub fn on_result<T>(&self, result: &Result<T, DataWriterError>) {
+        self.on_result_at(result, Instant::now());
+    }
+
+    fn on_result_at<T>(&self, result: &Result<T, DataWriterError>, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+
+        let is_failure = match result {
+            Ok(_) => false,
+            Err(DataWriterError::CircuitOpen) => return,
+            Err(err) => is_connectivity_error(err),
+        };
+
+        if !is_failure {
+            state.consecutive_failures = 0;
+            state.opened_at = None;
+            state.probe_started = None;
+            return;
+        }
+
+        state.consecutive_failures += 1;
+
+        if state.probe_started.is_some()
+            || state.consecutive_failures >= self.config.failures_threshold
+        {
+            state.opened_at = Some(now);
+            state.probe_started = None;
+        }
+    }
+}
+
+// Only errors which mean that server is not reachable are counted.
+// Business errors (RecordAlreadyExists, TableNotFound, ...) prove that server is alive
+fn is_connectivity_error(err: &DataWriterError) -> bool {
+    match err {
+        DataWriterError::ConnectionRefused(_) => true,
+        DataWriterError::Timeout(_) => true,
+        DataWriterError::FlUrlError(_) => true,
+        DataWriterError::HyperError(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{CircuitBreaker, CircuitBreakerConfig};
+    use crate::my_no_sql_data_writer::DataWriterError;
+
+    fn failure() -> Result<(), DataWriterError> {
+        Err(DataWriterError::Timeout(Box::new(DataWriterError::Error(
+            "timed out".to_string(),
+        ))))
+    }
+
+    #[test]
+    fn test_opens_after_threshold_and_probes_after_cooldown() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failures_threshold: 2,
+            cooldown: Duration::from_secs(10),
+        });
+
+        let now = Instant::now();
+
+        breaker.on_result_at(&failure(), now);
+        assert!(breaker.check_at(now).is_ok());
+
+        breaker.on_result_at(&failure(), now);
+        assert!(matches!(
+            breaker.check_at(now + Duration::from_secs(5)),
+            Err(DataWriterError::CircuitOpen)
+        ));
+
+        let after_cooldown = now + Duration::from_secs(11);
+        assert!(breaker.check_at(after_cooldown).is_ok());
+        // Only one probe at a time
+        assert!(breaker.check_at(after_cooldown).is_err());
+
+        // Failed probe opens the circuit again
+        breaker.on_result_at(&failure(), after_cooldown);
+        assert!(breaker.check_at(after_cooldown).is_err());
+
+        let after_second_cooldown = after_cooldown + Duration::from_secs(11);
+        assert!(breaker.check_at(after_second_cooldown).is_ok());
+        breaker.on_result_at(&Ok(()), after_second_cooldown);
+
+        assert!(!breaker.is_open());
+        assert!(breaker.check_at(after_second_cooldown).is_ok());
+    }
+
+    #[test]
+    fn test_business_errors_do_not_open_circuit() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failures_threshold: 1,
+            cooldown: Duration::from_secs(10),
+        });
+
+        let result: Result<(), DataWriterError> =
+            Err(DataWriterError::RecordAlreadyExists("exists".to_string()));
+        breaker.on_result(&result);
+
+        assert!(!breaker.is_open());
+    }
+}
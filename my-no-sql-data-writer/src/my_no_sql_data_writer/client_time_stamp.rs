@@ -0,0 +1,79 @@
+use my_no_sql_core::db_json_entity::{DbJsonEntity, JsonTimeStamp};
+
+use crate::DataWriterError;
+
+// Puts TimeStamp into the serialized entity the same way the server does:
+// existing TimeStamp is replaced, otherwise it is added at the end of the json.
+// Fails if the payload can not be parsed as an entity, so it is never sent unstamped
+pub fn inject_client_time_stamp(
+    mut payload: Vec<u8>,
+    time_stamp: &JsonTimeStamp,
+) -> Result<Vec<u8>, DataWriterError> {
+    let mut db_json_entity = match DbJsonEntity::from_slice(payload.as_slice()) {
+        Ok(db_json_entity) => db_json_entity,
+        Err(err) => {
+            return Err(DataWriterError::Error(format!(
+                "Can not inject client time stamp into the entity. Err: {:?}",
+                err
+            )))
+        }
+    };
+
+    if db_json_entity.time_stamp.is_some() {
+        db_json_entity.replace_timestamp_value(&mut payload, time_stamp);
+    } else {
+        db_json_entity.inject_at_the_end_of_json(&mut payload, time_stamp);
+    }
+
+    Ok(payload)
+}
+
+pub fn get_client_time_stamp(client_time_stamp: bool) -> Option<JsonTimeStamp> {
+    if client_time_stamp {
+        Some(JsonTimeStamp::now())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use my_no_sql_core::db_json_entity::JsonTimeStamp;
+    use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+    use super::inject_client_time_stamp;
+
+    #[test]
+    fn test_time_stamp_is_injected() {
+        let time_stamp = JsonTimeStamp::from_date_time(DateTimeAsMicroseconds::new(0));
+
+        let payload = r#"{"PartitionKey":"Pk","RowKey":"Rk"}"#;
+        let result = inject_client_time_stamp(payload.as_bytes().to_vec(), &time_stamp).unwrap();
+        let result: serde_json::Value = serde_json::from_slice(result.as_slice()).unwrap();
+
+        assert_eq!(result["TimeStamp"], time_stamp.as_str());
+        assert_eq!(result["PartitionKey"], "Pk");
+    }
+
+    #[test]
+    fn test_existing_time_stamp_is_replaced() {
+        let time_stamp = JsonTimeStamp::from_date_time(DateTimeAsMicroseconds::new(0));
+
+        let payload = r#"{"PartitionKey":"Pk","RowKey":"Rk","TimeStamp":null}"#;
+        let result = inject_client_time_stamp(payload.as_bytes().to_vec(), &time_stamp).unwrap();
+        let result: serde_json::Value = serde_json::from_slice(result.as_slice()).unwrap();
+
+        assert_eq!(result["TimeStamp"], time_stamp.as_str());
+        assert_eq!(result["RowKey"], "Rk");
+    }
+
+    #[test]
+    fn test_payload_which_is_not_an_entity_is_rejected() {
+        let time_stamp = JsonTimeStamp::from_date_time(DateTimeAsMicroseconds::new(0));
+
+        let payload = r#"{"RowKey":"Rk"}"#;
+        let result = inject_client_time_stamp(payload.as_bytes().to_vec(), &time_stamp);
+
+        assert!(result.is_err());
+    }
+}
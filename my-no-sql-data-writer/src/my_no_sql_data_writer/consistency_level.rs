@@ -0,0 +1,24 @@
+use flurl::FlUrl;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsistencyLevel {
+    // Read can be served by any node and may miss the latest writes
+    Eventual,
+    // Read is routed to the master node, so the latest write is visible
+    Strong,
+}
+
+impl Default for ConsistencyLevel {
+    fn default() -> Self {
+        Self::Eventual
+    }
+}
+
+impl ConsistencyLevel {
+    pub fn fill_fields(&self, fl_url: FlUrl) -> FlUrl {
+        match self {
+            ConsistencyLevel::Eventual => fl_url,
+            ConsistencyLevel::Strong => fl_url.with_header("readConsistency", "strong"),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use my_no_sql_abstractions::MyNoSqlEntity;
+
+use crate::DataWriterError;
+
+// Receives entities which were not written after all the retry attempts
+#[async_trait::async_trait]
+pub trait DeadLetterSink<TEntity: MyNoSqlEntity + Sync + Send> {
+    async fn dead_letter(&self, entity: &TEntity, err: &DataWriterError);
+}
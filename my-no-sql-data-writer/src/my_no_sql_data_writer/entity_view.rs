@@ -0,0 +1,108 @@
+use my_json::json_reader::JsonFirstLineReader;
+use rust_extensions::array_of_bytes_iterator::SliceIterator;
+use serde::de::DeserializeOwned;
+
+// Read-only view over the raw json of the entity. Only top level fields are indexed,
+// values are not deserialized until they are requested
+pub struct EntityView<'s> {
+    raw: &'s [u8],
+    fields: Vec<(String, &'s [u8])>,
+}
+
+impl<'s> EntityView<'s> {
+    pub fn new(raw: &'s [u8]) -> Result<Self, String> {
+        let fields = index_top_level_fields(raw)?;
+        Ok(Self { raw, fields })
+    }
+
+    pub fn as_bytes(&self) -> &'s [u8] {
+        self.raw
+    }
+
+    pub fn get_partition_key(&self) -> Option<&'s str> {
+        self.get_str("PartitionKey")
+    }
+
+    pub fn get_row_key(&self) -> Option<&'s str> {
+        self.get_str("RowKey")
+    }
+
+    pub fn has_field(&self, name: &str) -> bool {
+        self.get_raw(name).is_some()
+    }
+
+    // Raw json of the value: strings are with quotes, objects and arrays as is
+    pub fn get_raw(&self, name: &str) -> Option<&'s [u8]> {
+        self.fields
+            .iter()
+            .find(|(field_name, _)| *field_name == name)
+            .map(|(_, value)| *value)
+    }
+
+    // Content of the string value without unescaping. None if value is missing or not a string
+    pub fn get_str(&self, name: &str) -> Option<&'s str> {
+        let value = self.get_raw(name)?;
+
+        if value.len() < 2 || value[0] != b'"' {
+            return None;
+        }
+
+        std::str::from_utf8(&value[1..value.len() - 1]).ok()
+    }
+
+    pub fn get_value<T: DeserializeOwned>(&self, name: &str) -> Option<Result<T, String>> {
+        let value = self.get_raw(name)?;
+        Some(serde_json::from_slice(value).map_err(|err| err.to_string()))
+    }
+}
+
+fn index_top_level_fields(raw: &[u8]) -> Result<Vec<(String, &[u8])>, String> {
+    if raw.iter().find(|b| !b.is_ascii_whitespace()) != Some(&b'{') {
+        return Err("Entity json must be an object".to_string());
+    }
+
+    let mut json_first_line_reader = JsonFirstLineReader::new(SliceIterator::new(raw));
+
+    let mut result = Vec::new();
+
+    while let Some(line) = json_first_line_reader.get_next() {
+        let line = line.map_err(|err| format!("{:?}", err))?;
+
+        let name = line
+            .name
+            .as_unescaped_name(&json_first_line_reader)
+            .map_err(|err| format!("{:?}", err))?
+            .to_string();
+
+        result.push((name, &raw[line.value.start..line.value.end]));
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EntityView;
+
+    #[test]
+    fn test_entity_view() {
+        let src = r#"{ "PartitionKey": "Pk", "RowKey":"Rk", "Value": 15, "Nested": {"A":"}"}, "List": [1, 2], "Str": "a\"b" }"#;
+
+        let view = EntityView::new(src.as_bytes()).unwrap();
+
+        assert_eq!(view.get_partition_key(), Some("Pk"));
+        assert_eq!(view.get_row_key(), Some("Rk"));
+        assert_eq!(view.get_raw("Value"), Some("15".as_bytes()));
+        assert_eq!(view.get_value::<i32>("Value").unwrap().unwrap(), 15);
+        assert_eq!(view.get_raw("Nested"), Some(r#"{"A":"}"}"#.as_bytes()));
+        assert_eq!(view.get_value::<Vec<i32>>("List").unwrap().unwrap(), vec![1, 2]);
+        assert_eq!(view.get_str("Str"), Some(r#"a\"b"#));
+        assert_eq!(view.get_str("Value"), None);
+        assert!(!view.has_field("Missing"));
+    }
+
+    #[test]
+    fn test_not_an_object() {
+        assert!(EntityView::new("[1,2]".as_bytes()).is_err());
+    }
+}
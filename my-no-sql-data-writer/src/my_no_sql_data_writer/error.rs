@@ -2,6 +2,12 @@ use std::{str::Utf8Error, string::FromUtf8Error};
 
 use flurl::FlUrlError;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateTableAndSeedStage {
+    CreateTable,
+    Seed,
+}
+
 #[derive(Debug)]
 pub enum DataWriterError {
     TableAlreadyExists(String),
@@ -16,11 +22,122 @@ pub enum DataWriterError {
     FlUrlError(FlUrlError),
     HyperError(flurl::hyper::Error),
     JsonParseError(my_json::json_reader::JsonParseError),
+    CreateTableAndSeedFail(CreateTableAndSeedStage, Box<DataWriterError>),
+    // Server is down. Original FlUrlError or HyperError is kept as the source
+    ConnectionRefused(Box<DataWriterError>),
+    // Server is slow. Original FlUrlError or HyperError, or the status code of the gateway is kept as the source
+    Timeout(Box<DataWriterError>),
+    EntityTooLarge { size: usize, limit: usize },
+    // Request is not sent since circuit breaker is open
+    CircuitOpen,
+    // Table on the server is created with the schema version the writer is not compatible with.
+    // None if the server does not report the version
+    SchemaVersionMismatch { expected: u32, actual: Option<u32> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransportFailure {
+    ConnectionRefused,
+    Timeout,
+}
+
+impl DataWriterError {
+    fn categorize(self) -> Self {
+        match self.get_transport_failure() {
+            Some(TransportFailure::ConnectionRefused) => Self::ConnectionRefused(Box::new(self)),
+            Some(TransportFailure::Timeout) => Self::Timeout(Box::new(self)),
+            None => self,
+        }
+    }
+
+    fn get_transport_failure(&self) -> Option<TransportFailure> {
+        match self {
+            Self::FlUrlError(FlUrlError::Timeout) => Some(TransportFailure::Timeout),
+            Self::FlUrlError(FlUrlError::CanNotEstablishConnection(_)) => {
+                Some(TransportFailure::ConnectionRefused)
+            }
+            Self::FlUrlError(FlUrlError::HyperError(err)) | Self::HyperError(err) => {
+                if err.is_timeout() {
+                    return Some(TransportFailure::Timeout);
+                }
+
+                get_io_failure(err)
+            }
+            _ => None,
+        }
+    }
+
+    // Gateway in front of the server reports that the server did not respond in time
+    pub fn from_status_code(status_code: u16) -> Option<Self> {
+        match status_code {
+            408 | 504 => Some(Self::Timeout(Box::new(Self::Error(format!(
+                "Status code: {}",
+                status_code
+            ))))),
+            _ => None,
+        }
+    }
+}
+
+// Io error which failed the request is somewhere in the source chain
+fn get_io_failure(err: &(dyn std::error::Error + 'static)) -> Option<TransportFailure> {
+    let mut source = Some(err);
+
+    while let Some(err) = source {
+        if let Some(io_error) = err.downcast_ref::<std::io::Error>() {
+            return match io_error.kind() {
+                std::io::ErrorKind::ConnectionRefused => Some(TransportFailure::ConnectionRefused),
+                std::io::ErrorKind::TimedOut => Some(TransportFailure::Timeout),
+                _ => None,
+            };
+        }
+
+        source = err.source();
+    }
+
+    None
+}
+
+impl std::fmt::Display for DataWriterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataWriterError::ConnectionRefused(_) => write!(f, "Connection refused"),
+            DataWriterError::Timeout(_) => write!(f, "Timeout"),
+            DataWriterError::CircuitOpen => write!(f, "Circuit breaker is open"),
+            DataWriterError::SchemaVersionMismatch { expected, actual } => match actual {
+                Some(actual) => write!(
+                    f,
+                    "Table schema version is {} but {} is expected",
+                    actual, expected
+                ),
+                None => write!(
+                    f,
+                    "Table schema version is not reported but {} is expected",
+                    expected
+                ),
+            },
+            DataWriterError::EntityTooLarge { size, limit } => {
+                write!(f, "Entity size {} exceeds the limit {}", size, limit)
+            }
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+impl std::error::Error for DataWriterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DataWriterError::ConnectionRefused(src) => Some(src.as_ref()),
+            DataWriterError::Timeout(src) => Some(src.as_ref()),
+            DataWriterError::CreateTableAndSeedFail(_, src) => Some(src.as_ref()),
+            _ => None,
+        }
+    }
 }
 
 impl From<flurl::hyper::Error> for DataWriterError {
     fn from(src: flurl::hyper::Error) -> Self {
-        Self::HyperError(src)
+        Self::HyperError(src).categorize()
     }
 }
 
@@ -44,6 +161,78 @@ impl From<Utf8Error> for DataWriterError {
 
 impl From<FlUrlError> for DataWriterError {
     fn from(src: FlUrlError) -> Self {
-        Self::FlUrlError(src)
+        Self::FlUrlError(src).categorize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::DataWriterError;
+
+    #[test]
+    fn test_io_failure() {
+        let err = std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "refused");
+        assert_eq!(
+            super::get_io_failure(&err),
+            Some(super::TransportFailure::ConnectionRefused)
+        );
+
+        let err = std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out");
+        assert_eq!(
+            super::get_io_failure(&err),
+            Some(super::TransportFailure::Timeout)
+        );
+
+        let err = std::io::Error::new(std::io::ErrorKind::Other, "Connection refused");
+        assert_eq!(super::get_io_failure(&err), None);
+    }
+
+    #[test]
+    fn test_io_failure_is_found_in_the_source_chain() {
+        let err = DataWriterError::Timeout(Box::new(DataWriterError::Error("Other".to_string())));
+        assert_eq!(super::get_io_failure(&err), None);
+
+        let err = WrappedError(std::io::Error::new(
+            std::io::ErrorKind::ConnectionRefused,
+            "refused",
+        ));
+        assert_eq!(
+            super::get_io_failure(&err),
+            Some(super::TransportFailure::ConnectionRefused)
+        );
+    }
+
+    #[derive(Debug)]
+    struct WrappedError(std::io::Error);
+
+    impl std::fmt::Display for WrappedError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Wrapped")
+        }
+    }
+
+    impl Error for WrappedError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn test_categorize_does_not_look_at_the_message() {
+        let err = DataWriterError::Error("Os { code: 111, kind: ConnectionRefused }".to_string())
+            .categorize();
+        assert!(matches!(err, DataWriterError::Error(_)));
+    }
+
+    #[test]
+    fn test_from_status_code() {
+        let err = DataWriterError::from_status_code(504).unwrap();
+        assert!(matches!(err, DataWriterError::Timeout(_)));
+        assert!(err.source().is_some());
+
+        assert!(DataWriterError::from_status_code(408).is_some());
+        assert!(DataWriterError::from_status_code(500).is_none());
     }
 }
@@ -1,76 +1,152 @@
+use std::collections::VecDeque;
+
 use flurl::{FlUrl, FlUrlResponse};
+use futures::{Stream, StreamExt};
 use my_json::{
     json_reader::array_iterator::JsonArrayIterator,
     json_writer::{JsonArrayWriter, RawJsonObject},
 };
 use my_logger::LogEventCtx;
 use my_no_sql_abstractions::{DataSynchronizationPeriod, MyNoSqlEntity, MyNoSqlEntitySerializer};
-use rust_extensions::array_of_bytes_iterator::SliceIterator;
+use my_no_sql_core::db_json_entity::JsonTimeStamp;
+use rust_extensions::{array_of_bytes_iterator::SliceIterator, date_time::DateTimeAsMicroseconds};
 use serde::{Deserialize, Serialize};
 
-use crate::{CreateTableParams, DataWriterError, OperationFailHttpContract, UpdateReadStatistics};
-
-use super::fl_url_ext::FlUrlExt;
-
-const API_SEGMENT: &str = "api";
+use crate::{
+    CreateTableParams, DataWriterError, OperationFailHttpContract, PartitionMeta,
+    PartitionMetaHttpContract, ServerFailReason, UpdateReadStatistics,
+};
 
-const ROW_CONTROLLER: &str = "Row";
-const ROWS_CONTROLLER: &str = "Rows";
-const BULK_CONTROLLER: &str = "Bulk";
-const PARTITIONS_CONTROLLER: &str = "Partitions";
+use super::{
+    aggregation::{AggOp, AggregateHttpContract},
+    bulk_compression::BulkCompression,
+    client_time_stamp::{get_client_time_stamp, inject_client_time_stamp},
+    fl_url_ext::FlUrlExt,
+    metrics::RequestMetrics,
+    request_interceptor::InterceptedFlUrl,
+    url_builder::UrlBuilder,
+    writer_options::WriterOptions,
+    KeyCase,
+};
 
 pub async fn create_table_if_not_exists(
     flurl: FlUrl,
+    options: &WriterOptions,
     url: &str,
-    table_name: &'static str,
     params: &CreateTableParams,
+    schema_version: Option<u32>,
     sync_period: DataSynchronizationPeriod,
 ) -> Result<(), DataWriterError> {
+    let metrics = RequestMetrics::start("create_table_if_not_exists", options.table_name.as_str());
     let fl_url = flurl
-        .append_path_segment("Tables")
-        .append_path_segment("CreateIfNotExists")
+        .append_url(UrlBuilder::tables().create_if_not_exists())
         .append_data_sync_period(&sync_period)
-        .with_table_name_as_query_param(table_name);
+        .with_table_name_as_query_param(options.table_name.as_str());
+
+    let fl_url = append_schema_version(params.populate_params(fl_url), schema_version);
+
+    let mut response = fl_url
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .post(None)
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    create_table_errors_handler(&mut response, "create_table_if_not_exists", url).await?;
+
+    let schema_version = match schema_version {
+        Some(schema_version) => schema_version,
+        None => return Ok(()),
+    };
+
+    let body = response.get_body_as_slice().await?;
+    check_schema_version(schema_version, metrics.body_received(body))
+}
+
+fn append_schema_version(fl_url: FlUrl, schema_version: Option<u32>) -> FlUrl {
+    match schema_version {
+        Some(schema_version) => {
+            fl_url.append_query_param("schemaVersion", Some(schema_version.to_string()))
+        }
+        None => fl_url,
+    }
+}
 
-    let fl_url = params.populate_params(fl_url);
+#[derive(Serialize, Deserialize)]
+struct TableSchemaVersionHttpContract {
+    #[serde(rename = "schemaVersion")]
+    schema_version: Option<u32>,
+}
 
-    let mut response = fl_url.post(None).await?;
+// Server reports the schema version of the table in the response of CreateIfNotExists.
+// Version which is not reported can not be trusted, so it fails the same way as another version
+pub fn check_schema_version(expected: u32, body: &[u8]) -> Result<(), DataWriterError> {
+    let contract: Option<TableSchemaVersionHttpContract> = serde_json::from_slice(body).ok();
 
-    create_table_errors_handler(&mut response, "create_table_if_not_exists", url).await
+    match contract.and_then(|contract| contract.schema_version) {
+        Some(actual) if actual == expected => Ok(()),
+        actual => Err(DataWriterError::SchemaVersionMismatch { expected, actual }),
+    }
 }
 
 pub async fn create_table(
     flurl: FlUrl,
+    options: &WriterOptions,
     url: &str,
-    table_name: &str,
     params: CreateTableParams,
+    schema_version: Option<u32>,
     sync_period: &DataSynchronizationPeriod,
 ) -> Result<(), DataWriterError> {
+    let metrics = RequestMetrics::start("create_table", options.table_name.as_str());
     let fl_url = flurl
-        .append_path_segment("Tables")
-        .append_path_segment("Create")
-        .with_table_name_as_query_param(table_name)
+        .append_url(UrlBuilder::tables().create())
+        .with_table_name_as_query_param(options.table_name.as_str())
         .append_data_sync_period(sync_period);
 
-    let fl_url = params.populate_params(fl_url);
+    let fl_url = append_schema_version(params.populate_params(fl_url), schema_version);
 
-    let mut response = fl_url.post(None).await?;
+    let mut response = fl_url
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .post(None)
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
 
     create_table_errors_handler(&mut response, "create_table", url).await
 }
 
 pub async fn insert_entity<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
     flurl: FlUrl,
+    options: &WriterOptions,
     entity: &TEntity,
     sync_period: &DataSynchronizationPeriod,
 ) -> Result<(), DataWriterError> {
+    let payload = prepare_payload(
+        entity.serialize_entity(),
+        options.key_case,
+        get_client_time_stamp(options.client_time_stamp).as_ref(),
+    )?;
+    check_entity_size(payload.len(), options.max_entity_bytes)?;
+
+    let metrics = RequestMetrics::start("insert_entity", options.table_name.as_str());
     let response = flurl
-        .append_path_segment(ROW_CONTROLLER)
-        .append_path_segment("Insert")
+        .append_url(UrlBuilder::row().insert())
         .append_data_sync_period(sync_period)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
-        .post(entity.serialize_entity().into())
-        .await?;
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .post(metrics.body_sent(payload).into())
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
 
     if is_ok_result(&response) {
         return Ok(());
@@ -85,16 +161,171 @@ pub async fn insert_or_replace_entity<
     TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
 >(
     flurl: FlUrl,
+    options: &WriterOptions,
+    entity: &TEntity,
+    sync_period: &DataSynchronizationPeriod,
+) -> Result<(), DataWriterError> {
+    let payload = prepare_payload(
+        entity.serialize_entity(),
+        options.key_case,
+        get_client_time_stamp(options.client_time_stamp).as_ref(),
+    )?;
+    check_entity_size(payload.len(), options.max_entity_bytes)?;
+
+    let metrics = RequestMetrics::start("insert_or_replace_entity", options.table_name.as_str());
+    let response = flurl
+        .append_url(UrlBuilder::row().insert_or_replace())
+        .append_data_sync_period(sync_period)
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .post(metrics.body_sent(payload).into())
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if is_ok_result(&response) {
+        return Ok(());
+    }
+
+    let reason = response.receive_body().await?;
+    let reason = String::from_utf8(reason)?;
+    return Err(DataWriterError::Error(reason));
+}
+
+// Row is replaced only if its TimeStamp is still the expected one. Otherwise fails with RecordIsChanged
+pub async fn insert_or_replace_entity_if_unchanged<
+    TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
+>(
+    flurl: FlUrl,
+    options: &WriterOptions,
     entity: &TEntity,
+    expected_time_stamp: &str,
+    sync_period: &DataSynchronizationPeriod,
+) -> Result<(), DataWriterError> {
+    let payload = prepare_payload(entity.serialize_entity(), options.key_case, None)?;
+    check_entity_size(payload.len(), options.max_entity_bytes)?;
+
+    let metrics = RequestMetrics::start(
+        "insert_or_replace_entity_if_unchanged",
+        options.table_name.as_str(),
+    );
+    let mut response = flurl
+        .append_url(UrlBuilder::row().insert_or_replace())
+        .append_data_sync_period(sync_period)
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .append_query_param("expectedTimeStamp", Some(expected_time_stamp))
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .post(metrics.body_sent(payload).into())
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if is_ok_result(&response) {
+        return Ok(());
+    }
+
+    if response.get_status_code() == 409 {
+        let body = response.get_body_as_slice().await?;
+        return Err(record_is_changed_error(body));
+    }
+
+    check_error(&mut response).await?;
+
+    let reason = response.receive_body().await?;
+    let reason = String::from_utf8(reason)?;
+    return Err(DataWriterError::Error(reason));
+}
+
+// Conflict is reported with the fail contract. Body which is not the contract is kept as the message
+fn record_is_changed_error(body: &[u8]) -> DataWriterError {
+    match serde_json::from_slice::<OperationFailHttpContract>(body) {
+        Ok(fail_contract) => fail_contract.into(),
+        Err(_) => DataWriterError::RecordIsChanged(String::from_utf8_lossy(body).to_string()),
+    }
+}
+
+// Only the fields of the patch are sent. Server responds with the entity after the merge
+pub async fn merge_entity<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    partition_key: &str,
+    row_key: &str,
+    patch: &serde_json::Value,
+    sync_period: &DataSynchronizationPeriod,
+) -> Result<Option<TEntity>, DataWriterError> {
+    validate_merge_patch(patch)?;
+    let payload = serde_json::to_vec(patch).unwrap();
+    check_entity_size(payload.len(), options.max_entity_bytes)?;
+
+    let metrics = RequestMetrics::start("merge_entity", options.table_name.as_str());
+    let mut response = flurl
+        .append_url(UrlBuilder::row().merge())
+        .append_data_sync_period(sync_period)
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .with_partition_key_as_query_param(partition_key, options.key_case)
+        .with_row_key_as_query_param(row_key, options.key_case)
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .post(metrics.body_sent(payload).into())
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if response.get_status_code() == 404 {
+        return Ok(None);
+    }
+
+    check_error(&mut response).await?;
+
+    if is_ok_result(&response) {
+        let body = response.get_body_as_slice().await?;
+        check_entity_size(body.len(), options.max_entity_bytes)?;
+
+        let entity = deserialize_entity(options.table_name.as_str(), metrics.body_received(body))?;
+        return Ok(Some(entity));
+    }
+
+    return Ok(None);
+}
+
+pub async fn insert_or_replace_raw<
+    TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
+>(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    raw_json: &[u8],
     sync_period: &DataSynchronizationPeriod,
 ) -> Result<(), DataWriterError> {
+    validate_raw_entity(raw_json)?;
+
+    let payload = prepare_payload(
+        raw_json.to_vec(),
+        options.key_case,
+        get_client_time_stamp(options.client_time_stamp).as_ref(),
+    )?;
+    check_entity_size(payload.len(), options.max_entity_bytes)?;
+
+    let metrics = RequestMetrics::start("insert_or_replace_raw", options.table_name.as_str());
     let response = flurl
-        .append_path_segment(ROW_CONTROLLER)
-        .append_path_segment("InsertOrReplace")
+        .append_url(UrlBuilder::row().insert_or_replace())
         .append_data_sync_period(sync_period)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
-        .post(entity.serialize_entity().into())
-        .await?;
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .post(metrics.body_sent(payload).into())
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
 
     if is_ok_result(&response) {
         return Ok(());
@@ -109,6 +340,7 @@ pub async fn bulk_insert_or_replace<
     TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
 >(
     flurl: FlUrl,
+    options: &WriterOptions,
     entities: &[TEntity],
     sync_period: &DataSynchronizationPeriod,
 ) -> Result<(), DataWriterError> {
@@ -116,13 +348,107 @@ pub async fn bulk_insert_or_replace<
         return Ok(());
     }
 
+    post_bulk_insert_or_replace::<TEntity>(
+        flurl,
+        options,
+        Some(serialize_entities_to_body(options, entities.iter())?),
+        sync_period,
+    )
+    .await
+}
+
+pub async fn bulk_insert_or_replace_refs<
+    TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
+>(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    entities: &[&TEntity],
+    sync_period: &DataSynchronizationPeriod,
+) -> Result<(), DataWriterError> {
+    if entities.is_empty() {
+        return Ok(());
+    }
+
+    post_bulk_insert_or_replace::<TEntity>(
+        flurl,
+        options,
+        Some(serialize_entities_to_body(
+            options,
+            entities.iter().copied(),
+        )?),
+        sync_period,
+    )
+    .await
+}
+
+async fn post_bulk_insert_or_replace<
+    TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
+>(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    body: Option<Vec<u8>>,
+    sync_period: &DataSynchronizationPeriod,
+) -> Result<(), DataWriterError> {
+    let metrics = RequestMetrics::start("bulk_insert_or_replace", options.table_name.as_str());
+
+    let (flurl, body) = BulkCompression::apply(options.bulk_compression, flurl, body);
+
+    let response = flurl
+        .append_url(UrlBuilder::bulk().insert_or_replace())
+        .append_data_sync_period(sync_period)
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .post(body.map(|body| metrics.body_sent(body)))
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if is_ok_result(&response) {
+        return Ok(());
+    }
+
+    let reason = response.receive_body().await?;
+    let reason = String::from_utf8(reason)?;
+    return Err(DataWriterError::Error(reason));
+}
+
+// Entities are posted exactly as they are. Casing and client timestamp are not applied,
+// since it is used to restore the data which was already written once
+pub async fn bulk_insert_or_replace_raw(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    raw_entities: &[Vec<u8>],
+    sync_period: &DataSynchronizationPeriod,
+) -> Result<(), DataWriterError> {
+    if raw_entities.is_empty() {
+        return Ok(());
+    }
+
+    let mut json_array_writer = JsonArrayWriter::new();
+
+    for raw_entity in raw_entities {
+        validate_raw_entity(raw_entity)?;
+        let payload: RawJsonObject = raw_entity.clone().into();
+        json_array_writer.write(payload);
+    }
+
+    let metrics = RequestMetrics::start("bulk_insert_or_replace_raw", options.table_name.as_str());
+
     let response = flurl
-        .append_path_segment(BULK_CONTROLLER)
-        .append_path_segment("InsertOrReplace")
+        .append_url(UrlBuilder::bulk().insert_or_replace())
         .append_data_sync_period(sync_period)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
-        .post(serialize_entities_to_body(entities))
-        .await?;
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .post(Some(metrics.body_sent(json_array_writer.build())))
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
 
     if is_ok_result(&response) {
         return Ok(());
@@ -135,53 +461,122 @@ pub async fn bulk_insert_or_replace<
 
 pub async fn get_entity<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
     flurl: FlUrl,
+    options: &WriterOptions,
     partition_key: &str,
     row_key: &str,
     update_read_statistics: Option<&UpdateReadStatistics>,
 ) -> Result<Option<TEntity>, DataWriterError> {
+    let metrics = RequestMetrics::start("get_entity", options.table_name.as_str());
     let mut request = flurl
-        .append_path_segment(ROW_CONTROLLER)
-        .with_partition_key_as_query_param(partition_key)
-        .with_row_key_as_query_param(row_key)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME);
+        .append_url(UrlBuilder::row())
+        .with_partition_key_as_query_param(partition_key, options.key_case)
+        .with_row_key_as_query_param(row_key, options.key_case)
+        .with_table_name_as_query_param(options.table_name.as_str());
 
     if let Some(update_read_statistics) = update_read_statistics {
         request = update_read_statistics.fill_fields(request);
     }
 
-    let mut response = request.get().await?;
+    let mut response = request
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .get()
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
 
     if response.get_status_code() == 404 {
+        if options.strict_table_presence {
+            let body = response.get_body_as_slice().await?;
+            check_table_presence(options.table_name.as_str(), metrics.body_received(body))?;
+        }
+
         return Ok(None);
     }
 
     check_error(&mut response).await?;
 
     if is_ok_result(&response) {
-        let entity = TEntity::deserialize_entity(response.get_body_as_slice().await?).unwrap();
+        let body = read_entity_body(response, options.max_entity_bytes).await?;
+        let entity = deserialize_entity(options.table_name.as_str(), metrics.body_received(&body))?;
         return Ok(Some(entity));
     }
 
     return Ok(None);
 }
 
+pub async fn get_entity_raw<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    partition_key: &str,
+    row_key: &str,
+    update_read_statistics: Option<&UpdateReadStatistics>,
+) -> Result<Option<Vec<u8>>, DataWriterError> {
+    let metrics = RequestMetrics::start("get_entity_raw", options.table_name.as_str());
+    let mut request = flurl
+        .append_url(UrlBuilder::row())
+        .with_partition_key_as_query_param(partition_key, options.key_case)
+        .with_row_key_as_query_param(row_key, options.key_case)
+        .with_table_name_as_query_param(options.table_name.as_str());
+
+    if let Some(update_read_statistics) = update_read_statistics {
+        request = update_read_statistics.fill_fields(request);
+    }
+
+    let mut response = request
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .get()
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if response.get_status_code() == 404 {
+        return Ok(None);
+    }
+
+    check_error(&mut response).await?;
+
+    if is_ok_result(&response) {
+        let body = read_entity_body(response, options.max_entity_bytes).await?;
+        metrics.body_received(&body);
+        return Ok(Some(body));
+    }
+
+    return Ok(None);
+}
+
 pub async fn get_by_partition_key<
     TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
 >(
     flurl: FlUrl,
+    options: &WriterOptions,
     partition_key: &str,
     update_read_statistics: Option<&UpdateReadStatistics>,
 ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+    let metrics = RequestMetrics::start("get_by_partition_key", options.table_name.as_str());
     let mut request = flurl
-        .append_path_segment(ROW_CONTROLLER)
-        .with_partition_key_as_query_param(partition_key)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME);
+        .append_url(UrlBuilder::row())
+        .with_partition_key_as_query_param(partition_key, options.key_case)
+        .with_table_name_as_query_param(options.table_name.as_str());
 
     if let Some(update_read_statistics) = update_read_statistics {
         request = update_read_statistics.fill_fields(request);
     }
 
-    let mut response = request.get().await?;
+    let mut response = request
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .get()
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
 
     if response.get_status_code() == 404 {
         return Ok(None);
@@ -190,13 +585,153 @@ pub async fn get_by_partition_key<
     check_error(&mut response).await?;
 
     if is_ok_result(&response) {
-        let entities = deserialize_entities(response.get_body_as_slice().await?)?;
+        let entities = deserialize_entities(
+            options.table_name.as_str(),
+            metrics.body_received(response.get_body_as_slice().await?),
+        )?;
         return Ok(Some(entities));
     }
 
     return Ok(None);
 }
 
+// Body of the partition as the server sent it. None if partition is not found
+pub async fn get_partition_raw(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    partition_key: &str,
+) -> Result<Option<Vec<u8>>, DataWriterError> {
+    let metrics = RequestMetrics::start("get_partition_raw", options.table_name.as_str());
+    let mut response = flurl
+        .append_url(UrlBuilder::row())
+        .with_partition_key_as_query_param(partition_key, options.key_case)
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .get()
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if response.get_status_code() == 404 {
+        return Ok(None);
+    }
+
+    check_error(&mut response).await?;
+
+    if is_ok_result(&response) {
+        let body = response.get_body_as_slice().await?;
+        return Ok(Some(metrics.body_received(body).to_vec()));
+    }
+
+    return Ok(None);
+}
+
+pub async fn get_by_partition_and_row_prefix<
+    TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
+>(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    partition_key: &str,
+    row_key_prefix: &str,
+) -> Result<Vec<TEntity>, DataWriterError> {
+    let row_key_prefix = options.key_case.apply(row_key_prefix);
+    let metrics = RequestMetrics::start(
+        "get_by_partition_and_row_prefix",
+        options.table_name.as_str(),
+    );
+    let mut response = flurl
+        .append_url(UrlBuilder::row())
+        .with_partition_key_as_query_param(partition_key, options.key_case)
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .append_query_param("rowKeyPrefix", Some(row_key_prefix.as_ref()))
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .get()
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if response.get_status_code() == 404 {
+        return Ok(vec![]);
+    }
+
+    check_error(&mut response).await?;
+
+    if is_ok_result(&response) {
+        let entities: Vec<TEntity> = deserialize_entities(
+            options.table_name.as_str(),
+            metrics.body_received(response.get_body_as_slice().await?),
+        )?;
+
+        // Server which does not support rowKeyPrefix returns the whole partition
+        let result = entities
+            .into_iter()
+            .filter(|entity| entity.get_row_key().starts_with(row_key_prefix.as_ref()))
+            .collect();
+
+        return Ok(result);
+    }
+
+    return Ok(vec![]);
+}
+
+// Returns None if server does not support aggregation
+pub async fn aggregate_field<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    partition_key: Option<&str>,
+    field: &str,
+    op: AggOp,
+) -> Result<Option<f64>, DataWriterError> {
+    let metrics = RequestMetrics::start("aggregate_field", options.table_name.as_str());
+    let mut request = flurl
+        .append_url(UrlBuilder::api_rows().aggregate())
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .append_query_param("field", Some(field))
+        .append_query_param("op", Some(op.as_str()));
+
+    if let Some(partition_key) = partition_key {
+        request = request.with_partition_key_as_query_param(partition_key, options.key_case);
+    }
+
+    let mut response = request
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .get()
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    match response.get_status_code() {
+        404 | 405 | 501 => return Ok(None),
+        _ => {}
+    }
+
+    check_error(&mut response).await?;
+
+    if !is_ok_result(&response) {
+        return Ok(None);
+    }
+
+    let result: Result<AggregateHttpContract, _> =
+        serde_json::from_slice(metrics.body_received(response.get_body_as_slice().await?));
+
+    match result {
+        Ok(result) => Ok(Some(result.value)),
+        Err(err) => Err(DataWriterError::Error(format!(
+            "Failed to deserialize: {:?}",
+            err
+        ))),
+    }
+}
+
 pub async fn get_enum_case_models_by_partition_key<
     TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
     TResult: MyNoSqlEntity
@@ -207,10 +742,16 @@ pub async fn get_enum_case_models_by_partition_key<
         + 'static,
 >(
     flurl: FlUrl,
+    options: &WriterOptions,
     update_read_statistics: Option<&UpdateReadStatistics>,
 ) -> Result<Option<Vec<TResult>>, DataWriterError> {
-    let result: Option<Vec<TEntity>> =
-        get_by_partition_key(flurl, TResult::PARTITION_KEY, update_read_statistics).await?;
+    let result: Option<Vec<TEntity>> = get_by_partition_key(
+        flurl,
+        options,
+        TResult::PARTITION_KEY,
+        update_read_statistics,
+    )
+    .await?;
 
     match result {
         Some(entities) => {
@@ -236,10 +777,12 @@ pub async fn get_enum_case_model<
         + 'static,
 >(
     flurl: FlUrl,
+    options: &WriterOptions,
     update_read_statistics: Option<&UpdateReadStatistics>,
 ) -> Result<Option<TResult>, DataWriterError> {
     let entity: Option<TEntity> = get_entity(
         flurl,
+        options,
         TResult::PARTITION_KEY,
         TResult::ROW_KEY,
         update_read_statistics,
@@ -254,15 +797,28 @@ pub async fn get_enum_case_model<
 
 pub async fn get_by_row_key<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
     flurl: FlUrl,
+    options: &WriterOptions,
     row_key: &str,
+    skip: Option<i32>,
+    limit: Option<i32>,
 ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+    validate_skip_and_limit(skip, limit)?;
+
+    let metrics = RequestMetrics::start("get_by_row_key", options.table_name.as_str());
     let mut response = flurl
-        .append_path_segment(API_SEGMENT)
-        .append_path_segment(ROW_CONTROLLER)
-        .with_row_key_as_query_param(row_key)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
+        .append_url(UrlBuilder::api_row())
+        .with_row_key_as_query_param(row_key, options.key_case)
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .with_skip_as_query_param(skip)
+        .with_limit_as_query_param(limit)
+        .before_send(options.request_interceptor.as_ref())
+        .await
         .get()
-        .await?;
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
 
     if response.get_status_code() == 404 {
         return Ok(None);
@@ -271,7 +827,10 @@ pub async fn get_by_row_key<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + S
     check_error(&mut response).await?;
 
     if is_ok_result(&response) {
-        let entities = deserialize_entities(response.get_body_as_slice().await?)?;
+        let entities = deserialize_entities(
+            options.table_name.as_str(),
+            metrics.body_received(response.get_body_as_slice().await?),
+        )?;
         return Ok(Some(entities));
     }
 
@@ -280,33 +839,43 @@ pub async fn get_by_row_key<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + S
 
 pub async fn get_partition_keys(
     flurl: FlUrl,
-    table_name: &str,
+    options: &WriterOptions,
     skip: Option<i32>,
     limit: Option<i32>,
 ) -> Result<Vec<String>, DataWriterError> {
+    validate_skip_and_limit(skip, limit)?;
+
+    let metrics = RequestMetrics::start("get_partition_keys", options.table_name.as_str());
     #[derive(Serialize, Deserialize)]
     pub struct GetPartitionsJsonResult {
         pub amount: usize,
         pub data: Vec<String>,
     }
     let mut response = flurl
-        .append_path_segment(API_SEGMENT)
-        .append_path_segment(PARTITIONS_CONTROLLER)
-        .with_table_name_as_query_param(table_name)
+        .append_url(UrlBuilder::api_partitions())
+        .with_table_name_as_query_param(options.table_name.as_str())
         .with_skip_as_query_param(skip)
         .with_limit_as_query_param(limit)
+        .before_send(options.request_interceptor.as_ref())
+        .await
         .get()
-        .await?;
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
 
     if response.get_status_code() == 404 {
-        return Err(DataWriterError::TableNotFound(table_name.to_string()));
+        return Err(DataWriterError::TableNotFound(
+            options.table_name.as_str().to_string(),
+        ));
     }
 
     check_error(&mut response).await?;
 
     if is_ok_result(&response) {
         let result: Result<GetPartitionsJsonResult, _> =
-            serde_json::from_slice(response.get_body_as_slice().await?);
+            serde_json::from_slice(metrics.body_received(response.get_body_as_slice().await?));
         match result {
             Ok(result) => return Ok(result.data),
             Err(err) => {
@@ -321,25 +890,171 @@ pub async fn get_partition_keys(
     return Ok(vec![]);
 }
 
-pub async fn delete_enum_case<
-    TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
-    TResult: MyNoSqlEntity
-        + From<TEntity>
-        + my_no_sql_abstractions::GetMyNoSqlEntity
-        + Sync
-        + Send
-        + 'static,
->(
+pub async fn get_row_keys(
     flurl: FlUrl,
-) -> Result<Option<TResult>, DataWriterError> {
-    let entity: Option<TEntity> =
-        delete_row(flurl, TResult::PARTITION_KEY, TResult::ROW_KEY).await?;
+    options: &WriterOptions,
+    partition_key: &str,
+    skip: Option<i32>,
+    limit: Option<i32>,
+) -> Result<Vec<String>, DataWriterError> {
+    validate_skip_and_limit(skip, limit)?;
 
-    match entity {
-        Some(entity) => Ok(Some(entity.into())),
-        None => Ok(None),
+    let metrics = RequestMetrics::start("get_row_keys", options.table_name.as_str());
+    #[derive(Serialize, Deserialize)]
+    pub struct GetRowKeysJsonResult {
+        pub amount: usize,
+        pub data: Vec<String>,
     }
-}
+    let mut response = flurl
+        .append_url(UrlBuilder::api_rows().row_keys())
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .with_partition_key_as_query_param(partition_key, options.key_case)
+        .with_skip_as_query_param(skip)
+        .with_limit_as_query_param(limit)
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .get()
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if response.get_status_code() == 404 {
+        return Ok(vec![]);
+    }
+
+    check_error(&mut response).await?;
+
+    if is_ok_result(&response) {
+        let result: Result<GetRowKeysJsonResult, _> =
+            serde_json::from_slice(metrics.body_received(response.get_body_as_slice().await?));
+        match result {
+            Ok(result) => return Ok(result.data),
+            Err(err) => {
+                return Err(DataWriterError::Error(format!(
+                    "Failed to deserialize: {:?}",
+                    err
+                )))
+            }
+        }
+    }
+
+    return Ok(vec![]);
+}
+
+pub async fn get_partitions_metadata(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    skip: Option<i32>,
+    limit: Option<i32>,
+) -> Result<Vec<PartitionMeta>, DataWriterError> {
+    validate_skip_and_limit(skip, limit)?;
+
+    let metrics = RequestMetrics::start("get_partitions_metadata", options.table_name.as_str());
+    let mut response = flurl
+        .append_url(UrlBuilder::api_partitions().metadata())
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .with_skip_as_query_param(skip)
+        .with_limit_as_query_param(limit)
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .get()
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if response.get_status_code() == 404 {
+        return Err(DataWriterError::TableNotFound(
+            options.table_name.as_str().to_string(),
+        ));
+    }
+
+    check_error(&mut response).await?;
+
+    if is_ok_result(&response) {
+        let result: Result<Vec<PartitionMetaHttpContract>, _> =
+            serde_json::from_slice(metrics.body_received(response.get_body_as_slice().await?));
+        match result {
+            Ok(result) => return Ok(result.into_iter().map(|itm| itm.into()).collect()),
+            Err(err) => {
+                return Err(DataWriterError::Error(format!(
+                    "Failed to deserialize: {:?}",
+                    err
+                )))
+            }
+        }
+    }
+
+    return Ok(vec![]);
+}
+
+pub async fn get_partition_size(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    partition_key: &str,
+) -> Result<Option<usize>, DataWriterError> {
+    let metrics = RequestMetrics::start("get_partition_size", options.table_name.as_str());
+    let mut response = flurl
+        .append_url(UrlBuilder::api_partitions().metadata())
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .with_partition_key_as_query_param(partition_key, options.key_case)
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .get()
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if response.get_status_code() == 404 {
+        let body = response.get_body_as_slice().await?;
+        check_table_presence(options.table_name.as_str(), metrics.body_received(body))?;
+        return Ok(None);
+    }
+
+    check_error(&mut response).await?;
+
+    if !is_ok_result(&response) {
+        return Ok(None);
+    }
+
+    let result: Result<Vec<PartitionMetaHttpContract>, _> =
+        serde_json::from_slice(metrics.body_received(response.get_body_as_slice().await?));
+
+    match result {
+        // Server filters by partitionKey, so there is the requested partition only or nothing
+        Ok(result) => Ok(result.into_iter().next().map(|itm| itm.content_size)),
+        Err(err) => Err(DataWriterError::Error(format!(
+            "Failed to deserialize: {:?}",
+            err
+        ))),
+    }
+}
+
+pub async fn delete_enum_case<
+    TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
+    TResult: MyNoSqlEntity
+        + From<TEntity>
+        + my_no_sql_abstractions::GetMyNoSqlEntity
+        + Sync
+        + Send
+        + 'static,
+>(
+    flurl: FlUrl,
+    options: &WriterOptions,
+) -> Result<Option<TResult>, DataWriterError> {
+    let entity: Option<TEntity> =
+        delete_row(flurl, options, TResult::PARTITION_KEY, TResult::ROW_KEY).await?;
+
+    match entity {
+        Some(entity) => Ok(Some(entity.into())),
+        None => Ok(None),
+    }
+}
 
 pub async fn delete_enum_case_with_row_key<
     TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
@@ -351,9 +1066,11 @@ pub async fn delete_enum_case_with_row_key<
         + 'static,
 >(
     flurl: FlUrl,
+    options: &WriterOptions,
     row_key: &str,
 ) -> Result<Option<TResult>, DataWriterError> {
-    let entity: Option<TEntity> = delete_row(flurl, TResult::PARTITION_KEY, row_key).await?;
+    let entity: Option<TEntity> =
+        delete_row(flurl, options, TResult::PARTITION_KEY, row_key).await?;
 
     match entity {
         Some(entity) => Ok(Some(entity.into())),
@@ -363,17 +1080,24 @@ pub async fn delete_enum_case_with_row_key<
 
 pub async fn delete_row<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
     flurl: FlUrl,
+    options: &WriterOptions,
     partition_key: &str,
     row_key: &str,
 ) -> Result<Option<TEntity>, DataWriterError> {
+    let metrics = RequestMetrics::start("delete_row", options.table_name.as_str());
     let mut response = flurl
-        .append_path_segment(API_SEGMENT)
-        .append_path_segment(ROW_CONTROLLER)
-        .with_partition_key_as_query_param(partition_key)
-        .with_row_key_as_query_param(row_key)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
+        .append_url(UrlBuilder::api_row())
+        .with_partition_key_as_query_param(partition_key, options.key_case)
+        .with_row_key_as_query_param(row_key, options.key_case)
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .before_send(options.request_interceptor.as_ref())
+        .await
         .delete()
-        .await?;
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
 
     if response.get_status_code() == 404 {
         return Ok(None);
@@ -382,7 +1106,48 @@ pub async fn delete_row<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync
     check_error(&mut response).await?;
 
     if response.get_status_code() == 200 {
-        let entity = TEntity::deserialize_entity(response.get_body_as_slice().await?).unwrap();
+        let body = metrics.body_received(response.get_body_as_slice().await?);
+        let entity = deserialize_entity(options.table_name.as_str(), body)?;
+        return Ok(Some(entity));
+    }
+
+    return Ok(None);
+}
+
+pub async fn delete_row_if<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    partition_key: &str,
+    row_key: &str,
+    field_name: &str,
+    expected_value: &str,
+) -> Result<Option<TEntity>, DataWriterError> {
+    let metrics = RequestMetrics::start("delete_row_if", options.table_name.as_str());
+    let mut response = flurl
+        .append_url(UrlBuilder::api_row())
+        .with_partition_key_as_query_param(partition_key, options.key_case)
+        .with_row_key_as_query_param(row_key, options.key_case)
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .append_query_param("fieldName", Some(field_name))
+        .append_query_param("expectedValue", Some(expected_value))
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .delete()
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if response.get_status_code() == 404 {
+        return Ok(None);
+    }
+
+    check_error(&mut response).await?;
+
+    if response.get_status_code() == 200 {
+        let body = metrics.body_received(response.get_body_as_slice().await?);
+        let entity = deserialize_entity(options.table_name.as_str(), body)?;
         return Ok(Some(entity));
     }
 
@@ -391,15 +1156,22 @@ pub async fn delete_row<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync
 
 pub async fn delete_partitions(
     flurl: FlUrl,
-    table_name: &str,
+    options: &WriterOptions,
     partition_keys: &[&str],
 ) -> Result<(), DataWriterError> {
+    let metrics = RequestMetrics::start("delete_partitions", options.table_name.as_str());
     let mut response = flurl
-        .append_path_segment(ROWS_CONTROLLER)
-        .with_table_name_as_query_param(table_name)
-        .with_partition_keys_as_query_param(partition_keys)
+        .append_url(UrlBuilder::rows())
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .with_partition_keys_as_query_param(partition_keys, options.key_case)
+        .before_send(options.request_interceptor.as_ref())
+        .await
         .delete()
-        .await?;
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
 
     if response.get_status_code() == 404 {
         return Ok(());
@@ -410,14 +1182,177 @@ pub async fn delete_partitions(
     return Ok(());
 }
 
+pub async fn touch_rows<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    partition_key: &str,
+    row_keys: &[&str],
+) -> Result<(), DataWriterError> {
+    let metrics = RequestMetrics::start("touch_rows", options.table_name.as_str());
+    let mut flurl = flurl
+        .append_url(UrlBuilder::api_rows().update_last_read_time())
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .with_partition_key_as_query_param(partition_key, options.key_case);
+
+    for row_key in row_keys {
+        flurl = flurl.with_row_key_as_query_param(row_key, options.key_case);
+    }
+
+    let mut response = flurl
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .post(None)
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if response.get_status_code() == 404 {
+        return Ok(());
+    }
+
+    check_error(&mut response).await?;
+
+    return Ok(());
+}
+
+// false if there is no such partition. Missing table fails with TableNotFound
+pub async fn touch_partition(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    partition_key: &str,
+) -> Result<bool, DataWriterError> {
+    let metrics = RequestMetrics::start("touch_partition", options.table_name.as_str());
+    let mut response = flurl
+        .append_url(UrlBuilder::api_partitions().update_last_read_time())
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .with_partition_key_as_query_param(partition_key, options.key_case)
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .post(None)
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if response.get_status_code() == 404 {
+        let body = response.get_body_as_slice().await?;
+        check_table_presence(options.table_name.as_str(), metrics.body_received(body))?;
+        return Ok(false);
+    }
+
+    check_error(&mut response).await?;
+
+    return Ok(true);
+}
+
+// false if there is no such partition. Missing table fails with TableNotFound
+pub async fn set_partition_expiration(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    partition_key: &str,
+    expires: Option<DateTimeAsMicroseconds>,
+) -> Result<bool, DataWriterError> {
+    let expires = match expires {
+        Some(expires) => expires.to_rfc3339(),
+        None => "Null".to_string(),
+    };
+
+    let metrics = RequestMetrics::start("set_partition_expiration", options.table_name.as_str());
+    let mut response = flurl
+        .append_url(UrlBuilder::api_partitions().set_expiration_time())
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .with_partition_key_as_query_param(partition_key, options.key_case)
+        .append_query_param("expires", Some(expires))
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .post(None)
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if response.get_status_code() == 404 {
+        let body = response.get_body_as_slice().await?;
+        check_table_presence(options.table_name.as_str(), metrics.body_received(body))?;
+        return Ok(false);
+    }
+
+    check_error(&mut response).await?;
+
+    return Ok(true);
+}
+
 pub async fn get_all<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
     flurl: FlUrl,
+    options: &WriterOptions,
 ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+    let mut result = Vec::new();
+
+    if get_all_into(flurl, options, &mut result).await? {
+        return Ok(Some(result));
+    }
+
+    return Ok(None);
+}
+
+// Buffer is cleared and filled with the entities of the table. Returns false if there is no table
+pub async fn get_all_into<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
+    flurl: FlUrl,
+    options: &WriterOptions,
+    buf: &mut Vec<TEntity>,
+) -> Result<bool, DataWriterError> {
+    buf.clear();
+
+    let metrics = RequestMetrics::start("get_all", options.table_name.as_str());
     let mut response = flurl
-        .append_path_segment(ROW_CONTROLLER)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
+        .append_url(UrlBuilder::row())
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .before_send(options.request_interceptor.as_ref())
+        .await
         .get()
-        .await?;
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
+
+    if response.get_status_code() == 404 {
+        return Ok(false);
+    }
+
+    check_error(&mut response).await?;
+
+    if is_ok_result(&response) {
+        let body = metrics.body_received(response.get_body_as_slice().await?);
+        deserialize_entities_into(options.table_name.as_str(), body, buf)?;
+        return Ok(true);
+    }
+
+    return Ok(false);
+}
+
+// Response is given away before the body is read, so the body can be consumed by chunks.
+// None if there is no table
+pub async fn get_all_response(
+    flurl: FlUrl,
+    options: &WriterOptions,
+) -> Result<Option<FlUrlResponse>, DataWriterError> {
+    let metrics = RequestMetrics::start("get_all_streamed", options.table_name.as_str());
+    let mut response = flurl
+        .append_url(UrlBuilder::row())
+        .with_table_name_as_query_param(options.table_name.as_str())
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .get()
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
 
     if response.get_status_code() == 404 {
         return Ok(None);
@@ -426,27 +1361,95 @@ pub async fn get_all<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + S
     check_error(&mut response).await?;
 
     if is_ok_result(&response) {
-        let entities = deserialize_entities(response.get_body_as_slice().await?)?;
-        return Ok(Some(entities));
+        return Ok(Some(response));
     }
 
     return Ok(None);
 }
 
+pub fn get_body_chunks(
+    response: FlUrlResponse,
+) -> impl Stream<Item = Result<Vec<u8>, DataWriterError>> {
+    futures::stream::unfold(Some(response.get_body_as_stream()), |body| async move {
+        let mut body = body?;
+
+        match body.get_next_chunk().await {
+            Ok(Some(chunk)) => Some((Ok(chunk), Some(body))),
+            Ok(None) => None,
+            Err(err) => Some((Err(err.into()), None)),
+        }
+    })
+}
+
+// Entities are deserialized as soon as they are complete, so only the current chunk
+// and the entities which are not taken yet are kept in memory
+pub fn deserialize_entities_stream<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+    table_name: String,
+    chunks: impl Stream<Item = Result<Vec<u8>, DataWriterError>>,
+) -> impl Stream<Item = Result<TEntity, DataWriterError>> {
+    let state = (
+        Some(Box::pin(chunks)),
+        super::raw_json_array::RawJsonArraySplitter::new(),
+        VecDeque::new(),
+        table_name,
+    );
+
+    futures::stream::unfold(
+        state,
+        |(mut chunks, mut splitter, mut ready, table_name)| async move {
+            loop {
+                if let Some(raw) = ready.pop_front() {
+                    let entity = deserialize_entity(&table_name, &raw);
+                    return Some((entity, (chunks, splitter, ready, table_name)));
+                }
+
+                match chunks.as_mut()?.next().await {
+                    Some(Ok(chunk)) => match splitter.push(&chunk) {
+                        Ok(items) => ready.extend(items),
+                        Err(err) => return Some((Err(err), (None, splitter, ready, table_name))),
+                    },
+                    Some(Err(err)) => return Some((Err(err), (None, splitter, ready, table_name))),
+                    None => {
+                        if let Err(err) = splitter.finish() {
+                            return Some((Err(err), (None, splitter, ready, table_name)));
+                        }
+
+                        chunks = None;
+                    }
+                }
+            }
+        },
+    )
+}
+
 pub async fn clean_table_and_bulk_insert<
     TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
 >(
     flurl: FlUrl,
+    options: &WriterOptions,
     entities: &[TEntity],
     sync_period: &DataSynchronizationPeriod,
 ) -> Result<(), DataWriterError> {
+    let metrics = RequestMetrics::start("clean_table_and_bulk_insert", options.table_name.as_str());
+
+    let (flurl, body) = BulkCompression::apply(
+        options.bulk_compression,
+        flurl,
+        Some(serialize_entities_to_body(options, entities.iter())?),
+    );
+
     let mut response = flurl
-        .append_path_segment(BULK_CONTROLLER)
-        .append_path_segment("CleanAndBulkInsert")
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
+        .append_url(UrlBuilder::bulk().clean_and_insert())
+        .with_table_name_as_query_param(options.table_name.as_str())
         .append_data_sync_period(sync_period)
-        .post(serialize_entities_to_body(entities))
-        .await?;
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .post(body.map(|body| metrics.body_sent(body)))
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
 
     check_error(&mut response).await?;
 
@@ -457,52 +1460,231 @@ pub async fn clean_partition_and_bulk_insert<
     TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
 >(
     flurl: FlUrl,
+    options: &WriterOptions,
     partition_key: &str,
     entities: &[TEntity],
     sync_period: &DataSynchronizationPeriod,
 ) -> Result<(), DataWriterError> {
+    validate_partition_keys(partition_key, entities)?;
+
+    let metrics = RequestMetrics::start(
+        "clean_partition_and_bulk_insert",
+        options.table_name.as_str(),
+    );
     let mut response = flurl
-        .append_path_segment(BULK_CONTROLLER)
-        .append_path_segment("CleanAndBulkInsert")
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
+        .append_url(UrlBuilder::bulk().clean_and_insert())
+        .with_table_name_as_query_param(options.table_name.as_str())
         .append_data_sync_period(sync_period)
-        .with_partition_key_as_query_param(partition_key)
-        .post(serialize_entities_to_body(entities))
-        .await?;
+        .with_partition_key_as_query_param(partition_key, options.key_case)
+        .before_send(options.request_interceptor.as_ref())
+        .await
+        .post(
+            Some(serialize_entities_to_body(options, entities.iter())?)
+                .map(|body| metrics.body_sent(body)),
+        )
+        .await
+        .map_err(|err| metrics.failed(err))?;
+    metrics.response(response.get_status_code());
+    super::request_interceptor::after_response(options.request_interceptor.as_ref(), &response)
+        .await;
 
     check_error(&mut response).await?;
 
     return Ok(());
 }
 
+pub fn validate_skip_and_limit(
+    skip: Option<i32>,
+    limit: Option<i32>,
+) -> Result<(), DataWriterError> {
+    if let Some(skip) = skip {
+        if skip < 0 {
+            return Err(DataWriterError::Error(format!(
+                "skip must be non-negative. Got: {}",
+                skip
+            )));
+        }
+    }
+
+    if let Some(limit) = limit {
+        if limit < 0 {
+            return Err(DataWriterError::Error(format!(
+                "limit must be non-negative. Got: {}",
+                limit
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+// Checks that the json is an object with PartitionKey and RowKey the same way the server does
+pub fn validate_raw_entity(raw_json: &[u8]) -> Result<(), DataWriterError> {
+    match my_no_sql_core::db_json_entity::DbJsonEntity::from_slice(raw_json) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(DataWriterError::Error(format!(
+            "Invalid entity json: {:?}",
+            err
+        ))),
+    }
+}
+
+// Patch is merged field by field, so anything but an object is rejected before sending
+pub fn validate_merge_patch(patch: &serde_json::Value) -> Result<(), DataWriterError> {
+    if patch.is_object() {
+        return Ok(());
+    }
+
+    Err(DataWriterError::Error(format!(
+        "Merge patch must be a json object. Got: {}",
+        patch
+    )))
+}
+
+// Entities of the other partitions would be written next to the cleaned one, so they are rejected before sending
+pub fn validate_partition_keys<TEntity: MyNoSqlEntity>(
+    partition_key: &str,
+    entities: &[TEntity],
+) -> Result<(), DataWriterError> {
+    for entity in entities {
+        if entity.get_partition_key() != partition_key {
+            return Err(DataWriterError::Error(format!(
+                "Entity with RowKey '{}' has PartitionKey '{}' but partition '{}' is being replaced",
+                entity.get_row_key(),
+                entity.get_partition_key(),
+                partition_key
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+// Body is read chunk by chunk and the reading stops as soon as the limit is exceeded,
+// so the entity which is too large is never buffered. Size of the error is the amount read so far
+async fn read_entity_body(
+    response: FlUrlResponse,
+    max_entity_bytes: Option<usize>,
+) -> Result<Vec<u8>, DataWriterError> {
+    if max_entity_bytes.is_none() {
+        return Ok(response.receive_body().await?);
+    }
+
+    let mut body = response.get_body_as_stream();
+    let mut result = Vec::new();
+
+    while let Some(chunk) = body.get_next_chunk().await? {
+        result.extend_from_slice(&chunk);
+        check_entity_size(result.len(), max_entity_bytes)?;
+    }
+
+    Ok(result)
+}
+
+pub fn check_entity_size(
+    size: usize,
+    max_entity_bytes: Option<usize>,
+) -> Result<(), DataWriterError> {
+    if let Some(limit) = max_entity_bytes {
+        if size > limit {
+            return Err(DataWriterError::EntityTooLarge { size, limit });
+        }
+    }
+
+    Ok(())
+}
+
 fn is_ok_result(response: &FlUrlResponse) -> bool {
     response.get_status_code() >= 200 && response.get_status_code() < 300
 }
 
-fn serialize_entities_to_body<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+// Size of the body bulk_insert_or_replace sends with the same options. 0 if there are no entities,
+// since nothing is sent then. Entities are serialized and compressed to compute it.
+// The client TimeStamp is taken at the time of the estimate. The batch gets a new one when it is sent,
+// so the compressed size may differ by a few bytes
+pub fn estimate_bulk_body_size<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+    options: &WriterOptions,
     entities: &[TEntity],
-) -> Option<Vec<u8>> {
+) -> Result<usize, DataWriterError> {
+    if entities.is_empty() {
+        return Ok(0);
+    }
+
+    let body = serialize_entities_to_body(options, entities.iter())?;
+
+    match BulkCompression::compress(options.bulk_compression, Some(body)) {
+        (Some(body), _) => Ok(body.len()),
+        (None, _) => Ok(0),
+    }
+}
+
+fn serialize_entities_to_body<'s, TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + 's>(
+    options: &WriterOptions,
+    entities: impl ExactSizeIterator<Item = &'s TEntity>,
+) -> Result<Vec<u8>, DataWriterError> {
     if entities.len() == 0 {
-        return Some(vec![b'[', b']']);
+        return Ok(vec![b'[', b']']);
     }
 
+    // Whole batch is stamped with the same value
+    let time_stamp = get_client_time_stamp(options.client_time_stamp);
+
     let mut json_array_writer = JsonArrayWriter::new();
 
     for entity in entities {
-        let payload = entity.serialize_entity();
+        let payload = prepare_payload(
+            entity.serialize_entity(),
+            options.key_case,
+            time_stamp.as_ref(),
+        )?;
         let payload: RawJsonObject = payload.into();
         json_array_writer.write(payload);
     }
 
-    Some(json_array_writer.build())
+    Ok(json_array_writer.build())
+}
+
+fn prepare_payload(
+    payload: Vec<u8>,
+    key_case: KeyCase,
+    time_stamp: Option<&JsonTimeStamp>,
+) -> Result<Vec<u8>, DataWriterError> {
+    let payload = key_case.apply_to_entity_payload(payload);
+
+    match time_stamp {
+        Some(time_stamp) => inject_client_time_stamp(payload, time_stamp),
+        None => Ok(payload),
+    }
+}
+
+// Server responds 404 both if the row is missing and if the table is missing.
+// Missing table is recognized by the fail contract with the TableNotFound reason
+pub fn check_table_presence(
+    table_name: &str,
+    not_found_body: &[u8],
+) -> Result<(), DataWriterError> {
+    let fail_contract: OperationFailHttpContract = match serde_json::from_slice(not_found_body) {
+        Ok(fail_contract) => fail_contract,
+        Err(_) => return Ok(()),
+    };
+
+    if ServerFailReason::from(fail_contract.reason.as_str()) == ServerFailReason::TableNotFound {
+        return Err(DataWriterError::TableNotFound(table_name.to_string()));
+    }
+
+    Ok(())
 }
 
 async fn check_error(response: &mut FlUrlResponse) -> Result<(), DataWriterError> {
-    let result = match response.get_status_code() {
+    let status_code = response.get_status_code();
+    let result = match status_code {
         400 => Err(deserialize_error(response).await?),
 
         409 => Err(DataWriterError::TableNotFound("".to_string())),
-        _ => Ok(()),
+        _ => match DataWriterError::from_status_code(status_code) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        },
     };
 
     if let Err(err) = &result {
@@ -524,16 +1706,7 @@ async fn deserialize_error(
     let body_as_str = std::str::from_utf8(body)?;
 
     let result = match serde_json::from_str::<OperationFailHttpContract>(body_as_str) {
-        Ok(fail_contract) => match fail_contract.reason.as_str() {
-            "TableAlreadyExists" => DataWriterError::TableAlreadyExists(fail_contract.message),
-            "TableNotFound" => DataWriterError::TableNotFound(fail_contract.message),
-            "RecordAlreadyExists" => DataWriterError::RecordAlreadyExists(fail_contract.message),
-            "RequiredEntityFieldIsMissing" => {
-                DataWriterError::RequiredEntityFieldIsMissing(fail_contract.message)
-            }
-            "JsonParseFail" => DataWriterError::ServerCouldNotParseJson(fail_contract.message),
-            _ => DataWriterError::Error(format!("Not supported error. {:?}", fail_contract)),
-        },
+        Ok(fail_contract) => fail_contract.into(),
         Err(err) => {
             return Err(DataWriterError::Error(format!(
                 "Failed to deserialize error: {:?}",
@@ -545,42 +1718,79 @@ async fn deserialize_error(
     Ok(result)
 }
 
-fn deserialize_entities<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
-    src: &[u8],
-) -> Result<Vec<TEntity>, DataWriterError> {
-    let mut result = Vec::new();
-    let slice_iterator = SliceIterator::new(src);
-    let json_array_iterator = JsonArrayIterator::new(slice_iterator);
+// Json which is put into the error message is cut to keep the message readable
+const MAX_JSON_IN_ERROR_MESSAGE: usize = 256;
 
-    if let Err(err) = &json_array_iterator {
-        panic!(
-            "Can not deserialize entities for table: {}. Err: {:?}",
-            TEntity::TABLE_NAME,
-            err
-        );
+fn get_json_for_error_message(src: &[u8]) -> String {
+    if src.len() <= MAX_JSON_IN_ERROR_MESSAGE {
+        return String::from_utf8_lossy(src).to_string();
     }
 
-    let mut json_array_iterator = json_array_iterator.unwrap();
-
-    while let Some(item) = json_array_iterator.get_next() {
-        let itm = item.unwrap();
+    format!(
+        "{}... ({} bytes)",
+        String::from_utf8_lossy(&src[..MAX_JSON_IN_ERROR_MESSAGE]),
+        src.len()
+    )
+}
 
-        result.push(TEntity::deserialize_entity(itm.as_bytes(&json_array_iterator)).unwrap());
-    }
-    Ok(result)
+// Malformed entity from the server fails the read with the bytes of the entity in the message
+fn deserialize_entity<TEntity: MyNoSqlEntitySerializer>(
+    table_name: &str,
+    src: &[u8],
+) -> Result<TEntity, DataWriterError> {
+    TEntity::deserialize_entity(src).map_err(|err| {
+        DataWriterError::Error(format!(
+            "Can not deserialize entity of table: {}. Err: {}. Entity: {}",
+            table_name,
+            err,
+            get_json_for_error_message(src)
+        ))
+    })
+}
 
-    /*
+fn deserialize_entities<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+    table_name: &str,
+    src: &[u8],
+) -> Result<Vec<TEntity>, DataWriterError> {
     let mut result = Vec::new();
+    deserialize_entities_into(table_name, src, &mut result)?;
+    Ok(result)
+}
 
+fn deserialize_entities_into<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+    table_name: &str,
+    src: &[u8],
+    result: &mut Vec<TEntity>,
+) -> Result<(), DataWriterError> {
+    let slice_iterator = SliceIterator::new(src);
+    let mut json_array_iterator = match JsonArrayIterator::new(slice_iterator) {
+        Ok(json_array_iterator) => json_array_iterator,
+        Err(err) => {
+            return Err(DataWriterError::Error(format!(
+                "Can not deserialize entities for table: {}. Err: {:?}. Body: {}",
+                table_name,
+                err,
+                get_json_for_error_message(src)
+            )));
+        }
+    };
 
+    let mut index = 0;
+    while let Some(item) = json_array_iterator.get_next() {
+        let itm = item.map_err(|err| {
+            DataWriterError::Error(format!(
+                "Can not read entity #{} of table: {}. Err: {:?}",
+                index, table_name, err
+            ))
+        })?;
 
-    for itm in JsonArrayIterator::new(src) {
-        let itm = itm.unwrap();
+        let entity = deserialize_entity(table_name, itm.as_bytes(&json_array_iterator))?;
 
-        result.push(TEntity::deserialize_entity(itm).unwrap());
+        result.push(entity);
+        index += 1;
     }
-    Ok(result)
-     */
+
+    Ok(())
 }
 
 async fn create_table_errors_handler(
@@ -605,43 +1815,9 @@ async fn create_table_errors_handler(
 
 #[cfg(test)]
 mod tests {
-    use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
-    use serde::Serialize;
-    use serde_derive::Deserialize;
-
-    #[derive(Debug, Serialize, Deserialize)]
-    #[serde(rename_all = "PascalCase")]
-    struct TestEntity {
-        partition_key: String,
-        row_key: String,
-    }
-
-    impl MyNoSqlEntity for TestEntity {
-        const TABLE_NAME: &'static str = "test";
-        const LAZY_DESERIALIZATION: bool = false;
+    use futures::StreamExt;
 
-        fn get_partition_key(&self) -> &str {
-            &self.partition_key
-        }
-
-        fn get_row_key(&self) -> &str {
-            &self.row_key
-        }
-
-        fn get_time_stamp(&self) -> i64 {
-            0
-        }
-    }
-
-    impl MyNoSqlEntitySerializer for TestEntity {
-        fn serialize_entity(&self) -> Vec<u8> {
-            my_no_sql_core::entity_serializer::serialize(self)
-        }
-
-        fn deserialize_entity(src: &[u8]) -> Result<Self, String> {
-            my_no_sql_core::entity_serializer::deserialize(src)
-        }
-    }
+    use crate::test_fixtures::TestEntity;
 
     #[test]
     fn test() {
@@ -649,23 +1825,271 @@ mod tests {
             TestEntity {
                 partition_key: "1".to_string(),
                 row_key: "1".to_string(),
+                ..Default::default()
             },
             TestEntity {
                 partition_key: "1".to_string(),
                 row_key: "2".to_string(),
+                ..Default::default()
             },
             TestEntity {
                 partition_key: "2".to_string(),
                 row_key: "1".to_string(),
+                ..Default::default()
+            },
+            TestEntity {
+                partition_key: "2".to_string(),
+                row_key: "2".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let options = super::WriterOptions::new("test".to_string());
+
+        let as_json = super::serialize_entities_to_body(&options, entities.iter()).unwrap();
+
+        assert_eq!(
+            super::estimate_bulk_body_size(&options, &entities).unwrap(),
+            as_json.len()
+        );
+        assert_eq!(
+            super::estimate_bulk_body_size::<TestEntity>(&options, &[]).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_estimate_bulk_body_size_applies_the_options() {
+        let entities: Vec<TestEntity> = (0..100)
+            .map(|i| TestEntity {
+                partition_key: "pk".to_string(),
+                row_key: i.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        let mut options = super::WriterOptions::new("test".to_string());
+
+        let as_json = super::serialize_entities_to_body(&options, entities.iter()).unwrap();
+
+        options.client_time_stamp = true;
+        let stamped = super::estimate_bulk_body_size(&options, &entities).unwrap();
+        assert!(stamped > as_json.len());
+
+        options.client_time_stamp = false;
+        options.bulk_compression = Some(super::BulkCompression { min_size: 0 });
+        let compressed = super::estimate_bulk_body_size(&options, &entities).unwrap();
+        assert!(compressed < as_json.len());
+
+        options.bulk_compression = Some(super::BulkCompression {
+            min_size: as_json.len() + 1,
+        });
+        let below_threshold = super::estimate_bulk_body_size(&options, &entities).unwrap();
+        assert_eq!(below_threshold, as_json.len());
+    }
+
+    #[test]
+    fn test_validate_partition_keys() {
+        let entities = vec![
+            TestEntity {
+                partition_key: "1".to_string(),
+                row_key: "1".to_string(),
+                ..Default::default()
             },
             TestEntity {
                 partition_key: "2".to_string(),
                 row_key: "2".to_string(),
+                ..Default::default()
             },
         ];
 
-        let as_json = super::serialize_entities_to_body(&entities).unwrap();
+        assert!(super::validate_partition_keys("1", &entities[..1]).is_ok());
+
+        match super::validate_partition_keys("1", &entities) {
+            Err(crate::DataWriterError::Error(message)) => assert!(message.contains("'2'")),
+            _ => panic!("Mismatched partition key must be rejected"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_entities_stream() {
+        let src = r#"[{"PartitionKey":"pk","RowKey":"rk1"}, {"PartitionKey":"pk","RowKey":"rk2"}]"#;
+
+        let chunks = src
+            .as_bytes()
+            .chunks(5)
+            .map(|chunk| Ok(chunk.to_vec()))
+            .collect::<Vec<_>>();
+
+        let result: Vec<_> = super::deserialize_entities_stream::<TestEntity>(
+            "test".to_string(),
+            futures::stream::iter(chunks),
+        )
+        .collect()
+        .await;
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].as_ref().unwrap().row_key, "rk1");
+        assert_eq!(result[1].as_ref().unwrap().row_key, "rk2");
+
+        let chunks = vec![Ok(br#"[{"PartitionKey":"pk","RowKey":"rk1"},{"#.to_vec())];
+
+        let result: Vec<_> = super::deserialize_entities_stream::<TestEntity>(
+            "test".to_string(),
+            futures::stream::iter(chunks),
+        )
+        .collect()
+        .await;
+
+        assert_eq!(result.len(), 2);
+        assert!(result[0].is_ok());
+        assert!(result[1].is_err());
+    }
+
+    #[test]
+    fn test_malformed_entity_is_an_error() {
+        let src = br#"[{"PartitionKey":"pk","RowKey":"rk"},{"PartitionKey":1,"RowKey":"rk"}]"#;
+
+        match super::deserialize_entities::<TestEntity>("test", src) {
+            Err(crate::DataWriterError::Error(message)) => {
+                assert!(message.contains("test"));
+                assert!(message.contains(r#"{"PartitionKey":1,"RowKey":"rk"}"#));
+            }
+            _ => panic!("Malformed entity must be an error"),
+        }
+
+        assert!(super::deserialize_entities::<TestEntity>("test", b"not an array").is_err());
+    }
+
+    #[test]
+    fn test_malformed_entity_error_does_not_contain_the_whole_body() {
+        let mut src = String::from("[");
+        for i in 0..1000 {
+            src.push_str(&format!(r#"{{"PartitionKey":"pk","RowKey":"{}"}},"#, i));
+        }
+        src.push_str(r#"{"PartitionKey":1,"RowKey":"rk"}]"#);
+
+        match super::deserialize_entities::<TestEntity>("test", src.as_bytes()) {
+            Err(crate::DataWriterError::Error(message)) => {
+                assert!(message.contains(r#"{"PartitionKey":1,"RowKey":"rk"}"#));
+                assert!(!message.contains(r#""RowKey":"0""#));
+            }
+            _ => panic!("Malformed entity must be an error"),
+        }
 
-        println!("{}", std::str::from_utf8(&as_json).unwrap());
+        let big_entity = format!(r#"{{"PartitionKey":1,"RowKey":"{}"}}"#, "a".repeat(1000));
+        match super::deserialize_entity::<TestEntity>("test", big_entity.as_bytes()) {
+            Err(crate::DataWriterError::Error(message)) => {
+                assert!(message.len() < big_entity.len());
+                assert!(message.ends_with(&format!("... ({} bytes)", big_entity.len())));
+            }
+            _ => panic!("Malformed entity must be an error"),
+        }
+    }
+
+    #[test]
+    fn test_record_is_changed_error() {
+        let fail_contract = b"{\"reason\":\"RecordIsChanged\",\"message\":\"test\"}";
+        assert!(matches!(
+            super::record_is_changed_error(fail_contract),
+            crate::DataWriterError::RecordIsChanged(message) if message == "test"
+        ));
+
+        assert!(matches!(
+            super::record_is_changed_error(b"Conflict"),
+            crate::DataWriterError::RecordIsChanged(message) if message == "Conflict"
+        ));
+    }
+
+    #[test]
+    fn test_check_table_presence() {
+        let table_not_found = b"{\"reason\":\"TableNotFound\",\"message\":\"test\"}";
+        assert!(matches!(
+            super::check_table_presence("test", table_not_found),
+            Err(crate::DataWriterError::TableNotFound(table_name)) if table_name == "test"
+        ));
+
+        // Row is not found
+        assert!(super::check_table_presence("test", b"").is_ok());
+        assert!(super::check_table_presence("test", b"Not found").is_ok());
+
+        let record_not_found = b"{\"reason\":\"RecordNotFound\",\"message\":\"test\"}";
+        assert!(super::check_table_presence("test", record_not_found).is_ok());
+    }
+
+    #[test]
+    fn test_check_schema_version() {
+        assert!(super::check_schema_version(2, b"{\"schemaVersion\":2}").is_ok());
+
+        assert!(matches!(
+            super::check_schema_version(2, b"{\"schemaVersion\":1}"),
+            Err(crate::DataWriterError::SchemaVersionMismatch {
+                expected: 2,
+                actual: Some(1)
+            })
+        ));
+
+        for body in [b"".as_slice(), b"{}", b"Ok"] {
+            assert!(matches!(
+                super::check_schema_version(2, body),
+                Err(crate::DataWriterError::SchemaVersionMismatch {
+                    expected: 2,
+                    actual: None
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_raw_entity() {
+        assert!(super::validate_raw_entity(b"{\"PartitionKey\":\"pk\",\"RowKey\":\"rk\"}").is_ok());
+
+        assert!(matches!(
+            super::validate_raw_entity(b"{\"PartitionKey\":\"pk\"}"),
+            Err(crate::DataWriterError::Error(_))
+        ));
+
+        assert!(matches!(
+            super::validate_raw_entity(b"{\"RowKey\":\"rk\"}"),
+            Err(crate::DataWriterError::Error(_))
+        ));
+    }
+
+    #[test]
+    fn test_skip_and_limit_boundaries_are_accepted() {
+        assert!(super::validate_skip_and_limit(None, None).is_ok());
+        assert!(super::validate_skip_and_limit(Some(0), Some(0)).is_ok());
+        assert!(super::validate_skip_and_limit(Some(i32::MAX), Some(i32::MAX)).is_ok());
+    }
+
+    #[test]
+    fn test_negative_skip_and_limit_are_rejected() {
+        assert!(matches!(
+            super::validate_skip_and_limit(Some(-1), None),
+            Err(crate::DataWriterError::Error(_))
+        ));
+
+        assert!(matches!(
+            super::validate_skip_and_limit(None, Some(-1)),
+            Err(crate::DataWriterError::Error(_))
+        ));
+
+        assert!(matches!(
+            super::validate_skip_and_limit(Some(i32::MIN), Some(10)),
+            Err(crate::DataWriterError::Error(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_entity_size() {
+        assert!(super::check_entity_size(100, None).is_ok());
+        assert!(super::check_entity_size(100, Some(100)).is_ok());
+        assert!(matches!(
+            super::check_entity_size(101, Some(100)),
+            Err(crate::DataWriterError::EntityTooLarge {
+                size: 101,
+                limit: 100
+            })
+        ));
     }
 }
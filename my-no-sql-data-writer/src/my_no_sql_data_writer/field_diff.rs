@@ -0,0 +1,119 @@
+use serde_json::{Map, Value};
+
+const IGNORED_FIELDS: [&str; 2] = ["TimeStamp", "Expires"];
+
+// Top level fields which differ between local entity and the stored one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    pub changed: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl FieldDiff {
+    pub fn compute(local: &[u8], stored: &[u8]) -> Result<Option<Self>, String> {
+        let local = parse_object(local)?;
+        let stored = parse_object(stored)?;
+
+        let mut result = Self {
+            changed: Vec::new(),
+            added: Vec::new(),
+            removed: Vec::new(),
+        };
+
+        for (name, local_value) in &local {
+            if is_ignored(name) {
+                continue;
+            }
+
+            match stored.get(name) {
+                Some(stored_value) => {
+                    if stored_value != local_value {
+                        result.changed.push(name.to_string());
+                    }
+                }
+                None => result.added.push(name.to_string()),
+            }
+        }
+
+        for name in stored.keys() {
+            if is_ignored(name) {
+                continue;
+            }
+
+            if !local.contains_key(name) {
+                result.removed.push(name.to_string());
+            }
+        }
+
+        if result.is_empty() {
+            return Ok(None);
+        }
+
+        result.changed.sort();
+        result.added.sort();
+        result.removed.sort();
+
+        Ok(Some(result))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+
+    pub fn get_all_field_names(&self) -> Vec<&str> {
+        let mut result: Vec<&str> = self
+            .changed
+            .iter()
+            .chain(self.added.iter())
+            .chain(self.removed.iter())
+            .map(|itm| itm.as_str())
+            .collect();
+
+        result.sort();
+        result
+    }
+}
+
+fn parse_object(src: &[u8]) -> Result<Map<String, Value>, String> {
+    match serde_json::from_slice(src).map_err(|err| err.to_string())? {
+        Value::Object(result) => Ok(result),
+        _ => Err("Entity json must be an object".to_string()),
+    }
+}
+
+fn is_ignored(name: &str) -> bool {
+    IGNORED_FIELDS
+        .iter()
+        .any(|ignored| ignored.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FieldDiff;
+
+    #[test]
+    fn test_identical_entities_with_different_fields_order() {
+        let local = r#"{"PartitionKey":"Pk","RowKey":"Rk","A":1,"B":{"X":1,"Y":2}}"#;
+        let stored = r#"{"B":{"Y":2,"X":1},"A":1,"RowKey":"Rk","PartitionKey":"Pk","TimeStamp":"2024-01-01T00:00:00"}"#;
+
+        let result = FieldDiff::compute(local.as_bytes(), stored.as_bytes()).unwrap();
+
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_changed_added_removed() {
+        let local = r#"{"PartitionKey":"Pk","RowKey":"Rk","A":2,"C":3}"#;
+        let stored = r#"{"PartitionKey":"Pk","RowKey":"Rk","A":1,"B":2}"#;
+
+        let result = FieldDiff::compute(local.as_bytes(), stored.as_bytes())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(result.changed, vec!["A".to_string()]);
+        assert_eq!(result.added, vec!["C".to_string()]);
+        assert_eq!(result.removed, vec!["B".to_string()]);
+        assert_eq!(result.get_all_field_names(), vec!["A", "B", "C"]);
+    }
+}
@@ -1,18 +1,33 @@
 use flurl::FlUrl;
 use my_no_sql_abstractions::DataSynchronizationPeriod;
 
+use super::{url_builder::UrlBuilder, KeyCase};
+
 pub trait FlUrlExt {
+    fn append_url(self, url: UrlBuilder) -> FlUrl;
     fn with_table_name_as_query_param(self, table_name: &str) -> FlUrl;
     fn append_data_sync_period(self, sync_period: &DataSynchronizationPeriod) -> FlUrl;
-    fn with_partition_key_as_query_param(self, partition_key: &str) -> FlUrl;
-    fn with_partition_keys_as_query_param(self, partition_keys: &[&str]) -> FlUrl;
-    fn with_row_key_as_query_param(self, partition_key: &str) -> FlUrl;
+    fn with_partition_key_as_query_param(self, partition_key: &str, key_case: KeyCase) -> FlUrl;
+    fn with_partition_keys_as_query_param(
+        self,
+        partition_keys: &[&str],
+        key_case: KeyCase,
+    ) -> FlUrl;
+    fn with_row_key_as_query_param(self, row_key: &str, key_case: KeyCase) -> FlUrl;
 
     fn with_skip_as_query_param(self, skip: Option<i32>) -> FlUrl;
     fn with_limit_as_query_param(self, limit: Option<i32>) -> FlUrl;
 }
 
 impl FlUrlExt for FlUrl {
+    fn append_url(self, url: UrlBuilder) -> FlUrl {
+        let mut s = self;
+        for segment in url.get_segments() {
+            s = s.append_path_segment(segment);
+        }
+        s
+    }
+
     fn with_table_name_as_query_param(self, table_name: &str) -> FlUrl {
         self.append_query_param("tableName", Some(table_name))
     }
@@ -31,20 +46,24 @@ impl FlUrlExt for FlUrl {
         self.append_query_param("syncPeriod", Some(value))
     }
 
-    fn with_partition_key_as_query_param(self, partition_key: &str) -> FlUrl {
-        self.append_query_param("partitionKey", Some(partition_key))
+    fn with_partition_key_as_query_param(self, partition_key: &str, key_case: KeyCase) -> FlUrl {
+        self.append_query_param("partitionKey", Some(key_case.apply(partition_key).as_ref()))
     }
 
-    fn with_partition_keys_as_query_param(self, partition_keys: &[&str]) -> FlUrl {
+    fn with_partition_keys_as_query_param(
+        self,
+        partition_keys: &[&str],
+        key_case: KeyCase,
+    ) -> FlUrl {
         let mut s = self;
         for partition_key in partition_keys {
-            s = s.append_query_param("partitionKey", Some(*partition_key));
+            s = s.append_query_param("partitionKey", Some(key_case.apply(partition_key).as_ref()));
         }
         s
     }
 
-    fn with_row_key_as_query_param(self, row_key: &str) -> FlUrl {
-        self.append_query_param("rowKey", Some(row_key))
+    fn with_row_key_as_query_param(self, row_key: &str, key_case: KeyCase) -> FlUrl {
+        self.append_query_param("rowKey", Some(key_case.apply(row_key).as_ref()))
     }
 
     fn with_skip_as_query_param(self, skip: Option<i32>) -> FlUrl {
@@ -7,7 +7,10 @@ use flurl::my_ssh::SshSessionsPool;
 
 use rust_extensions::UnsafeValue;
 
-use super::{CreateTableParams, DataWriterError, MyNoSqlWriterSettings};
+use super::{
+    writer_options::WriterOptions, CircuitBreaker, ConsistencyLevel, CreateTableParams,
+    DataWriterError, MyNoSqlWriterSettings,
+};
 
 #[derive(Clone)]
 pub struct FlUrlFactory {
@@ -20,15 +23,17 @@ pub struct FlUrlFactory {
     #[cfg(feature = "with-ssh")]
     pub ssh_cert_credentials:
         Option<std::collections::HashMap<String, flurl::my_ssh::SshCredentialsSettingsModel>>,
+    pub options: WriterOptions,
+    pub circuit_breaker: Option<Arc<CircuitBreaker>>,
+    pub schema_version: Option<u32>,
     create_table_is_called: Arc<UnsafeValue<bool>>,
-    table_name: &'static str,
 }
 
 impl FlUrlFactory {
     pub fn new(
         settings: Arc<dyn MyNoSqlWriterSettings + Send + Sync + 'static>,
         auto_create_table_params: Option<Arc<CreateTableParams>>,
-        table_name: &'static str,
+        table_name: String,
     ) -> Self {
         Self {
             auto_create_table_params,
@@ -36,13 +41,20 @@ impl FlUrlFactory {
             ssh_sessions_pool: None,
             create_table_is_called: UnsafeValue::new(false).into(),
             settings,
-            table_name,
+            options: WriterOptions::new(table_name),
             #[cfg(feature = "with-ssh")]
             ssh_cert_credentials: None,
             #[cfg(feature = "with-ssh")]
             http_buffer_size: None,
+            circuit_breaker: None,
+            schema_version: None,
         }
     }
+
+    pub fn get_table_name(&self) -> &str {
+        self.options.table_name.as_str()
+    }
+
     #[cfg(not(feature = "with-ssh"))]
     async fn create_fl_url(&self, url: &str) -> FlUrl {
         let fl_url = flurl::FlUrl::new(url);
@@ -64,22 +76,81 @@ impl FlUrlFactory {
         fl_url
     }
 
-    pub async fn get_fl_url(&self) -> Result<(FlUrl, String), DataWriterError> {
+    pub fn is_circuit_open(&self) -> bool {
+        match &self.circuit_breaker {
+            Some(circuit_breaker) => circuit_breaker.is_open(),
+            None => false,
+        }
+    }
+
+    // Every result of the request made with get_fl_url should be passed through here
+    // to let the circuit breaker count failures
+    pub fn track_result<T>(
+        &self,
+        result: Result<T, DataWriterError>,
+    ) -> Result<T, DataWriterError> {
+        if let Some(circuit_breaker) = &self.circuit_breaker {
+            circuit_breaker.on_result(&result);
+        }
+
+        result
+    }
+
+    // Url of the node the requests go to. Table is auto created on the first call
+    async fn get_url(&self) -> Result<String, DataWriterError> {
+        if let Some(circuit_breaker) = &self.circuit_breaker {
+            circuit_breaker.check()?;
+        }
+
         let url = self.settings.get_url().await;
         if !self.create_table_is_called.get_value() {
-            if let Some(crate_table_params) = &self.auto_create_table_params {
-                self.create_table_if_not_exists(url.as_str(), crate_table_params)
-                    .await?;
+            match &self.auto_create_table_params {
+                Some(crate_table_params) => {
+                    let result = self
+                        .create_table_if_not_exists(url.as_str(), crate_table_params)
+                        .await;
+                    self.track_result(result)?;
+                }
+                None => {
+                    if let Some(schema_version) = self.schema_version {
+                        return Err(DataWriterError::Error(format!(
+                            "Table: {}. Schema version {} is checked when the table is auto created, but auto_create_table_params are not set",
+                            self.options.table_name, schema_version
+                        )));
+                    }
+                }
             }
 
             self.create_table_is_called.set_value(true);
         }
 
+        Ok(url)
+    }
+
+    pub async fn get_fl_url(&self) -> Result<(FlUrl, String), DataWriterError> {
+        let url = self.get_url().await?;
         let result = self.create_fl_url(url.as_str()).await;
 
         Ok((result, url))
     }
 
+    pub async fn get_fl_url_with_consistency(
+        &self,
+        consistency: ConsistencyLevel,
+    ) -> Result<(FlUrl, String), DataWriterError> {
+        let url = self.get_url().await?;
+
+        if consistency == ConsistencyLevel::Strong {
+            if let Some(master_url) = self.settings.get_master_url().await {
+                let fl_url = self.create_fl_url(master_url.as_str()).await;
+                return Ok((consistency.fill_fields(fl_url), master_url));
+            }
+        }
+
+        let fl_url = self.create_fl_url(url.as_str()).await;
+        Ok((consistency.fill_fields(fl_url), url))
+    }
+
     pub async fn create_table_if_not_exists(
         &self,
         url: &str,
@@ -88,11 +159,89 @@ impl FlUrlFactory {
         let fl_url = self.create_fl_url(url).await;
         super::execution::create_table_if_not_exists(
             fl_url,
+            &self.options,
             url,
-            self.table_name,
             create_table_params,
+            self.schema_version,
             my_no_sql_abstractions::DataSynchronizationPeriod::Sec1,
         )
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::FlUrlFactory;
+    use crate::{
+        CircuitBreaker, CircuitBreakerConfig, ConsistencyLevel, DataWriterError,
+        MyNoSqlWriterSettings,
+    };
+
+    struct TestSettings {
+        master_url: Option<String>,
+    }
+
+    #[async_trait::async_trait]
+    impl MyNoSqlWriterSettings for TestSettings {
+        async fn get_url(&self) -> String {
+            "http://replica:5123".to_string()
+        }
+
+        async fn get_master_url(&self) -> Option<String> {
+            self.master_url.clone()
+        }
+    }
+
+    fn create_factory(master_url: Option<&str>) -> FlUrlFactory {
+        let settings = TestSettings {
+            master_url: master_url.map(|itm| itm.to_string()),
+        };
+
+        FlUrlFactory::new(Arc::new(settings), None, "test".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_strong_reads_go_to_the_master_node() {
+        let factory = create_factory(Some("http://master:5123"));
+
+        let (_, url) = factory
+            .get_fl_url_with_consistency(ConsistencyLevel::Strong)
+            .await
+            .unwrap();
+        assert_eq!(url, "http://master:5123");
+
+        let (_, url) = factory
+            .get_fl_url_with_consistency(ConsistencyLevel::Eventual)
+            .await
+            .unwrap();
+        assert_eq!(url, "http://replica:5123");
+
+        let factory = create_factory(None);
+        let (_, url) = factory
+            .get_fl_url_with_consistency(ConsistencyLevel::Strong)
+            .await
+            .unwrap();
+        assert_eq!(url, "http://replica:5123");
+    }
+
+    #[tokio::test]
+    async fn test_strong_reads_are_stopped_by_the_open_circuit() {
+        let mut factory = create_factory(Some("http://master:5123"));
+
+        let circuit_breaker = Arc::new(CircuitBreaker::new(CircuitBreakerConfig {
+            failures_threshold: 1,
+            cooldown: Duration::from_secs(60),
+        }));
+        circuit_breaker.on_result::<()>(&Err(DataWriterError::ConnectionRefused(Box::new(
+            DataWriterError::Error("refused".to_string()),
+        ))));
+        factory.circuit_breaker = Some(circuit_breaker);
+
+        let result = factory
+            .get_fl_url_with_consistency(ConsistencyLevel::Strong)
+            .await;
+        assert!(matches!(result, Err(DataWriterError::CircuitOpen)));
+    }
+}
@@ -0,0 +1,77 @@
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCase {
+    AsIs,
+    Lower,
+    Upper,
+}
+
+impl Default for KeyCase {
+    fn default() -> Self {
+        Self::AsIs
+    }
+}
+
+impl KeyCase {
+    pub fn apply<'s>(&self, key: &'s str) -> Cow<'s, str> {
+        match self {
+            KeyCase::AsIs => Cow::Borrowed(key),
+            KeyCase::Lower => Cow::Owned(key.to_lowercase()),
+            KeyCase::Upper => Cow::Owned(key.to_uppercase()),
+        }
+    }
+
+    // Applies casing to PartitionKey and RowKey of serialized entity.
+    // Payload which is not a json object is sent as is
+    pub fn apply_to_entity_payload(&self, payload: Vec<u8>) -> Vec<u8> {
+        if *self == KeyCase::AsIs {
+            return payload;
+        }
+
+        let mut json: serde_json::Value = match serde_json::from_slice(payload.as_slice()) {
+            Ok(json) => json,
+            Err(_) => return payload,
+        };
+
+        let json_object = match json.as_object_mut() {
+            Some(json_object) => json_object,
+            None => return payload,
+        };
+
+        for field in ["PartitionKey", "RowKey"] {
+            if let Some(serde_json::Value::String(key)) = json_object.get_mut(field) {
+                *key = self.apply(key).into_owned();
+            }
+        }
+
+        match serde_json::to_vec(&json) {
+            Ok(result) => result,
+            Err(_) => payload,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyCase;
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(KeyCase::AsIs.apply("Pk"), "Pk");
+        assert_eq!(KeyCase::Lower.apply("Pk"), "pk");
+        assert_eq!(KeyCase::Upper.apply("Pk"), "PK");
+    }
+
+    #[test]
+    fn test_apply_to_entity_payload() {
+        let payload = r#"{"PartitionKey":"Pk","RowKey":"Rk","Value":"Value"}"#;
+
+        let result = KeyCase::Upper.apply_to_entity_payload(payload.as_bytes().to_vec());
+        let result: serde_json::Value = serde_json::from_slice(result.as_slice()).unwrap();
+
+        assert_eq!(result["PartitionKey"], "PK");
+        assert_eq!(result["RowKey"], "RK");
+        assert_eq!(result["Value"], "Value");
+    }
+}
@@ -0,0 +1,173 @@
+use flurl::FlUrlError;
+
+use super::DataWriterError;
+
+#[cfg(feature = "otel")]
+mod instruments {
+    use std::sync::OnceLock;
+
+    use opentelemetry::metrics::{Counter, Histogram};
+
+    pub struct Instruments {
+        pub requests: Counter<u64>,
+        pub duration: Histogram<f64>,
+        pub bytes_sent: Counter<u64>,
+        pub bytes_received: Counter<u64>,
+    }
+
+    static INSTRUMENTS: OnceLock<Instruments> = OnceLock::new();
+
+    pub fn get() -> &'static Instruments {
+        INSTRUMENTS.get_or_init(|| {
+            let meter = opentelemetry::global::meter("my-no-sql-data-writer");
+            Instruments {
+                requests: meter
+                    .u64_counter("my_no_sql.writer.requests")
+                    .with_description("Amount of http requests made by the data writer")
+                    .init(),
+                duration: meter
+                    .f64_histogram("my_no_sql.writer.request.duration")
+                    .with_description("Duration of the data writer http requests")
+                    .with_unit("s")
+                    .init(),
+                bytes_sent: meter
+                    .u64_counter("my_no_sql.writer.bytes_sent")
+                    .with_unit("By")
+                    .init(),
+                bytes_received: meter
+                    .u64_counter("my_no_sql.writer.bytes_received")
+                    .with_unit("By")
+                    .init(),
+            }
+        })
+    }
+}
+
+#[cfg(feature = "otel")]
+pub struct RequestMetrics {
+    operation: &'static str,
+    table_name: String,
+    started: std::time::Instant,
+}
+
+#[cfg(feature = "otel")]
+impl RequestMetrics {
+    pub fn start(operation: &'static str, table_name: &str) -> Self {
+        Self {
+            operation,
+            table_name: table_name.to_string(),
+            started: std::time::Instant::now(),
+        }
+    }
+
+    fn attributes(&self) -> [opentelemetry::KeyValue; 2] {
+        [
+            opentelemetry::KeyValue::new("operation", self.operation),
+            opentelemetry::KeyValue::new("table", self.table_name.clone()),
+        ]
+    }
+
+    pub fn body_sent(&self, body: Vec<u8>) -> Vec<u8> {
+        instruments::get()
+            .bytes_sent
+            .add(body.len() as u64, &self.attributes());
+        body
+    }
+
+    pub fn response(&self, status_code: u16) {
+        let [operation, table] = self.attributes();
+        let attributes = [
+            operation,
+            table,
+            opentelemetry::KeyValue::new("status_code", status_code as i64),
+        ];
+
+        let instruments = instruments::get();
+        instruments.requests.add(1, &attributes);
+        instruments
+            .duration
+            .record(self.started.elapsed().as_secs_f64(), &attributes);
+    }
+
+    pub fn body_received<'s>(&self, body: &'s [u8]) -> &'s [u8] {
+        instruments::get()
+            .bytes_received
+            .add(body.len() as u64, &self.attributes());
+        body
+    }
+
+    // Request which got no response is counted with the kind of the failure instead of the status code
+    pub fn failed(&self, err: FlUrlError) -> DataWriterError {
+        let err = DataWriterError::from(err);
+
+        let [operation, table] = self.attributes();
+        let attributes = [
+            operation,
+            table,
+            opentelemetry::KeyValue::new("error", get_failure_kind(&err)),
+        ];
+
+        let instruments = instruments::get();
+        instruments.requests.add(1, &attributes);
+        instruments
+            .duration
+            .record(self.started.elapsed().as_secs_f64(), &attributes);
+
+        err
+    }
+}
+
+#[cfg(feature = "otel")]
+fn get_failure_kind(err: &DataWriterError) -> &'static str {
+    match err {
+        DataWriterError::Timeout(_) => "timeout",
+        DataWriterError::ConnectionRefused(_) => "connection_refused",
+        _ => "other",
+    }
+}
+
+// Without the feature everything is inlined into nothing
+#[cfg(not(feature = "otel"))]
+pub struct RequestMetrics;
+
+#[cfg(not(feature = "otel"))]
+impl RequestMetrics {
+    #[inline(always)]
+    pub fn start(_operation: &'static str, _table_name: &str) -> Self {
+        Self
+    }
+
+    #[inline(always)]
+    pub fn body_sent(&self, body: Vec<u8>) -> Vec<u8> {
+        body
+    }
+
+    #[inline(always)]
+    pub fn response(&self, _status_code: u16) {}
+
+    #[inline(always)]
+    pub fn body_received<'s>(&self, body: &'s [u8]) -> &'s [u8] {
+        body
+    }
+
+    #[inline(always)]
+    pub fn failed(&self, err: FlUrlError) -> DataWriterError {
+        DataWriterError::from(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flurl::FlUrlError;
+
+    use super::RequestMetrics;
+    use crate::DataWriterError;
+
+    #[test]
+    fn test_failed_request_keeps_the_kind_of_the_failure() {
+        let metrics = RequestMetrics::start("get_entity", "test");
+
+        let err = metrics.failed(FlUrlError::Timeout);
+        assert!(matches!(err, DataWriterError::Timeout(_)));
+    }
+}
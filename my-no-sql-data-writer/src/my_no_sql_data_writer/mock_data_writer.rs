@@ -0,0 +1,1394 @@
+use std::{collections::BTreeMap, marker::PhantomData, sync::Arc};
+
+use futures::{Stream, StreamExt};
+
+use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+use my_no_sql_core::{
+    db::{ByRowKeyIterator, DbPartitionsContainer, DbRow},
+    db_json_entity::{DbJsonEntity, JsonTimeStamp},
+};
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+use tokio::sync::Mutex;
+
+use crate::{
+    AggOp, BulkMode, BulkStats, ConsistencyLevel, CreateTableAndSeedStage, CreateTableParams,
+    DataWriterError, EntityView, FieldDiff, MyNoSqlWriter, PartitionBulkResult,
+    UpdateReadStatistics, WriteNotification, WriteNotificationCallback, WriteResult,
+};
+
+// In-process replacement of MyNoSqlDataWriter for the tests.
+// Returns the same errors as the server does: TableNotFound, TableAlreadyExists, RecordAlreadyExists
+pub struct MockDataWriter<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> {
+    table_name: String,
+    table: Mutex<Option<DbPartitionsContainer>>,
+    partition_expirations: Mutex<BTreeMap<String, DateTimeAsMicroseconds>>,
+    phantom: PhantomData<TEntity>,
+    write_notification: Option<WriteNotificationCallback<TEntity>>,
+}
+
+impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MockDataWriter<TEntity> {
+    pub fn new(auto_create_table: bool) -> Self {
+        Self::new_with_table_name(auto_create_table, TEntity::TABLE_NAME.to_string())
+    }
+
+    // Same as MyNoSqlDataWriter::new_with_table_name: errors report the given table name
+    pub fn new_with_table_name(auto_create_table: bool, table_name: String) -> Self {
+        Self {
+            table_name,
+            table: Mutex::new(if auto_create_table {
+                Some(DbPartitionsContainer::new())
+            } else {
+                None
+            }),
+            partition_expirations: Mutex::new(BTreeMap::new()),
+            phantom: PhantomData,
+            write_notification: None,
+        }
+    }
+
+    // Same notifications as MyNoSqlDataWriter::on_successful_write gives
+    pub fn on_successful_write(mut self, callback: WriteNotificationCallback<TEntity>) -> Self {
+        self.write_notification = Some(callback);
+        self
+    }
+
+    fn notify_on_success<T>(
+        &self,
+        result: Result<T, DataWriterError>,
+        notification: WriteNotification<TEntity>,
+    ) -> Result<T, DataWriterError> {
+        if result.is_ok() {
+            if let Some(callback) = self.write_notification.as_ref() {
+                callback(notification);
+            }
+        }
+
+        result
+    }
+
+    pub async fn create_table(&self, _params: CreateTableParams) -> Result<(), DataWriterError> {
+        let mut table = self.table.lock().await;
+
+        if table.is_some() {
+            return Err(DataWriterError::TableAlreadyExists(
+                self.table_name.to_string(),
+            ));
+        }
+
+        *table = Some(DbPartitionsContainer::new());
+        Ok(())
+    }
+
+    pub async fn create_table_if_not_exists(
+        &self,
+        _params: &CreateTableParams,
+    ) -> Result<(), DataWriterError> {
+        let mut table = self.table.lock().await;
+
+        if table.is_none() {
+            *table = Some(DbPartitionsContainer::new());
+        }
+
+        Ok(())
+    }
+
+    pub async fn create_table_and_seed(
+        &self,
+        params: CreateTableParams,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        if let Err(err) = self.create_table_if_not_exists(&params).await {
+            return Err(DataWriterError::CreateTableAndSeedFail(
+                CreateTableAndSeedStage::CreateTable,
+                Box::new(err),
+            ));
+        }
+
+        if let Err(err) = self.clean_table_and_bulk_insert(entities).await {
+            return Err(DataWriterError::CreateTableAndSeedFail(
+                CreateTableAndSeedStage::Seed,
+                Box::new(err),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub async fn insert_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        let result: Result<(), DataWriterError> = async {
+            let db_row = to_db_row(entity)?;
+
+            let mut table = self.table.lock().await;
+            let table = get_table_mut(&mut table, &self.table_name)?;
+
+            let db_partition = table.add_partition_if_not_exists(&db_row);
+
+            if !db_partition.insert_row(db_row.clone()) {
+                return Err(DataWriterError::RecordAlreadyExists(format!(
+                    "Record with PartitionKey: {} and RowKey: {} already exists",
+                    db_row.get_partition_key(),
+                    db_row.get_row_key()
+                )));
+            }
+
+            Ok(())
+        }
+        .await;
+
+        self.notify_on_success(result, WriteNotification::Upserted(entity))
+    }
+
+    pub async fn insert_or_replace_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        let result = self.insert_or_replace_rows(std::iter::once(entity)).await;
+        self.notify_on_success(result, WriteNotification::Upserted(entity))
+    }
+
+    // Missing row is treated as changed since it was deleted after it was read
+    pub async fn insert_or_replace_entity_if_unchanged(
+        &self,
+        entity: &TEntity,
+        expected_time_stamp: &str,
+    ) -> Result<(), DataWriterError> {
+        let result: Result<(), DataWriterError> = async {
+            let db_row = to_db_row(entity)?;
+
+            let mut table = self.table.lock().await;
+            let table = get_table_mut(&mut table, &self.table_name)?;
+
+            let time_stamp = table
+                .get(entity.get_partition_key())
+                .and_then(|db_partition| db_partition.get_row(entity.get_row_key()))
+                .map(|db_row| db_row.get_time_stamp().to_string());
+
+            if time_stamp.as_deref() != Some(expected_time_stamp) {
+                return Err(DataWriterError::RecordIsChanged(format!(
+                    "Row {}/{} is changed",
+                    entity.get_partition_key(),
+                    entity.get_row_key()
+                )));
+            }
+
+            table
+                .add_partition_if_not_exists(&db_row)
+                .insert_or_replace_row(db_row.clone());
+
+            Ok(())
+        }
+        .await;
+
+        self.notify_on_success(result, WriteNotification::Upserted(entity))
+    }
+
+    // Keys of the row are not changed even if the patch has them
+    pub async fn merge_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        patch: &serde_json::Value,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        super::execution::validate_merge_patch(patch)?;
+
+        let mut table = self.table.lock().await;
+        let table = get_table_mut(&mut table, &self.table_name)?;
+
+        let db_row = match table
+            .get(partition_key)
+            .and_then(|db_partition| db_partition.get_row(row_key))
+        {
+            Some(db_row) => db_row,
+            None => return Ok(None),
+        };
+
+        let mut merged: serde_json::Value = serde_json::from_slice(db_row.to_vec().as_slice())
+            .map_err(|err| DataWriterError::Error(format!("{:?}", err)))?;
+
+        if let (Some(merged), Some(patch)) = (merged.as_object_mut(), patch.as_object()) {
+            for (field, value) in patch {
+                if field == "PartitionKey" || field == "RowKey" {
+                    continue;
+                }
+
+                merged.insert(field.clone(), value.clone());
+            }
+        }
+
+        let db_row = raw_to_db_row(serde_json::to_vec(&merged).unwrap().as_slice())?;
+
+        table
+            .add_partition_if_not_exists(&db_row)
+            .insert_or_replace_row(db_row.clone());
+
+        let entity = from_db_row(&db_row)?;
+
+        if let Some(callback) = self.write_notification.as_ref() {
+            callback(WriteNotification::Upserted(&entity));
+        }
+
+        Ok(Some(entity))
+    }
+
+    pub async fn insert_or_replace_raw(&self, raw_json: &[u8]) -> Result<(), DataWriterError> {
+        let result: Result<(), DataWriterError> = async {
+            super::execution::validate_raw_entity(raw_json)?;
+            let db_row = raw_to_db_row(raw_json)?;
+
+            let mut table = self.table.lock().await;
+            let table = get_table_mut(&mut table, &self.table_name)?;
+
+            table
+                .add_partition_if_not_exists(&db_row)
+                .insert_or_replace_row(db_row.clone());
+
+            Ok(())
+        }
+        .await;
+
+        self.notify_on_success(result, WriteNotification::RawUpserted(raw_json))
+    }
+
+    pub async fn bulk_insert_or_replace(
+        &self,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        let result = self.insert_or_replace_rows(entities.iter()).await;
+        self.notify_on_success(result, WriteNotification::BulkUpserted(entities))
+    }
+
+    // Entities are written by batches of batch_size as they come from the stream,
+    // so only one batch is kept in memory
+    pub async fn bulk_insert_or_replace_stream(
+        &self,
+        stream: impl Stream<Item = TEntity>,
+        batch_size: usize,
+    ) -> Result<BulkStats, DataWriterError> {
+        let batch_size = batch_size.max(1);
+        let mut stream = std::pin::pin!(stream);
+
+        let mut stats = BulkStats::default();
+        let mut batch = Vec::with_capacity(batch_size);
+
+        while let Some(entity) = stream.next().await {
+            batch.push(entity);
+
+            if batch.len() >= batch_size {
+                self.bulk_insert_or_replace(&batch).await?;
+                stats.add_batch(batch.len());
+                batch.clear();
+            }
+        }
+
+        if !batch.is_empty() {
+            self.bulk_insert_or_replace(&batch).await?;
+            stats.add_batch(batch.len());
+        }
+
+        Ok(stats)
+    }
+
+    async fn insert_or_replace_rows<'s>(
+        &self,
+        entities: impl Iterator<Item = &'s TEntity>,
+    ) -> Result<(), DataWriterError> {
+        let mut db_rows = Vec::new();
+
+        for entity in entities {
+            db_rows.push(to_db_row(entity)?);
+        }
+
+        let mut table = self.table.lock().await;
+        let table = get_table_mut(&mut table, &self.table_name)?;
+
+        for db_row in db_rows {
+            table
+                .add_partition_if_not_exists(&db_row)
+                .insert_or_replace_row(db_row.clone());
+        }
+
+        Ok(())
+    }
+
+    pub async fn bulk_insert_or_replace_by_partitions(
+        &self,
+        entities: &[TEntity],
+        mode: BulkMode,
+    ) -> Result<Vec<PartitionBulkResult>, DataWriterError> {
+        let groups = super::bulk_mode::group_by_partition(entities);
+
+        super::bulk_mode::write_by_partitions(
+            &groups,
+            mode,
+            self.write_notification.as_ref(),
+            |entities| self.insert_or_replace_rows(entities.iter().copied()),
+        )
+        .await
+    }
+
+    pub async fn get_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        let table = self.table.lock().await;
+        let table = get_table(&table, &self.table_name)?;
+
+        let db_row = table
+            .get(partition_key)
+            .and_then(|db_partition| db_partition.get_row(row_key));
+
+        match db_row {
+            Some(db_row) => Ok(Some(from_db_row(db_row)?)),
+            None => Ok(None),
+        }
+    }
+
+    // Mock has the only copy of the data, so every read is strongly consistent
+    pub async fn get_entity_with_consistency(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        _consistency: ConsistencyLevel,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        self.get_entity(partition_key, row_key).await
+    }
+
+    pub async fn get_entity_view<TResult>(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        view: impl FnOnce(EntityView) -> TResult,
+    ) -> Result<Option<TResult>, DataWriterError> {
+        let raw = {
+            let table = self.table.lock().await;
+            let table = get_table(&table, &self.table_name)?;
+
+            match table
+                .get(partition_key)
+                .and_then(|db_partition| db_partition.get_row(row_key))
+            {
+                Some(db_row) => db_row.to_vec(),
+                None => return Ok(None),
+            }
+        };
+
+        let entity_view = EntityView::new(raw.as_slice()).map_err(DataWriterError::Error)?;
+
+        Ok(Some(view(entity_view)))
+    }
+
+    pub async fn diff_with_stored(
+        &self,
+        entity: &TEntity,
+    ) -> Result<Option<FieldDiff>, DataWriterError> {
+        let stored = {
+            let table = self.table.lock().await;
+            let table = get_table(&table, &self.table_name)?;
+
+            match table
+                .get(entity.get_partition_key())
+                .and_then(|db_partition| db_partition.get_row(entity.get_row_key()))
+            {
+                Some(db_row) => db_row.to_vec(),
+                None => b"{}".to_vec(),
+            }
+        };
+
+        FieldDiff::compute(entity.serialize_entity().as_slice(), stored.as_slice())
+            .map_err(DataWriterError::Error)
+    }
+
+    // Compares with the stored row ignoring TimeStamp and Expires and writes only if they differ.
+    // Read and write are not atomic
+    pub async fn insert_or_replace_if_changed(
+        &self,
+        entity: &TEntity,
+    ) -> Result<WriteResult, DataWriterError> {
+        if self.diff_with_stored(entity).await?.is_none() {
+            return Ok(WriteResult::Unchanged);
+        }
+
+        self.insert_or_replace_entity(entity).await?;
+        Ok(WriteResult::Changed)
+    }
+
+    pub async fn get_by_partition_key(
+        &self,
+        partition_key: &str,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        let table = self.table.lock().await;
+        let table = get_table(&table, &self.table_name)?;
+
+        let db_partition = match table.get(partition_key) {
+            Some(db_partition) => db_partition,
+            None => return Ok(None),
+        };
+
+        let mut result = Vec::with_capacity(db_partition.get_rows_amount());
+
+        for db_row in db_partition.get_all_rows() {
+            result.push(from_db_row(db_row)?);
+        }
+
+        Ok(Some(result))
+    }
+
+    pub async fn get_by_partition_key_with_consistency(
+        &self,
+        partition_key: &str,
+        _consistency: ConsistencyLevel,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        self.get_by_partition_key(partition_key).await
+    }
+
+    pub async fn get_by_partition_and_row_prefix(
+        &self,
+        partition_key: &str,
+        row_key_prefix: &str,
+    ) -> Result<Vec<TEntity>, DataWriterError> {
+        let table = self.table.lock().await;
+        let table = get_table(&table, &self.table_name)?;
+
+        let mut result = Vec::new();
+
+        if let Some(db_partition) = table.get(partition_key) {
+            for db_row in db_partition.get_all_rows() {
+                if db_row.get_row_key().starts_with(row_key_prefix) {
+                    result.push(from_db_row(db_row)?);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub async fn get_by_row_key(
+        &self,
+        row_key: &str,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        super::execution::validate_skip_and_limit(skip, limit)?;
+
+        let table = self.table.lock().await;
+        let table = get_table(&table, &self.table_name)?;
+
+        let by_row_key = ByRowKeyIterator::new(
+            table.get_partitions(),
+            row_key,
+            skip.map(|itm| itm as usize),
+            limit.map(|itm| itm as usize),
+        );
+
+        let mut result = Vec::new();
+
+        for (_, db_row) in by_row_key {
+            result.push(from_db_row(db_row)?);
+        }
+
+        if result.len() == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(result))
+    }
+
+    pub async fn get_partition_keys(
+        &self,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError> {
+        super::execution::validate_skip_and_limit(skip, limit)?;
+
+        let table = self.table.lock().await;
+        let table = get_table(&table, &self.table_name)?;
+
+        let skip = skip.unwrap_or(0) as usize;
+        let limit = limit.map(|itm| itm as usize).unwrap_or(usize::MAX);
+
+        let result = table
+            .get_partitions()
+            .skip(skip)
+            .take(limit)
+            .map(|db_partition| db_partition.partition_key.to_string())
+            .collect();
+
+        Ok(result)
+    }
+
+    pub async fn get_row_keys(
+        &self,
+        partition_key: &str,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError> {
+        super::execution::validate_skip_and_limit(skip, limit)?;
+
+        let table = self.table.lock().await;
+        let table = get_table(&table, &self.table_name)?;
+
+        let db_partition = match table.get(partition_key) {
+            Some(db_partition) => db_partition,
+            None => return Ok(vec![]),
+        };
+
+        let skip = skip.unwrap_or(0) as usize;
+        let limit = limit.map(|itm| itm as usize).unwrap_or(usize::MAX);
+
+        let result = db_partition
+            .get_all_rows()
+            .skip(skip)
+            .take(limit)
+            .map(|db_row| db_row.get_row_key().to_string())
+            .collect();
+
+        Ok(result)
+    }
+
+    pub async fn get_partition_size(
+        &self,
+        partition_key: &str,
+    ) -> Result<Option<usize>, DataWriterError> {
+        let table = self.table.lock().await;
+        let table = get_table(&table, &self.table_name)?;
+
+        Ok(table
+            .get(partition_key)
+            .map(|db_partition| db_partition.get_content_size()))
+    }
+
+    pub async fn aggregate_field(
+        &self,
+        partition_key: Option<&str>,
+        field: &str,
+        op: AggOp,
+    ) -> Result<f64, DataWriterError> {
+        let entities = match partition_key {
+            Some(partition_key) => self.get_by_partition_key(partition_key).await?,
+            None => self.get_all().await?,
+        };
+
+        let entities = entities.unwrap_or_default();
+        Ok(super::aggregation::aggregate_entities(
+            entities.iter(),
+            field,
+            op,
+        ))
+    }
+
+    pub async fn delete_row(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        let result = self.remove_row(partition_key, row_key).await;
+        self.notify_on_success(
+            result,
+            WriteNotification::RowDeleted {
+                partition_key,
+                row_key,
+            },
+        )
+    }
+
+    async fn remove_row(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        let mut table = self.table.lock().await;
+        let table = get_table_mut(&mut table, &self.table_name)?;
+
+        let (removed_row, partition_is_empty) = match table.get_mut(partition_key) {
+            Some(db_partition) => {
+                let removed_row = db_partition.remove_row(row_key);
+                (removed_row, db_partition.is_empty())
+            }
+            None => return Ok(None),
+        };
+
+        if partition_is_empty {
+            table.remove(partition_key);
+        }
+
+        match removed_row {
+            Some(db_row) => Ok(Some(from_db_row(&db_row)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn delete_partitions(&self, partition_keys: &[&str]) -> Result<(), DataWriterError> {
+        let result: Result<(), DataWriterError> = async {
+            let mut table = self.table.lock().await;
+            let table = get_table_mut(&mut table, &self.table_name)?;
+
+            for partition_key in partition_keys {
+                table.remove(partition_key);
+            }
+
+            Ok(())
+        }
+        .await;
+
+        self.notify_on_success(result, WriteNotification::PartitionsDeleted(partition_keys))
+    }
+
+    pub async fn touch_rows(
+        &self,
+        _partition_key: &str,
+        _row_keys: &[&str],
+    ) -> Result<(), DataWriterError> {
+        let table = self.table.lock().await;
+        get_table(&table, &self.table_name)?;
+        Ok(())
+    }
+
+    pub async fn touch_partition(&self, partition_key: &str) -> Result<bool, DataWriterError> {
+        let table = self.table.lock().await;
+        let table = get_table(&table, &self.table_name)?;
+        Ok(table.has_partition(partition_key))
+    }
+
+    // Expiration moment is stored, but the mock does not expire partitions
+    pub async fn set_partition_expiration(
+        &self,
+        partition_key: &str,
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<bool, DataWriterError> {
+        let table = self.table.lock().await;
+        let table = get_table(&table, &self.table_name)?;
+
+        if !table.has_partition(partition_key) {
+            return Ok(false);
+        }
+
+        let mut partition_expirations = self.partition_expirations.lock().await;
+        match expires {
+            Some(expires) => {
+                partition_expirations.insert(partition_key.to_string(), expires);
+            }
+            None => {
+                partition_expirations.remove(partition_key);
+            }
+        }
+
+        Ok(true)
+    }
+
+    // Moment set by set_partition_expiration for the partition which still exists
+    pub async fn get_partition_expiration(
+        &self,
+        partition_key: &str,
+    ) -> Option<DateTimeAsMicroseconds> {
+        let table = self.table.lock().await;
+        if !table.as_ref()?.has_partition(partition_key) {
+            return None;
+        }
+
+        let partition_expirations = self.partition_expirations.lock().await;
+        partition_expirations.get(partition_key).copied()
+    }
+
+    pub async fn get_all(&self) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        let table = self.table.lock().await;
+        let table = get_table(&table, &self.table_name)?;
+
+        let mut result = Vec::new();
+
+        for db_partition in table.get_partitions() {
+            for db_row in db_partition.get_all_rows() {
+                result.push(from_db_row(db_row)?);
+            }
+        }
+
+        Ok(Some(result))
+    }
+
+    pub fn get_all_streamed(&self) -> impl Stream<Item = Result<TEntity, DataWriterError>> + '_ {
+        futures::stream::once(self.get_all()).flat_map(|result| {
+            let items: Vec<Result<TEntity, DataWriterError>> = match result {
+                Ok(entities) => entities.unwrap_or_default().into_iter().map(Ok).collect(),
+                Err(err) => vec![Err(err)],
+            };
+
+            futures::stream::iter(items)
+        })
+    }
+
+    pub async fn get_all_into(&self, buf: &mut Vec<TEntity>) -> Result<(), DataWriterError> {
+        buf.clear();
+
+        let table = self.table.lock().await;
+        let table = get_table(&table, &self.table_name)?;
+
+        for db_partition in table.get_partitions() {
+            for db_row in db_partition.get_all_rows() {
+                buf.push(from_db_row(db_row)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_all_reuse(
+        &self,
+        mut buf: Vec<TEntity>,
+    ) -> Result<Vec<TEntity>, DataWriterError> {
+        self.get_all_into(&mut buf).await?;
+        Ok(buf)
+    }
+
+    pub async fn clean_table_and_bulk_insert(
+        &self,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        {
+            let mut table = self.table.lock().await;
+            let table = get_table_mut(&mut table, &self.table_name)?;
+            table.clear();
+        }
+
+        let result = self.insert_or_replace_rows(entities.iter()).await;
+        self.notify_on_success(result, WriteNotification::TableReplaced(entities))
+    }
+
+    pub async fn clean_partition_and_bulk_insert(
+        &self,
+        partition_key: &str,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        super::execution::validate_partition_keys(partition_key, entities)?;
+
+        {
+            let mut table = self.table.lock().await;
+            let table = get_table_mut(&mut table, &self.table_name)?;
+            table.remove(partition_key);
+        }
+
+        let result = self.insert_or_replace_rows(entities.iter()).await;
+        self.notify_on_success(
+            result,
+            WriteNotification::PartitionReplaced {
+                partition_key,
+                entities,
+            },
+        )
+    }
+}
+
+fn get_table<'s>(
+    table: &'s Option<DbPartitionsContainer>,
+    table_name: &str,
+) -> Result<&'s DbPartitionsContainer, DataWriterError> {
+    match table {
+        Some(table) => Ok(table),
+        None => Err(DataWriterError::TableNotFound(table_name.to_string())),
+    }
+}
+
+fn get_table_mut<'s>(
+    table: &'s mut Option<DbPartitionsContainer>,
+    table_name: &str,
+) -> Result<&'s mut DbPartitionsContainer, DataWriterError> {
+    match table {
+        Some(table) => Ok(table),
+        None => Err(DataWriterError::TableNotFound(table_name.to_string())),
+    }
+}
+
+fn to_db_row<TEntity: MyNoSqlEntitySerializer>(
+    entity: &TEntity,
+) -> Result<Arc<DbRow>, DataWriterError> {
+    let payload = entity.serialize_entity();
+    raw_to_db_row(payload.as_slice())
+}
+
+fn raw_to_db_row(payload: &[u8]) -> Result<Arc<DbRow>, DataWriterError> {
+    match DbJsonEntity::try_parse_into_db_row(payload.into(), &JsonTimeStamp::now()) {
+        Ok(db_row) => Ok(Arc::new(db_row)),
+        Err(err) => Err(DataWriterError::ServerCouldNotParseJson(format!(
+            "{:?}",
+            err
+        ))),
+    }
+}
+
+fn from_db_row<TEntity: MyNoSqlEntitySerializer>(
+    db_row: &DbRow,
+) -> Result<TEntity, DataWriterError> {
+    TEntity::deserialize_entity(db_row.to_vec().as_slice()).map_err(DataWriterError::Error)
+}
+
+#[async_trait::async_trait]
+impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static>
+    MyNoSqlWriter<TEntity> for MockDataWriter<TEntity>
+{
+    async fn insert_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        self.insert_entity(entity).await
+    }
+
+    async fn insert_or_replace_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        self.insert_or_replace_entity(entity).await
+    }
+
+    async fn bulk_insert_or_replace(&self, entities: &[TEntity]) -> Result<(), DataWriterError> {
+        self.bulk_insert_or_replace(entities).await
+    }
+
+    async fn get_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        _update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        self.get_entity(partition_key, row_key).await
+    }
+
+    async fn get_by_partition_key(
+        &self,
+        partition_key: &str,
+        _update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        self.get_by_partition_key(partition_key).await
+    }
+
+    async fn get_by_row_key(
+        &self,
+        row_key: &str,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        self.get_by_row_key(row_key, skip, limit).await
+    }
+
+    async fn get_all(&self) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        self.get_all().await
+    }
+
+    async fn get_partition_keys(
+        &self,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError> {
+        self.get_partition_keys(skip, limit).await
+    }
+
+    async fn get_row_keys(
+        &self,
+        partition_key: &str,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError> {
+        self.get_row_keys(partition_key, skip, limit).await
+    }
+
+    async fn delete_row(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        self.delete_row(partition_key, row_key).await
+    }
+
+    async fn delete_partitions(&self, partition_keys: &[&str]) -> Result<(), DataWriterError> {
+        self.delete_partitions(partition_keys).await
+    }
+
+    async fn clean_table_and_bulk_insert(
+        &self,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        self.clean_table_and_bulk_insert(entities).await
+    }
+
+    async fn clean_partition_and_bulk_insert(
+        &self,
+        partition_key: &str,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        self.clean_partition_and_bulk_insert(partition_key, entities)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures::StreamExt;
+    use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+    use crate::{
+        test_fixtures::TestEntity, CreateTableParams, DataWriterError, MyNoSqlWriter,
+        WriteNotification, WriteResult,
+    };
+
+    use super::MockDataWriter;
+
+    fn create_entity(partition_key: &str, row_key: &str, value: i32) -> TestEntity {
+        TestEntity {
+            partition_key: partition_key.to_string(),
+            row_key: row_key.to_string(),
+            value,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_insert_or_replace_entity_if_unchanged() {
+        let writer = MockDataWriter::<TestEntity>::new(true);
+
+        writer
+            .insert_entity(&create_entity("pk", "rk", 1))
+            .await
+            .unwrap();
+
+        // TimeStamp is read from the stored json, the same way a client gets it from the server
+        let time_stamp = {
+            let table = writer.table.lock().await;
+            let db_partition = table.as_ref().unwrap().get("pk").unwrap();
+            let db_row = db_partition.get_row("rk").unwrap();
+            let value: serde_json::Value =
+                serde_json::from_slice(db_row.get_src_as_slice()).unwrap();
+            value["TimeStamp"].as_str().unwrap().to_string()
+        };
+
+        assert!(DateTimeAsMicroseconds::from_str(&time_stamp).is_some());
+
+        let result = writer
+            .insert_or_replace_entity_if_unchanged(&create_entity("pk", "rk", 2), "outdated")
+            .await;
+        assert!(matches!(result, Err(DataWriterError::RecordIsChanged(_))));
+
+        writer
+            .insert_or_replace_entity_if_unchanged(&create_entity("pk", "rk", 3), &time_stamp)
+            .await
+            .unwrap();
+
+        let entity = writer.get_entity("pk", "rk").await.unwrap().unwrap();
+        assert_eq!(entity.value, 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_all_into_reuses_buffer() {
+        let writer = MockDataWriter::<TestEntity>::new(true);
+
+        writer
+            .insert_entity(&create_entity("pk", "rk", 1))
+            .await
+            .unwrap();
+
+        let mut buf = vec![
+            create_entity("old", "old", 0),
+            create_entity("old", "old", 0),
+        ];
+        writer.get_all_into(&mut buf).await.unwrap();
+
+        assert_eq!(buf.len(), 1);
+        assert_eq!(buf[0].value, 1);
+
+        let capacity = buf.capacity();
+        let buf = writer.get_all_reuse(buf).await.unwrap();
+
+        assert_eq!(buf.len(), 1);
+        assert_eq!(buf.capacity(), capacity);
+    }
+
+    #[tokio::test]
+    async fn test_get_by_row_key_with_limit() {
+        let writer = MockDataWriter::<TestEntity>::new(true);
+
+        for partition_key in ["pk1", "pk2", "pk3", "pk4"] {
+            writer
+                .insert_entity(&create_entity(partition_key, "rk", 1))
+                .await
+                .unwrap();
+        }
+
+        let result = writer.get_by_row_key("rk", None, None).await.unwrap();
+        assert_eq!(result.unwrap().len(), 4);
+
+        let result = writer
+            .get_by_row_key("rk", Some(1), Some(2))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].partition_key, "pk2");
+        assert_eq!(result[1].partition_key, "pk3");
+
+        assert!(writer.get_by_row_key("rk", Some(-1), None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_all_streamed() {
+        let writer = MockDataWriter::<TestEntity>::new(true);
+
+        writer
+            .insert_entity(&create_entity("pk", "rk1", 1))
+            .await
+            .unwrap();
+        writer
+            .insert_entity(&create_entity("pk", "rk2", 2))
+            .await
+            .unwrap();
+
+        let values: Vec<_> = writer
+            .get_all_streamed()
+            .map(|entity| entity.unwrap().value)
+            .collect()
+            .await;
+
+        assert_eq!(values, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_merge_entity() {
+        let writer = MockDataWriter::<TestEntity>::new(true);
+
+        writer
+            .insert_entity(&create_entity("pk", "rk", 1))
+            .await
+            .unwrap();
+
+        let patch = serde_json::json!({"Value": 2, "RowKey": "other"});
+
+        let merged = writer
+            .merge_entity("pk", "rk", &patch)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(merged.row_key, "rk");
+        assert_eq!(merged.value, 2);
+
+        let entity = writer.get_entity("pk", "rk").await.unwrap().unwrap();
+        assert_eq!(entity.value, 2);
+
+        assert!(writer
+            .merge_entity("pk", "missing", &patch)
+            .await
+            .unwrap()
+            .is_none());
+
+        let result = writer.merge_entity("pk", "rk", &serde_json::json!(1)).await;
+        assert!(matches!(result, Err(DataWriterError::Error(_))));
+    }
+
+    #[tokio::test]
+    async fn test_set_partition_expiration() {
+        let writer = MockDataWriter::<TestEntity>::new(true);
+
+        writer
+            .insert_entity(&create_entity("pk", "rk", 1))
+            .await
+            .unwrap();
+
+        let expires = DateTimeAsMicroseconds::new(1_000_000);
+        assert!(writer
+            .set_partition_expiration("pk", Some(expires))
+            .await
+            .unwrap());
+        assert!(!writer
+            .set_partition_expiration("missing", Some(expires))
+            .await
+            .unwrap());
+
+        assert_eq!(
+            writer
+                .get_partition_expiration("pk")
+                .await
+                .unwrap()
+                .unix_microseconds,
+            expires.unix_microseconds
+        );
+
+        assert!(writer.set_partition_expiration("pk", None).await.unwrap());
+        assert!(writer.get_partition_expiration("pk").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_touch_partition() {
+        let writer = MockDataWriter::<TestEntity>::new(true);
+
+        writer
+            .insert_entity(&create_entity("pk", "rk", 1))
+            .await
+            .unwrap();
+
+        assert!(writer.touch_partition("pk").await.unwrap());
+        assert!(!writer.touch_partition("missing").await.unwrap());
+
+        let writer = MockDataWriter::<TestEntity>::new(false);
+        assert!(matches!(
+            writer.touch_partition("pk").await,
+            Err(DataWriterError::TableNotFound(_))
+        ));
+        assert!(matches!(
+            writer.set_partition_expiration("pk", None).await,
+            Err(DataWriterError::TableNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_touch_rows() {
+        let writer = MockDataWriter::<TestEntity>::new(true);
+        writer.touch_rows("pk", &["rk"]).await.unwrap();
+
+        let writer = MockDataWriter::<TestEntity>::new(false);
+        assert!(matches!(
+            writer.touch_rows("pk", &["rk"]).await,
+            Err(DataWriterError::TableNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_create_table_and_seed() {
+        let writer = MockDataWriter::<TestEntity>::new(false);
+
+        let params = || CreateTableParams {
+            persist: true,
+            max_partitions_amount: None,
+            max_rows_per_partition_amount: None,
+        };
+
+        writer
+            .create_table_and_seed(params(), &[create_entity("pk", "rk1", 1)])
+            .await
+            .unwrap();
+
+        let entity = writer.get_entity("pk", "rk1").await.unwrap().unwrap();
+        assert_eq!(entity.value, 1);
+
+        // Existing table is kept and its content is replaced by the seed
+        writer
+            .create_table_and_seed(params(), &[create_entity("pk", "rk2", 2)])
+            .await
+            .unwrap();
+
+        assert!(writer.get_entity("pk", "rk1").await.unwrap().is_none());
+        let entity = writer.get_entity("pk", "rk2").await.unwrap().unwrap();
+        assert_eq!(entity.value, 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_by_partition_and_row_prefix() {
+        let writer = MockDataWriter::<TestEntity>::new(true);
+
+        for (partition_key, row_key) in [("pk", "order-1"), ("pk", "order-2"), ("pk", "client-1")] {
+            writer
+                .insert_entity(&create_entity(partition_key, row_key, 1))
+                .await
+                .unwrap();
+        }
+        writer
+            .insert_entity(&create_entity("pk2", "order-3", 1))
+            .await
+            .unwrap();
+
+        let row_keys: Vec<String> = writer
+            .get_by_partition_and_row_prefix("pk", "order-")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entity| entity.row_key)
+            .collect();
+
+        assert_eq!(row_keys, vec!["order-1", "order-2"]);
+
+        assert!(writer
+            .get_by_partition_and_row_prefix("pk3", "order-")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_insert_or_replace_if_changed() {
+        let (writer, notifications) = collect_notifications(MockDataWriter::new(true));
+
+        let result = writer
+            .insert_or_replace_if_changed(&create_entity("pk", "rk", 1))
+            .await
+            .unwrap();
+        assert!(result.is_changed());
+
+        // Stored row has TimeStamp the entity does not have, it is not a change
+        let result = writer
+            .insert_or_replace_if_changed(&create_entity("pk", "rk", 1))
+            .await
+            .unwrap();
+        assert_eq!(result, WriteResult::Unchanged);
+
+        let result = writer
+            .insert_or_replace_if_changed(&create_entity("pk", "rk", 2))
+            .await
+            .unwrap();
+        assert_eq!(result, WriteResult::Changed);
+
+        let entity = writer.get_entity("pk", "rk").await.unwrap().unwrap();
+        assert_eq!(entity.value, 2);
+
+        assert_eq!(
+            *notifications.lock().unwrap(),
+            vec!["Upserted rk", "Upserted rk"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_table_not_found() {
+        let writer = MockDataWriter::<TestEntity>::new(false);
+
+        let result = writer.get_entity("pk", "rk").await;
+
+        assert!(matches!(result, Err(DataWriterError::TableNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_table_name_override() {
+        let writer =
+            MockDataWriter::<TestEntity>::new_with_table_name(false, "tenant-a".to_string());
+
+        let result = writer.get_entity("pk", "rk").await;
+        assert!(matches!(
+            result,
+            Err(DataWriterError::TableNotFound(table_name)) if table_name == "tenant-a"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insert_get_delete() {
+        let writer = MockDataWriter::<TestEntity>::new(true);
+
+        writer
+            .insert_entity(&create_entity("pk", "rk", 1))
+            .await
+            .unwrap();
+
+        let result = writer.insert_entity(&create_entity("pk", "rk", 2)).await;
+        assert!(matches!(
+            result,
+            Err(DataWriterError::RecordAlreadyExists(_))
+        ));
+
+        writer
+            .insert_or_replace_entity(&create_entity("pk", "rk", 3))
+            .await
+            .unwrap();
+
+        let entity = writer.get_entity("pk", "rk").await.unwrap().unwrap();
+        assert_eq!(entity.value, 3);
+
+        assert_eq!(
+            writer.get_row_keys("pk", None, None).await.unwrap(),
+            vec!["rk".to_string()]
+        );
+
+        let deleted = writer.delete_row("pk", "rk").await.unwrap().unwrap();
+        assert_eq!(deleted.value, 3);
+
+        assert!(writer.get_entity("pk", "rk").await.unwrap().is_none());
+        assert!(writer.get_partition_keys(None, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_partition_keys_sorted_is_stable() {
+        let writer = MockDataWriter::<TestEntity>::new(true);
+
+        for partition_key in ["c", "a", "b"] {
+            writer
+                .insert_entity(&create_entity(partition_key, "rk", 1))
+                .await
+                .unwrap();
+        }
+
+        let first = writer.get_partition_keys_sorted(None, None).await.unwrap();
+        let second = writer.get_partition_keys_sorted(None, None).await.unwrap();
+
+        assert_eq!(first, vec!["a", "b", "c"]);
+        assert_eq!(first, second);
+
+        assert_eq!(
+            writer
+                .get_partition_keys_sorted(Some(1), Some(1))
+                .await
+                .unwrap(),
+            vec!["b"]
+        );
+    }
+
+    fn collect_notifications(
+        writer: MockDataWriter<TestEntity>,
+    ) -> (
+        MockDataWriter<TestEntity>,
+        Arc<std::sync::Mutex<Vec<String>>>,
+    ) {
+        let notifications = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let collected = notifications.clone();
+
+        let writer = writer.on_successful_write(Arc::new(
+            move |notification: WriteNotification<TestEntity>| {
+                let notification = match notification {
+                    WriteNotification::Upserted(entity) => format!("Upserted {}", entity.row_key),
+                    WriteNotification::BulkUpserted(entities) => {
+                        format!("BulkUpserted {}", entities.len())
+                    }
+                    WriteNotification::RawUpserted(_) => "RawUpserted".to_string(),
+                    WriteNotification::RowDeleted { row_key, .. } => {
+                        format!("RowDeleted {}", row_key)
+                    }
+                    WriteNotification::PartitionsDeleted(partition_keys) => {
+                        format!("PartitionsDeleted {}", partition_keys.len())
+                    }
+                    WriteNotification::TableReplaced(entities) => {
+                        format!("TableReplaced {}", entities.len())
+                    }
+                    WriteNotification::PartitionReplaced { partition_key, .. } => {
+                        format!("PartitionReplaced {}", partition_key)
+                    }
+                };
+
+                collected.lock().unwrap().push(notification);
+            },
+        ));
+
+        (writer, notifications)
+    }
+
+    #[tokio::test]
+    async fn test_successful_writes_are_notified() {
+        let (writer, notifications) = collect_notifications(MockDataWriter::new(true));
+
+        writer
+            .insert_entity(&create_entity("pk", "rk", 1))
+            .await
+            .unwrap();
+
+        // Failed write is not notified
+        assert!(writer
+            .insert_entity(&create_entity("pk", "rk", 1))
+            .await
+            .is_err());
+
+        writer
+            .clean_table_and_bulk_insert(&[create_entity("pk", "rk2", 1)])
+            .await
+            .unwrap();
+
+        writer.delete_row("pk", "rk2").await.unwrap();
+
+        assert_eq!(
+            *notifications.lock().unwrap(),
+            vec!["Upserted rk", "TableReplaced 1", "RowDeleted rk2"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_writes_to_missing_table_are_not_notified() {
+        let (writer, notifications) = collect_notifications(MockDataWriter::new(false));
+
+        assert!(writer
+            .bulk_insert_or_replace(&[create_entity("pk", "rk", 1)])
+            .await
+            .is_err());
+
+        assert!(notifications.lock().unwrap().is_empty());
+    }
+}
@@ -2,12 +2,52 @@ mod error;
 mod my_no_sql_data_writer;
 mod settings;
 mod update_read_statistics;
-pub use error::DataWriterError;
+pub use error::{CreateTableAndSeedStage, DataWriterError};
 pub use my_no_sql_data_writer::*;
 pub use settings::*;
 pub use update_read_statistics::*;
 mod execution;
 mod fl_url_ext;
+mod metrics;
+mod url_builder;
 mod with_retries;
 pub use with_retries::*;
 mod fl_url_factory;
+mod writer_options;
+mod random_sample;
+mod entity_view;
+pub use entity_view::*;
+mod field_diff;
+pub use field_diff::*;
+mod write_result;
+pub use write_result::*;
+mod bulk_mode;
+pub use bulk_mode::{BulkMode, BulkStats, PartitionBulkResult};
+mod dead_letter_sink;
+pub use dead_letter_sink::*;
+mod partition_meta;
+pub use partition_meta::*;
+mod aggregation;
+pub use aggregation::AggOp;
+mod my_no_sql_writer;
+pub use my_no_sql_writer::*;
+mod consistency_level;
+pub use consistency_level::*;
+mod key_case;
+pub use key_case::*;
+mod client_time_stamp;
+mod circuit_breaker;
+mod raw_json_array;
+mod server_fail_reason;
+pub use server_fail_reason::*;
+mod write_notification;
+pub use write_notification::*;
+mod request_interceptor;
+pub use request_interceptor::{RequestInterceptor, RequestInterceptorRef};
+mod bulk_compression;
+pub use bulk_compression::*;
+pub use circuit_breaker::*;
+#[cfg(feature = "test-utils")]
+mod mock_data_writer;
+#[cfg(feature = "test-utils")]
+pub use mock_data_writer::*;
@@ -1,17 +1,27 @@
 use std::{marker::PhantomData, sync::Arc, time::Duration};
 
+use futures::{Stream, StreamExt};
+
 use flurl::FlUrl;
 
 use my_no_sql_abstractions::{DataSynchronizationPeriod, MyNoSqlEntity, MyNoSqlEntitySerializer};
+use rust_extensions::date_time::DateTimeAsMicroseconds;
 
 #[cfg(feature = "with-ssh")]
 use flurl::my_ssh::*;
 
 use serde::{Deserialize, Serialize};
 
-use crate::{MyNoSqlDataWriterWithRetries, MyNoSqlWriterSettings};
+use crate::{
+    AggOp, BulkCompression, BulkMode, BulkStats, CircuitBreaker, CircuitBreakerConfig,
+    ConsistencyLevel, CreateTableAndSeedStage, EntityView, FieldDiff, KeyCase,
+    MyNoSqlDataWriterWithRetries, MyNoSqlWriter, MyNoSqlWriterSettings, PartitionBulkResult,
+    PartitionMeta, RequestInterceptorRef, WriteNotification, WriteNotificationCallback,
+};
+
+use super::{fl_url_factory::FlUrlFactory, DataWriterError, UpdateReadStatistics, WriteResult};
 
-use super::{fl_url_factory::FlUrlFactory, DataWriterError, UpdateReadStatistics};
+const RESTORE_READ_BUFFER_SIZE: usize = 64 * 1024;
 
 pub struct CreateTableParams {
     pub persist: bool,
@@ -47,6 +57,7 @@ pub struct MyNoSqlDataWriter<TEntity: MyNoSqlEntity + Sync + Send> {
     sync_period: DataSynchronizationPeriod,
     phantom: PhantomData<TEntity>,
     fl_url_factory: FlUrlFactory,
+    write_notification: Option<WriteNotificationCallback<TEntity>>,
 }
 
 impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlDataWriter<TEntity> {
@@ -54,6 +65,21 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         settings: Arc<dyn MyNoSqlWriterSettings + Send + Sync + 'static>,
         auto_create_table_params: Option<CreateTableParams>,
         sync_period: DataSynchronizationPeriod,
+    ) -> Self {
+        Self::new_with_table_name(
+            settings,
+            TEntity::TABLE_NAME.to_string(),
+            auto_create_table_params,
+            sync_period,
+        )
+    }
+
+    // Table name is used instead of TEntity::TABLE_NAME. Useful to keep a table per tenant
+    pub fn new_with_table_name(
+        settings: Arc<dyn MyNoSqlWriterSettings + Send + Sync + 'static>,
+        table_name: String,
+        auto_create_table_params: Option<CreateTableParams>,
+        sync_period: DataSynchronizationPeriod,
     ) -> Self {
         Self {
             phantom: PhantomData,
@@ -61,22 +87,29 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
             fl_url_factory: FlUrlFactory::new(
                 settings,
                 auto_create_table_params.map(|itm| itm.into()),
-                TEntity::TABLE_NAME,
+                table_name,
             ),
+            write_notification: None,
         }
     }
 
+    pub fn get_table_name(&self) -> &str {
+        self.fl_url_factory.get_table_name()
+    }
+
     pub async fn create_table(&self, params: CreateTableParams) -> Result<(), DataWriterError> {
         let (fl_url, url) = self.fl_url_factory.get_fl_url().await?;
 
-        super::execution::create_table(
+        let result = super::execution::create_table(
             fl_url,
+            &self.fl_url_factory.options,
             url.as_str(),
-            TEntity::TABLE_NAME,
             params,
+            self.fl_url_factory.schema_version,
             &self.sync_period,
         )
-        .await
+        .await;
+        self.fl_url_factory.track_result(result)
     }
 
     #[cfg(feature = "with-ssh")]
@@ -105,14 +138,132 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         params: &CreateTableParams,
     ) -> Result<(), DataWriterError> {
         let (fl_url, url) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::create_table_if_not_exists(
+        let result = super::execution::create_table_if_not_exists(
             fl_url,
+            &self.fl_url_factory.options,
             url.as_str(),
-            TEntity::TABLE_NAME,
             params,
+            self.fl_url_factory.schema_version,
             self.sync_period,
         )
-        .await
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    // Creates table if it does not exist and replaces its content with the entities.
+    // Error is wrapped into CreateTableAndSeedFail with the stage which failed
+    pub async fn create_table_and_seed(
+        &self,
+        params: CreateTableParams,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        if let Err(err) = self.create_table_if_not_exists(&params).await {
+            return Err(DataWriterError::CreateTableAndSeedFail(
+                CreateTableAndSeedStage::CreateTable,
+                Box::new(err),
+            ));
+        }
+
+        if let Err(err) = self.clean_table_and_bulk_insert(entities).await {
+            return Err(DataWriterError::CreateTableAndSeedFail(
+                CreateTableAndSeedStage::Seed,
+                Box::new(err),
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Partition and row keys are sent to the server in the given case.
+    // Writer created by with_retries inherits the casing
+    pub fn with_key_case(mut self, key_case: KeyCase) -> Self {
+        self.fl_url_factory.options.key_case = key_case;
+        self
+    }
+
+    // Single entity which is bigger than max_entity_bytes is not sent on insert
+    // and is not accepted on read. Fails with EntityTooLarge
+    pub fn with_max_entity_bytes(mut self, max_entity_bytes: usize) -> Self {
+        self.fl_url_factory.options.max_entity_bytes = Some(max_entity_bytes);
+        self
+    }
+
+    // Table is created with the schema version and the version the server reports is checked
+    // before the first request. Requests fail with SchemaVersionMismatch until the versions match.
+    // Requires auto_create_table_params, since the version is reported by the table creation.
+    // Writer created by with_retries inherits it
+    pub fn with_schema_version(mut self, schema_version: u32) -> Self {
+        self.fl_url_factory.schema_version = Some(schema_version);
+        self
+    }
+
+    // TimeStamp is stamped by the writer with the local time instead of the server
+    pub fn with_client_timestamp(mut self, client_time_stamp: bool) -> Self {
+        self.fl_url_factory.options.client_time_stamp = client_time_stamp;
+        self
+    }
+
+    // 404 of get_entity caused by the missing table fails with TableNotFound instead of None.
+    // Writer created by with_retries inherits the mode
+    pub fn with_strict_table_presence(mut self) -> Self {
+        self.fl_url_factory.options.strict_table_presence = true;
+        self
+    }
+
+    // After failures_threshold consecutive connectivity failures requests fail fast with CircuitOpen
+    // until cooldown is passed. Writer created by with_retries shares the same circuit breaker
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.fl_url_factory.circuit_breaker = Some(Arc::new(CircuitBreaker::new(config)));
+        self
+    }
+
+    pub fn is_circuit_open(&self) -> bool {
+        self.fl_url_factory.is_circuit_open()
+    }
+
+    // Every request the writer makes goes through the interceptor. Writer created by with_retries shares it
+    pub fn with_request_interceptor(mut self, request_interceptor: RequestInterceptorRef) -> Self {
+        self.fl_url_factory.options.request_interceptor = Some(request_interceptor);
+        self
+    }
+
+    // Bodies of bulk_insert_or_replace and clean_table_and_bulk_insert which are bigger than
+    // min_size are sent with Content-Encoding: gzip. Writer created by with_retries inherits it
+    pub fn with_bulk_compression(mut self, bulk_compression: BulkCompression) -> Self {
+        self.fl_url_factory.options.bulk_compression = Some(bulk_compression);
+        self
+    }
+
+    // Same as with_bulk_compression with the default min_size. false turns the compression off
+    pub fn compress_bulk_bodies(mut self, compress: bool) -> Self {
+        self.fl_url_factory.options.bulk_compression = if compress {
+            Some(BulkCompression::default())
+        } else {
+            None
+        };
+        self
+    }
+
+    // Callback is invoked after each write confirmed by the server, so the local cache
+    // can be updated without waiting for the sync from the server.
+    // Writer created by with_retries gets the callback as well
+    pub fn on_successful_write(mut self, callback: WriteNotificationCallback<TEntity>) -> Self {
+        self.write_notification = Some(callback);
+        self
+    }
+
+    fn notify_on_success<T>(
+        &self,
+        result: Result<T, DataWriterError>,
+        notification: WriteNotification<TEntity>,
+    ) -> Result<T, DataWriterError> {
+        if result.is_ok() {
+            if let Some(callback) = self.write_notification.as_ref() {
+                callback(notification);
+            }
+        }
+
+        result
     }
 
     pub fn with_retries(
@@ -125,17 +276,103 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
             self.sync_period,
             delay_between_attempts,
             max_attempts,
+            self.write_notification.clone(),
         )
     }
 
     pub async fn insert_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::insert_entity(fl_url, entity, &self.sync_period).await
+        let result = super::execution::insert_entity(
+            fl_url,
+            &self.fl_url_factory.options,
+            entity,
+            &self.sync_period,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(result, WriteNotification::Upserted(entity))
     }
 
     pub async fn insert_or_replace_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::insert_or_replace_entity(fl_url, entity, &self.sync_period).await
+        let result = super::execution::insert_or_replace_entity(
+            fl_url,
+            &self.fl_url_factory.options,
+            entity,
+            &self.sync_period,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(result, WriteNotification::Upserted(entity))
+    }
+
+    // Compare-and-swap by TimeStamp. Fails with RecordIsChanged if the row was changed
+    // since expected_time_stamp was read, so the caller can read it again and retry
+    pub async fn insert_or_replace_entity_if_unchanged(
+        &self,
+        entity: &TEntity,
+        expected_time_stamp: &str,
+    ) -> Result<(), DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::insert_or_replace_entity_if_unchanged(
+            fl_url,
+            &self.fl_url_factory.options,
+            entity,
+            expected_time_stamp,
+            &self.sync_period,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(result, WriteNotification::Upserted(entity))
+    }
+
+    // Changes only the fields given in the patch, so the fields set by the other writers are kept.
+    // Returns the entity after the merge or None if there is no such row
+    pub async fn merge_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        patch: &serde_json::Value,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::merge_entity(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            row_key,
+            patch,
+            &self.sync_period,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+
+        if let Ok(Some(entity)) = &result {
+            if let Some(callback) = self.write_notification.as_ref() {
+                callback(WriteNotification::Upserted(entity));
+            }
+        }
+
+        result
+    }
+
+    // Posts already serialized entity as is. Only PartitionKey and RowKey are validated and cased
+    pub async fn insert_or_replace_raw(&self, raw_json: &[u8]) -> Result<(), DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::insert_or_replace_raw::<TEntity>(
+            fl_url,
+            &self.fl_url_factory.options,
+            raw_json,
+            &self.sync_period,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(result, WriteNotification::RawUpserted(raw_json))
+    }
+
+    // Size of the body bulk_insert_or_replace sends, with the key case, the client time stamp
+    // and the bulk compression of the writer applied. 0 for no entities, since nothing is sent
+    pub fn estimate_bulk_body_size(&self, entities: &[TEntity]) -> Result<usize, DataWriterError> {
+        super::execution::estimate_bulk_body_size(&self.fl_url_factory.options, entities)
     }
 
     pub async fn bulk_insert_or_replace(
@@ -143,7 +380,182 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         entities: &[TEntity],
     ) -> Result<(), DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::bulk_insert_or_replace(fl_url, entities, &self.sync_period).await
+        let result = super::execution::bulk_insert_or_replace(
+            fl_url,
+            &self.fl_url_factory.options,
+            entities,
+            &self.sync_period,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(result, WriteNotification::BulkUpserted(entities))
+    }
+
+    // Entities are written by batches of batch_size as they come from the stream,
+    // so only one batch is kept in memory
+    pub async fn bulk_insert_or_replace_stream(
+        &self,
+        stream: impl Stream<Item = TEntity>,
+        batch_size: usize,
+    ) -> Result<BulkStats, DataWriterError> {
+        let batch_size = batch_size.max(1);
+        let mut stream = std::pin::pin!(stream);
+
+        let mut stats = BulkStats::default();
+        let mut batch = Vec::with_capacity(batch_size);
+
+        while let Some(entity) = stream.next().await {
+            batch.push(entity);
+
+            if batch.len() >= batch_size {
+                self.bulk_insert_or_replace(&batch).await?;
+                stats.add_batch(batch.len());
+                batch.clear();
+            }
+        }
+
+        if !batch.is_empty() {
+            self.bulk_insert_or_replace(&batch).await?;
+            stats.add_batch(batch.len());
+        }
+
+        Ok(stats)
+    }
+
+    // Entities are posted exactly as they are, without casing and client timestamp
+    pub async fn bulk_insert_or_replace_raw(
+        &self,
+        raw_entities: &[Vec<u8>],
+    ) -> Result<(), DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::bulk_insert_or_replace_raw(
+            fl_url,
+            &self.fl_url_factory.options,
+            raw_entities,
+            &self.sync_period,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+
+        if result.is_ok() {
+            if let Some(callback) = self.write_notification.as_ref() {
+                for raw_entity in raw_entities {
+                    callback(WriteNotification::RawUpserted(raw_entity));
+                }
+            }
+        }
+
+        result
+    }
+
+    // Writes the table as a json array of the entities exactly as the server serializes them.
+    // Table is read by one request and the body is written as it comes, so the table is never kept in memory.
+    // Empty array is written if there is no table
+    pub async fn export_table_raw(
+        &self,
+        out: &mut (impl tokio::io::AsyncWrite + Unpin + Send),
+    ) -> Result<u64, DataWriterError> {
+        let mut written = 0;
+
+        match self.get_all_response().await? {
+            Some(response) => {
+                let mut chunks = Box::pin(super::execution::get_body_chunks(response));
+
+                while let Some(chunk) = chunks.next().await {
+                    written += super::raw_json_array::write_to(out, &chunk?).await?;
+                }
+            }
+            None => {
+                written += super::raw_json_array::write_to(out, b"[]").await?;
+            }
+        }
+
+        if let Err(err) = tokio::io::AsyncWriteExt::flush(out).await {
+            return Err(DataWriterError::Error(format!(
+                "Can not write exported data: {}",
+                err
+            )));
+        }
+
+        Ok(written)
+    }
+
+    // Reads json array made by export_table_raw and writes it by batches of batch_size.
+    // Only one batch is kept in memory
+    pub async fn restore_table_raw(
+        &self,
+        input: &mut (impl tokio::io::AsyncRead + Unpin + Send),
+        batch_size: usize,
+    ) -> Result<BulkStats, DataWriterError> {
+        let batch_size = batch_size.max(1);
+
+        let mut splitter = super::raw_json_array::RawJsonArraySplitter::new();
+        let mut buffer = vec![0u8; RESTORE_READ_BUFFER_SIZE];
+
+        let mut stats = BulkStats::default();
+        let mut batch = Vec::with_capacity(batch_size);
+
+        loop {
+            let read = match tokio::io::AsyncReadExt::read(input, &mut buffer).await {
+                Ok(read) => read,
+                Err(err) => {
+                    return Err(DataWriterError::Error(format!(
+                        "Can not read data to restore: {}",
+                        err
+                    )))
+                }
+            };
+
+            if read == 0 {
+                break;
+            }
+
+            for raw_entity in splitter.push(&buffer[..read])? {
+                batch.push(raw_entity);
+
+                if batch.len() >= batch_size {
+                    self.bulk_insert_or_replace_raw(&batch).await?;
+                    stats.add_batch(batch.len());
+                    batch.clear();
+                }
+            }
+        }
+
+        splitter.finish()?;
+
+        if !batch.is_empty() {
+            self.bulk_insert_or_replace_raw(&batch).await?;
+            stats.add_batch(batch.len());
+        }
+
+        Ok(stats)
+    }
+
+    // Writes every partition with its own request
+    pub async fn bulk_insert_or_replace_by_partitions(
+        &self,
+        entities: &[TEntity],
+        mode: BulkMode,
+    ) -> Result<Vec<PartitionBulkResult>, DataWriterError> {
+        let groups = super::bulk_mode::group_by_partition(entities);
+
+        super::bulk_mode::write_by_partitions(
+            &groups,
+            mode,
+            self.write_notification.as_ref(),
+            |entities| async move {
+                let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+                let result = super::execution::bulk_insert_or_replace_refs(
+                    fl_url,
+                    &self.fl_url_factory.options,
+                    entities,
+                    &self.sync_period,
+                )
+                .await;
+                self.fl_url_factory.track_result(result)
+            },
+        )
+        .await
     }
 
     pub async fn get_entity(
@@ -153,13 +565,102 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         update_read_statistics: Option<UpdateReadStatistics>,
     ) -> Result<Option<TEntity>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_entity(
+        let result = super::execution::get_entity(
             fl_url,
+            &self.fl_url_factory.options,
             partition_key,
             row_key,
             update_read_statistics.as_ref(),
         )
-        .await
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    pub async fn get_entity_with_consistency(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+        consistency: ConsistencyLevel,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        let (fl_url, _) = self
+            .fl_url_factory
+            .get_fl_url_with_consistency(consistency)
+            .await?;
+        let result = super::execution::get_entity(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            row_key,
+            update_read_statistics.as_ref(),
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    // Gives access to the fields of the entity without deserializing it
+    pub async fn get_entity_view<TResult>(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        view: impl FnOnce(EntityView) -> TResult,
+    ) -> Result<Option<TResult>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::get_entity_raw::<TEntity>(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            row_key,
+            None,
+        )
+        .await;
+        let raw = self.fl_url_factory.track_result(result)?;
+
+        let raw = match raw {
+            Some(raw) => raw,
+            None => return Ok(None),
+        };
+
+        let entity_view = EntityView::new(raw.as_slice()).map_err(DataWriterError::Error)?;
+
+        Ok(Some(view(entity_view)))
+    }
+
+    // Compares the entity with the stored row without writing it. None if they are identical.
+    // If there is no stored row - all the fields of the entity are reported as added
+    pub async fn diff_with_stored(
+        &self,
+        entity: &TEntity,
+    ) -> Result<Option<FieldDiff>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::get_entity_raw::<TEntity>(
+            fl_url,
+            &self.fl_url_factory.options,
+            entity.get_partition_key(),
+            entity.get_row_key(),
+            None,
+        )
+        .await;
+        let stored = self.fl_url_factory.track_result(result)?;
+
+        let stored = stored.unwrap_or_else(|| b"{}".to_vec());
+
+        FieldDiff::compute(entity.serialize_entity().as_slice(), stored.as_slice())
+            .map_err(DataWriterError::Error)
+    }
+
+    // Compares with the stored row ignoring TimeStamp and Expires and writes only if they differ.
+    // Read and write are not atomic
+    pub async fn insert_or_replace_if_changed(
+        &self,
+        entity: &TEntity,
+    ) -> Result<WriteResult, DataWriterError> {
+        if self.diff_with_stored(entity).await?.is_none() {
+            return Ok(WriteResult::Unchanged);
+        }
+
+        self.insert_or_replace_entity(entity).await?;
+        Ok(WriteResult::Changed)
     }
 
     pub async fn get_by_partition_key(
@@ -168,12 +669,50 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         update_read_statistics: Option<UpdateReadStatistics>,
     ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_by_partition_key(
+        let result = super::execution::get_by_partition_key(
             fl_url,
+            &self.fl_url_factory.options,
             partition_key,
             update_read_statistics.as_ref(),
         )
-        .await
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    pub async fn get_by_partition_key_with_consistency(
+        &self,
+        partition_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+        consistency: ConsistencyLevel,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        let (fl_url, _) = self
+            .fl_url_factory
+            .get_fl_url_with_consistency(consistency)
+            .await?;
+        let result = super::execution::get_by_partition_key(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            update_read_statistics.as_ref(),
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    pub async fn get_by_partition_and_row_prefix(
+        &self,
+        partition_key: &str,
+        row_key_prefix: &str,
+    ) -> Result<Vec<TEntity>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::get_by_partition_and_row_prefix(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            row_key_prefix,
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
     }
 
     pub async fn get_enum_case_models_by_partition_key<
@@ -188,11 +727,13 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         update_read_statistics: Option<UpdateReadStatistics>,
     ) -> Result<Option<Vec<TResult>>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_enum_case_models_by_partition_key(
+        let result = super::execution::get_enum_case_models_by_partition_key(
             fl_url,
+            &self.fl_url_factory.options,
             update_read_statistics.as_ref(),
         )
-        .await
+        .await;
+        self.fl_url_factory.track_result(result)
     }
 
     pub async fn get_enum_case_model<
@@ -207,15 +748,32 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         update_read_statistics: Option<UpdateReadStatistics>,
     ) -> Result<Option<TResult>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_enum_case_model(fl_url, update_read_statistics.as_ref()).await
+        let result = super::execution::get_enum_case_model(
+            fl_url,
+            &self.fl_url_factory.options,
+            update_read_statistics.as_ref(),
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
     }
 
+    // Rows with the row key are taken across all the partitions. Without limit all of them are returned
     pub async fn get_by_row_key(
         &self,
         row_key: &str,
+        skip: Option<i32>,
+        limit: Option<i32>,
     ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_by_row_key(fl_url, row_key).await
+        let result = super::execution::get_by_row_key(
+            fl_url,
+            &self.fl_url_factory.options,
+            row_key,
+            skip,
+            limit,
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
     }
 
     pub async fn get_partition_keys(
@@ -224,7 +782,113 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         limit: Option<i32>,
     ) -> Result<Vec<String>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_partition_keys(fl_url, TEntity::TABLE_NAME, skip, limit).await
+        let result =
+            super::execution::get_partition_keys(fl_url, &self.fl_url_factory.options, skip, limit)
+                .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    pub async fn get_row_keys(
+        &self,
+        partition_key: &str,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::get_row_keys(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            skip,
+            limit,
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    pub async fn get_partitions_metadata(
+        &self,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<PartitionMeta>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::get_partitions_metadata(
+            fl_url,
+            &self.fl_url_factory.options,
+            skip,
+            limit,
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    // Size of the partition content in bytes. None if there is no such partition,
+    // missing table fails with TableNotFound
+    pub async fn get_partition_size(
+        &self,
+        partition_key: &str,
+    ) -> Result<Option<usize>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::get_partition_size(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    // Aggregation is done by the server. If server does not support it,
+    // rows are loaded and aggregated on the client side
+    pub async fn aggregate_field(
+        &self,
+        partition_key: Option<&str>,
+        field: &str,
+        op: AggOp,
+    ) -> Result<f64, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::aggregate_field::<TEntity>(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            field,
+            op,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result)?;
+
+        if let Some(result) = result {
+            return Ok(result);
+        }
+
+        let entities = match partition_key {
+            Some(partition_key) => self.get_by_partition_key(partition_key, None).await?,
+            None => self.get_all().await?,
+        };
+
+        let entities = entities.unwrap_or_default();
+        Ok(super::aggregation::aggregate_entities(
+            entities.iter(),
+            field,
+            op,
+        ))
+    }
+
+    // Partitions are picked uniformly, then rows are sampled uniformly across the picked partitions
+    pub async fn get_random_sample(&self, n: usize) -> Result<Vec<TEntity>, DataWriterError> {
+        let partition_keys = self.get_partition_keys(None, None).await?;
+
+        let mut sample = super::random_sample::RandomSample::new(n);
+
+        for partition_key in super::random_sample::pick_random_partitions(partition_keys, n) {
+            if let Some(entities) = self.get_by_partition_key(&partition_key, None).await? {
+                for entity in entities {
+                    sample.add(entity);
+                }
+            }
+        }
+
+        Ok(sample.into_vec())
     }
 
     pub async fn delete_enum_case<
@@ -238,7 +902,15 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         &self,
     ) -> Result<Option<TResult>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::delete_enum_case(fl_url).await
+        let result = super::execution::delete_enum_case(fl_url, &self.fl_url_factory.options).await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(
+            result,
+            WriteNotification::RowDeleted {
+                partition_key: TResult::PARTITION_KEY,
+                row_key: TResult::ROW_KEY,
+            },
+        )
     }
 
     pub async fn delete_enum_case_with_row_key<
@@ -253,7 +925,20 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         row_key: &str,
     ) -> Result<Option<TResult>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::delete_enum_case_with_row_key(fl_url, row_key).await
+        let result = super::execution::delete_enum_case_with_row_key(
+            fl_url,
+            &self.fl_url_factory.options,
+            row_key,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(
+            result,
+            WriteNotification::RowDeleted {
+                partition_key: TResult::PARTITION_KEY,
+                row_key,
+            },
+        )
     }
 
     pub async fn delete_row(
@@ -262,17 +947,155 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         row_key: &str,
     ) -> Result<Option<TEntity>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::delete_row(fl_url, partition_key, row_key).await
+        let result = super::execution::delete_row(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            row_key,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(
+            result,
+            WriteNotification::RowDeleted {
+                partition_key,
+                row_key,
+            },
+        )
+    }
+
+    // Returns RecordIsChanged error if the field of the row does not have the expected value
+    pub async fn delete_row_if(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        field_name: &str,
+        expected_value: &str,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::delete_row_if(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            row_key,
+            field_name,
+            expected_value,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(
+            result,
+            WriteNotification::RowDeleted {
+                partition_key,
+                row_key,
+            },
+        )
     }
 
     pub async fn delete_partitions(&self, partition_keys: &[&str]) -> Result<(), DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::delete_partitions(fl_url, TEntity::TABLE_NAME, partition_keys).await
+        let result = super::execution::delete_partitions(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_keys,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(result, WriteNotification::PartitionsDeleted(partition_keys))
+    }
+
+    // Updates last read time of the rows to keep them away from GC
+    pub async fn touch_rows(
+        &self,
+        partition_key: &str,
+        row_keys: &[&str],
+    ) -> Result<(), DataWriterError> {
+        if row_keys.len() == 0 {
+            return Ok(());
+        }
+
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::touch_rows::<TEntity>(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            row_keys,
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    // Updates last read time of the partition with one request. Partition is kept away from GC.
+    // false if there is no such partition
+    pub async fn touch_partition(&self, partition_key: &str) -> Result<bool, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result =
+            super::execution::touch_partition(fl_url, &self.fl_url_factory.options, partition_key)
+                .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    // Expiration moment of the whole partition. None clears it.
+    // false if there is no such partition
+    pub async fn set_partition_expiration(
+        &self,
+        partition_key: &str,
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<bool, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::set_partition_expiration(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            expires,
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
     }
 
     pub async fn get_all(&self) -> Result<Option<Vec<TEntity>>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_all(fl_url).await
+        let result = super::execution::get_all(fl_url, &self.fl_url_factory.options).await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    // Buffer is cleared and filled with the entities of the table, so periodic full reads
+    // do not allocate a new Vec each time. Buffer stays empty if there is no table
+    pub async fn get_all_into(&self, buf: &mut Vec<TEntity>) -> Result<(), DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result =
+            super::execution::get_all_into(fl_url, &self.fl_url_factory.options, buf).await;
+        self.fl_url_factory.track_result(result)?;
+        Ok(())
+    }
+
+    pub async fn get_all_reuse(
+        &self,
+        mut buf: Vec<TEntity>,
+    ) -> Result<Vec<TEntity>, DataWriterError> {
+        self.get_all_into(&mut buf).await?;
+        Ok(buf)
+    }
+
+    // Entities are given away as the body of the response comes, so the whole table is never kept in memory.
+    // Stream is empty if there is no table
+    pub fn get_all_streamed(&self) -> impl Stream<Item = Result<TEntity, DataWriterError>> + '_ {
+        futures::stream::once(self.get_all_response()).flat_map(move |response| match response {
+            Ok(Some(response)) => {
+                futures::future::Either::Left(super::execution::deserialize_entities_stream(
+                    self.fl_url_factory.get_table_name().to_string(),
+                    super::execution::get_body_chunks(response),
+                ))
+            }
+            Ok(None) => futures::future::Either::Right(futures::stream::iter(None)),
+            Err(err) => futures::future::Either::Right(futures::stream::iter(Some(Err(err)))),
+        })
+    }
+
+    async fn get_all_response(&self) -> Result<Option<flurl::FlUrlResponse>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result = super::execution::get_all_response(fl_url, &self.fl_url_factory.options).await;
+        self.fl_url_factory.track_result(result)
     }
 
     pub async fn clean_table_and_bulk_insert(
@@ -280,7 +1103,15 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         entities: &[TEntity],
     ) -> Result<(), DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::clean_table_and_bulk_insert(fl_url, entities, &self.sync_period).await
+        let result = super::execution::clean_table_and_bulk_insert(
+            fl_url,
+            &self.fl_url_factory.options,
+            entities,
+            &self.sync_period,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(result, WriteNotification::TableReplaced(entities))
     }
 
     pub async fn clean_partition_and_bulk_insert(
@@ -289,13 +1120,22 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         entities: &[TEntity],
     ) -> Result<(), DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::clean_partition_and_bulk_insert(
+        let result = super::execution::clean_partition_and_bulk_insert(
             fl_url,
+            &self.fl_url_factory.options,
             partition_key,
             entities,
             &self.sync_period,
         )
-        .await
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(
+            result,
+            WriteNotification::PartitionReplaced {
+                partition_key,
+                entities,
+            },
+        )
     }
 }
 
@@ -304,3 +1144,253 @@ pub struct OperationFailHttpContract {
     pub reason: String,
     pub message: String,
 }
+
+#[async_trait::async_trait]
+impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static>
+    MyNoSqlWriter<TEntity> for MyNoSqlDataWriter<TEntity>
+{
+    async fn insert_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        self.insert_entity(entity).await
+    }
+
+    async fn insert_or_replace_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        self.insert_or_replace_entity(entity).await
+    }
+
+    async fn bulk_insert_or_replace(&self, entities: &[TEntity]) -> Result<(), DataWriterError> {
+        self.bulk_insert_or_replace(entities).await
+    }
+
+    async fn get_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        self.get_entity(partition_key, row_key, update_read_statistics)
+            .await
+    }
+
+    async fn get_by_partition_key(
+        &self,
+        partition_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        self.get_by_partition_key(partition_key, update_read_statistics)
+            .await
+    }
+
+    async fn get_by_row_key(
+        &self,
+        row_key: &str,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        self.get_by_row_key(row_key, skip, limit).await
+    }
+
+    async fn get_all(&self) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        self.get_all().await
+    }
+
+    async fn get_partition_keys(
+        &self,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError> {
+        self.get_partition_keys(skip, limit).await
+    }
+
+    async fn get_row_keys(
+        &self,
+        partition_key: &str,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError> {
+        self.get_row_keys(partition_key, skip, limit).await
+    }
+
+    async fn delete_row(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        self.delete_row(partition_key, row_key).await
+    }
+
+    async fn delete_partitions(&self, partition_keys: &[&str]) -> Result<(), DataWriterError> {
+        self.delete_partitions(partition_keys).await
+    }
+
+    async fn clean_table_and_bulk_insert(
+        &self,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        self.clean_table_and_bulk_insert(entities).await
+    }
+
+    async fn clean_partition_and_bulk_insert(
+        &self,
+        partition_key: &str,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        self.clean_partition_and_bulk_insert(partition_key, entities)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        test_fixtures::{create_intercepted_writer, create_writer, start_http_server, TestEntity},
+        CreateTableAndSeedStage, CreateTableParams, DataWriterError,
+    };
+
+    #[tokio::test]
+    async fn test_touch_rows_sends_all_the_row_keys_in_one_request() {
+        let (writer, interceptor) = create_intercepted_writer("test");
+
+        writer.touch_rows("pk", &[]).await.unwrap();
+        assert!(interceptor.get_urls().is_empty());
+
+        assert!(writer.touch_rows("pk", &["rk1", "rk2"]).await.is_err());
+
+        let urls = interceptor.get_urls();
+        assert_eq!(urls.len(), 1);
+        assert!(urls[0].contains("api/Rows/UpdateLastReadTime"));
+        assert!(urls[0].contains("tableName=test"));
+        assert!(urls[0].contains("partitionKey=pk"));
+        assert!(urls[0].contains("rowKey=rk1"));
+        assert!(urls[0].contains("rowKey=rk2"));
+    }
+
+    #[tokio::test]
+    async fn test_create_table_and_seed_stops_when_table_is_not_created() {
+        let (writer, interceptor) = create_intercepted_writer("test");
+
+        let result = writer
+            .create_table_and_seed(
+                CreateTableParams {
+                    persist: true,
+                    max_partitions_amount: None,
+                    max_rows_per_partition_amount: None,
+                },
+                &[TestEntity::default()],
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(DataWriterError::CreateTableAndSeedFail(
+                CreateTableAndSeedStage::CreateTable,
+                _
+            ))
+        ));
+
+        // Seed is not attempted
+        let urls = interceptor.get_urls();
+        assert_eq!(urls.len(), 1);
+        assert!(urls[0].contains("CreateIfNotExists"));
+    }
+
+    #[tokio::test]
+    async fn test_get_by_partition_and_row_prefix_sends_the_prefix() {
+        let (writer, interceptor) = create_intercepted_writer("test");
+
+        assert!(writer
+            .get_by_partition_and_row_prefix("pk", "order-")
+            .await
+            .is_err());
+
+        let urls = interceptor.get_urls();
+        assert_eq!(urls.len(), 1);
+        assert!(urls[0].contains("partitionKey=pk"));
+        assert!(urls[0].contains("rowKeyPrefix=order-"));
+    }
+
+    #[tokio::test]
+    async fn test_insert_or_replace_if_changed_does_not_write_when_read_fails() {
+        let (writer, interceptor) = create_intercepted_writer("test");
+
+        let entity = TestEntity {
+            partition_key: "pk".to_string(),
+            row_key: "rk".to_string(),
+            value: 1,
+        };
+
+        assert!(writer.insert_or_replace_if_changed(&entity).await.is_err());
+
+        let urls = interceptor.get_urls();
+        assert_eq!(urls.len(), 1);
+        assert!(urls[0].contains("rowKey=rk"));
+    }
+
+    #[tokio::test]
+    async fn test_requests_go_to_the_overridden_table() {
+        let (writer, interceptor) = create_intercepted_writer("tenant-a");
+        assert_eq!(writer.get_table_name(), "tenant-a");
+
+        let entity = TestEntity {
+            partition_key: "pk".to_string(),
+            row_key: "rk".to_string(),
+            value: 1,
+        };
+
+        assert!(writer.insert_or_replace_entity(&entity).await.is_err());
+        assert!(writer
+            .with_retries(Duration::from_millis(1), 1)
+            .insert_or_replace_entity(&entity)
+            .await
+            .is_err());
+
+        let urls = interceptor.get_urls();
+        assert!(urls.len() >= 2);
+
+        for url in urls {
+            assert!(url.contains("tableName=tenant-a"));
+            assert!(!url.contains("tableName=test"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_partition_size_of_missing_partition() {
+        let url = start_http_server(404, "").await;
+        let writer = create_writer(url);
+
+        assert!(writer.get_partition_size("pk").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_partition_size_of_missing_table() {
+        let url =
+            start_http_server(404, "{\"reason\":\"TableNotFound\",\"message\":\"test\"}").await;
+        let writer = create_writer(url);
+
+        let result = writer.get_partition_size("pk").await;
+        assert!(matches!(
+            result,
+            Err(DataWriterError::TableNotFound(table_name)) if table_name == "test"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insert_or_replace_raw_checks_size_of_the_sent_payload() {
+        let raw_json = br#"{"PartitionKey":"pk","RowKey":"rk"}"#;
+
+        let (writer, interceptor) = create_intercepted_writer("test");
+        let writer = writer
+            .with_max_entity_bytes(raw_json.len())
+            .with_client_timestamp(true);
+
+        let result = writer.insert_or_replace_raw(raw_json).await;
+
+        assert!(matches!(
+            result,
+            Err(DataWriterError::EntityTooLarge { size, limit })
+                if size > raw_json.len() && limit == raw_json.len()
+        ));
+        assert!(interceptor.get_urls().is_empty());
+    }
+}
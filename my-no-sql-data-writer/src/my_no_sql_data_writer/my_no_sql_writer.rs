@@ -0,0 +1,146 @@
+use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+
+use crate::{DataWriterError, UpdateReadStatistics};
+
+// Common interface of the writers, so application code can be generic over it
+// and MockDataWriter can be injected in the tests
+#[async_trait::async_trait]
+pub trait MyNoSqlWriter<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static> {
+    async fn insert_entity(&self, entity: &TEntity) -> Result<(), DataWriterError>;
+
+    async fn insert_or_replace_entity(&self, entity: &TEntity) -> Result<(), DataWriterError>;
+
+    async fn bulk_insert_or_replace(&self, entities: &[TEntity]) -> Result<(), DataWriterError>;
+
+    async fn get_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<TEntity>, DataWriterError>;
+
+    async fn get_by_partition_key(
+        &self,
+        partition_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError>;
+
+    async fn get_by_row_key(
+        &self,
+        row_key: &str,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError>;
+
+    async fn get_all(&self) -> Result<Option<Vec<TEntity>>, DataWriterError>;
+
+    async fn get_partition_keys(
+        &self,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError>;
+
+    // Partition keys in ascending order. The page is cut from the sorted keys of the whole table,
+    // so pages stay stable whatever order the server returns the keys in
+    async fn get_partition_keys_sorted(
+        &self,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError> {
+        super::execution::validate_skip_and_limit(skip, limit)?;
+
+        let mut result = self.get_partition_keys(None, None).await?;
+        result.sort();
+
+        let skip = skip.unwrap_or(0) as usize;
+        let limit = limit.map(|itm| itm as usize).unwrap_or(usize::MAX);
+
+        Ok(result.into_iter().skip(skip).take(limit).collect())
+    }
+
+    async fn get_row_keys(
+        &self,
+        partition_key: &str,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError>;
+
+    async fn delete_row(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Result<Option<TEntity>, DataWriterError>;
+
+    async fn delete_partitions(&self, partition_keys: &[&str]) -> Result<(), DataWriterError>;
+
+    async fn clean_table_and_bulk_insert(
+        &self,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError>;
+
+    async fn clean_partition_and_bulk_insert(
+        &self,
+        partition_key: &str,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MyNoSqlWriter;
+    use crate::{
+        test_fixtures::{create_intercepted_writer, TestEntity},
+        DataWriterError,
+    };
+
+    // Application code which knows only the trait
+    async fn rename_row(
+        writer: &impl MyNoSqlWriter<TestEntity>,
+        partition_key: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<(), DataWriterError> {
+        let Some(mut entity) = writer.get_entity(partition_key, from, None).await? else {
+            return Ok(());
+        };
+
+        writer.delete_row(partition_key, from).await?;
+
+        entity.row_key = to.to_string();
+        writer.insert_entity(&entity).await
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[tokio::test]
+    async fn test_mock_is_used_through_the_trait() {
+        let writer = crate::MockDataWriter::<TestEntity>::new(true);
+
+        writer
+            .insert_entity(&TestEntity {
+                partition_key: "pk".to_string(),
+                row_key: "rk1".to_string(),
+                value: 5,
+            })
+            .await
+            .unwrap();
+
+        rename_row(&writer, "pk", "rk1", "rk2").await.unwrap();
+
+        assert!(writer.get_entity("pk", "rk1").await.unwrap().is_none());
+
+        let entity = writer.get_entity("pk", "rk2").await.unwrap().unwrap();
+        assert_eq!(entity.value, 5);
+    }
+
+    #[tokio::test]
+    async fn test_writer_is_used_through_the_trait() {
+        let (writer, interceptor) = create_intercepted_writer("test");
+
+        assert!(rename_row(&writer, "pk", "rk1", "rk2").await.is_err());
+
+        let urls = interceptor.get_urls();
+        assert_eq!(urls.len(), 1);
+        assert!(urls[0].contains("partitionKey=pk"));
+        assert!(urls[0].contains("rowKey=rk1"));
+    }
+}
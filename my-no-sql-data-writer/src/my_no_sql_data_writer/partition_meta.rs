@@ -0,0 +1,33 @@
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone)]
+pub struct PartitionMeta {
+    pub partition_key: String,
+    pub rows_amount: usize,
+    pub content_size: usize,
+    pub last_write_moment: DateTimeAsMicroseconds,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PartitionMetaHttpContract {
+    #[serde(rename = "partitionKey")]
+    pub partition_key: String,
+    #[serde(rename = "rowsAmount")]
+    pub rows_amount: usize,
+    #[serde(rename = "contentSize")]
+    pub content_size: usize,
+    #[serde(rename = "lastWriteMoment")]
+    pub last_write_moment: i64,
+}
+
+impl Into<PartitionMeta> for PartitionMetaHttpContract {
+    fn into(self) -> PartitionMeta {
+        PartitionMeta {
+            partition_key: self.partition_key,
+            rows_amount: self.rows_amount,
+            content_size: self.content_size,
+            last_write_moment: DateTimeAsMicroseconds::new(self.last_write_moment),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::hash::{BuildHasher, Hasher};
+
+// Reservoir sampling: every added item has the same chance to be in the result
+pub struct RandomSample<T> {
+    amount: usize,
+    seen: usize,
+    items: Vec<T>,
+}
+
+impl<T> RandomSample<T> {
+    pub fn new(amount: usize) -> Self {
+        Self {
+            amount,
+            seen: 0,
+            items: Vec::with_capacity(amount),
+        }
+    }
+
+    pub fn add(&mut self, item: T) {
+        self.seen += 1;
+
+        if self.items.len() < self.amount {
+            self.items.push(item);
+            return;
+        }
+
+        let index = random_index(self.seen);
+
+        if index < self.amount {
+            self.items[index] = item;
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.items
+    }
+}
+
+pub fn pick_random_partitions(mut partition_keys: Vec<String>, amount: usize) -> Vec<String> {
+    let mut result = Vec::with_capacity(amount.min(partition_keys.len()));
+
+    while result.len() < amount && partition_keys.len() > 0 {
+        let index = random_index(partition_keys.len());
+        result.push(partition_keys.swap_remove(index));
+    }
+
+    result
+}
+
+fn random_index(len: usize) -> usize {
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_usize(len);
+    (hasher.finish() % len as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_is_limited_by_amount() {
+        let mut sample = RandomSample::new(5);
+
+        for i in 0..100 {
+            sample.add(i);
+        }
+
+        let result = sample.into_vec();
+        assert_eq!(result.len(), 5);
+        assert!(result.iter().all(|itm| *itm < 100));
+    }
+
+    #[test]
+    fn test_pick_random_partitions() {
+        let partition_keys: Vec<String> = (0..10).map(|i| i.to_string()).collect();
+
+        let mut result = pick_random_partitions(partition_keys.clone(), 3);
+        assert_eq!(result.len(), 3);
+        result.sort();
+        result.dedup();
+        assert_eq!(result.len(), 3);
+
+        let result = pick_random_partitions(partition_keys, 20);
+        assert_eq!(result.len(), 10);
+    }
+}
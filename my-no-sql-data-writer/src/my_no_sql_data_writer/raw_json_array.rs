@@ -0,0 +1,169 @@
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use super::DataWriterError;
+
+pub async fn write_to(
+    out: &mut (impl AsyncWrite + Unpin + Send),
+    data: &[u8],
+) -> Result<u64, DataWriterError> {
+    if let Err(err) = out.write_all(data).await {
+        return Err(DataWriterError::Error(format!(
+            "Can not write exported data: {}",
+            err
+        )));
+    }
+
+    Ok(data.len() as u64)
+}
+
+// Splits json array of objects which comes by chunks into the raw objects.
+// Object is given away as soon as it is complete, so the whole array is never kept in memory
+pub struct RawJsonArraySplitter {
+    started: bool,
+    finished: bool,
+    depth: usize,
+    in_string: bool,
+    escape: bool,
+    current: Vec<u8>,
+}
+
+impl RawJsonArraySplitter {
+    pub fn new() -> Self {
+        Self {
+            started: false,
+            finished: false,
+            depth: 0,
+            in_string: false,
+            escape: false,
+            current: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Vec<Vec<u8>>, DataWriterError> {
+        let mut result = Vec::new();
+
+        for b in chunk.iter().copied() {
+            if self.finished {
+                if b.is_ascii_whitespace() {
+                    continue;
+                }
+                return Err(DataWriterError::Error(
+                    "Unexpected data after the json array".to_string(),
+                ));
+            }
+
+            if !self.started {
+                if b.is_ascii_whitespace() {
+                    continue;
+                }
+
+                if b != b'[' {
+                    return Err(DataWriterError::Error("Json array is expected".to_string()));
+                }
+
+                self.started = true;
+                continue;
+            }
+
+            if self.depth == 0 {
+                match b {
+                    b',' => continue,
+                    b']' => {
+                        self.finished = true;
+                        continue;
+                    }
+                    b'{' => {
+                        self.depth = 1;
+                        self.current.push(b);
+                        continue;
+                    }
+                    _ => {
+                        if b.is_ascii_whitespace() {
+                            continue;
+                        }
+
+                        return Err(DataWriterError::Error(
+                            "Json object is expected as an element of the array".to_string(),
+                        ));
+                    }
+                }
+            }
+
+            self.current.push(b);
+
+            if self.in_string {
+                if self.escape {
+                    self.escape = false;
+                } else if b == b'\\' {
+                    self.escape = true;
+                } else if b == b'"' {
+                    self.in_string = false;
+                }
+                continue;
+            }
+
+            match b {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' => {
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        result.push(std::mem::take(&mut self.current));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub fn finish(&self) -> Result<(), DataWriterError> {
+        if self.finished {
+            return Ok(());
+        }
+
+        Err(DataWriterError::Error(
+            "Json array is not complete".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RawJsonArraySplitter;
+
+    #[test]
+    fn test_split_by_chunks() {
+        let src = r#"[{"PartitionKey":"pk","RowKey":"r}k","Value":[1,{"a":"\""}]}, {"PartitionKey":"pk","RowKey":"rk2"}]"#;
+
+        let mut splitter = RawJsonArraySplitter::new();
+        let mut result = Vec::new();
+
+        for chunk in src.as_bytes().chunks(3) {
+            result.extend(splitter.push(chunk).unwrap());
+        }
+
+        splitter.finish().unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(
+            std::str::from_utf8(&result[0]).unwrap(),
+            r#"{"PartitionKey":"pk","RowKey":"r}k","Value":[1,{"a":"\""}]}"#
+        );
+        assert_eq!(
+            std::str::from_utf8(&result[1]).unwrap(),
+            r#"{"PartitionKey":"pk","RowKey":"rk2"}"#
+        );
+    }
+
+    #[test]
+    fn test_incomplete_array() {
+        let mut splitter = RawJsonArraySplitter::new();
+        splitter.push(br#"[{"PartitionKey":"pk""#).unwrap();
+        assert!(splitter.finish().is_err());
+
+        let mut splitter = RawJsonArraySplitter::new();
+        assert!(splitter.push(b"{}").is_err());
+    }
+}
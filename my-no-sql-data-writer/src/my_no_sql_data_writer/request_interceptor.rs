@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use flurl::{FlUrl, FlUrlResponse};
+
+// Observes every request the writer makes. before is invoked with the request which is about to be sent,
+// with the path and the query of the operation. Useful for custom headers, logging and test assertions
+#[async_trait::async_trait]
+pub trait RequestInterceptor {
+    async fn before(&self, req: &mut FlUrl);
+    async fn after(&self, resp: &FlUrlResponse);
+}
+
+pub type RequestInterceptorRef = Arc<dyn RequestInterceptor + Send + Sync + 'static>;
+
+#[async_trait::async_trait]
+pub trait InterceptedFlUrl {
+    // Called as the last step of building the request, right before it is sent
+    async fn before_send(self, request_interceptor: Option<&RequestInterceptorRef>) -> FlUrl;
+}
+
+#[async_trait::async_trait]
+impl InterceptedFlUrl for FlUrl {
+    async fn before_send(mut self, request_interceptor: Option<&RequestInterceptorRef>) -> FlUrl {
+        if let Some(request_interceptor) = request_interceptor {
+            request_interceptor.before(&mut self).await;
+        }
+
+        self
+    }
+}
+
+pub async fn after_response(
+    request_interceptor: Option<&RequestInterceptorRef>,
+    response: &FlUrlResponse,
+) {
+    if let Some(request_interceptor) = request_interceptor {
+        request_interceptor.after(response).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use flurl::FlUrl;
+
+    use super::{InterceptedFlUrl, RequestInterceptorRef};
+    use crate::{
+        my_no_sql_data_writer::{fl_url_ext::FlUrlExt, url_builder::UrlBuilder},
+        test_fixtures::UrlsInterceptor,
+    };
+
+    #[tokio::test]
+    async fn test_interceptor_sees_the_request_which_is_sent() {
+        let interceptor = Arc::new(UrlsInterceptor::default());
+        let request_interceptor: RequestInterceptorRef = interceptor.clone();
+
+        FlUrl::new("http://localhost:5123")
+            .append_url(UrlBuilder::row().insert())
+            .with_table_name_as_query_param("test-table")
+            .before_send(Some(&request_interceptor))
+            .await;
+
+        let urls = interceptor.get_urls();
+        assert_eq!(urls.len(), 1);
+        assert!(urls[0].contains("Row/Insert"));
+        assert!(urls[0].contains("tableName=test-table"));
+    }
+}
@@ -0,0 +1,97 @@
+use super::{DataWriterError, OperationFailHttpContract};
+
+// Reasons of OperationFailHttpContract the server responds with.
+// TryFrom<&str> is available through the From implementation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerFailReason {
+    TableAlreadyExists,
+    TableNotFound,
+    RecordAlreadyExists,
+    RecordIsChanged,
+    RequiredEntityFieldIsMissing,
+    JsonParseFail,
+    Unknown(String),
+}
+
+impl ServerFailReason {
+    pub fn into_data_writer_error(self, message: String) -> DataWriterError {
+        match self {
+            Self::TableAlreadyExists => DataWriterError::TableAlreadyExists(message),
+            Self::TableNotFound => DataWriterError::TableNotFound(message),
+            Self::RecordAlreadyExists => DataWriterError::RecordAlreadyExists(message),
+            Self::RecordIsChanged => DataWriterError::RecordIsChanged(message),
+            Self::RequiredEntityFieldIsMissing => {
+                DataWriterError::RequiredEntityFieldIsMissing(message)
+            }
+            Self::JsonParseFail => DataWriterError::ServerCouldNotParseJson(message),
+            Self::Unknown(reason) => DataWriterError::Error(format!(
+                "Not supported error. {:?}",
+                OperationFailHttpContract { reason, message }
+            )),
+        }
+    }
+}
+
+impl<'s> From<&'s str> for ServerFailReason {
+    fn from(src: &'s str) -> Self {
+        match src {
+            "TableAlreadyExists" => Self::TableAlreadyExists,
+            "TableNotFound" => Self::TableNotFound,
+            "RecordAlreadyExists" => Self::RecordAlreadyExists,
+            "RecordIsChanged" => Self::RecordIsChanged,
+            "RequiredEntityFieldIsMissing" => Self::RequiredEntityFieldIsMissing,
+            "JsonParseFail" => Self::JsonParseFail,
+            _ => Self::Unknown(src.to_string()),
+        }
+    }
+}
+
+impl From<OperationFailHttpContract> for DataWriterError {
+    fn from(src: OperationFailHttpContract) -> Self {
+        ServerFailReason::from(src.reason.as_str()).into_data_writer_error(src.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ServerFailReason;
+    use crate::{DataWriterError, OperationFailHttpContract};
+
+    #[test]
+    fn test_known_reasons() {
+        assert_eq!(
+            ServerFailReason::try_from("TableNotFound").unwrap(),
+            ServerFailReason::TableNotFound
+        );
+        assert_eq!(
+            ServerFailReason::from("JsonParseFail"),
+            ServerFailReason::JsonParseFail
+        );
+
+        let err: DataWriterError = OperationFailHttpContract {
+            reason: "RecordAlreadyExists".to_string(),
+            message: "exists".to_string(),
+        }
+        .into();
+
+        assert!(
+            matches!(err, DataWriterError::RecordAlreadyExists(message) if message == "exists")
+        );
+    }
+
+    #[test]
+    fn test_unknown_reason() {
+        assert_eq!(
+            ServerFailReason::from("SomethingNew"),
+            ServerFailReason::Unknown("SomethingNew".to_string())
+        );
+
+        let err: DataWriterError = OperationFailHttpContract {
+            reason: "SomethingNew".to_string(),
+            message: "message".to_string(),
+        }
+        .into();
+
+        assert!(matches!(err, DataWriterError::Error(_)));
+    }
+}
@@ -1,4 +1,9 @@
 #[async_trait::async_trait]
 pub trait MyNoSqlWriterSettings {
     async fn get_url(&self) -> String;
+
+    // Url of the master node. Reads with ConsistencyLevel::Strong are sent there if it is set
+    async fn get_master_url(&self) -> Option<String> {
+        None
+    }
 }
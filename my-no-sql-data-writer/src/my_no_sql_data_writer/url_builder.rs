@@ -0,0 +1,181 @@
+const API_SEGMENT: &str = "api";
+
+const TABLES_CONTROLLER: &str = "Tables";
+const ROW_CONTROLLER: &str = "Row";
+const ROWS_CONTROLLER: &str = "Rows";
+const BULK_CONTROLLER: &str = "Bulk";
+const PARTITIONS_CONTROLLER: &str = "Partitions";
+
+// Path of the server endpoint. All the paths the writer calls are built here,
+// so the list of endpoints can be checked in one place
+#[derive(Debug, Clone)]
+pub struct UrlBuilder {
+    segments: Vec<&'static str>,
+}
+
+impl UrlBuilder {
+    fn new(segments: &[&'static str]) -> Self {
+        Self {
+            segments: segments.to_vec(),
+        }
+    }
+
+    fn append(mut self, segment: &'static str) -> Self {
+        self.segments.push(segment);
+        self
+    }
+
+    pub fn tables() -> Self {
+        Self::new(&[TABLES_CONTROLLER])
+    }
+
+    pub fn row() -> Self {
+        Self::new(&[ROW_CONTROLLER])
+    }
+
+    pub fn rows() -> Self {
+        Self::new(&[ROWS_CONTROLLER])
+    }
+
+    pub fn bulk() -> Self {
+        Self::new(&[BULK_CONTROLLER])
+    }
+
+    pub fn api_row() -> Self {
+        Self::new(&[API_SEGMENT, ROW_CONTROLLER])
+    }
+
+    pub fn api_rows() -> Self {
+        Self::new(&[API_SEGMENT, ROWS_CONTROLLER])
+    }
+
+    pub fn api_partitions() -> Self {
+        Self::new(&[API_SEGMENT, PARTITIONS_CONTROLLER])
+    }
+
+    pub fn create(self) -> Self {
+        self.append("Create")
+    }
+
+    pub fn create_if_not_exists(self) -> Self {
+        self.append("CreateIfNotExists")
+    }
+
+    pub fn insert(self) -> Self {
+        self.append("Insert")
+    }
+
+    pub fn insert_or_replace(self) -> Self {
+        self.append("InsertOrReplace")
+    }
+
+    pub fn merge(self) -> Self {
+        self.append("Merge")
+    }
+
+    pub fn clean_and_insert(self) -> Self {
+        self.append("CleanAndBulkInsert")
+    }
+
+    pub fn metadata(self) -> Self {
+        self.append("Metadata")
+    }
+
+    pub fn update_last_read_time(self) -> Self {
+        self.append("UpdateLastReadTime")
+    }
+
+    pub fn aggregate(self) -> Self {
+        self.append("Aggregate")
+    }
+
+    pub fn set_expiration_time(self) -> Self {
+        self.append("SetExpirationTime")
+    }
+
+    pub fn row_keys(self) -> Self {
+        self.append("RowKeys")
+    }
+
+    pub fn get_segments(&self) -> &[&'static str] {
+        &self.segments
+    }
+
+    pub fn as_path(&self) -> String {
+        self.segments.join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UrlBuilder;
+
+    #[test]
+    fn test_tables_urls() {
+        assert_eq!(UrlBuilder::tables().create().as_path(), "Tables/Create");
+        assert_eq!(
+            UrlBuilder::tables().create_if_not_exists().as_path(),
+            "Tables/CreateIfNotExists"
+        );
+    }
+
+    #[test]
+    fn test_row_urls() {
+        assert_eq!(UrlBuilder::row().as_path(), "Row");
+        assert_eq!(UrlBuilder::row().insert().as_path(), "Row/Insert");
+        assert_eq!(
+            UrlBuilder::row().insert_or_replace().as_path(),
+            "Row/InsertOrReplace"
+        );
+        assert_eq!(UrlBuilder::row().merge().as_path(), "Row/Merge");
+        assert_eq!(UrlBuilder::api_row().as_path(), "api/Row");
+    }
+
+    #[test]
+    fn test_rows_urls() {
+        assert_eq!(UrlBuilder::rows().as_path(), "Rows");
+        assert_eq!(
+            UrlBuilder::api_rows().update_last_read_time().as_path(),
+            "api/Rows/UpdateLastReadTime"
+        );
+        assert_eq!(
+            UrlBuilder::api_rows().aggregate().as_path(),
+            "api/Rows/Aggregate"
+        );
+        assert_eq!(
+            UrlBuilder::api_rows().row_keys().as_path(),
+            "api/Rows/RowKeys"
+        );
+    }
+
+    #[test]
+    fn test_bulk_urls() {
+        assert_eq!(
+            UrlBuilder::bulk().insert_or_replace().as_path(),
+            "Bulk/InsertOrReplace"
+        );
+        assert_eq!(
+            UrlBuilder::bulk().clean_and_insert().as_path(),
+            "Bulk/CleanAndBulkInsert"
+        );
+    }
+
+    #[test]
+    fn test_partitions_urls() {
+        assert_eq!(UrlBuilder::api_partitions().as_path(), "api/Partitions");
+        assert_eq!(
+            UrlBuilder::api_partitions().metadata().as_path(),
+            "api/Partitions/Metadata"
+        );
+        assert_eq!(
+            UrlBuilder::api_partitions()
+                .update_last_read_time()
+                .as_path(),
+            "api/Partitions/UpdateLastReadTime"
+        );
+        assert_eq!(
+            UrlBuilder::api_partitions().set_expiration_time().as_path(),
+            "api/Partitions/SetExpirationTime"
+        );
+    }
+}
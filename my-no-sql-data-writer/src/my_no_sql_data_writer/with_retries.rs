@@ -1,8 +1,16 @@
-use std::{marker::PhantomData, time::Duration};
+use std::{marker::PhantomData, sync::Arc, time::Duration};
+
+use futures::{Stream, StreamExt};
 
 use my_no_sql_abstractions::{DataSynchronizationPeriod, MyNoSqlEntity, MyNoSqlEntitySerializer};
+use rust_extensions::date_time::DateTimeAsMicroseconds;
 
-use crate::{DataWriterError, UpdateReadStatistics};
+use crate::{
+    AggOp, BulkMode, BulkStats, CircuitBreaker, CircuitBreakerConfig, ConsistencyLevel,
+    DataWriterError, DeadLetterSink, EntityView, FieldDiff, KeyCase, MyNoSqlWriter,
+    PartitionBulkResult, PartitionMeta, UpdateReadStatistics, WriteNotification,
+    WriteNotificationCallback, WriteResult,
+};
 
 use super::fl_url_factory::FlUrlFactory;
 
@@ -12,6 +20,8 @@ pub struct MyNoSqlDataWriterWithRetries<TEntity: MyNoSqlEntity + Sync + Send> {
     attempt_delay: Duration,
     phantom: PhantomData<TEntity>,
     max_attempts: usize,
+    dead_letter_sink: Option<Arc<dyn DeadLetterSink<TEntity> + Send + Sync + 'static>>,
+    write_notification: Option<WriteNotificationCallback<TEntity>>,
 }
 
 impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
@@ -22,6 +32,7 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
         sync_period: DataSynchronizationPeriod,
         attempt_delay: Duration,
         max_attempts: usize,
+        write_notification: Option<WriteNotificationCallback<TEntity>>,
     ) -> Self {
         Self {
             phantom: PhantomData,
@@ -29,28 +40,225 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
             attempt_delay,
             max_attempts,
             fl_url_factory,
+            dead_letter_sink: None,
+            write_notification,
+        }
+    }
+
+    // Entities of the failed writes are handed to the sink. RecordAlreadyExists is not a failure
+    pub fn with_dead_letter(
+        mut self,
+        sink: Arc<dyn DeadLetterSink<TEntity> + Send + Sync + 'static>,
+    ) -> Self {
+        self.dead_letter_sink = Some(sink);
+        self
+    }
+
+    pub fn with_key_case(mut self, key_case: KeyCase) -> Self {
+        self.fl_url_factory.options.key_case = key_case;
+        self
+    }
+
+    pub fn with_max_entity_bytes(mut self, max_entity_bytes: usize) -> Self {
+        self.fl_url_factory.options.max_entity_bytes = Some(max_entity_bytes);
+        self
+    }
+
+    pub fn with_client_timestamp(mut self, client_time_stamp: bool) -> Self {
+        self.fl_url_factory.options.client_time_stamp = client_time_stamp;
+        self
+    }
+
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.fl_url_factory.circuit_breaker = Some(Arc::new(CircuitBreaker::new(config)));
+        self
+    }
+
+    pub fn is_circuit_open(&self) -> bool {
+        self.fl_url_factory.is_circuit_open()
+    }
+
+    // Callback is invoked after each write confirmed by the server.
+    // Writer created by MyNoSqlDataWriter::with_retries gets the callback of the writer
+    pub fn on_successful_write(mut self, callback: WriteNotificationCallback<TEntity>) -> Self {
+        self.write_notification = Some(callback);
+        self
+    }
+
+    fn notify_on_success<T>(
+        &self,
+        result: Result<T, DataWriterError>,
+        notification: WriteNotification<TEntity>,
+    ) -> Result<T, DataWriterError> {
+        if result.is_ok() {
+            if let Some(callback) = self.write_notification.as_ref() {
+                callback(notification);
+            }
+        }
+
+        result
+    }
+
+    async fn dead_letter_if_failed<'s>(
+        &self,
+        entities: impl IntoIterator<Item = &'s TEntity>,
+        result: Result<(), DataWriterError>,
+    ) -> Result<(), DataWriterError> {
+        if let Err(err) = &result {
+            if let DataWriterError::RecordAlreadyExists(_) = err {
+                return result;
+            }
+
+            if let Some(sink) = self.dead_letter_sink.as_ref() {
+                for entity in entities {
+                    sink.dead_letter(entity, err).await;
+                }
+            }
         }
+
+        result
     }
 
     pub async fn insert_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::insert_entity(fl_url, entity, &self.sync_period).await
+        let result: Result<(), DataWriterError> = async {
+            let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+            let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+            let result = super::execution::insert_entity(
+                fl_url,
+                &self.fl_url_factory.options,
+                entity,
+                &self.sync_period,
+            )
+            .await;
+            self.fl_url_factory.track_result(result)
+        }
+        .await;
+
+        let result = self
+            .dead_letter_if_failed(std::slice::from_ref(entity), result)
+            .await;
+        self.notify_on_success(result, WriteNotification::Upserted(entity))
     }
 
     pub async fn insert_or_replace_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        let result: Result<(), DataWriterError> = async {
+            let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+            let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+            let result = super::execution::insert_or_replace_entity(
+                fl_url,
+                &self.fl_url_factory.options,
+                entity,
+                &self.sync_period,
+            )
+            .await;
+            self.fl_url_factory.track_result(result)
+        }
+        .await;
+
+        let result = self
+            .dead_letter_if_failed(std::slice::from_ref(entity), result)
+            .await;
+        self.notify_on_success(result, WriteNotification::Upserted(entity))
+    }
+
+    pub async fn insert_or_replace_raw(&self, raw_json: &[u8]) -> Result<(), DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
         let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::insert_or_replace_entity(fl_url, entity, &self.sync_period).await
+        let result = super::execution::insert_or_replace_raw::<TEntity>(
+            fl_url,
+            &self.fl_url_factory.options,
+            raw_json,
+            &self.sync_period,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(result, WriteNotification::RawUpserted(raw_json))
     }
 
     pub async fn bulk_insert_or_replace(
         &self,
         entities: &[TEntity],
     ) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::bulk_insert_or_replace(fl_url, entities, &self.sync_period).await
+        let result: Result<(), DataWriterError> = async {
+            let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+            let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+            let result = super::execution::bulk_insert_or_replace(
+                fl_url,
+                &self.fl_url_factory.options,
+                entities,
+                &self.sync_period,
+            )
+            .await;
+            self.fl_url_factory.track_result(result)
+        }
+        .await;
+
+        let result = self.dead_letter_if_failed(entities, result).await;
+        self.notify_on_success(result, WriteNotification::BulkUpserted(entities))
+    }
+
+    // Entities are written by batches of batch_size as they come from the stream,
+    // so only one batch is kept in memory
+    pub async fn bulk_insert_or_replace_stream(
+        &self,
+        stream: impl Stream<Item = TEntity>,
+        batch_size: usize,
+    ) -> Result<BulkStats, DataWriterError> {
+        let batch_size = batch_size.max(1);
+        let mut stream = std::pin::pin!(stream);
+
+        let mut stats = BulkStats::default();
+        let mut batch = Vec::with_capacity(batch_size);
+
+        while let Some(entity) = stream.next().await {
+            batch.push(entity);
+
+            if batch.len() >= batch_size {
+                self.bulk_insert_or_replace(&batch).await?;
+                stats.add_batch(batch.len());
+                batch.clear();
+            }
+        }
+
+        if !batch.is_empty() {
+            self.bulk_insert_or_replace(&batch).await?;
+            stats.add_batch(batch.len());
+        }
+
+        Ok(stats)
+    }
+
+    pub async fn bulk_insert_or_replace_by_partitions(
+        &self,
+        entities: &[TEntity],
+        mode: BulkMode,
+    ) -> Result<Vec<PartitionBulkResult>, DataWriterError> {
+        let groups = super::bulk_mode::group_by_partition(entities);
+
+        super::bulk_mode::write_by_partitions(
+            &groups,
+            mode,
+            self.write_notification.as_ref(),
+            |entities| async move {
+                let result: Result<(), DataWriterError> = async {
+                    let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+                    let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+                    let result = super::execution::bulk_insert_or_replace_refs(
+                        fl_url,
+                        &self.fl_url_factory.options,
+                        entities,
+                        &self.sync_period,
+                    )
+                    .await;
+                    self.fl_url_factory.track_result(result)
+                }
+                .await;
+
+                self.dead_letter_if_failed(entities.iter().copied(), result)
+                    .await
+            },
+        )
+        .await
     }
 
     pub async fn get_entity(
@@ -61,13 +269,105 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
     ) -> Result<Option<TEntity>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
         let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::get_entity(
+        let result = super::execution::get_entity(
             fl_url,
+            &self.fl_url_factory.options,
             partition_key,
             row_key,
             update_read_statistics.as_ref(),
         )
-        .await
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    pub async fn get_entity_with_consistency(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+        consistency: ConsistencyLevel,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        let (fl_url, _) = self
+            .fl_url_factory
+            .get_fl_url_with_consistency(consistency)
+            .await?;
+        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+        let result = super::execution::get_entity(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            row_key,
+            update_read_statistics.as_ref(),
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    // Gives access to the fields of the entity without deserializing it
+    pub async fn get_entity_view<TResult>(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        view: impl FnOnce(EntityView) -> TResult,
+    ) -> Result<Option<TResult>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+        let result = super::execution::get_entity_raw::<TEntity>(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            row_key,
+            None,
+        )
+        .await;
+        let raw = self.fl_url_factory.track_result(result)?;
+
+        let raw = match raw {
+            Some(raw) => raw,
+            None => return Ok(None),
+        };
+
+        let entity_view = EntityView::new(raw.as_slice()).map_err(DataWriterError::Error)?;
+
+        Ok(Some(view(entity_view)))
+    }
+
+    // Compares the entity with the stored row without writing it. None if they are identical.
+    // If there is no stored row - all the fields of the entity are reported as added
+    pub async fn diff_with_stored(
+        &self,
+        entity: &TEntity,
+    ) -> Result<Option<FieldDiff>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+        let result = super::execution::get_entity_raw::<TEntity>(
+            fl_url,
+            &self.fl_url_factory.options,
+            entity.get_partition_key(),
+            entity.get_row_key(),
+            None,
+        )
+        .await;
+        let stored = self.fl_url_factory.track_result(result)?;
+
+        let stored = stored.unwrap_or_else(|| b"{}".to_vec());
+
+        FieldDiff::compute(entity.serialize_entity().as_slice(), stored.as_slice())
+            .map_err(DataWriterError::Error)
+    }
+
+    // Compares with the stored row ignoring TimeStamp and Expires and writes only if they differ.
+    // Read and write are not atomic
+    pub async fn insert_or_replace_if_changed(
+        &self,
+        entity: &TEntity,
+    ) -> Result<WriteResult, DataWriterError> {
+        if self.diff_with_stored(entity).await?.is_none() {
+            return Ok(WriteResult::Unchanged);
+        }
+
+        self.insert_or_replace_entity(entity).await?;
+        Ok(WriteResult::Changed)
     }
 
     pub async fn get_by_partition_key(
@@ -77,12 +377,52 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
     ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
         let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::get_by_partition_key(
+        let result = super::execution::get_by_partition_key(
             fl_url,
+            &self.fl_url_factory.options,
             partition_key,
             update_read_statistics.as_ref(),
         )
-        .await
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    pub async fn get_by_partition_key_with_consistency(
+        &self,
+        partition_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+        consistency: ConsistencyLevel,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        let (fl_url, _) = self
+            .fl_url_factory
+            .get_fl_url_with_consistency(consistency)
+            .await?;
+        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+        let result = super::execution::get_by_partition_key(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            update_read_statistics.as_ref(),
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    pub async fn get_by_partition_and_row_prefix(
+        &self,
+        partition_key: &str,
+        row_key_prefix: &str,
+    ) -> Result<Vec<TEntity>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+        let result = super::execution::get_by_partition_and_row_prefix(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            row_key_prefix,
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
     }
 
     pub async fn get_enum_case_models_by_partition_key<
@@ -98,11 +438,13 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
     ) -> Result<Option<Vec<TResult>>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
         let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::get_enum_case_models_by_partition_key(
+        let result = super::execution::get_enum_case_models_by_partition_key(
             fl_url,
+            &self.fl_url_factory.options,
             update_read_statistics.as_ref(),
         )
-        .await
+        .await;
+        self.fl_url_factory.track_result(result)
     }
 
     pub async fn get_enum_case_model<
@@ -118,16 +460,116 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
     ) -> Result<Option<TResult>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
         let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::get_enum_case_model(fl_url, update_read_statistics.as_ref()).await
+        let result = super::execution::get_enum_case_model(
+            fl_url,
+            &self.fl_url_factory.options,
+            update_read_statistics.as_ref(),
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
     }
 
     pub async fn get_by_row_key(
         &self,
         row_key: &str,
+        skip: Option<i32>,
+        limit: Option<i32>,
     ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
         let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::get_by_row_key(fl_url, row_key).await
+        let result = super::execution::get_by_row_key(
+            fl_url,
+            &self.fl_url_factory.options,
+            row_key,
+            skip,
+            limit,
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    pub async fn get_partitions_metadata(
+        &self,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<PartitionMeta>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+        let result = super::execution::get_partitions_metadata(
+            fl_url,
+            &self.fl_url_factory.options,
+            skip,
+            limit,
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    pub async fn get_partition_size(
+        &self,
+        partition_key: &str,
+    ) -> Result<Option<usize>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+        let result = super::execution::get_partition_size(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    pub async fn aggregate_field(
+        &self,
+        partition_key: Option<&str>,
+        field: &str,
+        op: AggOp,
+    ) -> Result<f64, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+        let result = super::execution::aggregate_field::<TEntity>(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            field,
+            op,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result)?;
+
+        if let Some(result) = result {
+            return Ok(result);
+        }
+
+        let entities = match partition_key {
+            Some(partition_key) => self.get_by_partition_key(partition_key, None).await?,
+            None => self.get_all().await?,
+        };
+
+        let entities = entities.unwrap_or_default();
+        Ok(super::aggregation::aggregate_entities(
+            entities.iter(),
+            field,
+            op,
+        ))
+    }
+
+    // Partitions are picked uniformly, then rows are sampled uniformly across the picked partitions
+    pub async fn get_random_sample(&self, n: usize) -> Result<Vec<TEntity>, DataWriterError> {
+        let partition_keys = self.get_partition_keys(None, None).await?;
+
+        let mut sample = super::random_sample::RandomSample::new(n);
+
+        for partition_key in super::random_sample::pick_random_partitions(partition_keys, n) {
+            if let Some(entities) = self.get_by_partition_key(&partition_key, None).await? {
+                for entity in entities {
+                    sample.add(entity);
+                }
+            }
+        }
+
+        Ok(sample.into_vec())
     }
 
     pub async fn delete_enum_case<
@@ -142,7 +584,15 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
     ) -> Result<Option<TResult>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
         let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::delete_enum_case(fl_url).await
+        let result = super::execution::delete_enum_case(fl_url, &self.fl_url_factory.options).await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(
+            result,
+            WriteNotification::RowDeleted {
+                partition_key: TResult::PARTITION_KEY,
+                row_key: TResult::ROW_KEY,
+            },
+        )
     }
 
     pub async fn delete_enum_case_with_row_key<
@@ -158,7 +608,20 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
     ) -> Result<Option<TResult>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
         let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::delete_enum_case_with_row_key(fl_url, row_key).await
+        let result = super::execution::delete_enum_case_with_row_key(
+            fl_url,
+            &self.fl_url_factory.options,
+            row_key,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(
+            result,
+            WriteNotification::RowDeleted {
+                partition_key: TResult::PARTITION_KEY,
+                row_key,
+            },
+        )
     }
 
     pub async fn delete_row(
@@ -168,28 +631,162 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
     ) -> Result<Option<TEntity>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
         let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::delete_row(fl_url, partition_key, row_key).await
+        let result = super::execution::delete_row(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            row_key,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(
+            result,
+            WriteNotification::RowDeleted {
+                partition_key,
+                row_key,
+            },
+        )
+    }
+
+    // Returns RecordIsChanged error if the field of the row does not have the expected value
+    pub async fn delete_row_if(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        field_name: &str,
+        expected_value: &str,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+        let result = super::execution::delete_row_if(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            row_key,
+            field_name,
+            expected_value,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(
+            result,
+            WriteNotification::RowDeleted {
+                partition_key,
+                row_key,
+            },
+        )
     }
 
     pub async fn delete_partitions(&self, partition_keys: &[&str]) -> Result<(), DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
         let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::delete_partitions(fl_url, TEntity::TABLE_NAME, partition_keys).await
+        let result = super::execution::delete_partitions(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_keys,
+        )
+        .await;
+        let result = self.fl_url_factory.track_result(result);
+        self.notify_on_success(result, WriteNotification::PartitionsDeleted(partition_keys))
+    }
+
+    // Updates last read time of the rows to keep them away from GC
+    pub async fn touch_rows(
+        &self,
+        partition_key: &str,
+        row_keys: &[&str],
+    ) -> Result<(), DataWriterError> {
+        if row_keys.len() == 0 {
+            return Ok(());
+        }
+
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+        let result = super::execution::touch_rows::<TEntity>(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            row_keys,
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    // Updates last read time of the partition with one request. Partition is kept away from GC.
+    // false if there is no such partition
+    pub async fn touch_partition(&self, partition_key: &str) -> Result<bool, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+        let result =
+            super::execution::touch_partition(fl_url, &self.fl_url_factory.options, partition_key)
+                .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    // Expiration moment of the whole partition. None clears it.
+    // false if there is no such partition
+    pub async fn set_partition_expiration(
+        &self,
+        partition_key: &str,
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<bool, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+        let result = super::execution::set_partition_expiration(
+            fl_url,
+            &self.fl_url_factory.options,
+            partition_key,
+            expires,
+        )
+        .await;
+        self.fl_url_factory.track_result(result)
     }
 
     pub async fn get_all(&self) -> Result<Option<Vec<TEntity>>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
         let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::get_all(fl_url).await
+        let result = super::execution::get_all(fl_url, &self.fl_url_factory.options).await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    // Buffer is cleared and filled with the entities of the table. Buffer stays empty if there is no table
+    pub async fn get_all_into(&self, buf: &mut Vec<TEntity>) -> Result<(), DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+        let result =
+            super::execution::get_all_into(fl_url, &self.fl_url_factory.options, buf).await;
+        self.fl_url_factory.track_result(result)?;
+        Ok(())
+    }
+
+    pub async fn get_all_reuse(
+        &self,
+        mut buf: Vec<TEntity>,
+    ) -> Result<Vec<TEntity>, DataWriterError> {
+        self.get_all_into(&mut buf).await?;
+        Ok(buf)
     }
 
     pub async fn clean_table_and_bulk_insert(
         &self,
         entities: &[TEntity],
     ) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::clean_table_and_bulk_insert(fl_url, entities, &self.sync_period).await
+        let result: Result<(), DataWriterError> = async {
+            let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+            let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+            let result = super::execution::clean_table_and_bulk_insert(
+                fl_url,
+                &self.fl_url_factory.options,
+                entities,
+                &self.sync_period,
+            )
+            .await;
+            self.fl_url_factory.track_result(result)
+        }
+        .await;
+
+        let result = self.dead_letter_if_failed(entities, result).await;
+        self.notify_on_success(result, WriteNotification::TableReplaced(entities))
     }
 
     pub async fn clean_partition_and_bulk_insert(
@@ -197,23 +794,276 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
         partition_key: &str,
         entities: &[TEntity],
     ) -> Result<(), DataWriterError> {
+        let result: Result<(), DataWriterError> = async {
+            let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+            let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
+            let result = super::execution::clean_partition_and_bulk_insert(
+                fl_url,
+                &self.fl_url_factory.options,
+                partition_key,
+                entities,
+                &self.sync_period,
+            )
+            .await;
+            self.fl_url_factory.track_result(result)
+        }
+        .await;
+
+        let result = self.dead_letter_if_failed(entities, result).await;
+        self.notify_on_success(
+            result,
+            WriteNotification::PartitionReplaced {
+                partition_key,
+                entities,
+            },
+        )
+    }
+
+    pub async fn get_partition_keys(
+        &self,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
+        let result =
+            super::execution::get_partition_keys(fl_url, &self.fl_url_factory.options, skip, limit)
+                .await;
+        self.fl_url_factory.track_result(result)
+    }
+
+    pub async fn get_row_keys(
+        &self,
+        partition_key: &str,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError> {
         let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
         let fl_url = fl_url.with_retries(self.max_attempts, self.attempt_delay);
-        super::execution::clean_partition_and_bulk_insert(
+        let result = super::execution::get_row_keys(
             fl_url,
+            &self.fl_url_factory.options,
             partition_key,
-            entities,
-            &self.sync_period,
+            skip,
+            limit,
         )
-        .await
+        .await;
+        self.fl_url_factory.track_result(result)
     }
+}
 
-    pub async fn get_partition_keys(
+#[async_trait::async_trait]
+impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static>
+    MyNoSqlWriter<TEntity> for MyNoSqlDataWriterWithRetries<TEntity>
+{
+    async fn insert_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        self.insert_entity(entity).await
+    }
+
+    async fn insert_or_replace_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        self.insert_or_replace_entity(entity).await
+    }
+
+    async fn bulk_insert_or_replace(&self, entities: &[TEntity]) -> Result<(), DataWriterError> {
+        self.bulk_insert_or_replace(entities).await
+    }
+
+    async fn get_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        self.get_entity(partition_key, row_key, update_read_statistics)
+            .await
+    }
+
+    async fn get_by_partition_key(
+        &self,
+        partition_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        self.get_by_partition_key(partition_key, update_read_statistics)
+            .await
+    }
+
+    async fn get_by_row_key(
+        &self,
+        row_key: &str,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        self.get_by_row_key(row_key, skip, limit).await
+    }
+
+    async fn get_all(&self) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        self.get_all().await
+    }
+
+    async fn get_partition_keys(
         &self,
         skip: Option<i32>,
         limit: Option<i32>,
     ) -> Result<Vec<String>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_partition_keys(fl_url, TEntity::TABLE_NAME, skip, limit).await
+        self.get_partition_keys(skip, limit).await
+    }
+
+    async fn get_row_keys(
+        &self,
+        partition_key: &str,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError> {
+        self.get_row_keys(partition_key, skip, limit).await
+    }
+
+    async fn delete_row(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        self.delete_row(partition_key, row_key).await
+    }
+
+    async fn delete_partitions(&self, partition_keys: &[&str]) -> Result<(), DataWriterError> {
+        self.delete_partitions(partition_keys).await
+    }
+
+    async fn clean_table_and_bulk_insert(
+        &self,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        self.clean_table_and_bulk_insert(entities).await
+    }
+
+    async fn clean_partition_and_bulk_insert(
+        &self,
+        partition_key: &str,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        self.clean_partition_and_bulk_insert(partition_key, entities)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    use my_no_sql_abstractions::DataSynchronizationPeriod;
+
+    use crate::{
+        test_fixtures::{TestEntity, UnreachableSettings},
+        DataWriterError, DeadLetterSink, MyNoSqlDataWriter, MyNoSqlWriterSettings,
+        WriteNotification,
+    };
+
+    struct TestSettings;
+
+    #[async_trait::async_trait]
+    impl MyNoSqlWriterSettings for TestSettings {
+        async fn get_url(&self) -> String {
+            "http://127.0.0.1:5123".to_string()
+        }
+    }
+
+    #[test]
+    fn test_write_notification_is_carried_to_writer_with_retries() {
+        let notified = Arc::new(AtomicUsize::new(0));
+        let counter = notified.clone();
+
+        let writer = MyNoSqlDataWriter::<TestEntity>::new(
+            Arc::new(TestSettings),
+            None,
+            DataSynchronizationPeriod::Sec1,
+        )
+        .on_successful_write(Arc::new(move |_: WriteNotification<TestEntity>| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }))
+        .with_retries(Duration::from_millis(10), 3);
+
+        let entity = TestEntity::default();
+
+        let _ = writer.notify_on_success(Ok(()), WriteNotification::Upserted(&entity));
+        let _ = writer.notify_on_success(
+            Err::<(), _>(DataWriterError::Error("fail".to_string())),
+            WriteNotification::Upserted(&entity),
+        );
+
+        assert_eq!(notified.load(Ordering::SeqCst), 1);
+    }
+
+    #[derive(Default)]
+    struct RowKeysSink {
+        row_keys: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl DeadLetterSink<TestEntity> for RowKeysSink {
+        async fn dead_letter(&self, entity: &TestEntity, _err: &DataWriterError) {
+            self.row_keys.lock().unwrap().push(entity.row_key.clone());
+        }
+    }
+
+    fn create_entity(row_key: &str) -> TestEntity {
+        TestEntity {
+            partition_key: "pk".to_string(),
+            row_key: row_key.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_failed_writes_are_dead_lettered() {
+        let sink = Arc::new(RowKeysSink::default());
+
+        let writer = MyNoSqlDataWriter::<TestEntity>::new(
+            Arc::new(TestSettings),
+            None,
+            DataSynchronizationPeriod::Sec1,
+        )
+        .with_retries(Duration::from_millis(10), 3)
+        .with_dead_letter(sink.clone());
+
+        let entities = [create_entity("rk1"), create_entity("rk2")];
+
+        let _ = writer.dead_letter_if_failed(&entities, Ok(())).await;
+        let _ = writer
+            .dead_letter_if_failed(
+                &entities,
+                Err(DataWriterError::RecordAlreadyExists("rk1".to_string())),
+            )
+            .await;
+        assert!(sink.row_keys.lock().unwrap().is_empty());
+
+        let result = writer
+            .dead_letter_if_failed(&entities, Err(DataWriterError::Error("fail".to_string())))
+            .await;
+        assert!(result.is_err());
+
+        assert_eq!(*sink.row_keys.lock().unwrap(), vec!["rk1", "rk2"]);
+    }
+
+    #[tokio::test]
+    async fn test_entity_is_dead_lettered_after_all_the_attempts() {
+        let sink = Arc::new(RowKeysSink::default());
+
+        let writer = MyNoSqlDataWriter::<TestEntity>::new(
+            Arc::new(UnreachableSettings),
+            None,
+            DataSynchronizationPeriod::Immediately,
+        )
+        .with_retries(Duration::from_millis(1), 2)
+        .with_dead_letter(sink.clone());
+
+        let result = writer.insert_or_replace_entity(&create_entity("rk1")).await;
+        assert!(result.is_err());
+
+        assert_eq!(*sink.row_keys.lock().unwrap(), vec!["rk1"]);
     }
 }
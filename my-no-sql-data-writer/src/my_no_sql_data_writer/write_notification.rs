@@ -0,0 +1,22 @@
+use std::sync::Arc;
+
+pub type WriteNotificationCallback<TEntity> =
+    Arc<dyn Fn(WriteNotification<'_, TEntity>) + Send + Sync + 'static>;
+
+// Write which is confirmed by the server. Keys are the ones given to the writer,
+// before KeyCase is applied
+pub enum WriteNotification<'s, TEntity> {
+    Upserted(&'s TEntity),
+    BulkUpserted(&'s [TEntity]),
+    RawUpserted(&'s [u8]),
+    RowDeleted {
+        partition_key: &'s str,
+        row_key: &'s str,
+    },
+    PartitionsDeleted(&'s [&'s str]),
+    TableReplaced(&'s [TEntity]),
+    PartitionReplaced {
+        partition_key: &'s str,
+        entities: &'s [TEntity],
+    },
+}
@@ -0,0 +1,12 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteResult {
+    Changed,
+    // Stored row already had the same content, nothing was written
+    Unchanged,
+}
+
+impl WriteResult {
+    pub fn is_changed(&self) -> bool {
+        *self == WriteResult::Changed
+    }
+}
@@ -0,0 +1,27 @@
+use super::{BulkCompression, KeyCase, RequestInterceptorRef};
+
+// Options of the writer every request is made with. Set up by the builders of the writer
+#[derive(Clone)]
+pub struct WriterOptions {
+    pub table_name: String,
+    pub key_case: KeyCase,
+    pub max_entity_bytes: Option<usize>,
+    pub client_time_stamp: bool,
+    pub strict_table_presence: bool,
+    pub request_interceptor: Option<RequestInterceptorRef>,
+    pub bulk_compression: Option<BulkCompression>,
+}
+
+impl WriterOptions {
+    pub fn new(table_name: String) -> Self {
+        Self {
+            table_name,
+            key_case: KeyCase::AsIs,
+            max_entity_bytes: None,
+            client_time_stamp: false,
+            strict_table_presence: false,
+            request_interceptor: None,
+            bulk_compression: None,
+        }
+    }
+}
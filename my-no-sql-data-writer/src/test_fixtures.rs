@@ -0,0 +1,148 @@
+use std::sync::{Arc, Mutex};
+
+use flurl::{FlUrl, FlUrlResponse};
+use my_no_sql_abstractions::{DataSynchronizationPeriod, MyNoSqlEntity, MyNoSqlEntitySerializer};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use crate::{MyNoSqlDataWriter, MyNoSqlWriterSettings, RequestInterceptor};
+
+// Entity shared by the unit tests of the crate
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TestEntity {
+    pub partition_key: String,
+    pub row_key: String,
+    #[serde(default)]
+    pub value: i32,
+}
+
+impl MyNoSqlEntity for TestEntity {
+    const TABLE_NAME: &'static str = "test";
+    const LAZY_DESERIALIZATION: bool = false;
+
+    fn get_partition_key(&self) -> &str {
+        &self.partition_key
+    }
+
+    fn get_row_key(&self) -> &str {
+        &self.row_key
+    }
+
+    fn get_time_stamp(&self) -> i64 {
+        0
+    }
+}
+
+impl MyNoSqlEntitySerializer for TestEntity {
+    fn serialize_entity(&self) -> Vec<u8> {
+        my_no_sql_core::entity_serializer::serialize(self)
+    }
+
+    fn deserialize_entity(src: &[u8]) -> Result<Self, String> {
+        my_no_sql_core::entity_serializer::deserialize(src)
+    }
+}
+
+// Nobody listens on the port, so every request fails with connection refused
+pub struct UnreachableSettings;
+
+#[async_trait::async_trait]
+impl MyNoSqlWriterSettings for UnreachableSettings {
+    async fn get_url(&self) -> String {
+        "http://127.0.0.1:1".to_string()
+    }
+}
+
+// Keeps the urls of the requests the writer is about to send
+#[derive(Default)]
+pub struct UrlsInterceptor {
+    pub urls: Mutex<Vec<String>>,
+}
+
+impl UrlsInterceptor {
+    pub fn get_urls(&self) -> Vec<String> {
+        self.urls.lock().unwrap().clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl RequestInterceptor for UrlsInterceptor {
+    async fn before(&self, req: &mut FlUrl) {
+        self.urls.lock().unwrap().push(req.url.as_str().to_string());
+    }
+
+    async fn after(&self, _resp: &FlUrlResponse) {}
+}
+
+// Writer which records the requests it makes. None of them reaches a server
+pub fn create_intercepted_writer(
+    table_name: &str,
+) -> (MyNoSqlDataWriter<TestEntity>, Arc<UrlsInterceptor>) {
+    let interceptor = Arc::new(UrlsInterceptor::default());
+
+    let writer = MyNoSqlDataWriter::new_with_table_name(
+        Arc::new(UnreachableSettings),
+        table_name.to_string(),
+        None,
+        DataSynchronizationPeriod::Immediately,
+    )
+    .with_request_interceptor(interceptor.clone());
+
+    (writer, interceptor)
+}
+
+pub struct TestSettings(pub String);
+
+#[async_trait::async_trait]
+impl MyNoSqlWriterSettings for TestSettings {
+    async fn get_url(&self) -> String {
+        self.0.clone()
+    }
+}
+
+// Server which answers every request with the same status code and body
+pub async fn start_http_server(status_code: u16, body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+
+            // Requests of the tests have no body, so the headers are the whole request
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                let read = socket.read(&mut buffer).await.unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..read]);
+            }
+
+            let response = format!(
+                "HTTP/1.1 {} Status\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status_code,
+                body.len(),
+                body
+            );
+
+            let _ = socket.write_all(response.as_bytes()).await;
+            let _ = socket.shutdown().await;
+        }
+    });
+
+    url
+}
+
+pub fn create_writer(url: String) -> MyNoSqlDataWriter<TestEntity> {
+    MyNoSqlDataWriter::new(
+        Arc::new(TestSettings(url)),
+        None,
+        DataSynchronizationPeriod::Immediately,
+    )
+}
@@ -115,3 +115,16 @@ pub fn get_fn_standard_serialize_deserialize() -> proc_macro2::TokenStream {
         }
     }
 }
+
+pub fn get_fn_simd_json_serialize_deserialize() -> proc_macro2::TokenStream {
+    quote::quote! {
+        fn serialize_entity(&self) -> Vec<u8> {
+            my_no_sql_sdk::core::entity_serializer::serialize_with::<my_no_sql_sdk::core::entity_serializer::SimdJsonBackend, _>(self)
+        }
+
+
+        fn deserialize_entity(src: &[u8]) -> Result<Self, String> {
+          my_no_sql_sdk::core::entity_serializer::deserialize_with::<my_no_sql_sdk::core::entity_serializer::SimdJsonBackend, _>(src)
+        }
+    }
+}
@@ -11,6 +11,10 @@ mod my_no_sql_entity;
 struct MyNoSqlEntityParameters<'s> {
     #[default]
     pub table_name: &'s str,
+
+    // Entity is serialized with simd-json. Requires simd-json feature of my-no-sql-sdk
+    #[has_attribute]
+    pub simd_json: bool,
 }
 
 #[proc_macro_attribute]
@@ -15,7 +15,7 @@ pub fn generate(
 
     let params = MyNoSqlEntityParameters::try_from(&attr)?;
 
-    let result = super::generate_base_impl(&ast, params.table_name)?;
+    let result = super::generate_base_impl(&ast, params.table_name, params.simd_json)?;
 
     Ok(result.into())
 }
@@ -3,12 +3,17 @@ use crate::entity_utils::*;
 pub fn generate_base_impl(
     ast: &proc_macro2::TokenStream,
     table_name: &str,
+    simd_json: bool,
 ) -> Result<proc_macro2::TokenStream, syn::Error> {
     let (struct_name, new_struct) = compile_struct_with_new_fields(ast, true, true, true);
 
     let fn_get_time_stamp = get_fn_get_time_stamp_token();
 
-    let fn_serialize_deserialize = get_fn_standard_serialize_deserialize();
+    let fn_serialize_deserialize = if simd_json {
+        get_fn_simd_json_serialize_deserialize()
+    } else {
+        get_fn_standard_serialize_deserialize()
+    };
 
     let result = quote::quote! {
 
@@ -0,0 +1,102 @@
+use std::{
+    sync::atomic::{AtomicI64, Ordering},
+    time::Duration,
+};
+
+use rust_extensions::date_time::{AtomicDateTimeAsMicroseconds, DateTimeAsMicroseconds};
+
+// Tracks the moment the connection to the server was seen alive for the last time.
+// Every incoming packet counts, pong to the ping included, so a quiet table stays fresh
+// as long as the connection is alive
+pub struct ConnectionLiveness {
+    // Increased on every new connection. 0 - never connected
+    connection_id: AtomicI64,
+    last_alive: AtomicDateTimeAsMicroseconds,
+}
+
+impl ConnectionLiveness {
+    pub fn new() -> Self {
+        Self {
+            connection_id: AtomicI64::new(0),
+            last_alive: AtomicDateTimeAsMicroseconds::new(0),
+        }
+    }
+
+    pub fn connected(&self) {
+        self.last_alive.update(DateTimeAsMicroseconds::now());
+        self.connection_id.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn packet_received(&self) {
+        self.last_alive.update(DateTimeAsMicroseconds::now());
+    }
+
+    pub fn get_connection_id(&self) -> i64 {
+        self.connection_id.load(Ordering::SeqCst)
+    }
+
+    // After disconnect the moment stays where the last packet came
+    pub fn get_last_alive_moment(&self) -> Option<DateTimeAsMicroseconds> {
+        let result = self.last_alive.as_date_time();
+
+        if result.unix_microseconds == 0 {
+            return None;
+        }
+
+        Some(result)
+    }
+
+    pub fn is_stale(&self, max_staleness: Duration) -> bool {
+        match self.get_last_alive_moment() {
+            Some(last_alive) => {
+                DateTimeAsMicroseconds::now().unix_microseconds - last_alive.unix_microseconds
+                    > max_staleness.as_micros() as i64
+            }
+            None => true,
+        }
+    }
+}
+
+impl Default for ConnectionLiveness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::ConnectionLiveness;
+
+    #[test]
+    fn test_never_connected_is_stale() {
+        let liveness = ConnectionLiveness::new();
+
+        assert!(liveness.is_stale(Duration::from_secs(60)));
+        assert_eq!(liveness.get_connection_id(), 0);
+    }
+
+    #[test]
+    fn test_packets_keep_connection_fresh() {
+        let liveness = ConnectionLiveness::new();
+        liveness.connected();
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(liveness.is_stale(Duration::from_millis(10)));
+
+        liveness.packet_received();
+        assert!(!liveness.is_stale(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_every_connection_gets_new_id() {
+        let liveness = ConnectionLiveness::new();
+
+        liveness.connected();
+        let first = liveness.get_connection_id();
+        liveness.connected();
+
+        assert!(liveness.get_connection_id() > first);
+    }
+}
@@ -2,25 +2,31 @@ use std::{collections::BTreeMap, sync::Arc};
 
 use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
 
-use crate::subscribers::{LazyMyNoSqlEntity, MyNoSqlDataReaderCallBacksPusher};
+use crate::subscribers::{LazyMyNoSqlEntity, MemoryFootprint, MyNoSqlDataReaderCallBacksPusher};
 
 pub struct DataReaderEntitiesSet<
     TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static,
 > {
     entities: Option<BTreeMap<String, BTreeMap<String, LazyMyNoSqlEntity<TMyNoSqlEntity>>>>,
-    table_name: &'static str,
+    table_name: String,
+    footprint: MemoryFootprint,
 }
 
 impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static>
     DataReaderEntitiesSet<TMyNoSqlEntity>
 {
-    pub fn new(table_name: &'static str) -> Self {
+    pub fn new(table_name: String) -> Self {
         Self {
             entities: None,
             table_name,
+            footprint: MemoryFootprint::default(),
         }
     }
 
+    pub fn get_footprint(&self) -> MemoryFootprint {
+        self.footprint
+    }
+
     pub fn is_initialized(&self) -> bool {
         self.entities.is_some()
     }
@@ -56,16 +62,26 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'st
         let mut new_table: BTreeMap<String, BTreeMap<String, LazyMyNoSqlEntity<TMyNoSqlEntity>>> =
             BTreeMap::new();
 
+        let mut footprint = MemoryFootprint::default();
+
         for (partition_key, src_entities_by_partition) in data {
+            footprint.add_partition(partition_key.as_str());
             new_table.insert(partition_key.to_string(), BTreeMap::new());
 
             let by_partition = new_table.get_mut(partition_key.as_str()).unwrap();
 
             for entity in src_entities_by_partition {
-                by_partition.insert(entity.get_row_key().to_string(), entity);
+                let row_key = entity.get_row_key().to_string();
+                footprint.add_entity::<TMyNoSqlEntity>(row_key.as_str());
+
+                if let Some(replaced) = by_partition.insert(row_key, entity) {
+                    footprint.remove_entity::<TMyNoSqlEntity>(replaced.get_row_key());
+                }
             }
         }
 
+        self.footprint = footprint;
+
         let table_before = self.entities.replace(new_table);
 
         InitTableResult {
@@ -91,6 +107,21 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'st
             }
         }
 
+        if let Some(before_partition) = before_partition.as_ref() {
+            self.footprint.remove_partition(partition_key);
+
+            for row_key in before_partition.keys() {
+                self.footprint.remove_entity::<TMyNoSqlEntity>(row_key);
+            }
+        }
+
+        self.footprint.add_partition(partition_key);
+
+        for row_key in new_partition.keys() {
+            self.footprint.add_entity::<TMyNoSqlEntity>(row_key);
+        }
+
+        let entities = self.entities.as_mut().unwrap();
         entities.insert(partition_key.to_string(), new_partition);
 
         InitPartitionResult {
@@ -104,6 +135,7 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'st
         src_data: BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
         callbacks: &Option<Arc<MyNoSqlDataReaderCallBacksPusher<TMyNoSqlEntity>>>,
     ) {
+        let mut footprint = self.footprint;
         let entities = self.init_and_get_table();
 
         for (partition_key, src_entities) in src_data {
@@ -115,6 +147,7 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'st
 
             if !entities.contains_key(partition_key.as_str()) {
                 entities.insert(partition_key.to_string(), BTreeMap::new());
+                footprint.add_partition(partition_key.as_str());
             }
 
             let by_partition = entities.get_mut(partition_key.as_str()).unwrap();
@@ -123,7 +156,13 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'st
                 if let Some(updates) = updates.as_mut() {
                     updates.push(entity.clone());
                 }
-                by_partition.insert(entity.get_row_key().to_string(), entity);
+
+                let row_key = entity.get_row_key().to_string();
+                footprint.add_entity::<TMyNoSqlEntity>(row_key.as_str());
+
+                if let Some(replaced) = by_partition.insert(row_key, entity) {
+                    footprint.remove_entity::<TMyNoSqlEntity>(replaced.get_row_key());
+                }
             }
 
             if let Some(callbacks) = callbacks {
@@ -134,6 +173,8 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'st
                 }
             }
         }
+
+        self.footprint = footprint;
     }
 
     pub fn delete_rows(
@@ -141,33 +182,63 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'st
         rows_to_delete: Vec<my_no_sql_tcp_shared::DeleteRowTcpContract>,
         callbacks: &Option<Arc<MyNoSqlDataReaderCallBacksPusher<TMyNoSqlEntity>>>,
     ) {
-        let mut deleted_rows = if callbacks.is_some() {
+        let removed_rows = self.remove_rows(rows_to_delete, callbacks.is_some());
+
+        if let Some(callbacks) = callbacks.as_ref() {
+            if let Some(partitions) = removed_rows {
+                for (partition_key, rows) in partitions {
+                    callbacks.deleted(partition_key.as_str(), rows);
+                }
+            }
+        }
+    }
+
+    pub fn expire_rows(
+        &mut self,
+        rows_to_expire: Vec<my_no_sql_tcp_shared::DeleteRowTcpContract>,
+        callbacks: &Option<Arc<MyNoSqlDataReaderCallBacksPusher<TMyNoSqlEntity>>>,
+    ) {
+        let removed_rows = self.remove_rows(rows_to_expire, callbacks.is_some());
+
+        if let Some(callbacks) = callbacks.as_ref() {
+            if let Some(partitions) = removed_rows {
+                for (partition_key, rows) in partitions {
+                    callbacks.expired(partition_key.as_str(), rows);
+                }
+            }
+        }
+    }
+
+    fn remove_rows(
+        &mut self,
+        rows_to_remove: Vec<my_no_sql_tcp_shared::DeleteRowTcpContract>,
+        collect_removed: bool,
+    ) -> Option<BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>>> {
+        let mut removed_rows = if collect_removed {
             Some(BTreeMap::new())
         } else {
             None
         };
 
+        let mut footprint = self.footprint;
         let entities = self.init_and_get_table();
 
-        for row_to_delete in &rows_to_delete {
+        for row_to_remove in &rows_to_remove {
             let mut delete_partition = false;
-            if let Some(partition) = entities.get_mut(row_to_delete.partition_key.as_str()) {
-                if partition.remove(row_to_delete.row_key.as_str()).is_some() {
-                    if let Some(deleted_rows) = deleted_rows.as_mut() {
-                        if !deleted_rows.contains_key(row_to_delete.partition_key.as_str()) {
-                            deleted_rows
-                                .insert(row_to_delete.partition_key.to_string(), Vec::new());
+            if let Some(partition) = entities.get_mut(row_to_remove.partition_key.as_str()) {
+                if let Some(removed_row) = partition.remove(row_to_remove.row_key.as_str()) {
+                    footprint.remove_entity::<TMyNoSqlEntity>(row_to_remove.row_key.as_str());
+
+                    if let Some(removed_rows) = removed_rows.as_mut() {
+                        if !removed_rows.contains_key(row_to_remove.partition_key.as_str()) {
+                            removed_rows
+                                .insert(row_to_remove.partition_key.to_string(), Vec::new());
                         }
 
-                        deleted_rows
-                            .get_mut(row_to_delete.partition_key.as_str())
+                        removed_rows
+                            .get_mut(row_to_remove.partition_key.as_str())
                             .unwrap()
-                            .push(
-                                partition
-                                    .get(row_to_delete.row_key.as_str())
-                                    .unwrap()
-                                    .clone(),
-                            );
+                            .push(removed_row);
                     }
                 }
 
@@ -175,17 +246,35 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'st
             }
 
             if delete_partition {
-                entities.remove(row_to_delete.partition_key.as_str());
+                entities.remove(row_to_remove.partition_key.as_str());
+                footprint.remove_partition(row_to_remove.partition_key.as_str());
             }
         }
 
-        if let Some(callbacks) = callbacks.as_ref() {
-            if let Some(partitions) = deleted_rows {
-                for (partition_key, rows) in partitions {
-                    callbacks.deleted(partition_key.as_str(), rows);
+        self.footprint = footprint;
+
+        removed_rows
+    }
+
+    pub fn get_table_as_json_array(&self) -> Option<Vec<u8>> {
+        let entities = self.entities.as_ref()?;
+
+        let mut result = Vec::new();
+        result.push(b'[');
+
+        for partition in entities.values() {
+            for entity in partition.values() {
+                if result.len() > 1 {
+                    result.push(b',');
                 }
+
+                entity.write_json(&mut result);
             }
         }
+
+        result.push(b']');
+
+        Some(result)
     }
 
     pub fn get_partition_keys(&self) -> Vec<String> {
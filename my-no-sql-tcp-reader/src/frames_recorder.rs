@@ -0,0 +1,128 @@
+use std::{
+    io::{Read, Write},
+    path::Path,
+};
+
+use my_no_sql_tcp_shared::MyNoSqlTcpContract;
+use my_tcp_sockets::socket_reader::SocketReaderInMem;
+
+// Frames are stored in the wire format, each one prefixed with its length as u32 LE
+pub struct FramesRecorder {
+    file: std::sync::Mutex<Option<std::fs::File>>,
+}
+
+impl FramesRecorder {
+    pub fn new() -> Self {
+        Self {
+            file: std::sync::Mutex::new(None),
+        }
+    }
+
+    pub fn start(&self, path: &Path) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        *self.file.lock().unwrap() = Some(file);
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        *self.file.lock().unwrap() = None;
+    }
+
+    pub fn record(&self, contract: &MyNoSqlTcpContract) {
+        let mut file = self.file.lock().unwrap();
+
+        let Some(file) = file.as_mut() else {
+            return;
+        };
+
+        if let Err(err) = write_frame(file, contract) {
+            my_logger::LOGGER.write_error(
+                "FramesRecorder".to_string(),
+                format!("Can not record frame: {}", err),
+                None.into(),
+            );
+        }
+    }
+}
+
+pub fn write_frame(out: &mut impl Write, contract: &MyNoSqlTcpContract) -> std::io::Result<()> {
+    let mut payload = Vec::new();
+    contract.serialize(&mut payload);
+    out.write_all(&(payload.len() as u32).to_le_bytes())?;
+    out.write_all(&payload)?;
+    out.flush()
+}
+
+pub async fn read_frames(path: &Path) -> std::io::Result<Vec<MyNoSqlTcpContract>> {
+    let mut content = Vec::new();
+    std::fs::File::open(path)?.read_to_end(&mut content)?;
+
+    let mut result = Vec::new();
+    let mut pos = 0;
+
+    while pos < content.len() {
+        if pos + 4 > content.len() {
+            return Err(invalid_data("Truncated frame length"));
+        }
+
+        let len = u32::from_le_bytes(content[pos..pos + 4].try_into().unwrap()) as usize;
+        pos += 4;
+
+        if pos + len > content.len() {
+            return Err(invalid_data("Truncated frame payload"));
+        }
+
+        let mut reader = SocketReaderInMem::new(content[pos..pos + len].to_vec());
+        pos += len;
+
+        let contract = MyNoSqlTcpContract::deserialize(&mut reader)
+            .await
+            .map_err(|err| invalid_data(format!("Can not deserialize frame: {:?}", err)))?;
+
+        result.push(contract);
+    }
+
+    Ok(result)
+}
+
+fn invalid_data(message: impl Into<String>) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use my_no_sql_tcp_shared::MyNoSqlTcpContract;
+
+    #[tokio::test]
+    async fn test_recorded_frames_are_read_back() {
+        let path = std::env::temp_dir().join("my-no-sql-frames-recorder-test.bin");
+
+        let recorder = super::FramesRecorder::new();
+        recorder.start(&path).unwrap();
+
+        recorder.record(&MyNoSqlTcpContract::Ping);
+        recorder.record(&MyNoSqlTcpContract::UpdateRows {
+            table_name: "test-table".to_string(),
+            data: b"[{\"PartitionKey\":\"pk\",\"RowKey\":\"rk\"}]".to_vec(),
+        });
+
+        recorder.stop();
+
+        let frames = super::read_frames(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(frames.len(), 2);
+        assert!(matches!(frames[0], MyNoSqlTcpContract::Ping));
+
+        match &frames[1] {
+            MyNoSqlTcpContract::UpdateRows { table_name, data } => {
+                assert_eq!(table_name, "test-table");
+                assert_eq!(
+                    data.as_slice(),
+                    b"[{\"PartitionKey\":\"pk\",\"RowKey\":\"rk\"}]"
+                );
+            }
+            other => panic!("Unexpected frame {:?}", other),
+        }
+    }
+}
@@ -1,15 +1,24 @@
+mod connection_liveness;
 mod data_reader_entities_set;
+#[cfg(feature = "debug_frames")]
+mod frames_recorder;
+mod multi_reader_builder;
 mod my_no_sql_tcp_connection;
 mod settings;
 mod subscribers;
 mod tcp_events;
+#[cfg(test)]
+mod test_fixtures;
+pub use connection_liveness::ConnectionLiveness;
 pub use data_reader_entities_set::*;
+pub use multi_reader_builder::*;
 
 pub use my_no_sql_tcp_connection::MyNoSqlTcpConnection;
 pub use settings::*;
 pub use subscribers::{
-    LazyMyNoSqlEntity, MyNoSqlDataReader, MyNoSqlDataReaderCallBacks, MyNoSqlDataReaderData,
-    MyNoSqlDataReaderTcp,
+    ChangeBatch, Generation, LazyMyNoSqlEntity, MemoryFootprint, MemoryLimitCallback,
+    MyNoSqlDataReader, MyNoSqlDataReaderCallBacks, MyNoSqlDataReaderData, MyNoSqlDataReaderTcp,
+    PartitionsSnapshot, RowChange,
 };
 
 #[cfg(feature = "mocks")]
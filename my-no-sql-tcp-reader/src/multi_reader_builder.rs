@@ -0,0 +1,170 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::Arc,
+};
+
+use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+
+use crate::{MyNoSqlDataReaderTcp, MyNoSqlTcpConnection};
+
+type ReaderKey = (TypeId, String);
+
+// Registers readers of several tables and starts the connection only after all of them are subscribed
+pub struct MultiReaderBuilder<'s> {
+    connection: &'s MyNoSqlTcpConnection,
+    readers: HashMap<ReaderKey, Arc<dyn Any + Send + Sync + 'static>>,
+}
+
+impl<'s> MultiReaderBuilder<'s> {
+    pub fn new(connection: &'s MyNoSqlTcpConnection) -> Self {
+        Self {
+            connection,
+            readers: HashMap::new(),
+        }
+    }
+
+    pub async fn add<
+        TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
+    >(
+        self,
+    ) -> Self {
+        self.add_with_table_name::<TMyNoSqlEntity>(TMyNoSqlEntity::TABLE_NAME.to_string())
+            .await
+    }
+
+    // Several tables of the same entity type can be registered, one per table name
+    pub async fn add_with_table_name<
+        TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
+    >(
+        mut self,
+        table_name: String,
+    ) -> Self {
+        let reader = self
+            .connection
+            .get_reader_with_table_name::<TMyNoSqlEntity>(table_name.clone())
+            .await;
+        self.readers
+            .insert((TypeId::of::<TMyNoSqlEntity>(), table_name), reader);
+        self
+    }
+
+    pub async fn start(self) -> MultiReaders {
+        self.connection.start().await;
+        self.into_readers()
+    }
+
+    fn into_readers(self) -> MultiReaders {
+        MultiReaders {
+            readers: self.readers,
+        }
+    }
+}
+
+pub struct MultiReaders {
+    readers: HashMap<ReaderKey, Arc<dyn Any + Send + Sync + 'static>>,
+}
+
+impl MultiReaders {
+    pub fn try_get<
+        TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
+    >(
+        &self,
+    ) -> Option<Arc<MyNoSqlDataReaderTcp<TMyNoSqlEntity>>> {
+        self.try_get_by_table_name(TMyNoSqlEntity::TABLE_NAME)
+    }
+
+    pub fn get<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static>(
+        &self,
+    ) -> Arc<MyNoSqlDataReaderTcp<TMyNoSqlEntity>> {
+        self.get_by_table_name(TMyNoSqlEntity::TABLE_NAME)
+    }
+
+    // Looks up a reader registered with add_with_table_name
+    pub fn try_get_by_table_name<
+        TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
+    >(
+        &self,
+        table_name: &str,
+    ) -> Option<Arc<MyNoSqlDataReaderTcp<TMyNoSqlEntity>>> {
+        let key = (TypeId::of::<TMyNoSqlEntity>(), table_name.to_string());
+        let reader = self.readers.get(&key)?;
+        reader
+            .clone()
+            .downcast::<MyNoSqlDataReaderTcp<TMyNoSqlEntity>>()
+            .ok()
+    }
+
+    pub fn get_by_table_name<
+        TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
+    >(
+        &self,
+        table_name: &str,
+    ) -> Arc<MyNoSqlDataReaderTcp<TMyNoSqlEntity>> {
+        match self.try_get_by_table_name(table_name) {
+            Some(reader) => reader,
+            None => panic!("Reader for the table {} is not registered", table_name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{test_fixtures::TestRow, MyNoSqlTcpConnection, MyNoSqlTcpConnectionSettings};
+
+    struct TestSettings;
+
+    #[async_trait::async_trait]
+    impl MyNoSqlTcpConnectionSettings for TestSettings {
+        async fn get_host_port(&self) -> String {
+            "127.0.0.1:5125".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_readers_of_the_same_type_are_kept_per_table() {
+        let connection = MyNoSqlTcpConnection::new("test", Arc::new(TestSettings));
+
+        let readers = connection
+            .get_readers()
+            .add_with_table_name::<TestRow>("tenant-a".to_string())
+            .await
+            .add_with_table_name::<TestRow>("tenant-b".to_string())
+            .await
+            .into_readers();
+
+        let tenant_a = readers.get_by_table_name::<TestRow>("tenant-a");
+        let tenant_b = readers.get_by_table_name::<TestRow>("tenant-b");
+
+        assert_eq!(tenant_a.get_table_name(), "tenant-a");
+        assert_eq!(tenant_b.get_table_name(), "tenant-b");
+
+        assert!(readers.try_get::<TestRow>().is_none());
+        assert!(readers
+            .try_get_by_table_name::<TestRow>("tenant-c")
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_readers_are_subscribed_before_start() {
+        let connection = MyNoSqlTcpConnection::new("test", Arc::new(TestSettings));
+
+        let readers = connection
+            .get_readers()
+            .add::<TestRow>()
+            .await
+            .into_readers();
+
+        assert_eq!(readers.get::<TestRow>().get_table_name(), "test");
+        assert_eq!(
+            connection
+                .tcp_events
+                .subscribers
+                .get_tables_to_subscribe()
+                .await,
+            vec!["test".to_string()]
+        );
+    }
+}
@@ -1,12 +1,15 @@
 use std::{sync::Arc, time::Duration};
 
 use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
-use my_no_sql_tcp_shared::{sync_to_main::SyncToMainNodeHandler, MyNoSqlTcpSerializerFactory};
+use my_no_sql_tcp_shared::{
+    sync_to_main::SyncToMainNodeHandler, MyNoSqlTcpContract, MyNoSqlTcpSerializerFactory,
+};
 use my_tcp_sockets::TcpClient;
 use rust_extensions::{AppStates, StrOrString};
 
 use crate::{
-    subscribers::MyNoSqlDataReaderTcp, tcp_events::TcpEvents, MyNoSqlTcpConnectionSettings,
+    subscribers::MyNoSqlDataReaderTcp, tcp_events::TcpEvents, MultiReaderBuilder,
+    MyNoSqlTcpConnectionSettings,
 };
 
 pub struct TcpConnectionSettings {
@@ -24,6 +27,7 @@ pub struct MyNoSqlTcpConnection {
     tcp_client: TcpClient,
     pub ping_timeout: Duration,
     pub connect_timeout: Duration,
+    pub stop_timeout: Duration,
     pub tcp_events: Arc<TcpEvents>,
     app_states: Arc<AppStates>,
 }
@@ -41,6 +45,7 @@ impl MyNoSqlTcpConnection {
             tcp_client: TcpClient::new("MyNoSqlClient".to_string(), Arc::new(settings)),
             ping_timeout: Duration::from_secs(3),
             connect_timeout: Duration::from_secs(3),
+            stop_timeout: Duration::from_secs(3),
             tcp_events: Arc::new(TcpEvents::new(
                 app_name.to_string(),
                 Arc::new(SyncToMainNodeHandler::new(my_logger::LOGGER.clone())),
@@ -53,16 +58,60 @@ impl MyNoSqlTcpConnection {
         TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
     >(
         &self,
+    ) -> Arc<MyNoSqlDataReaderTcp<TMyNoSqlEntity>> {
+        self.get_reader_with_table_name::<TMyNoSqlEntity>(TMyNoSqlEntity::TABLE_NAME.to_string())
+            .await
+    }
+
+    // Subscribes to the table with the given name instead of TMyNoSqlEntity::TABLE_NAME.
+    // Useful to read a table per tenant
+    pub async fn get_reader_with_table_name<
+        TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
+    >(
+        &self,
+        table_name: String,
     ) -> Arc<MyNoSqlDataReaderTcp<TMyNoSqlEntity>> {
         self.tcp_events
             .subscribers
             .create_subscriber(
+                table_name,
                 self.app_states.clone(),
                 self.tcp_events.sync_handler.clone(),
+                self.tcp_events.liveness.clone(),
+                None,
             )
             .await
     }
 
+    // Reader keeps only the listed fields plus PartitionKey, RowKey, TimeStamp and Expires.
+    // Fields of the entity which are not projected must be optional or have defaults
+    pub async fn get_reader_projected<
+        TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
+    >(
+        &self,
+        fields: &[&'static str],
+    ) -> Arc<MyNoSqlDataReaderTcp<TMyNoSqlEntity>> {
+        self.tcp_events
+            .subscribers
+            .create_subscriber(
+                TMyNoSqlEntity::TABLE_NAME.to_string(),
+                self.app_states.clone(),
+                self.tcp_events.sync_handler.clone(),
+                self.tcp_events.liveness.clone(),
+                Some(fields.to_vec()),
+            )
+            .await
+    }
+
+    // Amount of the statistics updates which are waiting to be sent to the main node
+    pub async fn get_sync_to_main_queue_depth(&self) -> usize {
+        self.tcp_events.sync_handler.get_queue_depth().await
+    }
+
+    pub fn get_readers(&self) -> MultiReaderBuilder {
+        MultiReaderBuilder::new(self)
+    }
+
     pub async fn start(&self) {
         self.app_states.set_initialized();
 
@@ -79,4 +128,169 @@ impl MyNoSqlTcpConnection {
             .start(self.app_states.clone())
             .await;
     }
+
+    // Dumps every incoming frame into the file until stop_recording_frames is called
+    #[cfg(feature = "debug_frames")]
+    pub fn record_frames(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        self.tcp_events.frames_recorder.start(path.as_ref())
+    }
+
+    #[cfg(feature = "debug_frames")]
+    pub fn stop_recording_frames(&self) {
+        self.tcp_events.frames_recorder.stop();
+    }
+
+    // Applies recorded frames to the readers of this connection. Meant to be used on a connection
+    // which is not started. Returns amount of replayed frames
+    #[cfg(feature = "debug_frames")]
+    pub async fn replay_frames(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<usize> {
+        let frames = crate::frames_recorder::read_frames(path.as_ref()).await?;
+        let amount = frames.len();
+
+        for frame in frames {
+            self.tcp_events.apply_contract(frame).await;
+        }
+
+        Ok(amount)
+    }
+
+    // Unsubscribes from all the tables, closes the connection and stops background tasks.
+    // Returns false if the connection was not closed within stop_timeout
+    pub async fn stop(&self) -> bool {
+        self.app_states.set_shutting_down();
+
+        let connection = self.tcp_events.get_connection().await;
+
+        if let Some(connection) = &connection {
+            for table_name in self.tcp_events.subscribers.get_tables_to_subscribe().await {
+                connection
+                    .send(&MyNoSqlTcpContract::Unsubscribe(table_name))
+                    .await;
+            }
+        }
+
+        // Client is stopped before the connection is closed, so it does not reconnect
+        self.tcp_client.stop().await;
+
+        if let Some(connection) = connection {
+            connection.disconnect().await;
+        }
+
+        self.tcp_events.sync_handler.stop().await;
+
+        let started = tokio::time::Instant::now();
+
+        while self.tcp_events.get_connection().await.is_some() {
+            if started.elapsed() >= self.stop_timeout {
+                return false;
+            }
+
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use my_no_sql_tcp_shared::MyNoSqlTcpContract;
+    use my_tcp_sockets::socket_reader::SocketReaderInMem;
+    use tokio::{io::AsyncReadExt, net::TcpListener};
+
+    use crate::{test_fixtures::TestRow, MyNoSqlTcpConnectionSettings};
+
+    use super::MyNoSqlTcpConnection;
+
+    struct TestSettings(String);
+
+    #[async_trait::async_trait]
+    impl MyNoSqlTcpConnectionSettings for TestSettings {
+        async fn get_host_port(&self) -> String {
+            self.0.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stop_without_connection() {
+        let connection =
+            MyNoSqlTcpConnection::new("test", Arc::new(TestSettings("127.0.0.1:5126".into())));
+
+        assert!(connection.stop().await);
+        assert!(connection.app_states.is_shutting_down());
+    }
+
+    #[tokio::test]
+    async fn test_reader_is_subscribed_to_the_overridden_table() {
+        let connection =
+            MyNoSqlTcpConnection::new("test", Arc::new(TestSettings("127.0.0.1:5126".into())));
+
+        let reader = connection
+            .get_reader_with_table_name::<TestRow>("tenant-a".to_string())
+            .await;
+
+        assert_eq!(reader.get_table_name(), "tenant-a");
+        assert_eq!(
+            connection
+                .tcp_events
+                .subscribers
+                .get_tables_to_subscribe()
+                .await,
+            vec!["tenant-a".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stop_closes_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let host_port = listener.local_addr().unwrap().to_string();
+
+        let connection = MyNoSqlTcpConnection::new("test", Arc::new(TestSettings(host_port)));
+        connection.get_reader::<TestRow>().await;
+        connection.start().await;
+
+        let (mut server_socket, _) =
+            tokio::time::timeout(Duration::from_secs(5), listener.accept())
+                .await
+                .unwrap()
+                .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while connection.tcp_events.get_connection().await.is_none() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        assert!(connection.stop().await);
+        assert!(connection.tcp_events.get_connection().await.is_none());
+
+        // Client side is closed, so the server reads up to the end of the stream
+        let mut received = Vec::new();
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            server_socket.read_to_end(&mut received),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let mut reader = SocketReaderInMem::new(received);
+        let mut unsubscribed = Vec::new();
+
+        while let Ok(contract) = MyNoSqlTcpContract::deserialize(&mut reader).await {
+            if let MyNoSqlTcpContract::Unsubscribe(table_name) = contract {
+                unsubscribed.push(table_name);
+            }
+        }
+
+        assert_eq!(unsubscribed, vec!["test".to_string()]);
+
+        // Stopped client does not come back
+        let reconnect = tokio::time::timeout(Duration::from_secs(5), listener.accept()).await;
+        assert!(reconnect.is_err());
+    }
 }
@@ -0,0 +1,231 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+
+use super::LazyMyNoSqlEntity;
+
+pub enum RowChange<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static>
+{
+    Upserted(Arc<TMyNoSqlEntity>),
+    Deleted,
+}
+
+impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static>
+    RowChange<TMyNoSqlEntity>
+{
+    pub fn is_deleted(&self) -> bool {
+        matches!(self, Self::Deleted)
+    }
+
+    pub fn get_entity(&self) -> Option<&Arc<TMyNoSqlEntity>> {
+        match self {
+            Self::Upserted(entity) => Some(entity),
+            Self::Deleted => None,
+        }
+    }
+}
+
+// Last change of every row which was changed since the previous drain. Expired rows are reported as deleted
+pub struct ChangeBatch<
+    TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static,
+> {
+    pub partitions: BTreeMap<String, BTreeMap<String, RowChange<TMyNoSqlEntity>>>,
+}
+
+impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static>
+    ChangeBatch<TMyNoSqlEntity>
+{
+    pub fn is_empty(&self) -> bool {
+        self.partitions.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.partitions
+            .values()
+            .map(|partition| partition.len())
+            .sum()
+    }
+
+    pub fn get(&self, partition_key: &str, row_key: &str) -> Option<&RowChange<TMyNoSqlEntity>> {
+        self.partitions.get(partition_key)?.get(row_key)
+    }
+}
+
+enum BufferedChange<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static>
+{
+    Upserted(LazyMyNoSqlEntity<TMyNoSqlEntity>),
+    Deleted,
+}
+
+// Changes are coalesced by row, so the buffer never holds more items than the amount of the changed rows.
+// Entities are kept as they came and are deserialized on drain
+pub struct ChangeBuffer<
+    TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static,
+> {
+    changes: BTreeMap<String, BTreeMap<String, BufferedChange<TMyNoSqlEntity>>>,
+}
+
+impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static>
+    ChangeBuffer<TMyNoSqlEntity>
+{
+    pub fn new() -> Self {
+        Self {
+            changes: BTreeMap::new(),
+        }
+    }
+
+    fn set(&mut self, partition_key: &str, row_key: &str, change: BufferedChange<TMyNoSqlEntity>) {
+        match self.changes.get_mut(partition_key) {
+            Some(partition) => {
+                partition.insert(row_key.to_string(), change);
+            }
+            None => {
+                let mut partition = BTreeMap::new();
+                partition.insert(row_key.to_string(), change);
+                self.changes.insert(partition_key.to_string(), partition);
+            }
+        }
+    }
+
+    pub fn upserted(&mut self, partition_key: &str, entity: &LazyMyNoSqlEntity<TMyNoSqlEntity>) {
+        self.set(
+            partition_key,
+            entity.get_row_key(),
+            BufferedChange::Upserted(entity.clone()),
+        );
+    }
+
+    pub fn deleted(&mut self, partition_key: &str, row_key: &str) {
+        self.set(partition_key, row_key, BufferedChange::Deleted);
+    }
+
+    // Rows which are in the partition now are upserted, rows which are gone are deleted
+    pub fn partition_replaced(
+        &mut self,
+        partition_key: &str,
+        before: Option<&BTreeMap<String, LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
+        now: Option<&BTreeMap<String, LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
+    ) {
+        if let Some(before) = before {
+            for row_key in before.keys() {
+                let is_gone = match now {
+                    Some(now) => !now.contains_key(row_key),
+                    None => true,
+                };
+
+                if is_gone {
+                    self.deleted(partition_key, row_key);
+                }
+            }
+        }
+
+        if let Some(now) = now {
+            for entity in now.values() {
+                self.upserted(partition_key, entity);
+            }
+        }
+    }
+
+    pub fn table_replaced(
+        &mut self,
+        before: Option<&BTreeMap<String, BTreeMap<String, LazyMyNoSqlEntity<TMyNoSqlEntity>>>>,
+        now: &BTreeMap<String, BTreeMap<String, LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
+    ) {
+        if let Some(before) = before {
+            for (partition_key, before_partition) in before {
+                if !now.contains_key(partition_key) {
+                    self.partition_replaced(partition_key, Some(before_partition), None);
+                }
+            }
+        }
+
+        for (partition_key, now_partition) in now {
+            let before_partition = before.and_then(|before| before.get(partition_key));
+            self.partition_replaced(partition_key, before_partition, Some(now_partition));
+        }
+    }
+
+    pub fn drain(&mut self) -> ChangeBatch<TMyNoSqlEntity> {
+        let mut partitions = BTreeMap::new();
+
+        for (partition_key, changes) in std::mem::take(&mut self.changes) {
+            let mut partition = BTreeMap::new();
+
+            for (row_key, change) in changes {
+                let change = match change {
+                    BufferedChange::Upserted(mut entity) => {
+                        RowChange::Upserted(entity.get().clone())
+                    }
+                    BufferedChange::Deleted => RowChange::Deleted,
+                };
+
+                partition.insert(row_key, change);
+            }
+
+            partitions.insert(partition_key, partition);
+        }
+
+        ChangeBatch { partitions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use super::ChangeBuffer;
+    use crate::subscribers::LazyMyNoSqlEntity;
+    use crate::test_fixtures::TestRow;
+
+    fn row(partition_key: &str, row_key: &str, value: u64) -> LazyMyNoSqlEntity<TestRow> {
+        LazyMyNoSqlEntity::Deserialized(Arc::new(TestRow {
+            partition_key: partition_key.to_string(),
+            row_key: row_key.to_string(),
+            value,
+            ..Default::default()
+        }))
+    }
+
+    #[test]
+    fn test_changes_are_coalesced_and_drained() {
+        let mut buffer = ChangeBuffer::new();
+
+        buffer.upserted("pk", &row("pk", "rk1", 1));
+        buffer.upserted("pk", &row("pk", "rk1", 2));
+        buffer.upserted("pk", &row("pk", "rk2", 1));
+        buffer.deleted("pk", "rk2");
+
+        let batch = buffer.drain();
+
+        assert_eq!(batch.len(), 2);
+        assert_eq!(
+            batch.get("pk", "rk1").unwrap().get_entity().unwrap().value,
+            2
+        );
+        assert!(batch.get("pk", "rk2").unwrap().is_deleted());
+
+        assert!(buffer.drain().is_empty());
+    }
+
+    #[test]
+    fn test_partition_replaced() {
+        let mut before = BTreeMap::new();
+        before.insert("rk1".to_string(), row("pk", "rk1", 1));
+        before.insert("rk2".to_string(), row("pk", "rk2", 1));
+
+        let mut now = BTreeMap::new();
+        now.insert("rk2".to_string(), row("pk", "rk2", 2));
+
+        let mut buffer = ChangeBuffer::new();
+        buffer.partition_replaced("pk", Some(&before), Some(&now));
+
+        let batch = buffer.drain();
+
+        assert_eq!(batch.len(), 2);
+        assert!(batch.get("pk", "rk1").unwrap().is_deleted());
+        assert_eq!(
+            batch.get("pk", "rk2").unwrap().get_entity().unwrap().value,
+            2
+        );
+    }
+}
@@ -2,6 +2,7 @@ use std::{fmt::Debug, sync::Arc};
 
 use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
 use my_no_sql_core::db_json_entity::DbJsonEntity;
+use rust_extensions::date_time::DateTimeAsMicroseconds;
 
 pub struct EntityRawData {
     pub db_json_entity: DbJsonEntity,
@@ -48,6 +49,38 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'st
         }
     }
 
+    pub fn get_time_stamp(&self) -> Option<DateTimeAsMicroseconds> {
+        match self {
+            LazyMyNoSqlEntity::Deserialized(entity) => {
+                Some(DateTimeAsMicroseconds::new(entity.get_time_stamp()))
+            }
+            LazyMyNoSqlEntity::Raw(src) => {
+                let time_stamp = src.db_json_entity.get_time_stamp(&src.data)?;
+                DateTimeAsMicroseconds::parse_iso_string(time_stamp)
+            }
+        }
+    }
+
+    pub fn write_json(&self, out: &mut Vec<u8>) {
+        match self {
+            LazyMyNoSqlEntity::Deserialized(entity) => {
+                out.extend_from_slice(entity.serialize_entity().as_slice())
+            }
+            LazyMyNoSqlEntity::Raw(src) => out.extend_from_slice(src.data.as_slice()),
+        }
+    }
+
+    // Raw row is parsed as it is stored, so fields which are not modeled by the entity are kept.
+    // Deserialized row has only the fields of the entity
+    pub fn to_json_value(&self) -> Option<serde_json::Value> {
+        match self {
+            LazyMyNoSqlEntity::Deserialized(entity) => {
+                serde_json::from_slice(entity.serialize_entity().as_slice()).ok()
+            }
+            LazyMyNoSqlEntity::Raw(src) => serde_json::from_slice(src.data.as_slice()).ok(),
+        }
+    }
+
     pub fn clone(&self) -> Self {
         match self {
             LazyMyNoSqlEntity::Deserialized(entity) => {
@@ -85,3 +118,40 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Debug + Send + Sy
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use my_no_sql_core::db_json_entity::DbJsonEntity;
+
+    use crate::test_fixtures::TestRow;
+
+    use super::{EntityRawData, LazyMyNoSqlEntity};
+
+    const SRC: &str = r#"{"PartitionKey":"pk","RowKey":"rk","value":1,"Unmodeled":"x"}"#;
+
+    #[test]
+    fn test_raw_json_value_keeps_unmodeled_fields() {
+        let data = SRC.as_bytes().to_vec();
+        let db_json_entity = DbJsonEntity::from_slice(&data).unwrap();
+
+        let entity: LazyMyNoSqlEntity<TestRow> = LazyMyNoSqlEntity::Raw(Arc::new(EntityRawData {
+            db_json_entity,
+            data,
+        }));
+
+        let value = entity.to_json_value().unwrap();
+        assert_eq!(value["Unmodeled"], "x");
+    }
+
+    #[test]
+    fn test_deserialized_json_value_has_fields_of_entity() {
+        let entity: LazyMyNoSqlEntity<TestRow> =
+            serde_json::from_str::<TestRow>(SRC).unwrap().into();
+
+        let value = entity.to_json_value().unwrap();
+        assert_eq!(value["value"], 1);
+        assert!(value.get("Unmodeled").is_none());
+    }
+}
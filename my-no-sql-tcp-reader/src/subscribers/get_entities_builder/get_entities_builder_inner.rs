@@ -50,7 +50,7 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'st
         self.inner
             .get_sync_handler()
             .update(
-                TMyNoSqlEntity::TABLE_NAME,
+                self.inner.get_table_name(),
                 &self.partition_key,
                 || db_rows.iter().map(|itm| itm.get_row_key()),
                 &self.update_statistic_data,
@@ -72,7 +72,7 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'st
         self.inner
             .get_sync_handler()
             .update(
-                TMyNoSqlEntity::TABLE_NAME,
+                self.inner.get_table_name(),
                 &self.partition_key,
                 || db_rows.iter().map(|itm| itm.get_row_key()),
                 &self.update_statistic_data,
@@ -91,7 +91,7 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'st
         self.inner
             .get_sync_handler()
             .update(
-                TMyNoSqlEntity::TABLE_NAME,
+                self.inner.get_table_name(),
                 &self.partition_key,
                 || db_rows.values().map(|itm| itm.get_row_key()),
                 &self.update_statistic_data,
@@ -113,7 +113,7 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'st
         self.inner
             .get_sync_handler()
             .update(
-                TMyNoSqlEntity::TABLE_NAME,
+                self.inner.get_table_name(),
                 &self.partition_key,
                 || db_rows.values().map(|itm| itm.get_row_key()),
                 &self.update_statistic_data,
@@ -58,7 +58,7 @@ impl<'s, TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send +
             self.inner
                 .get_sync_handler()
                 .update(
-                    TMyNoSqlEntity::TABLE_NAME,
+                    self.inner.get_table_name(),
                     self.partition_key,
                     || [self.row_key].into_iter(),
                     &self.update_statistic_data,
@@ -0,0 +1,132 @@
+use std::{collections::HashMap, sync::Arc};
+
+use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+
+// Index of deserialized entities by (PartitionKey, RowKey) for O(1) point lookups.
+// Keys are nested, so a lookup does not allocate the tuple key and a partition can be dropped at once
+pub struct HashIndex<
+    TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static,
+> {
+    entities: HashMap<String, HashMap<String, Arc<TMyNoSqlEntity>>>,
+}
+
+impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static>
+    HashIndex<TMyNoSqlEntity>
+{
+    pub fn new() -> Self {
+        Self {
+            entities: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, entity: &Arc<TMyNoSqlEntity>) {
+        self.entities
+            .entry(entity.get_partition_key().to_string())
+            .or_default()
+            .insert(entity.get_row_key().to_string(), entity.clone());
+    }
+
+    pub fn remove(&mut self, partition_key: &str, row_key: &str) {
+        let Some(partition) = self.entities.get_mut(partition_key) else {
+            return;
+        };
+
+        partition.remove(row_key);
+
+        if partition.is_empty() {
+            self.entities.remove(partition_key);
+        }
+    }
+
+    pub fn remove_partition(&mut self, partition_key: &str) {
+        self.entities.remove(partition_key);
+    }
+
+    pub fn clear(&mut self) {
+        self.entities.clear();
+    }
+
+    pub fn get(&self, partition_key: &str, row_key: &str) -> Option<Arc<TMyNoSqlEntity>> {
+        self.entities.get(partition_key)?.get(row_key).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc, time::Instant};
+
+    use super::HashIndex;
+    use crate::test_fixtures::TestRow;
+
+    fn row(partition_key: &str, row_key: &str) -> Arc<TestRow> {
+        Arc::new(TestRow {
+            partition_key: partition_key.to_string(),
+            row_key: row_key.to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut index = HashIndex::new();
+
+        index.insert(&row("pk1", "rk1"));
+        index.insert(&row("pk1", "rk2"));
+        index.insert(&row("pk2", "rk1"));
+
+        assert_eq!(index.get("pk1", "rk2").unwrap().row_key, "rk2");
+
+        index.remove("pk1", "rk2");
+        assert!(index.get("pk1", "rk2").is_none());
+        assert!(index.get("pk1", "rk1").is_some());
+
+        index.remove_partition("pk1");
+        assert!(index.get("pk1", "rk1").is_none());
+        assert!(index.get("pk2", "rk1").is_some());
+    }
+
+    // cargo test --release -p my-no-sql-tcp-reader bench_lookups -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_lookups() {
+        const PARTITIONS: usize = 1_000;
+        const ROWS: usize = 1_000;
+        const LOOKUPS: usize = 1_000_000;
+
+        let mut index = HashIndex::new();
+        let mut sorted: BTreeMap<String, BTreeMap<String, Arc<TestRow>>> = BTreeMap::new();
+
+        for p in 0..PARTITIONS {
+            for r in 0..ROWS {
+                let entity = row(&format!("pk{}", p), &format!("rk{}", r));
+                index.insert(&entity);
+                sorted
+                    .entry(entity.partition_key.clone())
+                    .or_default()
+                    .insert(entity.row_key.clone(), entity);
+            }
+        }
+
+        let keys: Vec<(String, String)> = (0..LOOKUPS)
+            .map(|i| {
+                (
+                    format!("pk{}", (i * 7919) % PARTITIONS),
+                    format!("rk{}", (i * 104729) % ROWS),
+                )
+            })
+            .collect();
+
+        let started = Instant::now();
+        for (partition_key, row_key) in keys.iter() {
+            let partition = sorted.get(partition_key).unwrap();
+            std::hint::black_box(partition.get(row_key).unwrap());
+        }
+        println!("Sorted lookups: {:?}", started.elapsed());
+
+        let started = Instant::now();
+        for (partition_key, row_key) in keys.iter() {
+            std::hint::black_box(index.get(partition_key, row_key).unwrap());
+        }
+        println!("Hash index lookups: {:?}", started.elapsed());
+    }
+}
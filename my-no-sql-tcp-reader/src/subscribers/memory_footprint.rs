@@ -0,0 +1,205 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+
+use super::LazyMyNoSqlEntity;
+
+pub type MemoryLimitCallback = Arc<dyn Fn(MemoryFootprint) + Send + Sync + 'static>;
+
+// Estimation of the memory the reader keeps. It is tracked as rows are added and removed.
+// Every entity is charged by its keys and the size of the entity struct whether it is deserialized yet or not,
+// so the charge does not change when the entity is deserialized on read. Heap data of the entity is not included
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryFootprint {
+    pub partitions: usize,
+    pub entities: usize,
+    pub bytes: usize,
+}
+
+impl MemoryFootprint {
+    // Goes through all the entities. Reader does not use it, the footprint is tracked on every change
+    pub fn calculate<
+        TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static,
+    >(
+        entities: &BTreeMap<String, BTreeMap<String, LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
+    ) -> Self {
+        let mut result = Self::default();
+
+        for (partition_key, partition) in entities {
+            result.add_partition(partition_key);
+
+            for row_key in partition.keys() {
+                result.add_entity::<TMyNoSqlEntity>(row_key);
+            }
+        }
+
+        result
+    }
+
+    pub fn add_partition(&mut self, partition_key: &str) {
+        self.partitions += 1;
+        self.bytes += partition_key.len();
+    }
+
+    pub fn remove_partition(&mut self, partition_key: &str) {
+        self.partitions = self.partitions.saturating_sub(1);
+        self.bytes = self.bytes.saturating_sub(partition_key.len());
+    }
+
+    pub fn add_entity<TMyNoSqlEntity>(&mut self, row_key: &str) {
+        self.entities += 1;
+        self.bytes += row_key.len() + std::mem::size_of::<TMyNoSqlEntity>();
+    }
+
+    pub fn remove_entity<TMyNoSqlEntity>(&mut self, row_key: &str) {
+        self.entities = self.entities.saturating_sub(1);
+        self.bytes = self
+            .bytes
+            .saturating_sub(row_key.len() + std::mem::size_of::<TMyNoSqlEntity>());
+    }
+}
+
+// Callback is invoked by the reader after the lock of the data is released
+pub struct MemoryLimitExceeded {
+    on_exceed: MemoryLimitCallback,
+    footprint: MemoryFootprint,
+}
+
+impl MemoryLimitExceeded {
+    pub fn notify(self) {
+        (self.on_exceed)(self.footprint);
+    }
+}
+
+pub struct MemoryLimit {
+    limit: usize,
+    on_exceed: MemoryLimitCallback,
+    stop_updates: bool,
+    exceeded: bool,
+}
+
+impl MemoryLimit {
+    pub fn new(limit: usize, on_exceed: MemoryLimitCallback, stop_updates: bool) -> Self {
+        Self {
+            limit,
+            on_exceed,
+            stop_updates,
+            exceeded: false,
+        }
+    }
+
+    pub fn updates_are_stopped(&self) -> bool {
+        self.stop_updates && self.exceeded
+    }
+
+    // Callback is invoked once when the limit is crossed. If footprint goes below the limit
+    // it is going to be invoked again on the next crossing
+    pub fn check(&mut self, footprint: MemoryFootprint) -> Option<MemoryLimitExceeded> {
+        if footprint.bytes <= self.limit {
+            self.exceeded = false;
+            return None;
+        }
+
+        if self.exceeded {
+            return None;
+        }
+
+        self.exceeded = true;
+
+        Some(MemoryLimitExceeded {
+            on_exceed: self.on_exceed.clone(),
+            footprint,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use super::{MemoryFootprint, MemoryLimit};
+    use crate::subscribers::LazyMyNoSqlEntity;
+    use crate::test_fixtures::TestRow;
+
+    fn create_table(rows: usize) -> BTreeMap<String, BTreeMap<String, LazyMyNoSqlEntity<TestRow>>> {
+        let mut partition = BTreeMap::new();
+
+        for i in 0..rows {
+            let entity = TestRow {
+                partition_key: "pk".to_string(),
+                row_key: i.to_string(),
+                ..Default::default()
+            };
+            partition.insert(i.to_string(), entity.into());
+        }
+
+        let mut result = BTreeMap::new();
+        result.insert("pk".to_string(), partition);
+        result
+    }
+
+    #[test]
+    fn test_calculate() {
+        let footprint = MemoryFootprint::calculate(&create_table(3));
+
+        assert_eq!(footprint.partitions, 1);
+        assert_eq!(footprint.entities, 3);
+        assert_eq!(footprint.bytes, 2 + 3 + 3 * std::mem::size_of::<TestRow>());
+    }
+
+    #[test]
+    fn test_callback_is_invoked_once_per_crossing() {
+        let invoked = Arc::new(AtomicUsize::new(0));
+        let invoked_in_callback = invoked.clone();
+
+        let mut limit = MemoryLimit::new(
+            MemoryFootprint::calculate(&create_table(2)).bytes,
+            Arc::new(move |_| {
+                invoked_in_callback.fetch_add(1, Ordering::SeqCst);
+            }),
+            true,
+        );
+
+        let mut check = |rows| {
+            if let Some(exceeded) = limit.check(MemoryFootprint::calculate(&create_table(rows))) {
+                exceeded.notify();
+            }
+        };
+
+        check(2);
+        assert_eq!(invoked.load(Ordering::SeqCst), 0);
+
+        check(3);
+        check(4);
+        assert_eq!(invoked.load(Ordering::SeqCst), 1);
+
+        check(1);
+        check(3);
+        assert_eq!(invoked.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_updates_are_stopped_until_footprint_is_under_the_limit() {
+        let mut limit = MemoryLimit::new(
+            MemoryFootprint::calculate(&create_table(2)).bytes,
+            Arc::new(|_| {}),
+            true,
+        );
+
+        assert!(limit
+            .check(MemoryFootprint::calculate(&create_table(3)))
+            .is_some());
+        assert!(limit.updates_are_stopped());
+
+        assert!(limit
+            .check(MemoryFootprint::calculate(&create_table(1)))
+            .is_none());
+        assert!(!limit.updates_are_stopped());
+    }
+}
@@ -6,10 +6,13 @@ mod my_no_sql_data_reader_callbacks;
 mod my_no_sql_data_reader_callbacks_pusher;
 mod my_no_sql_data_reader_data;
 mod my_no_sql_data_reader_tcp;
+mod projection;
+mod secondary_index;
 mod subscribers;
 mod update_event_trait;
 pub use my_no_sql_data_reader_data::MyNoSqlDataReaderData;
 pub use my_no_sql_data_reader_tcp::MyNoSqlDataReaderTcp;
+pub use secondary_index::*;
 
 pub use get_entities_builder::*;
 pub use get_entity_builder::*;
@@ -26,5 +29,13 @@ pub use my_no_sql_data_reader_mock::*;
 mod my_no_sql_data_reader_mock_inner;
 #[cfg(feature = "mocks")]
 pub use my_no_sql_data_reader_mock_inner::*;
+mod hash_index;
+pub use hash_index::*;
+mod memory_footprint;
+pub use memory_footprint::*;
+mod partitions_snapshot;
+pub use partitions_snapshot::*;
+mod change_batch;
+pub use change_batch::*;
 mod entity_with_lazy_deserialization;
 pub use entity_with_lazy_deserialization::*;
@@ -1,10 +1,13 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
 use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+use rust_extensions::date_time::DateTimeAsMicroseconds;
 
 use crate::MyNoSqlDataReaderCallBacks;
 
-use super::{GetEntitiesBuilder, GetEntityBuilder};
+use super::{ChangeBatch, GetEntitiesBuilder, GetEntityBuilder, MemoryLimitCallback};
+
+const WAIT_FOR_ENTITY_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 #[async_trait::async_trait]
 pub trait MyNoSqlDataReader<
@@ -13,6 +16,19 @@ pub trait MyNoSqlDataReader<
 {
     async fn get_table_snapshot_as_vec(&self) -> Option<Vec<Arc<TMyNoSqlEntity>>>;
 
+    // Default implementation reads partition by partition, so it is not consistent across partitions
+    async fn get_full_snapshot(&self) -> BTreeMap<String, BTreeMap<String, Arc<TMyNoSqlEntity>>> {
+        let mut result = BTreeMap::new();
+
+        for partition_key in self.get_partition_keys().await {
+            if let Some(partition) = self.get_by_partition_key(&partition_key).await {
+                result.insert(partition_key, partition);
+            }
+        }
+
+        result
+    }
+
     async fn get_by_partition_key(
         &self,
         partition_key: &str,
@@ -27,6 +43,44 @@ pub trait MyNoSqlDataReader<
 
     async fn get_entity(&self, partition_key: &str, row_key: &str) -> Option<Arc<TMyNoSqlEntity>>;
 
+    // Existence check which neither deserializes nor clones the entity
+    async fn contains_entity(&self, partition_key: &str, row_key: &str) -> bool {
+        self.get_entity(partition_key, row_key).await.is_some()
+    }
+
+    // Escape hatch to inspect the row without deserializing it into the entity.
+    // Fields the entity does not model are kept only while the row is not deserialized:
+    // deserialized row is serialized back from the entity
+    async fn get_entity_as_json_value(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Option<serde_json::Value> {
+        let entity = self.get_entity(partition_key, row_key).await?;
+        serde_json::from_slice(entity.serialize_entity().as_slice()).ok()
+    }
+
+    // Partition as json array without deserializing the rows. Useful to forward the data.
+    // Default implementation serializes the entities back
+    async fn get_partition_raw(&self, partition_key: &str) -> Option<Vec<u8>> {
+        let entities = self.get_by_partition_key_as_vec(partition_key).await?;
+
+        let mut result = Vec::new();
+        result.push(b'[');
+
+        for (no, entity) in entities.iter().enumerate() {
+            if no > 0 {
+                result.push(b',');
+            }
+
+            result.extend_from_slice(entity.serialize_entity().as_slice());
+        }
+
+        result.push(b']');
+
+        Some(result)
+    }
+
     async fn get_enum_case_model<
         's,
         T: MyNoSqlEntity
@@ -87,6 +141,18 @@ pub trait MyNoSqlDataReader<
         Some(result)
     }
 
+    async fn get_entities_changed_since(
+        &self,
+        since: DateTimeAsMicroseconds,
+    ) -> Vec<Arc<TMyNoSqlEntity>> {
+        let snapshot = self.get_table_snapshot_as_vec().await.unwrap_or_default();
+
+        snapshot
+            .into_iter()
+            .filter(|entity| entity.get_time_stamp() > since.unix_microseconds)
+            .collect()
+    }
+
     fn get_entities<'s>(&self, partition_key: &'s str) -> GetEntitiesBuilder<TMyNoSqlEntity>;
 
     fn get_entity_with_callback_to_server<'s>(
@@ -97,6 +163,96 @@ pub trait MyNoSqlDataReader<
 
     async fn has_partition(&self, partition_key: &str) -> bool;
 
+    // Visits the entities of the partition in RowKey order under one lock without copying them
+    // Default implementation visits a copy of the partition
+    async fn for_each_in_partition<TCallback: FnMut(&str, &Arc<TMyNoSqlEntity>) + Send>(
+        &self,
+        partition_key: &str,
+        mut callback: TCallback,
+    ) {
+        if let Some(partition) = self.get_by_partition_key(partition_key).await {
+            for (row_key, entity) in partition.iter() {
+                callback(row_key, entity);
+            }
+        }
+    }
+
+    // Amount of the entities of the partition matching the predicate. Nothing is cloned
+    async fn count_entities<TPredicate: Fn(&TMyNoSqlEntity) -> bool + Send>(
+        &self,
+        partition_key: &str,
+        predicate: TPredicate,
+    ) -> usize {
+        match self.get_by_partition_key_as_vec(partition_key).await {
+            Some(entities) => entities
+                .iter()
+                .filter(|entity| predicate(entity.as_ref()))
+                .count(),
+            None => 0,
+        }
+    }
+
+    // Populates the reader before or instead of the connection to the server
+    async fn seed<TEntities: Iterator<Item = Arc<TMyNoSqlEntity>> + Send>(
+        &self,
+        entities: TEntities,
+    );
+
+    // Default implementation polls get_entity
+    async fn wait_for_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        timeout: Duration,
+    ) -> Option<Arc<TMyNoSqlEntity>> {
+        let wait = async {
+            loop {
+                if let Some(entity) = self.get_entity(partition_key, row_key).await {
+                    return entity;
+                }
+
+                tokio::time::sleep(WAIT_FOR_ENTITY_POLL_INTERVAL).await;
+            }
+        };
+
+        tokio::time::timeout(timeout, wait).await.ok()
+    }
+
+    // Declares an index by a non-key field which is maintained on every change of the table.
+    // Entities for which extractor returns None are not indexed
+    async fn build_secondary_index<
+        K: ToString + 'static,
+        TExtractor: Fn(&TMyNoSqlEntity) -> Option<K> + Send + Sync + 'static,
+    >(
+        &self,
+        index_name: &str,
+        extractor: TExtractor,
+    );
+
+    async fn get_by_secondary<K: ToString + ?Sized + Sync>(
+        &self,
+        index_name: &str,
+        value: &K,
+    ) -> Vec<Arc<TMyNoSqlEntity>>;
+
+    // Safety valve for the fast growing tables. Checked after each applied batch of updates.
+    // Reader which does not keep the data itself ignores it
+    async fn set_memory_limit(
+        &self,
+        _bytes: usize,
+        _on_exceed: MemoryLimitCallback,
+        _stop_updates: bool,
+    ) {
+    }
+
+    // Makes get_entity O(1) at the cost of keeping every entity deserialized in a hash map.
+    // It is an optimization only, so the reader which does not support it ignores it
+    async fn enable_hash_index(&self) {}
+
+    // Pull based alternative to the callbacks. Gives the last change of every row since the previous call.
+    // Changes are not recorded until the first call, which gives an empty batch
+    async fn drain_changes(&self) -> ChangeBatch<TMyNoSqlEntity>;
+
     async fn wait_until_first_data_arrives(&self);
 
     async fn assign_callback<
@@ -13,6 +13,12 @@ pub trait MyNoSqlDataReaderCallBacks<
         entities: Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>,
     );
     async fn deleted(&self, partition_key: &str, entities: Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>);
+
+    // Rows removed by the master node because of the expiration time.
+    // By default they are reported the same way as deleted ones
+    async fn expired(&self, partition_key: &str, entities: Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>) {
+        self.deleted(partition_key, entities).await;
+    }
 }
 
 #[async_trait::async_trait]
@@ -35,3 +41,57 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'st
         panic!("This is a dumb implementation")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::MyNoSqlDataReaderCallBacks;
+    use crate::{subscribers::LazyMyNoSqlEntity, test_fixtures::TestRow};
+
+    #[derive(Default)]
+    struct DeletedRecorder {
+        deleted: Mutex<Vec<(String, usize)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl MyNoSqlDataReaderCallBacks<TestRow> for DeletedRecorder {
+        async fn inserted_or_replaced(
+            &self,
+            _partition_key: &str,
+            _entities: Vec<LazyMyNoSqlEntity<TestRow>>,
+        ) {
+        }
+
+        async fn deleted(&self, partition_key: &str, entities: Vec<LazyMyNoSqlEntity<TestRow>>) {
+            self.deleted
+                .lock()
+                .unwrap()
+                .push((partition_key.to_string(), entities.len()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_expired_is_reported_as_deleted_by_default() {
+        let callbacks = DeletedRecorder::default();
+
+        callbacks
+            .expired(
+                "pk",
+                vec![LazyMyNoSqlEntity::Deserialized(
+                    TestRow {
+                        partition_key: "pk".to_string(),
+                        row_key: "rk".to_string(),
+                        ..Default::default()
+                    }
+                    .into(),
+                )],
+            )
+            .await;
+
+        assert_eq!(
+            callbacks.deleted.lock().unwrap().as_slice(),
+            &[("pk".to_string(), 1)]
+        );
+    }
+}
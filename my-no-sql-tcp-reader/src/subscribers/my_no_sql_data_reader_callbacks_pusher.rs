@@ -13,6 +13,7 @@ pub enum PusherEvents<
 > {
     InsertedOrReplaced(String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>),
     Deleted(String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>),
+    Expired(String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>),
 }
 
 pub struct MyNoSqlDataReaderCallBacksPusher<TMyNoSqlEntity>
@@ -61,6 +62,15 @@ where
         self.events_loop
             .send(PusherEvents::Deleted(partition_key.to_string(), entities));
     }
+
+    pub fn expired(
+        &self,
+        partition_key: &str,
+        entities: Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>,
+    ) {
+        self.events_loop
+            .send(PusherEvents::Expired(partition_key.to_string(), entities));
+    }
 }
 
 #[async_trait::async_trait]
@@ -88,6 +98,15 @@ where
         self.events_loop
             .send(PusherEvents::Deleted(partition_key.to_string(), entities));
     }
+
+    async fn expired(
+        &self,
+        partition_key: &str,
+        entities: Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>,
+    ) {
+        self.events_loop
+            .send(PusherEvents::Expired(partition_key.to_string(), entities));
+    }
 }
 
 pub struct MyNoSqlDataReaderCallBacksSender<
@@ -128,6 +147,11 @@ impl<
                     .deleted(partition_key.as_str(), entities)
                     .await;
             }
+            PusherEvents::Expired(partition_key, entities) => {
+                self.callbacks
+                    .expired(partition_key.as_str(), entities)
+                    .await;
+            }
         }
         if self.item.is_some() {}
     }
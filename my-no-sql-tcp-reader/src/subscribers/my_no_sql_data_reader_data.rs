@@ -1,11 +1,18 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+};
 
 use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
-use rust_extensions::ApplicationStates;
+use rust_extensions::{date_time::DateTimeAsMicroseconds, ApplicationStates};
 
 use crate::DataReaderEntitiesSet;
 
-use super::{LazyMyNoSqlEntity, MyNoSqlDataReaderCallBacks, MyNoSqlDataReaderCallBacksPusher};
+use super::{
+    ChangeBatch, ChangeBuffer, Generation, HashIndex, LazyMyNoSqlEntity, MemoryFootprint,
+    MemoryLimit, MemoryLimitCallback, MemoryLimitExceeded, MyNoSqlDataReaderCallBacks,
+    MyNoSqlDataReaderCallBacksPusher, SecondaryIndex, SecondaryKeyExtractor,
+};
 
 pub struct MyNoSqlDataReaderData<
     TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static,
@@ -13,6 +20,14 @@ pub struct MyNoSqlDataReaderData<
     entities: DataReaderEntitiesSet<TMyNoSqlEntity>,
     callbacks: Option<Arc<MyNoSqlDataReaderCallBacksPusher<TMyNoSqlEntity>>>,
     app_states: Arc<dyn ApplicationStates + Send + Sync + 'static>,
+    secondary_indexes: HashMap<String, SecondaryIndex<TMyNoSqlEntity>>,
+    memory_limit: Option<MemoryLimit>,
+    memory_limit_exceeded: Option<MemoryLimitExceeded>,
+    hash_index: Option<HashIndex<TMyNoSqlEntity>>,
+    generation: Generation,
+    // Allocated by the first drain_changes, so readers which never drain keep nothing
+    changes: Option<ChangeBuffer<TMyNoSqlEntity>>,
+    seeded: bool,
 }
 
 impl<TMyNoSqlEntity> MyNoSqlDataReaderData<TMyNoSqlEntity>
@@ -20,13 +35,20 @@ where
     TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static,
 {
     pub async fn new(
-        table_name: &'static str,
+        table_name: String,
         app_states: Arc<dyn ApplicationStates + Send + Sync + 'static>,
     ) -> Self {
         Self {
             entities: DataReaderEntitiesSet::new(table_name),
             callbacks: None,
             app_states,
+            secondary_indexes: HashMap::new(),
+            memory_limit: None,
+            memory_limit_exceeded: None,
+            hash_index: None,
+            generation: Generation::default(),
+            changes: None,
+            seeded: false,
         }
     }
 
@@ -42,12 +64,91 @@ where
         self.callbacks = Some(Arc::new(pusher));
     }
 
+    // Footprint is checked after each applied batch. While updates are stopped update_rows and init_partition
+    // are skipped, so the reader misses them. init_table is always applied: it replaces the whole table
+    // and brings the reader back in sync with the server
+    pub fn set_memory_limit(
+        &mut self,
+        bytes: usize,
+        on_exceed: MemoryLimitCallback,
+        stop_updates: bool,
+    ) {
+        self.memory_limit = Some(MemoryLimit::new(bytes, on_exceed, stop_updates));
+        self.check_memory_limit();
+    }
+
+    pub fn get_memory_footprint(&self) -> MemoryFootprint {
+        self.entities.get_footprint()
+    }
+
+    // Callback has to be invoked by the caller after the lock of the data is released
+    pub fn take_memory_limit_exceeded(&mut self) -> Option<MemoryLimitExceeded> {
+        self.memory_limit_exceeded.take()
+    }
+
+    fn updates_are_stopped(&self) -> bool {
+        match &self.memory_limit {
+            Some(memory_limit) => memory_limit.updates_are_stopped(),
+            None => false,
+        }
+    }
+
+    // Is increased by every applied batch. Batch is applied under the lock, so all the data
+    // which is read at the same generation belongs to the same state of the table
+    pub fn get_generation(&self) -> Generation {
+        self.generation
+    }
+
+    fn batch_applied(&mut self) {
+        self.generation = self.generation.next();
+        self.check_memory_limit();
+    }
+
+    fn check_memory_limit(&mut self) {
+        if let Some(memory_limit) = self.memory_limit.as_mut() {
+            if let Some(exceeded) = memory_limit.check(self.entities.get_footprint()) {
+                self.memory_limit_exceeded = Some(exceeded);
+            }
+        }
+    }
+
+    // Seeded rows survive the first init_table unless the server sends the same row.
+    // Later init_table calls replace the table as usual
+    pub fn seed(&mut self, data: BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>>) {
+        self.update_rows(data);
+        self.seeded = true;
+    }
+
     pub async fn init_table(
         &mut self,
-        data: BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
+        mut data: BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
     ) {
+        if self.seeded {
+            self.seeded = false;
+            self.merge_seeded_rows(&mut data);
+        }
+
+        if self.has_indexes() {
+            for index in self.secondary_indexes.values_mut() {
+                index.clear();
+            }
+
+            if let Some(hash_index) = self.hash_index.as_mut() {
+                hash_index.clear();
+            }
+
+            self.index_entities(data.values_mut().flatten());
+        }
+
         let init_table_result = self.entities.init_table(data);
 
+        if let Some(changes) = self.changes.as_mut() {
+            changes.table_replaced(
+                init_table_result.table_before.as_ref(),
+                init_table_result.table_now,
+            );
+        }
+
         if let Some(callbacks) = self.callbacks.as_ref() {
             super::callback_triggers::trigger_table_difference(
                 callbacks.as_ref(),
@@ -56,17 +157,44 @@ where
             )
             .await;
         }
+
+        self.batch_applied();
     }
 
     pub async fn init_partition(
         &mut self,
         partition_key: &str,
-        src_entities: BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
+        mut src_entities: BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
     ) {
+        if self.updates_are_stopped() {
+            return;
+        }
+
         //let callbacks = self.callbacks.clone();
 
+        if self.has_indexes() {
+            for index in self.secondary_indexes.values_mut() {
+                index.remove_partition(partition_key);
+            }
+
+            if let Some(hash_index) = self.hash_index.as_mut() {
+                hash_index.remove_partition(partition_key);
+            }
+
+            self.index_entities(src_entities.values_mut().flatten());
+        }
+
         let init_partition_result = self.entities.init_partition(partition_key, src_entities);
 
+        // partition_now is the partition which was replaced, partition_before is the new one
+        if let Some(changes) = self.changes.as_mut() {
+            changes.partition_replaced(
+                partition_key,
+                init_partition_result.partition_now.as_ref(),
+                Some(init_partition_result.partition_before),
+            );
+        }
+
         if let Some(callbacks) = self.callbacks.as_ref() {
             super::callback_triggers::trigger_partition_difference(
                 callbacks.as_ref(),
@@ -76,23 +204,191 @@ where
             )
             .await;
         }
+
+        self.batch_applied();
     }
 
     pub fn update_rows(
         &mut self,
-        src_data: BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
+        mut src_data: BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
     ) {
+        if self.updates_are_stopped() {
+            return;
+        }
+
+        if self.has_indexes() {
+            self.index_entities(src_data.values_mut().flatten());
+        }
+
+        if let Some(changes) = self.changes.as_mut() {
+            for (partition_key, entities) in src_data.iter() {
+                for entity in entities {
+                    changes.upserted(partition_key, entity);
+                }
+            }
+        }
+
         self.entities.update_rows(src_data, &self.callbacks);
+        self.batch_applied();
     }
 
     pub fn delete_rows(&mut self, rows_to_delete: Vec<my_no_sql_tcp_shared::DeleteRowTcpContract>) {
+        self.remove_from_indexes(&rows_to_delete);
+        self.record_removed(&rows_to_delete);
         self.entities.delete_rows(rows_to_delete, &self.callbacks);
+        self.batch_applied();
+    }
+
+    pub fn expire_rows(&mut self, rows_to_expire: Vec<my_no_sql_tcp_shared::DeleteRowTcpContract>) {
+        self.remove_from_indexes(&rows_to_expire);
+        self.record_removed(&rows_to_expire);
+        self.entities.expire_rows(rows_to_expire, &self.callbacks);
+        self.batch_applied();
+    }
+
+    // Index is built from the entities which are already in the reader and is kept up to date
+    // by the incoming changes. Entities have to be deserialized to be indexed
+    pub fn build_secondary_index(
+        &mut self,
+        index_name: &str,
+        extractor: SecondaryKeyExtractor<TMyNoSqlEntity>,
+    ) {
+        let mut index = SecondaryIndex::new(extractor);
+
+        if let Some(entities) = self.entities.as_mut() {
+            for partition in entities.values_mut() {
+                for entity in partition.values_mut() {
+                    index.insert(entity.get());
+                }
+            }
+        }
+
+        self.secondary_indexes.insert(index_name.to_string(), index);
+    }
+
+    pub fn get_by_secondary(
+        &self,
+        index_name: &str,
+        secondary_key: &str,
+    ) -> Vec<Arc<TMyNoSqlEntity>> {
+        match self.secondary_indexes.get(index_name) {
+            Some(index) => index.get(secondary_key),
+            None => Vec::new(),
+        }
+    }
+
+    fn index_entities<'s>(
+        &mut self,
+        entities: impl Iterator<Item = &'s mut LazyMyNoSqlEntity<TMyNoSqlEntity>>,
+    ) {
+        for entity in entities {
+            let entity = entity.get();
+            for index in self.secondary_indexes.values_mut() {
+                index.insert(entity);
+            }
+
+            if let Some(hash_index) = self.hash_index.as_mut() {
+                hash_index.insert(entity);
+            }
+        }
+    }
+
+    fn remove_from_indexes(&mut self, rows: &[my_no_sql_tcp_shared::DeleteRowTcpContract]) {
+        for index in self.secondary_indexes.values_mut() {
+            for row in rows {
+                index.remove(&row.partition_key, &row.row_key);
+            }
+        }
+
+        if let Some(hash_index) = self.hash_index.as_mut() {
+            for row in rows {
+                hash_index.remove(&row.partition_key, &row.row_key);
+            }
+        }
+    }
+
+    // Rows which are not in the reader are not reported as deleted
+    fn record_removed(&mut self, rows: &[my_no_sql_tcp_shared::DeleteRowTcpContract]) {
+        let Some(entities) = self.entities.as_ref() else {
+            return;
+        };
+
+        for row in rows {
+            let exists = match entities.get(&row.partition_key) {
+                Some(partition) => partition.contains_key(&row.row_key),
+                None => false,
+            };
+
+            if exists {
+                if let Some(changes) = self.changes.as_mut() {
+                    changes.deleted(&row.partition_key, &row.row_key);
+                }
+            }
+        }
+    }
+
+    // Changes are recorded starting from the first call, so the first call gives an empty batch
+    pub fn drain_changes(&mut self) -> ChangeBatch<TMyNoSqlEntity> {
+        self.changes.get_or_insert_with(ChangeBuffer::new).drain()
+    }
+
+    fn merge_seeded_rows(
+        &self,
+        data: &mut BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
+    ) {
+        let entities = match self.entities.as_ref() {
+            Some(entities) => entities,
+            None => return,
+        };
+
+        for (partition_key, partition) in entities {
+            let server_rows = data.entry(partition_key.to_string()).or_default();
+
+            let server_row_keys: HashSet<String> = server_rows
+                .iter()
+                .map(|entity| entity.get_row_key().to_string())
+                .collect();
+
+            for (row_key, entity) in partition {
+                if !server_row_keys.contains(row_key) {
+                    server_rows.push(entity.clone());
+                }
+            }
+        }
+    }
+
+    fn has_indexes(&self) -> bool {
+        !self.secondary_indexes.is_empty() || self.hash_index.is_some()
+    }
+
+    // Keeps deserialized entities by (PartitionKey, RowKey) in a hash map, so get_entity does not
+    // go through the sorted partitions. Costs memory and deserializes every entity of the table
+    pub fn enable_hash_index(&mut self) {
+        if self.hash_index.is_some() {
+            return;
+        }
+
+        let mut hash_index = HashIndex::new();
+
+        if let Some(entities) = self.entities.as_mut() {
+            for partition in entities.values_mut() {
+                for entity in partition.values_mut() {
+                    hash_index.insert(entity.get());
+                }
+            }
+        }
+
+        self.hash_index = Some(hash_index);
     }
 
     pub fn get_partition_keys(&self) -> Vec<String> {
         self.entities.get_partition_keys()
     }
 
+    pub fn get_table_as_json_array(&self) -> Option<Vec<u8>> {
+        self.entities.get_table_as_json_array()
+    }
+
     pub fn get_table_snapshot(
         &mut self,
     ) -> Option<BTreeMap<String, BTreeMap<String, Arc<TMyNoSqlEntity>>>> {
@@ -133,11 +429,39 @@ where
         Some(result)
     }
 
+    pub fn get_entities_changed_since(
+        &mut self,
+        since: DateTimeAsMicroseconds,
+    ) -> Vec<Arc<TMyNoSqlEntity>> {
+        let mut result = Vec::new();
+
+        let entities = match self.entities.as_mut() {
+            Some(entities) => entities,
+            None => return result,
+        };
+
+        for partition in entities.values_mut() {
+            for entity in partition.values_mut() {
+                if let Some(time_stamp) = entity.get_time_stamp() {
+                    if time_stamp.unix_microseconds > since.unix_microseconds {
+                        result.push(entity.get().clone());
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
     pub fn get_entity(
         &mut self,
         partition_key: &str,
         row_key: &str,
     ) -> Option<Arc<TMyNoSqlEntity>> {
+        if let Some(hash_index) = self.hash_index.as_ref() {
+            return hash_index.get(partition_key, row_key);
+        }
+
         let entities = self.entities.as_mut()?;
 
         let partition = entities.get_mut(partition_key)?;
@@ -147,6 +471,42 @@ where
         Some(row.get().clone())
     }
 
+    pub fn get_entity_as_json_value(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Option<serde_json::Value> {
+        let entities = self.entities.as_ref()?;
+
+        let partition = entities.get(partition_key)?;
+
+        let row = partition.get(row_key)?;
+
+        row.to_json_value()
+    }
+
+    // Stored json of the rows as an array. Rows which are not deserialized yet are copied as is
+    pub fn get_partition_raw(&self, partition_key: &str) -> Option<Vec<u8>> {
+        let entities = self.entities.as_ref()?;
+
+        let partition = entities.get(partition_key)?;
+
+        let mut result = Vec::new();
+        result.push(b'[');
+
+        for (no, row) in partition.values().enumerate() {
+            if no > 0 {
+                result.push(b',');
+            }
+
+            row.write_json(&mut result);
+        }
+
+        result.push(b']');
+
+        Some(result)
+    }
+
     pub fn get_by_partition(
         &mut self,
         partition_key: &str,
@@ -164,6 +524,48 @@ where
         Some(result)
     }
 
+    pub fn for_each_in_partition(
+        &mut self,
+        partition_key: &str,
+        mut callback: impl FnMut(&str, &Arc<TMyNoSqlEntity>),
+    ) {
+        let Some(entities) = self.entities.as_mut() else {
+            return;
+        };
+
+        let Some(partition) = entities.get_mut(partition_key) else {
+            return;
+        };
+
+        for (row_key, entity) in partition.iter_mut() {
+            callback(row_key, entity.get());
+        }
+    }
+
+    pub fn count_in_partition(
+        &mut self,
+        partition_key: &str,
+        predicate: impl Fn(&TMyNoSqlEntity) -> bool,
+    ) -> usize {
+        let Some(entities) = self.entities.as_mut() else {
+            return 0;
+        };
+
+        let Some(partition) = entities.get_mut(partition_key) else {
+            return 0;
+        };
+
+        let mut result = 0;
+
+        for entity in partition.values_mut() {
+            if predicate(entity.get()) {
+                result += 1;
+            }
+        }
+
+        result
+    }
+
     pub fn get_by_partition_with_filter(
         &mut self,
         partition_key: &str,
@@ -194,6 +596,18 @@ where
         Some(partition.values_mut())
     }
 
+    pub fn contains_entity(&self, partition_key: &str, row_key: &str) -> bool {
+        let entities = match self.entities.as_ref() {
+            Some(entities) => entities,
+            None => return false,
+        };
+
+        match entities.get(partition_key) {
+            Some(partition) => partition.contains_key(row_key),
+            None => false,
+        }
+    }
+
     pub fn has_partition(&self, partition_key: &str) -> bool {
         let entities = self.entities.as_ref();
 
@@ -256,3 +670,157 @@ where
         self.entities.is_initialized()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use rust_extensions::AppStates;
+
+    use my_no_sql_tcp_shared::DeleteRowTcpContract;
+
+    use super::MyNoSqlDataReaderData;
+    use crate::{
+        subscribers::{LazyMyNoSqlEntity, MemoryFootprint},
+        test_fixtures::TestRow,
+    };
+
+    fn rows(items: &[(&str, &str, u64)]) -> BTreeMap<String, Vec<LazyMyNoSqlEntity<TestRow>>> {
+        let mut result: BTreeMap<String, Vec<LazyMyNoSqlEntity<TestRow>>> = BTreeMap::new();
+
+        for (partition_key, row_key, value) in items {
+            let entity = TestRow {
+                partition_key: partition_key.to_string(),
+                row_key: row_key.to_string(),
+                value: *value,
+                ..Default::default()
+            };
+
+            result
+                .entry(partition_key.to_string())
+                .or_default()
+                .push(entity.into());
+        }
+
+        result
+    }
+
+    async fn create_data() -> MyNoSqlDataReaderData<TestRow> {
+        MyNoSqlDataReaderData::new(
+            "test".to_string(),
+            Arc::new(AppStates::create_initialized()),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_seeded_rows_survive_first_init_table() {
+        let mut data = create_data().await;
+
+        data.seed(rows(&[
+            ("pk1", "rk1", 1),
+            ("pk1", "rk2", 1),
+            ("pk2", "rk1", 1),
+        ]));
+
+        data.init_table(rows(&[("pk1", "rk1", 2), ("pk3", "rk1", 2)]))
+            .await;
+
+        assert_eq!(data.get_entity("pk1", "rk1").unwrap().value, 2);
+        assert_eq!(data.get_entity("pk1", "rk2").unwrap().value, 1);
+        assert_eq!(data.get_entity("pk2", "rk1").unwrap().value, 1);
+        assert_eq!(data.get_entity("pk3", "rk1").unwrap().value, 2);
+
+        data.init_table(rows(&[("pk3", "rk1", 3)])).await;
+
+        assert!(data.get_entity("pk1", "rk2").is_none());
+        assert!(data.get_entity("pk2", "rk1").is_none());
+        assert_eq!(data.get_entity("pk3", "rk1").unwrap().value, 3);
+    }
+
+    #[tokio::test]
+    async fn test_changes_are_recorded_after_first_drain() {
+        let mut data = create_data().await;
+
+        data.init_table(rows(&[("pk1", "rk1", 1)])).await;
+        data.update_rows(rows(&[("pk1", "rk2", 1)]));
+
+        assert!(data.changes.is_none());
+        assert!(data.drain_changes().is_empty());
+
+        data.update_rows(rows(&[("pk1", "rk3", 1)]));
+
+        let batch = data.drain_changes();
+        assert_eq!(batch.len(), 1);
+        assert!(batch.get("pk1", "rk3").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_init_table_without_seed_replaces_table() {
+        let mut data = create_data().await;
+
+        data.update_rows(rows(&[("pk1", "rk1", 1)]));
+        data.init_table(rows(&[("pk2", "rk1", 2)])).await;
+
+        assert!(data.get_entity("pk1", "rk1").is_none());
+        assert_eq!(data.get_entity("pk2", "rk1").unwrap().value, 2);
+    }
+
+    fn assert_footprint_is_tracked(data: &MyNoSqlDataReaderData<TestRow>) {
+        let calculated = match data.entities.as_ref() {
+            Some(entities) => MemoryFootprint::calculate(entities),
+            None => MemoryFootprint::default(),
+        };
+
+        assert_eq!(data.get_memory_footprint(), calculated);
+    }
+
+    #[tokio::test]
+    async fn test_memory_footprint_is_tracked_by_changes() {
+        let mut data = create_data().await;
+
+        data.init_table(rows(&[("pk1", "rk1", 1), ("pk1", "rk2", 1)]))
+            .await;
+        assert_footprint_is_tracked(&data);
+
+        data.update_rows(rows(&[("pk1", "rk1", 2), ("pk2", "rk1", 2)]));
+        assert_eq!(data.get_memory_footprint().entities, 3);
+        assert_footprint_is_tracked(&data);
+
+        data.delete_rows(vec![DeleteRowTcpContract {
+            partition_key: "pk2".to_string(),
+            row_key: "rk1".to_string(),
+        }]);
+        assert_eq!(data.get_memory_footprint().partitions, 1);
+        assert_footprint_is_tracked(&data);
+
+        data.init_partition("pk1", rows(&[("pk1", "rk3", 3)])).await;
+        assert_footprint_is_tracked(&data);
+    }
+
+    #[tokio::test]
+    async fn test_init_table_is_applied_when_updates_are_stopped() {
+        let mut data = create_data().await;
+
+        data.init_table(rows(&[("pk1", "rk1", 1)])).await;
+
+        let limit = data.get_memory_footprint().bytes;
+        data.set_memory_limit(limit, Arc::new(|_| {}), true);
+        assert!(data.take_memory_limit_exceeded().is_none());
+
+        data.update_rows(rows(&[("pk1", "rk2", 1)]));
+        assert!(data.take_memory_limit_exceeded().is_some());
+
+        // Updates are skipped while the table is over the limit
+        data.update_rows(rows(&[("pk1", "rk3", 1)]));
+        assert!(data.get_entity("pk1", "rk3").is_none());
+
+        data.init_table(rows(&[("pk2", "rk1", 2)])).await;
+        assert_eq!(data.get_entity("pk2", "rk1").unwrap().value, 2);
+        assert!(data.get_entity("pk1", "rk2").is_none());
+
+        // Table is back under the limit, so updates are applied again
+        data.update_rows(rows(&[("pk2", "rk1", 3)]));
+        assert_eq!(data.get_entity("pk2", "rk1").unwrap().value, 3);
+    }
+}
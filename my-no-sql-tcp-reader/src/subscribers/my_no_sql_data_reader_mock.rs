@@ -1,10 +1,14 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
 use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+use rust_extensions::date_time::DateTimeAsMicroseconds;
 
 use crate::MyNoSqlDataReaderCallBacks;
 
-use super::{GetEntitiesBuilder, GetEntityBuilder, MyNoSqlDataReader, MyNoSqlDataReaderMockInner};
+use super::{
+    ChangeBatch, GetEntitiesBuilder, GetEntityBuilder, MemoryLimitCallback, MyNoSqlDataReader,
+    MyNoSqlDataReaderMockInner,
+};
 
 pub struct MyNoSqlDataReaderMock<
     TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
@@ -45,6 +49,10 @@ where
         Some(result)
     }
 
+    async fn get_full_snapshot(&self) -> BTreeMap<String, BTreeMap<String, Arc<TMyNoSqlEntity>>> {
+        self.inner.get_full_snapshot().await
+    }
+
     async fn get_by_partition_key(
         &self,
         partition_key: &str,
@@ -67,6 +75,17 @@ where
         self.inner.get_entity(partition_key, row_key).await
     }
 
+    async fn contains_entity(&self, partition_key: &str, row_key: &str) -> bool {
+        self.inner.contains_entity(partition_key, row_key).await
+    }
+
+    async fn get_entities_changed_since(
+        &self,
+        since: DateTimeAsMicroseconds,
+    ) -> Vec<Arc<TMyNoSqlEntity>> {
+        self.inner.get_entities_changed_since(since).await
+    }
+
     fn get_entities<'s>(&self, partition_key: &'s str) -> GetEntitiesBuilder<TMyNoSqlEntity> {
         GetEntitiesBuilder::new_mock(partition_key.to_string(), self.inner.clone())
     }
@@ -83,6 +102,84 @@ where
         self.inner.has_partition(partition_key).await
     }
 
+    async fn for_each_in_partition<TCallback: FnMut(&str, &Arc<TMyNoSqlEntity>) + Send>(
+        &self,
+        partition_key: &str,
+        callback: TCallback,
+    ) {
+        self.inner
+            .for_each_in_partition(partition_key, callback)
+            .await
+    }
+
+    async fn count_entities<TPredicate: Fn(&TMyNoSqlEntity) -> bool + Send>(
+        &self,
+        partition_key: &str,
+        predicate: TPredicate,
+    ) -> usize {
+        self.inner.count_entities(partition_key, predicate).await
+    }
+
+    async fn seed<TEntities: Iterator<Item = Arc<TMyNoSqlEntity>> + Send>(
+        &self,
+        entities: TEntities,
+    ) {
+        self.inner.update(entities).await
+    }
+
+    async fn wait_for_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        timeout: Duration,
+    ) -> Option<Arc<TMyNoSqlEntity>> {
+        self.inner
+            .wait_for_entity(partition_key, row_key, timeout)
+            .await
+    }
+
+    async fn build_secondary_index<
+        K: ToString + 'static,
+        TExtractor: Fn(&TMyNoSqlEntity) -> Option<K> + Send + Sync + 'static,
+    >(
+        &self,
+        index_name: &str,
+        extractor: TExtractor,
+    ) {
+        self.inner
+            .build_secondary_index(
+                index_name,
+                Box::new(move |entity| extractor(entity).map(|key| key.to_string())),
+            )
+            .await
+    }
+
+    async fn get_by_secondary<K: ToString + ?Sized + Sync>(
+        &self,
+        index_name: &str,
+        value: &K,
+    ) -> Vec<Arc<TMyNoSqlEntity>> {
+        self.inner
+            .get_by_secondary(index_name, value.to_string().as_str())
+            .await
+    }
+
+    // Mock is filled by the test directly, so memory limit is not applied
+    async fn set_memory_limit(
+        &self,
+        _bytes: usize,
+        _on_exceed: MemoryLimitCallback,
+        _stop_updates: bool,
+    ) {
+    }
+
+    // Mock has no sorted structures to speed up, so it is a no-op
+    async fn enable_hash_index(&self) {}
+
+    async fn drain_changes(&self) -> ChangeBatch<TMyNoSqlEntity> {
+        self.inner.drain_changes().await
+    }
+
     async fn wait_until_first_data_arrives(&self) {
         todo!("Not Implemented");
     }
@@ -96,3 +193,113 @@ where
         self.inner.assign_callback(callbacks).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::MyNoSqlDataReaderMock;
+    use crate::{subscribers::MyNoSqlDataReader, test_fixtures::TestRow};
+
+    fn row(partition_key: &str, row_key: &str, value: u64) -> Arc<TestRow> {
+        Arc::new(TestRow {
+            partition_key: partition_key.to_string(),
+            row_key: row_key.to_string(),
+            value,
+            ..Default::default()
+        })
+    }
+
+    // Mock relies on the default implementations of the trait for these methods
+    #[tokio::test]
+    async fn test_default_implementations() {
+        let reader = MyNoSqlDataReaderMock::<TestRow>::new();
+        reader
+            .update(vec![row("pk", "rk1", 1), row("pk", "rk2", 2)].into_iter())
+            .await;
+
+        let value = reader.get_entity_as_json_value("pk", "rk2").await.unwrap();
+        assert_eq!(value["value"], 2);
+        assert!(reader.get_entity_as_json_value("pk", "rk3").await.is_none());
+
+        let raw = reader.get_partition_raw("pk").await.unwrap();
+        let raw: serde_json::Value = serde_json::from_slice(&raw).unwrap();
+        assert_eq!(raw.as_array().unwrap().len(), 2);
+        assert_eq!(raw[0]["RowKey"], "rk1");
+        assert!(reader.get_partition_raw("pk2").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_full_snapshot() {
+        let reader = MyNoSqlDataReaderMock::<TestRow>::new();
+        assert!(reader.get_full_snapshot().await.is_empty());
+
+        reader
+            .update(
+                vec![
+                    row("pk1", "rk1", 1),
+                    row("pk1", "rk2", 2),
+                    row("pk2", "rk1", 3),
+                ]
+                .into_iter(),
+            )
+            .await;
+
+        let snapshot = reader.get_full_snapshot().await;
+
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot["pk1"].len(), 2);
+        assert_eq!(snapshot["pk2"]["rk1"].value, 3);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_entity() {
+        let reader = MyNoSqlDataReaderMock::<TestRow>::new();
+
+        let (entity, _) = tokio::join!(
+            reader.wait_for_entity("pk", "rk", Duration::from_secs(5)),
+            async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                reader.update(vec![row("pk", "rk", 1)].into_iter()).await;
+            }
+        );
+
+        assert_eq!(entity.unwrap().value, 1);
+
+        let entity = reader
+            .wait_for_entity("pk", "rk2", Duration::from_millis(50))
+            .await;
+        assert!(entity.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_for_each_in_partition() {
+        let reader = MyNoSqlDataReaderMock::<TestRow>::new();
+        reader
+            .update(vec![row("pk1", "rk1", 1), row("pk2", "rk1", 2)].into_iter())
+            .await;
+
+        let mut visited = Vec::new();
+        reader
+            .for_each_in_partition("pk1", |row_key, entity| {
+                visited.push((row_key.to_string(), entity.value))
+            })
+            .await;
+
+        assert_eq!(visited, vec![("rk1".to_string(), 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_count_entities() {
+        let reader = MyNoSqlDataReaderMock::<TestRow>::new();
+        reader
+            .update(vec![row("pk", "rk1", 1), row("pk", "rk2", 2)].into_iter())
+            .await;
+
+        assert_eq!(
+            reader.count_entities("pk", |entity| entity.value > 1).await,
+            1
+        );
+        assert_eq!(reader.count_entities("pk2", |_| true).await, 0);
+    }
+}
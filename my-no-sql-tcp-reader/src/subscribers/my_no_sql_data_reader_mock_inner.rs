@@ -1,21 +1,28 @@
 use std::{
     collections::{BTreeMap, HashSet},
     sync::Arc,
+    time::Duration,
 };
 
 use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
-use rust_extensions::{lazy::LazyVec, AppStates};
-use tokio::sync::RwLock;
+use rust_extensions::{date_time::DateTimeAsMicroseconds, lazy::LazyVec, AppStates};
+use tokio::sync::{Notify, RwLock};
 
 use crate::MyNoSqlDataReaderCallBacks;
 
-use super::MyNoSqlDataReaderCallBacksPusher;
+use super::{
+    ChangeBatch, ChangeBuffer, LazyMyNoSqlEntity, MyNoSqlDataReaderCallBacksPusher,
+    SecondaryKeyExtractor,
+};
 
 pub struct MyNoSqlDataReaderMockInnerData<
     TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
 > {
     pub items: BTreeMap<String, BTreeMap<String, Arc<TMyNoSqlEntity>>>,
+    pub update_moments: BTreeMap<String, BTreeMap<String, DateTimeAsMicroseconds>>,
     pub callbacks: Option<Arc<MyNoSqlDataReaderCallBacksPusher<TMyNoSqlEntity>>>,
+    pub secondary_indexes: BTreeMap<String, SecondaryKeyExtractor<TMyNoSqlEntity>>,
+    pub changes: Option<ChangeBuffer<TMyNoSqlEntity>>,
 }
 
 impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static>
@@ -24,7 +31,10 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'st
     pub fn new() -> Self {
         Self {
             items: BTreeMap::new(),
+            update_moments: BTreeMap::new(),
             callbacks: None,
+            secondary_indexes: BTreeMap::new(),
+            changes: None,
         }
     }
 }
@@ -34,6 +44,7 @@ pub struct MyNoSqlDataReaderMockInner<
 > {
     pub inner: RwLock<MyNoSqlDataReaderMockInnerData<TMyNoSqlEntity>>,
     app_states: Arc<AppStates>,
+    updated: Notify,
 }
 
 impl<TMyNoSqlEntity> MyNoSqlDataReaderMockInner<TMyNoSqlEntity>
@@ -44,6 +55,7 @@ where
         Self {
             inner: RwLock::new(MyNoSqlDataReaderMockInnerData::new()),
             app_states: Arc::new(AppStates::create_initialized()),
+            updated: Notify::new(),
         }
     }
 
@@ -62,23 +74,48 @@ where
 
     pub async fn update(&self, items: impl Iterator<Item = Arc<TMyNoSqlEntity>>) {
         let mut write_access = self.inner.write().await;
+        let now = DateTimeAsMicroseconds::now();
         for item in items {
             let partition_key = item.get_partition_key();
             let row_key = item.get_row_key();
 
+            if let Some(changes) = write_access.changes.as_mut() {
+                changes.upserted(
+                    partition_key,
+                    &LazyMyNoSqlEntity::Deserialized(item.clone()),
+                );
+            }
+
+            write_access
+                .update_moments
+                .entry(partition_key.to_string())
+                .or_insert_with(BTreeMap::new)
+                .insert(row_key.to_string(), now);
+
             let partition = write_access
                 .items
                 .entry(partition_key.to_string())
                 .or_insert_with(BTreeMap::new);
             partition.insert(row_key.to_string(), item);
         }
+
+        self.updated.notify_waiters();
     }
     pub async fn delete(&self, to_delete: impl Iterator<Item = (String, String)>) {
         let mut write_access = self.inner.write().await;
+        let write_access = &mut *write_access;
 
         let mut partitions_to_remove = HashSet::new();
         for (partition_key, row_key) in to_delete {
             if let Some(partition) = write_access.items.get_mut(&partition_key) {
+                if partition.remove(&row_key).is_some() {
+                    if let Some(changes) = write_access.changes.as_mut() {
+                        changes.deleted(&partition_key, &row_key);
+                    }
+                }
+            }
+
+            if let Some(partition) = write_access.update_moments.get_mut(&partition_key) {
                 partition.remove(&row_key);
             }
 
@@ -91,9 +128,20 @@ where
 
         for partition_to_remove in partitions_to_remove {
             write_access.items.remove(partition_to_remove.as_str());
+            write_access
+                .update_moments
+                .remove(partition_to_remove.as_str());
         }
     }
 
+    pub async fn drain_changes(&self) -> ChangeBatch<TMyNoSqlEntity> {
+        let mut write_access = self.inner.write().await;
+        write_access
+            .changes
+            .get_or_insert_with(ChangeBuffer::new)
+            .drain()
+    }
+
     pub async fn get_table_snapshot_as_vec(&self) -> Vec<Arc<TMyNoSqlEntity>> {
         let read_access = self.inner.read().await;
         let mut result = Vec::new();
@@ -106,6 +154,39 @@ where
         result
     }
 
+    pub async fn get_full_snapshot(
+        &self,
+    ) -> BTreeMap<String, BTreeMap<String, Arc<TMyNoSqlEntity>>> {
+        let read_access = self.inner.read().await;
+        read_access.items.clone()
+    }
+
+    pub async fn get_entities_changed_since(
+        &self,
+        since: DateTimeAsMicroseconds,
+    ) -> Vec<Arc<TMyNoSqlEntity>> {
+        let read_access = self.inner.read().await;
+        let mut result = Vec::new();
+
+        for (partition_key, partition) in read_access.update_moments.iter() {
+            for (row_key, update_moment) in partition {
+                if update_moment.unix_microseconds <= since.unix_microseconds {
+                    continue;
+                }
+
+                if let Some(item) = read_access
+                    .items
+                    .get(partition_key)
+                    .and_then(|partition| partition.get(row_key))
+                {
+                    result.push(item.clone());
+                }
+            }
+        }
+
+        result
+    }
+
     pub async fn get_by_partition_key(
         &self,
         partition_key: &str,
@@ -114,6 +195,36 @@ where
         read_access.items.get(partition_key).cloned()
     }
 
+    pub async fn for_each_in_partition(
+        &self,
+        partition_key: &str,
+        mut callback: impl FnMut(&str, &Arc<TMyNoSqlEntity>),
+    ) {
+        let read_access = self.inner.read().await;
+
+        if let Some(partition) = read_access.items.get(partition_key) {
+            for (row_key, entity) in partition {
+                callback(row_key, entity);
+            }
+        }
+    }
+
+    pub async fn count_entities(
+        &self,
+        partition_key: &str,
+        predicate: impl Fn(&TMyNoSqlEntity) -> bool,
+    ) -> usize {
+        let read_access = self.inner.read().await;
+
+        match read_access.items.get(partition_key) {
+            Some(partition) => partition
+                .values()
+                .filter(|entity| predicate(entity.as_ref()))
+                .count(),
+            None => 0,
+        }
+    }
+
     pub async fn get_partition_keys(&self) -> Vec<String> {
         let read_access = self.inner.read().await;
         read_access.items.keys().cloned().collect()
@@ -194,8 +305,74 @@ where
         result.get_result()
     }
 
+    pub async fn contains_entity(&self, partition_key: &str, row_key: &str) -> bool {
+        let read_access = self.inner.read().await;
+        match read_access.items.get(partition_key) {
+            Some(partition) => partition.contains_key(row_key),
+            None => false,
+        }
+    }
+
     pub async fn has_partition(&self, partition_key: &str) -> bool {
         let read_access = self.inner.read().await;
         read_access.items.contains_key(partition_key)
     }
+
+    pub async fn wait_for_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        timeout: Duration,
+    ) -> Option<Arc<TMyNoSqlEntity>> {
+        let wait = async {
+            loop {
+                let notified = self.updated.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                if let Some(entity) = self.get_entity(partition_key, row_key).await {
+                    return entity;
+                }
+
+                notified.await;
+            }
+        };
+
+        tokio::time::timeout(timeout, wait).await.ok()
+    }
+
+    pub async fn build_secondary_index(
+        &self,
+        index_name: &str,
+        extractor: SecondaryKeyExtractor<TMyNoSqlEntity>,
+    ) {
+        let mut write_access = self.inner.write().await;
+        write_access
+            .secondary_indexes
+            .insert(index_name.to_string(), extractor);
+    }
+
+    // Mock does not keep the index, it scans the items with the declared extractor
+    pub async fn get_by_secondary(
+        &self,
+        index_name: &str,
+        secondary_key: &str,
+    ) -> Vec<Arc<TMyNoSqlEntity>> {
+        let read_access = self.inner.read().await;
+        let mut result = Vec::new();
+
+        let Some(extractor) = read_access.secondary_indexes.get(index_name) else {
+            return result;
+        };
+
+        for partition in read_access.items.values() {
+            for item in partition.values() {
+                if extractor(item).as_deref() == Some(secondary_key) {
+                    result.push(item.clone());
+                }
+            }
+        }
+
+        result
+    }
 }
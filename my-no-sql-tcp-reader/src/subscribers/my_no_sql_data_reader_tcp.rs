@@ -1,28 +1,57 @@
-use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use async_trait::async_trait;
 use my_json::json_reader::array_iterator::JsonArrayIterator;
 use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
 use my_no_sql_tcp_shared::sync_to_main::SyncToMainNodeHandler;
-use rust_extensions::{array_of_bytes_iterator::SliceIterator, ApplicationStates, StrOrString};
+use rust_extensions::{
+    array_of_bytes_iterator::SliceIterator,
+    date_time::{AtomicDateTimeAsMicroseconds, DateTimeAsMicroseconds},
+    ApplicationStates, StrOrString,
+};
 use serde::de::DeserializeOwned;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
+
+use crate::ConnectionLiveness;
 
 use super::{
-    EntityRawData, GetEntitiesBuilder, GetEntityBuilder, LazyMyNoSqlEntity, MyNoSqlDataReader,
-    MyNoSqlDataReaderCallBacks, MyNoSqlDataReaderData, UpdateEvent,
+    ChangeBatch, EntityRawData, GetEntitiesBuilder, GetEntityBuilder, LazyMyNoSqlEntity,
+    MemoryFootprint, MemoryLimitCallback, MemoryLimitExceeded, MyNoSqlDataReader,
+    MyNoSqlDataReaderCallBacks, MyNoSqlDataReaderData, PartitionsSnapshot, UpdateEvent,
 };
 
 pub struct MyNoSqlDataReaderInner<
     TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
 > {
+    table_name: String,
     data: Mutex<MyNoSqlDataReaderData<TMyNoSqlEntity>>,
     sync_handler: Arc<SyncToMainNodeHandler>,
+    projection: Option<Vec<&'static str>>,
+    updated: Notify,
+    last_update: AtomicDateTimeAsMicroseconds,
+    liveness: Arc<ConnectionLiveness>,
+    // Connection the last init_table came by. Data is in sync while it is the current connection
+    synced_connection_id: AtomicI64,
+    #[cfg(feature = "fresh-reads")]
+    fresh_reads_writer:
+        std::sync::OnceLock<Arc<my_no_sql_data_writer::MyNoSqlDataWriter<TMyNoSqlEntity>>>,
 }
 
 impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static>
     MyNoSqlDataReaderInner<TMyNoSqlEntity>
 {
+    pub fn get_table_name(&self) -> &str {
+        self.table_name.as_str()
+    }
+
     pub fn get_data(&self) -> &Mutex<MyNoSqlDataReaderData<TMyNoSqlEntity>> {
         &self.data
     }
@@ -30,6 +59,11 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'st
     pub fn get_sync_handler(&self) -> &Arc<SyncToMainNodeHandler> {
         &self.sync_handler
     }
+
+    fn on_update(&self) {
+        self.last_update.update(DateTimeAsMicroseconds::now());
+        self.updated.notify_waiters();
+    }
 }
 
 pub struct MyNoSqlDataReaderTcp<
@@ -43,19 +77,32 @@ where
     TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
 {
     pub async fn new(
+        table_name: String,
         app_states: Arc<dyn ApplicationStates + Send + Sync + 'static>,
         sync_handler: Arc<SyncToMainNodeHandler>,
+        liveness: Arc<ConnectionLiveness>,
+        projection: Option<Vec<&'static str>>,
     ) -> Self {
         Self {
             inner: Arc::new(MyNoSqlDataReaderInner {
-                data: Mutex::new(
-                    MyNoSqlDataReaderData::new(TMyNoSqlEntity::TABLE_NAME, app_states).await,
-                ),
+                data: Mutex::new(MyNoSqlDataReaderData::new(table_name.clone(), app_states).await),
+                table_name,
                 sync_handler,
+                projection,
+                updated: Notify::new(),
+                last_update: AtomicDateTimeAsMicroseconds::new(0),
+                liveness,
+                synced_connection_id: AtomicI64::new(0),
+                #[cfg(feature = "fresh-reads")]
+                fresh_reads_writer: std::sync::OnceLock::new(),
             }),
         }
     }
 
+    pub fn get_table_name(&self) -> &str {
+        self.inner.get_table_name()
+    }
+
     pub async fn get_table_snapshot(
         &self,
     ) -> Option<BTreeMap<String, BTreeMap<String, Arc<TMyNoSqlEntity>>>> {
@@ -68,6 +115,13 @@ where
         reader.get_table_snapshot_as_vec()
     }
 
+    pub async fn get_full_snapshot(
+        &self,
+    ) -> BTreeMap<String, BTreeMap<String, Arc<TMyNoSqlEntity>>> {
+        let mut reader = self.inner.data.lock().await;
+        reader.get_table_snapshot().unwrap_or_default()
+    }
+
     pub async fn get_by_partition_key(
         &self,
         partition_key: &str,
@@ -93,6 +147,33 @@ where
         reader.get_entity(partition_key, row_key)
     }
 
+    pub async fn contains_entity(&self, partition_key: &str, row_key: &str) -> bool {
+        let reader = self.inner.data.lock().await;
+        reader.contains_entity(partition_key, row_key)
+    }
+
+    pub async fn get_entity_as_json_value(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Option<serde_json::Value> {
+        let reader = self.inner.data.lock().await;
+        reader.get_entity_as_json_value(partition_key, row_key)
+    }
+
+    pub async fn get_partition_raw(&self, partition_key: &str) -> Option<Vec<u8>> {
+        let reader = self.inner.data.lock().await;
+        reader.get_partition_raw(partition_key)
+    }
+
+    pub async fn get_entities_changed_since(
+        &self,
+        since: DateTimeAsMicroseconds,
+    ) -> Vec<Arc<TMyNoSqlEntity>> {
+        let mut reader = self.inner.data.lock().await;
+        reader.get_entities_changed_since(since)
+    }
+
     pub fn get_entities<'s>(
         &self,
         partition_key: impl Into<StrOrString<'s>>,
@@ -113,6 +194,47 @@ where
         reader.has_partition(partition_key)
     }
 
+    pub async fn for_each_in_partition(
+        &self,
+        partition_key: &str,
+        callback: impl FnMut(&str, &Arc<TMyNoSqlEntity>),
+    ) {
+        let mut reader = self.inner.data.lock().await;
+        reader.for_each_in_partition(partition_key, callback)
+    }
+
+    // Counts under one lock. Entities are not cloned
+    pub async fn count_entities(
+        &self,
+        partition_key: &str,
+        predicate: impl Fn(&TMyNoSqlEntity) -> bool,
+    ) -> usize {
+        let mut reader = self.inner.data.lock().await;
+        reader.count_in_partition(partition_key, predicate)
+    }
+
+    // Puts entities into the reader as if they came as an update from the server.
+    // Data which comes later by TCP is merged on top of them: the first init_table keeps
+    // seeded rows the server does not have
+    pub async fn seed(&self, entities: impl Iterator<Item = Arc<TMyNoSqlEntity>>) {
+        let mut data: BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>> = BTreeMap::new();
+
+        for entity in entities {
+            data.entry(entity.get_partition_key().to_string())
+                .or_default()
+                .push(LazyMyNoSqlEntity::Deserialized(entity));
+        }
+
+        let memory_limit_exceeded = {
+            let mut write_access = self.inner.data.lock().await;
+            write_access.seed(data);
+            self.inner.updated.notify_waiters();
+            write_access.take_memory_limit_exceeded()
+        };
+
+        notify_memory_limit_exceeded(memory_limit_exceeded);
+    }
+
     pub async fn iter_and_find_entity_inside_partition(
         &self,
         partition_key: &str,
@@ -137,36 +259,48 @@ where
         &self,
         data: &[u8],
     ) -> BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>> {
-        let slice_iterator = SliceIterator::new(data);
-
-        let json_array_iterator = JsonArrayIterator::new(slice_iterator);
-
-        if let Err(err) = &json_array_iterator {
-            panic!(
-                "Table: {}. The whole array of json entities is broken. Err: {:?}",
-                TMyNoSqlEntity::TABLE_NAME,
+        match Self::try_deserialize_array(data, self.inner.projection.as_deref()) {
+            Ok(result) => result,
+            Err(err) => panic!(
+                "Table: {}. The whole array of json entities is broken. Err: {}",
+                self.inner.get_table_name(),
                 err
-            );
+            ),
         }
+    }
+
+    fn try_deserialize_array(
+        data: &[u8],
+        projection: Option<&[&'static str]>,
+    ) -> Result<BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>>, String> {
+        let slice_iterator = SliceIterator::new(data);
+
+        let mut json_array_iterator =
+            JsonArrayIterator::new(slice_iterator).map_err(|err| format!("{:?}", err))?;
 
-        let mut json_array_iterator = json_array_iterator.unwrap();
         let mut result = BTreeMap::new();
 
         while let Some(db_entity) = json_array_iterator.get_next() {
-            if let Err(err) = &db_entity {
-                panic!(
-                    "Table: {}. The whole array of json entities is broken. Err: {:?}",
-                    TMyNoSqlEntity::TABLE_NAME,
-                    err
-                );
-            }
+            let db_entity_data = db_entity.map_err(|err| format!("{:?}", err))?;
 
-            let db_entity_data = db_entity.unwrap();
+            let projected = match projection {
+                Some(fields) => Some(super::projection::project_entity(
+                    db_entity_data.as_bytes(&json_array_iterator),
+                    fields,
+                )?),
+                None => None,
+            };
+
+            let entity_data = match projected.as_ref() {
+                Some(projected) => projected.as_slice(),
+                None => db_entity_data.as_bytes(&json_array_iterator),
+            };
 
             let item_to_insert = if TMyNoSqlEntity::LAZY_DESERIALIZATION {
-                let data = db_entity_data.as_bytes(&json_array_iterator).to_vec();
+                let data = entity_data.to_vec();
                 let db_json_entity =
-                    my_no_sql_core::db_json_entity::DbJsonEntity::from_slice(&data).unwrap();
+                    my_no_sql_core::db_json_entity::DbJsonEntity::from_slice(&data)
+                        .map_err(|err| format!("{:?}", err))?;
 
                 LazyMyNoSqlEntity::Raw(
                     EntityRawData {
@@ -177,11 +311,7 @@ where
                 )
             } else {
                 LazyMyNoSqlEntity::Deserialized(
-                    TMyNoSqlEntity::deserialize_entity(
-                        db_entity_data.as_bytes(&json_array_iterator),
-                    )
-                    .unwrap()
-                    .into(),
+                    TMyNoSqlEntity::deserialize_entity(entity_data)?.into(),
                 )
             };
 
@@ -193,37 +323,51 @@ where
             result.get_mut(partition_key).unwrap().push(item_to_insert);
         }
 
-        result
+        Ok(result)
+    }
 
-        /*
+    /// Writes the current table content to the file as a json array.
+    /// Does nothing if the table has not been initialized yet, so the previous snapshot stays intact.
+    pub async fn save_snapshot(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let content = {
+            let reader = self.inner.data.lock().await;
+            reader.get_table_as_json_array()
+        };
 
-        let mut result = BTreeMap::new();
+        if let Some(content) = content {
+            tokio::fs::write(path, content).await?;
+        }
 
-        for db_entity in JsonArrayIterator::new(data) {
-            if let Err(err) = &db_entity {
-                panic!(
-                    "Table: {}. The whole array of json entities is broken. Err: {:?}",
-                    TMyNoSqlEntity::TABLE_NAME,
-                    err
-                );
-            }
+        Ok(())
+    }
 
-            let db_entity_data = db_entity.unwrap();
+    /// Restores the table from a snapshot made by [`Self::save_snapshot`].
+    ///
+    /// Snapshot data is stale: it reflects the table at the moment it was saved and is served
+    /// only until the first InitTable arrives from the server, which replaces it completely.
+    /// If the table is already initialized by the server the snapshot is ignored.
+    pub async fn load_snapshot(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let content = tokio::fs::read(path).await?;
 
-            let el = TMyNoSqlEntity::deserialize_entity(db_entity_data);
+        let data =
+            Self::try_deserialize_array(content.as_slice(), self.inner.projection.as_deref())
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
 
-            if let Some(el) = el {
-                let partition_key = el.get_partition_key();
-                if !result.contains_key(partition_key) {
-                    result.insert(partition_key.to_string(), Vec::new());
-                }
+        let memory_limit_exceeded = {
+            let mut write_access = self.inner.data.lock().await;
 
-                result.get_mut(partition_key).unwrap().push(el);
+            if write_access.has_entities_at_all().await {
+                return Ok(());
             }
-        }
 
-        result
-         */
+            write_access.init_table(data).await;
+            self.inner.updated.notify_waiters();
+            write_access.take_memory_limit_exceeded()
+        };
+
+        notify_memory_limit_exceeded(memory_limit_exceeded);
+
+        Ok(())
     }
 
     pub async fn get_enum_case_models_by_partition_key<
@@ -271,6 +415,178 @@ where
         let write_access = self.inner.data.lock().await;
         write_access.get_partition_keys()
     }
+
+    // Resolves as soon as the entity is in the table or with None if it does not appear within timeout
+    pub async fn wait_for_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        timeout: Duration,
+    ) -> Option<Arc<TMyNoSqlEntity>> {
+        let wait = async {
+            loop {
+                let notified = self.inner.updated.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                if let Some(entity) = self.get_entity(partition_key, row_key).await {
+                    return entity;
+                }
+
+                notified.await;
+            }
+        };
+
+        tokio::time::timeout(timeout, wait).await.ok()
+    }
+
+    pub async fn build_secondary_index<K: ToString + 'static>(
+        &self,
+        index_name: &str,
+        extractor: impl Fn(&TMyNoSqlEntity) -> Option<K> + Send + Sync + 'static,
+    ) {
+        let mut write_access = self.inner.data.lock().await;
+        write_access.build_secondary_index(
+            index_name,
+            Box::new(move |entity| extractor(entity).map(|key| key.to_string())),
+        );
+    }
+
+    pub async fn get_by_secondary<K: ToString + ?Sized>(
+        &self,
+        index_name: &str,
+        value: &K,
+    ) -> Vec<Arc<TMyNoSqlEntity>> {
+        let secondary_key = value.to_string();
+        let read_access = self.inner.data.lock().await;
+        read_access.get_by_secondary(index_name, secondary_key.as_str())
+    }
+
+    // on_exceed is called when the table grows beyond the limit after the applied batch.
+    // If stop_updates is set - updates are ignored until deletes bring the table under the limit.
+    // Skipped updates are lost until the next init_table, which is applied even when updates are stopped
+    pub async fn set_memory_limit(
+        &self,
+        bytes: usize,
+        on_exceed: MemoryLimitCallback,
+        stop_updates: bool,
+    ) {
+        let memory_limit_exceeded = {
+            let mut write_access = self.inner.data.lock().await;
+            write_access.set_memory_limit(bytes, on_exceed, stop_updates);
+            write_access.take_memory_limit_exceeded()
+        };
+
+        notify_memory_limit_exceeded(memory_limit_exceeded);
+    }
+
+    pub async fn get_memory_footprint(&self) -> MemoryFootprint {
+        let read_access = self.inner.data.lock().await;
+        read_access.get_memory_footprint()
+    }
+
+    // Opt-in O(1) get_entity for hot keys. Every entity is deserialized and kept in a hash map
+    pub async fn enable_hash_index(&self) {
+        let mut write_access = self.inner.data.lock().await;
+        write_access.enable_hash_index();
+    }
+
+    pub async fn drain_changes(&self) -> ChangeBatch<TMyNoSqlEntity> {
+        let mut write_access = self.inner.data.lock().await;
+        write_access.drain_changes()
+    }
+
+    // Partitions are copied under one lock, so all of them belong to the same applied batches.
+    // Partitions which do not exist are absent from the snapshot
+    pub async fn read_at_generation(
+        &self,
+        partition_keys: &[&str],
+    ) -> PartitionsSnapshot<TMyNoSqlEntity> {
+        let mut write_access = self.inner.data.lock().await;
+        PartitionsSnapshot::new(&mut write_access, partition_keys)
+    }
+
+    // Moment the last update came from the server. None if nothing has come yet.
+    // Seeded and snapshot data do not count as an update
+    pub fn get_last_update_moment(&self) -> Option<DateTimeAsMicroseconds> {
+        let result = self.inner.last_update.as_date_time();
+
+        if result.unix_microseconds == 0 {
+            return None;
+        }
+
+        Some(result)
+    }
+
+    // Data is stale if the connection has not been seen alive within max_staleness
+    // or the table has not been initialized by the current connection yet
+    pub fn is_stale(&self, max_staleness: Duration) -> bool {
+        if self.inner.synced_connection_id.load(Ordering::SeqCst)
+            != self.inner.liveness.get_connection_id()
+        {
+            return true;
+        }
+
+        self.inner.liveness.is_stale(max_staleness)
+    }
+}
+
+#[cfg(feature = "fresh-reads")]
+impl<TMyNoSqlEntity> MyNoSqlDataReaderTcp<TMyNoSqlEntity>
+where
+    TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
+{
+    // Writer which is used by get_entity_fresh to read from the server directly.
+    // Can be set only once and must write the same table the reader reads
+    pub fn set_fresh_reads_writer(
+        &self,
+        writer: Arc<my_no_sql_data_writer::MyNoSqlDataWriter<TMyNoSqlEntity>>,
+    ) -> Result<(), my_no_sql_data_writer::DataWriterError> {
+        if writer.get_table_name() != self.inner.get_table_name() {
+            return Err(my_no_sql_data_writer::DataWriterError::Error(format!(
+                "Table: {}. Fresh reads writer writes the table {}",
+                self.inner.get_table_name(),
+                writer.get_table_name()
+            )));
+        }
+
+        if self.inner.fresh_reads_writer.set(writer).is_err() {
+            return Err(my_no_sql_data_writer::DataWriterError::Error(format!(
+                "Table: {}. Fresh reads writer is already set",
+                self.inner.get_table_name()
+            )));
+        }
+
+        Ok(())
+    }
+
+    // Reads from the local cache if the connection was seen alive within max_staleness.
+    // Otherwise reads from the server by http. The connection is pinged every few seconds,
+    // so max_staleness below the ping interval sends reads to the server between pings
+    pub async fn get_entity_fresh(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        max_staleness: Duration,
+    ) -> Result<Option<Arc<TMyNoSqlEntity>>, my_no_sql_data_writer::DataWriterError> {
+        if !self.is_stale(max_staleness) {
+            return Ok(self.get_entity(partition_key, row_key).await);
+        }
+
+        let writer = match self.inner.fresh_reads_writer.get() {
+            Some(writer) => writer,
+            None => {
+                return Err(my_no_sql_data_writer::DataWriterError::Error(format!(
+                    "Table: {}. Data is older than {:?} and fresh reads writer is not set",
+                    self.inner.get_table_name(),
+                    max_staleness
+                )));
+            }
+        };
+
+        let entity = writer.get_entity(partition_key, row_key, None).await?;
+        Ok(entity.map(Arc::new))
+    }
 }
 
 #[async_trait]
@@ -280,27 +596,65 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> Upda
     async fn init_table(&self, data: Vec<u8>) {
         let data = self.deserialize_array(data.as_slice());
 
-        let mut write_access = self.inner.data.lock().await;
-        write_access.init_table(data).await;
+        let memory_limit_exceeded = {
+            let mut write_access = self.inner.data.lock().await;
+            write_access.init_table(data).await;
+            self.inner
+                .synced_connection_id
+                .store(self.inner.liveness.get_connection_id(), Ordering::SeqCst);
+            self.inner.on_update();
+            write_access.take_memory_limit_exceeded()
+        };
+
+        notify_memory_limit_exceeded(memory_limit_exceeded);
     }
 
     async fn init_partition(&self, partition_key: &str, data: Vec<u8>) {
         let data = self.deserialize_array(data.as_slice());
 
-        let mut write_access = self.inner.data.lock().await;
-        write_access.init_partition(partition_key, data).await;
+        let memory_limit_exceeded = {
+            let mut write_access = self.inner.data.lock().await;
+            write_access.init_partition(partition_key, data).await;
+            self.inner.on_update();
+            write_access.take_memory_limit_exceeded()
+        };
+
+        notify_memory_limit_exceeded(memory_limit_exceeded);
     }
 
     async fn update_rows(&self, data: Vec<u8>) {
         let data = self.deserialize_array(data.as_slice());
 
-        let mut write_access = self.inner.data.lock().await;
-        write_access.update_rows(data);
+        let memory_limit_exceeded = {
+            let mut write_access = self.inner.data.lock().await;
+            write_access.update_rows(data);
+            self.inner.on_update();
+            write_access.take_memory_limit_exceeded()
+        };
+
+        notify_memory_limit_exceeded(memory_limit_exceeded);
     }
 
     async fn delete_rows(&self, rows_to_delete: Vec<my_no_sql_tcp_shared::DeleteRowTcpContract>) {
-        let mut write_access = self.inner.data.lock().await;
-        write_access.delete_rows(rows_to_delete);
+        let memory_limit_exceeded = {
+            let mut write_access = self.inner.data.lock().await;
+            write_access.delete_rows(rows_to_delete);
+            self.inner.last_update.update(DateTimeAsMicroseconds::now());
+            write_access.take_memory_limit_exceeded()
+        };
+
+        notify_memory_limit_exceeded(memory_limit_exceeded);
+    }
+
+    async fn expire_rows(&self, rows_to_expire: Vec<my_no_sql_tcp_shared::DeleteRowTcpContract>) {
+        let memory_limit_exceeded = {
+            let mut write_access = self.inner.data.lock().await;
+            write_access.expire_rows(rows_to_expire);
+            self.inner.last_update.update(DateTimeAsMicroseconds::now());
+            write_access.take_memory_limit_exceeded()
+        };
+
+        notify_memory_limit_exceeded(memory_limit_exceeded);
     }
 }
 
@@ -317,6 +671,10 @@ where
         self.get_table_snapshot_as_vec().await
     }
 
+    async fn get_full_snapshot(&self) -> BTreeMap<String, BTreeMap<String, Arc<TMyNoSqlEntity>>> {
+        self.get_full_snapshot().await
+    }
+
     async fn get_by_partition_key(
         &self,
         partition_key: &str,
@@ -335,6 +693,29 @@ where
         self.get_entity(partition_key, row_key).await
     }
 
+    async fn contains_entity(&self, partition_key: &str, row_key: &str) -> bool {
+        self.contains_entity(partition_key, row_key).await
+    }
+
+    async fn get_entity_as_json_value(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Option<serde_json::Value> {
+        self.get_entity_as_json_value(partition_key, row_key).await
+    }
+
+    async fn get_partition_raw(&self, partition_key: &str) -> Option<Vec<u8>> {
+        self.get_partition_raw(partition_key).await
+    }
+
+    async fn get_entities_changed_since(
+        &self,
+        since: DateTimeAsMicroseconds,
+    ) -> Vec<Arc<TMyNoSqlEntity>> {
+        self.get_entities_changed_since(since).await
+    }
+
     fn get_entities<'s>(&self, partition_key: &'s str) -> GetEntitiesBuilder<TMyNoSqlEntity> {
         self.get_entities(partition_key)
     }
@@ -351,6 +732,74 @@ where
         self.has_partition(partition_key).await
     }
 
+    async fn for_each_in_partition<TCallback: FnMut(&str, &Arc<TMyNoSqlEntity>) + Send>(
+        &self,
+        partition_key: &str,
+        callback: TCallback,
+    ) {
+        self.for_each_in_partition(partition_key, callback).await
+    }
+
+    async fn count_entities<TPredicate: Fn(&TMyNoSqlEntity) -> bool + Send>(
+        &self,
+        partition_key: &str,
+        predicate: TPredicate,
+    ) -> usize {
+        self.count_entities(partition_key, predicate).await
+    }
+
+    async fn seed<TEntities: Iterator<Item = Arc<TMyNoSqlEntity>> + Send>(
+        &self,
+        entities: TEntities,
+    ) {
+        self.seed(entities).await
+    }
+
+    async fn wait_for_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        timeout: Duration,
+    ) -> Option<Arc<TMyNoSqlEntity>> {
+        self.wait_for_entity(partition_key, row_key, timeout).await
+    }
+
+    async fn build_secondary_index<
+        K: ToString + 'static,
+        TExtractor: Fn(&TMyNoSqlEntity) -> Option<K> + Send + Sync + 'static,
+    >(
+        &self,
+        index_name: &str,
+        extractor: TExtractor,
+    ) {
+        self.build_secondary_index(index_name, extractor).await
+    }
+
+    async fn get_by_secondary<K: ToString + ?Sized + Sync>(
+        &self,
+        index_name: &str,
+        value: &K,
+    ) -> Vec<Arc<TMyNoSqlEntity>> {
+        self.get_by_secondary(index_name, value).await
+    }
+
+    async fn set_memory_limit(
+        &self,
+        bytes: usize,
+        on_exceed: MemoryLimitCallback,
+        stop_updates: bool,
+    ) {
+        self.set_memory_limit(bytes, on_exceed, stop_updates).await
+    }
+
+    async fn enable_hash_index(&self) {
+        self.enable_hash_index().await
+    }
+
+    async fn drain_changes(&self) -> ChangeBatch<TMyNoSqlEntity> {
+        self.drain_changes().await
+    }
+
     async fn wait_until_first_data_arrives(&self) {
         loop {
             {
@@ -374,3 +823,349 @@ where
         write_access.assign_callback(callbacks).await;
     }
 }
+
+// Memory limit callback is the user code, so it is invoked after the lock of the data is released
+fn notify_memory_limit_exceeded(memory_limit_exceeded: Option<MemoryLimitExceeded>) {
+    if let Some(memory_limit_exceeded) = memory_limit_exceeded {
+        memory_limit_exceeded.notify();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use my_no_sql_tcp_shared::{sync_to_main::SyncToMainNodeHandler, DeleteRowTcpContract};
+    use rust_extensions::AppStates;
+
+    use crate::{subscribers::UpdateEvent, test_fixtures::TestRow, ConnectionLiveness};
+
+    use super::MyNoSqlDataReaderTcp;
+
+    async fn create_reader(liveness: Arc<ConnectionLiveness>) -> MyNoSqlDataReaderTcp<TestRow> {
+        MyNoSqlDataReaderTcp::new(
+            "test".to_string(),
+            Arc::new(AppStates::create_initialized()),
+            Arc::new(SyncToMainNodeHandler::new(my_logger::LOGGER.clone())),
+            liveness,
+            None,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_quiet_table_is_fresh_while_connection_is_alive() {
+        let liveness = Arc::new(ConnectionLiveness::new());
+        let reader = create_reader(liveness.clone()).await;
+
+        assert!(reader.is_stale(Duration::from_secs(60)));
+
+        liveness.connected();
+        // Not initialized by the connection yet
+        assert!(reader.is_stale(Duration::from_secs(60)));
+
+        reader.init_table(b"[]".to_vec()).await;
+        assert!(!reader.is_stale(Duration::from_millis(50)));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(reader.is_stale(Duration::from_millis(50)));
+
+        // Pong keeps the table fresh without any update of the data
+        liveness.packet_received();
+        assert!(!reader.is_stale(Duration::from_millis(50)));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_is_stale_until_table_is_initialized_again() {
+        let liveness = Arc::new(ConnectionLiveness::new());
+        let reader = create_reader(liveness.clone()).await;
+
+        liveness.connected();
+        reader.init_table(b"[]".to_vec()).await;
+        assert!(!reader.is_stale(Duration::from_secs(60)));
+
+        liveness.connected();
+        assert!(reader.is_stale(Duration::from_secs(60)));
+
+        reader.init_table(b"[]".to_vec()).await;
+        assert!(!reader.is_stale(Duration::from_secs(60)));
+    }
+
+    #[tokio::test]
+    async fn test_full_snapshot() {
+        let reader = create_reader(Arc::new(ConnectionLiveness::new())).await;
+
+        assert!(reader.get_full_snapshot().await.is_empty());
+
+        reader
+            .init_table(
+                br#"[{"PartitionKey":"pk1","RowKey":"rk1","value":1},{"PartitionKey":"pk1","RowKey":"rk2","value":2},{"PartitionKey":"pk2","RowKey":"rk1","value":3}]"#
+                    .to_vec(),
+            )
+            .await;
+
+        let snapshot = reader.get_full_snapshot().await;
+
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot["pk1"].len(), 2);
+        assert_eq!(snapshot["pk1"]["rk2"].value, 2);
+        assert_eq!(snapshot["pk2"]["rk1"].value, 3);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_entity() {
+        let reader = create_reader(Arc::new(ConnectionLiveness::new())).await;
+        reader.init_table(b"[]".to_vec()).await;
+
+        let (entity, _) = tokio::join!(
+            reader.wait_for_entity("pk", "rk", Duration::from_secs(5)),
+            async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                reader
+                    .update_rows(br#"[{"PartitionKey":"pk","RowKey":"rk","value":1}]"#.to_vec())
+                    .await;
+            }
+        );
+
+        assert_eq!(entity.unwrap().value, 1);
+
+        // Entity which is already in the table is returned without waiting
+        let entity = reader
+            .wait_for_entity("pk", "rk", Duration::from_millis(1))
+            .await;
+        assert!(entity.is_some());
+
+        let entity = reader
+            .wait_for_entity("pk", "rk2", Duration::from_millis(50))
+            .await;
+        assert!(entity.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_for_each_in_partition() {
+        let reader = create_reader(Arc::new(ConnectionLiveness::new())).await;
+        reader
+            .init_table(
+                br#"[{"PartitionKey":"pk1","RowKey":"rk1","value":1},{"PartitionKey":"pk1","RowKey":"rk2","value":2},{"PartitionKey":"pk2","RowKey":"rk1","value":3}]"#
+                    .to_vec(),
+            )
+            .await;
+
+        let mut visited = Vec::new();
+        reader
+            .for_each_in_partition("pk1", |row_key, entity| {
+                visited.push((row_key.to_string(), entity.value))
+            })
+            .await;
+
+        assert_eq!(
+            visited,
+            vec![("rk1".to_string(), 1), ("rk2".to_string(), 2)]
+        );
+
+        let mut visited = 0;
+        reader
+            .for_each_in_partition("pk3", |_, _| visited += 1)
+            .await;
+        assert_eq!(visited, 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_partition_raw() {
+        let reader = create_reader(Arc::new(ConnectionLiveness::new())).await;
+        assert!(reader.get_partition_raw("pk").await.is_none());
+
+        reader
+            .init_table(
+                br#"[{"PartitionKey":"pk","RowKey":"rk1","value":1},{"PartitionKey":"pk","RowKey":"rk2","value":2}]"#
+                    .to_vec(),
+            )
+            .await;
+
+        let raw = reader.get_partition_raw("pk").await.unwrap();
+        let raw: serde_json::Value = serde_json::from_slice(&raw).unwrap();
+
+        assert_eq!(raw.as_array().unwrap().len(), 2);
+        assert_eq!(raw[0]["RowKey"], "rk1");
+        assert_eq!(raw[1]["value"], 2);
+
+        assert!(reader.get_partition_raw("pk2").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_count_entities() {
+        let reader = create_reader(Arc::new(ConnectionLiveness::new())).await;
+        assert_eq!(reader.count_entities("pk", |_| true).await, 0);
+
+        reader
+            .init_table(
+                br#"[{"PartitionKey":"pk","RowKey":"rk1","value":1},{"PartitionKey":"pk","RowKey":"rk2","value":2},{"PartitionKey":"pk","RowKey":"rk3","value":3}]"#
+                    .to_vec(),
+            )
+            .await;
+
+        assert_eq!(reader.count_entities("pk", |_| true).await, 3);
+        assert_eq!(
+            reader.count_entities("pk", |entity| entity.value >= 2).await,
+            2
+        );
+        assert_eq!(reader.count_entities("pk2", |_| true).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_expired_rows_are_removed() {
+        let reader = create_reader(Arc::new(ConnectionLiveness::new())).await;
+        reader
+            .init_table(
+                br#"[{"PartitionKey":"pk1","RowKey":"rk1"},{"PartitionKey":"pk1","RowKey":"rk2"},{"PartitionKey":"pk2","RowKey":"rk1"}]"#
+                    .to_vec(),
+            )
+            .await;
+
+        reader
+            .expire_rows(vec![
+                DeleteRowTcpContract {
+                    partition_key: "pk1".to_string(),
+                    row_key: "rk1".to_string(),
+                },
+                DeleteRowTcpContract {
+                    partition_key: "pk2".to_string(),
+                    row_key: "rk1".to_string(),
+                },
+            ])
+            .await;
+
+        assert!(reader.get_entity("pk1", "rk1").await.is_none());
+        assert!(reader.get_entity("pk1", "rk2").await.is_some());
+        assert_eq!(reader.get_partition_keys().await, vec!["pk1".to_string()]);
+    }
+
+    fn snapshot_path(test_name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "my-no-sql-reader-{}-{}.json",
+            test_name,
+            std::process::id()
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_is_loaded_until_table_is_initialized() {
+        let path = snapshot_path("snapshot-round-trip");
+
+        let reader = create_reader(Arc::new(ConnectionLiveness::new())).await;
+        reader
+            .init_table(
+                br#"[{"PartitionKey":"pk","RowKey":"rk1","value":1},{"PartitionKey":"pk","RowKey":"rk2","value":2}]"#
+                    .to_vec(),
+            )
+            .await;
+        reader.save_snapshot(&path).await.unwrap();
+
+        let restarted = create_reader(Arc::new(ConnectionLiveness::new())).await;
+        restarted.load_snapshot(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(restarted.get_entity("pk", "rk1").await.unwrap().value, 1);
+        assert_eq!(restarted.get_entity("pk", "rk2").await.unwrap().value, 2);
+
+        // Server data replaces the snapshot completely
+        restarted
+            .init_table(br#"[{"PartitionKey":"pk","RowKey":"rk3","value":3}]"#.to_vec())
+            .await;
+
+        assert!(restarted.get_entity("pk", "rk1").await.is_none());
+        assert_eq!(restarted.get_entity("pk", "rk3").await.unwrap().value, 3);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_is_not_saved_before_table_is_initialized() {
+        let path = snapshot_path("snapshot-not-initialized");
+
+        let reader = create_reader(Arc::new(ConnectionLiveness::new())).await;
+        reader.save_snapshot(&path).await.unwrap();
+
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_is_ignored_when_table_is_initialized() {
+        let path = snapshot_path("snapshot-ignored");
+        std::fs::write(
+            &path,
+            br#"[{"PartitionKey":"pk","RowKey":"rk1","value":1}]"#,
+        )
+        .unwrap();
+
+        let reader = create_reader(Arc::new(ConnectionLiveness::new())).await;
+        reader
+            .init_table(br#"[{"PartitionKey":"pk","RowKey":"rk2","value":2}]"#.to_vec())
+            .await;
+
+        reader.load_snapshot(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(reader.get_entity("pk", "rk1").await.is_none());
+        assert_eq!(reader.get_entity("pk", "rk2").await.unwrap().value, 2);
+    }
+
+    #[tokio::test]
+    async fn test_broken_snapshot_is_rejected() {
+        let path = snapshot_path("snapshot-broken");
+        // Entity without RowKey
+        std::fs::write(&path, br#"[{"PartitionKey":"pk","value":1}]"#).unwrap();
+
+        let reader = create_reader(Arc::new(ConnectionLiveness::new())).await;
+        let err = reader.load_snapshot(&path).await.unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(reader.get_partition_keys().await.is_empty());
+    }
+
+    #[cfg(feature = "fresh-reads")]
+    mod fresh_reads {
+        use std::sync::Arc;
+
+        use my_no_sql_abstractions::DataSynchronizationPeriod;
+        use my_no_sql_data_writer::{MyNoSqlDataWriter, MyNoSqlWriterSettings};
+
+        use crate::{test_fixtures::TestRow, ConnectionLiveness};
+
+        struct TestSettings;
+
+        #[async_trait::async_trait]
+        impl MyNoSqlWriterSettings for TestSettings {
+            async fn get_url(&self) -> String {
+                "http://127.0.0.1:5123".to_string()
+            }
+        }
+
+        fn create_writer(table_name: &str) -> Arc<MyNoSqlDataWriter<TestRow>> {
+            Arc::new(MyNoSqlDataWriter::new_with_table_name(
+                Arc::new(TestSettings),
+                table_name.to_string(),
+                None,
+                DataSynchronizationPeriod::Immediately,
+            ))
+        }
+
+        #[tokio::test]
+        async fn test_fresh_reads_writer_is_set_once() {
+            let reader = super::create_reader(Arc::new(ConnectionLiveness::new())).await;
+
+            assert!(reader.set_fresh_reads_writer(create_writer("test")).is_ok());
+            assert!(reader
+                .set_fresh_reads_writer(create_writer("test"))
+                .is_err());
+        }
+
+        #[tokio::test]
+        async fn test_fresh_reads_writer_of_other_table_is_rejected() {
+            let reader = super::create_reader(Arc::new(ConnectionLiveness::new())).await;
+
+            assert!(reader
+                .set_fresh_reads_writer(create_writer("test-tenant"))
+                .is_err());
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+
+use super::MyNoSqlDataReaderData;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Generation(u64);
+
+impl Generation {
+    pub fn next(&self) -> Self {
+        Self(self.0 + 1)
+    }
+
+    pub fn get_value(&self) -> u64 {
+        self.0
+    }
+}
+
+// Several partitions copied at the same generation. Entities are shared with the reader,
+// so only the maps are copied and updates are not blocked while the snapshot is alive
+pub struct PartitionsSnapshot<TMyNoSqlEntity: MyNoSqlEntity + Send + Sync + 'static> {
+    generation: Generation,
+    partitions: BTreeMap<String, BTreeMap<String, Arc<TMyNoSqlEntity>>>,
+}
+
+impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static>
+    PartitionsSnapshot<TMyNoSqlEntity>
+{
+    pub fn new(data: &mut MyNoSqlDataReaderData<TMyNoSqlEntity>, partition_keys: &[&str]) -> Self {
+        let mut partitions = BTreeMap::new();
+
+        for partition_key in partition_keys {
+            if let Some(partition) = data.get_by_partition(partition_key) {
+                partitions.insert(partition_key.to_string(), partition);
+            }
+        }
+
+        Self {
+            generation: data.get_generation(),
+            partitions,
+        }
+    }
+
+    pub fn get_generation(&self) -> Generation {
+        self.generation
+    }
+
+    pub fn get_entity(&self, partition_key: &str, row_key: &str) -> Option<Arc<TMyNoSqlEntity>> {
+        self.partitions.get(partition_key)?.get(row_key).cloned()
+    }
+
+    pub fn get_by_partition_key(
+        &self,
+        partition_key: &str,
+    ) -> Option<&BTreeMap<String, Arc<TMyNoSqlEntity>>> {
+        self.partitions.get(partition_key)
+    }
+
+    pub fn get_by_partition_key_as_vec(
+        &self,
+        partition_key: &str,
+    ) -> Option<Vec<Arc<TMyNoSqlEntity>>> {
+        let partition = self.partitions.get(partition_key)?;
+        Some(partition.values().cloned().collect())
+    }
+
+    pub fn has_partition(&self, partition_key: &str) -> bool {
+        self.partitions.contains_key(partition_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use rust_extensions::AppStates;
+
+    use super::PartitionsSnapshot;
+    use crate::subscribers::{LazyMyNoSqlEntity, MyNoSqlDataReaderData};
+    use crate::test_fixtures::TestRow;
+
+    // Both partitions are written in the same batch
+    fn batch(value: u64) -> BTreeMap<String, Vec<LazyMyNoSqlEntity<TestRow>>> {
+        let mut result = BTreeMap::new();
+
+        for partition_key in ["pk1", "pk2"] {
+            let entity = TestRow {
+                partition_key: partition_key.to_string(),
+                row_key: "rk".to_string(),
+                value,
+                ..Default::default()
+            };
+
+            result.insert(partition_key.to_string(), vec![entity.into()]);
+        }
+
+        result
+    }
+
+    fn read_values(snapshot: &PartitionsSnapshot<TestRow>) -> (u64, u64) {
+        (
+            snapshot.get_entity("pk1", "rk").unwrap().value,
+            snapshot.get_entity("pk2", "rk").unwrap().value,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_is_pinned_to_generation() {
+        let mut data = MyNoSqlDataReaderData::new(
+            "test".to_string(),
+            Arc::new(AppStates::create_initialized()),
+        )
+        .await;
+
+        data.update_rows(batch(1));
+
+        let snapshot = PartitionsSnapshot::new(&mut data, &["pk1", "pk2"]);
+        let generation = snapshot.get_generation();
+
+        data.update_rows(batch(2));
+
+        assert_eq!(read_values(&snapshot), (1, 1));
+        assert_eq!(snapshot.get_generation(), generation);
+
+        let snapshot = PartitionsSnapshot::new(&mut data, &["pk1", "pk2"]);
+        assert_eq!(read_values(&snapshot), (2, 2));
+        assert_eq!(snapshot.get_generation(), generation.next());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_keeps_only_requested_partitions() {
+        let mut data = MyNoSqlDataReaderData::new(
+            "test".to_string(),
+            Arc::new(AppStates::create_initialized()),
+        )
+        .await;
+
+        data.update_rows(batch(1));
+
+        let snapshot = PartitionsSnapshot::new(&mut data, &["pk1", "pk3"]);
+
+        assert!(snapshot.has_partition("pk1"));
+        assert!(!snapshot.has_partition("pk2"));
+        assert!(!snapshot.has_partition("pk3"));
+        assert_eq!(
+            snapshot.get_by_partition_key_as_vec("pk1").unwrap().len(),
+            1
+        );
+        assert!(snapshot.get_by_partition_key("pk2").is_none());
+    }
+}
@@ -0,0 +1,42 @@
+use my_no_sql_core::db_json_entity::consts;
+
+const SYSTEM_FIELDS: [&str; 4] = [
+    consts::PARTITION_KEY,
+    consts::ROW_KEY,
+    consts::TIME_STAMP,
+    consts::EXPIRES,
+];
+
+// Keeps only the projected fields and the system ones (PartitionKey, RowKey, TimeStamp, Expires)
+pub fn project_entity(src: &[u8], fields: &[&str]) -> Result<Vec<u8>, String> {
+    let mut entity: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_slice(src).map_err(|err| err.to_string())?;
+
+    entity.retain(|name, _| {
+        fields.contains(&name.as_str())
+            || SYSTEM_FIELDS
+                .iter()
+                .any(|system_field| system_field.eq_ignore_ascii_case(name))
+    });
+
+    serde_json::to_vec(&entity).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_project_entity() {
+        let src = r#"{"PartitionKey":"Pk","RowKey":"Rk","timestamp":"2024-01-01T00:00:00","A":1,"B":{"C":2},"D":"Big"}"#;
+
+        let result = super::project_entity(src.as_bytes(), &["A", "B"]).unwrap();
+
+        let result: serde_json::Value = serde_json::from_slice(result.as_slice()).unwrap();
+
+        assert_eq!(result["PartitionKey"], "Pk");
+        assert_eq!(result["RowKey"], "Rk");
+        assert_eq!(result["timestamp"], "2024-01-01T00:00:00");
+        assert_eq!(result["A"], 1);
+        assert_eq!(result["B"]["C"], 2);
+        assert!(result.get("D").is_none());
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+
+pub type SecondaryKeyExtractor<TMyNoSqlEntity> =
+    Box<dyn Fn(&TMyNoSqlEntity) -> Option<String> + Send + Sync + 'static>;
+
+pub struct SecondaryIndex<
+    TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static,
+> {
+    extractor: SecondaryKeyExtractor<TMyNoSqlEntity>,
+    // PartitionKey -> RowKey -> secondary key. Used to find the entry to remove
+    keys: BTreeMap<String, BTreeMap<String, String>>,
+    // Secondary key -> (PartitionKey, RowKey) -> entity
+    entities: HashMap<String, BTreeMap<(String, String), Arc<TMyNoSqlEntity>>>,
+}
+
+impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static>
+    SecondaryIndex<TMyNoSqlEntity>
+{
+    pub fn new(extractor: SecondaryKeyExtractor<TMyNoSqlEntity>) -> Self {
+        Self {
+            extractor,
+            keys: BTreeMap::new(),
+            entities: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, entity: &Arc<TMyNoSqlEntity>) {
+        let partition_key = entity.get_partition_key();
+        let row_key = entity.get_row_key();
+
+        self.remove(partition_key, row_key);
+
+        let Some(secondary_key) = (self.extractor)(entity) else {
+            return;
+        };
+
+        self.entities
+            .entry(secondary_key.clone())
+            .or_default()
+            .insert(
+                (partition_key.to_string(), row_key.to_string()),
+                entity.clone(),
+            );
+
+        self.keys
+            .entry(partition_key.to_string())
+            .or_default()
+            .insert(row_key.to_string(), secondary_key);
+    }
+
+    pub fn remove(&mut self, partition_key: &str, row_key: &str) {
+        let Some(partition) = self.keys.get_mut(partition_key) else {
+            return;
+        };
+
+        let Some(secondary_key) = partition.remove(row_key) else {
+            return;
+        };
+
+        if partition.is_empty() {
+            self.keys.remove(partition_key);
+        }
+
+        self.remove_from_entities(&secondary_key, partition_key, row_key);
+    }
+
+    pub fn remove_partition(&mut self, partition_key: &str) {
+        let Some(partition) = self.keys.remove(partition_key) else {
+            return;
+        };
+
+        for (row_key, secondary_key) in partition {
+            self.remove_from_entities(&secondary_key, partition_key, &row_key);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.entities.clear();
+    }
+
+    pub fn get(&self, secondary_key: &str) -> Vec<Arc<TMyNoSqlEntity>> {
+        match self.entities.get(secondary_key) {
+            Some(entities) => entities.values().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn remove_from_entities(&mut self, secondary_key: &str, partition_key: &str, row_key: &str) {
+        if let Some(entities) = self.entities.get_mut(secondary_key) {
+            entities.remove(&(partition_key.to_string(), row_key.to_string()));
+
+            if entities.is_empty() {
+                self.entities.remove(secondary_key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use my_no_sql_abstractions::MyNoSqlEntity;
+
+    use super::SecondaryIndex;
+    use crate::test_fixtures::TestRow;
+
+    fn row(partition_key: &str, row_key: &str, email: Option<&str>) -> Arc<TestRow> {
+        Arc::new(TestRow {
+            partition_key: partition_key.to_string(),
+            row_key: row_key.to_string(),
+            email: email.map(|itm| itm.to_string()),
+            ..Default::default()
+        })
+    }
+
+    fn create_index() -> SecondaryIndex<TestRow> {
+        SecondaryIndex::new(Box::new(|entity: &TestRow| entity.email.clone()))
+    }
+
+    #[test]
+    fn test_update_moves_entity_to_the_new_key() {
+        let mut index = create_index();
+
+        index.insert(&row("pk1", "rk1", Some("a@test")));
+        index.insert(&row("pk2", "rk1", Some("a@test")));
+        assert_eq!(index.get("a@test").len(), 2);
+
+        index.insert(&row("pk1", "rk1", Some("b@test")));
+
+        assert_eq!(index.get("a@test").len(), 1);
+        assert_eq!(index.get("b@test")[0].get_partition_key(), "pk1");
+    }
+
+    #[test]
+    fn test_entities_without_key_are_not_indexed() {
+        let mut index = create_index();
+
+        index.insert(&row("pk1", "rk1", Some("a@test")));
+        index.insert(&row("pk1", "rk1", None));
+
+        assert!(index.get("a@test").is_empty());
+        assert!(index.keys.is_empty());
+    }
+
+    #[test]
+    fn test_remove_and_remove_partition() {
+        let mut index = create_index();
+
+        index.insert(&row("pk1", "rk1", Some("a@test")));
+        index.insert(&row("pk1", "rk2", Some("a@test")));
+        index.insert(&row("pk2", "rk1", Some("a@test")));
+
+        index.remove("pk1", "rk1");
+        assert_eq!(index.get("a@test").len(), 2);
+
+        index.remove_partition("pk1");
+        let result = index.get("a@test");
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].get_partition_key(), "pk2");
+
+        index.remove_partition("pk2");
+        assert!(index.entities.is_empty());
+    }
+}
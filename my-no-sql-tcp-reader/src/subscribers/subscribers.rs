@@ -5,6 +5,8 @@ use my_no_sql_tcp_shared::sync_to_main::SyncToMainNodeHandler;
 use rust_extensions::ApplicationStates;
 use tokio::sync::RwLock;
 
+use crate::ConnectionLiveness;
+
 use super::{MyNoSqlDataReaderTcp, UpdateEvent};
 
 pub struct Subscribers {
@@ -20,26 +22,33 @@ impl Subscribers {
 
     pub async fn create_subscriber<TMyNoSqlEntity>(
         &self,
+        table_name: String,
         app_states: Arc<dyn ApplicationStates + Send + Sync + 'static>,
         sync_handler: Arc<SyncToMainNodeHandler>,
+        liveness: Arc<ConnectionLiveness>,
+        projection: Option<Vec<&'static str>>,
     ) -> Arc<MyNoSqlDataReaderTcp<TMyNoSqlEntity>>
     where
         TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
     {
         let mut write_access = self.subscribers.write().await;
 
-        if write_access.contains_key(TMyNoSqlEntity::TABLE_NAME) {
-            panic!(
-                "You already subscribed for the table {}",
-                TMyNoSqlEntity::TABLE_NAME
-            );
+        if write_access.contains_key(table_name.as_str()) {
+            panic!("You already subscribed for the table {}", table_name);
         }
 
-        let new_reader = MyNoSqlDataReaderTcp::new(app_states, sync_handler).await;
+        let new_reader = MyNoSqlDataReaderTcp::new(
+            table_name.clone(),
+            app_states,
+            sync_handler,
+            liveness,
+            projection,
+        )
+        .await;
 
         let new_reader = Arc::new(new_reader);
 
-        write_access.insert(TMyNoSqlEntity::TABLE_NAME.to_string(), new_reader.clone());
+        write_access.insert(table_name, new_reader.clone());
 
         new_reader
     }
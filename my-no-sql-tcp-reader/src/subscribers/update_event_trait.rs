@@ -7,4 +7,5 @@ pub trait UpdateEvent {
     async fn init_partition(&self, partition_key: &str, data: Vec<u8>);
     async fn update_rows(&self, data: Vec<u8>);
     async fn delete_rows(&self, rows_to_delete: Vec<DeleteRowTcpContract>);
+    async fn expire_rows(&self, rows_to_expire: Vec<DeleteRowTcpContract>);
 }
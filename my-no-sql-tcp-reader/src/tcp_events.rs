@@ -4,14 +4,19 @@ use my_no_sql_tcp_shared::{
     sync_to_main::SyncToMainNodeHandler, MyNoSqlReaderTcpSerializer, MyNoSqlTcpContract,
 };
 use my_tcp_sockets::{tcp_connection::TcpSocketConnection, SocketEventCallback};
+use tokio::sync::Mutex;
 
-use crate::subscribers::Subscribers;
+use crate::{subscribers::Subscribers, ConnectionLiveness};
 
 pub type TcpConnection = TcpSocketConnection<MyNoSqlTcpContract, MyNoSqlReaderTcpSerializer, ()>;
 pub struct TcpEvents {
     app_name: String,
     pub subscribers: Subscribers,
     pub sync_handler: Arc<SyncToMainNodeHandler>,
+    pub liveness: Arc<ConnectionLiveness>,
+    connection: Mutex<Option<Arc<TcpConnection>>>,
+    #[cfg(feature = "debug_frames")]
+    pub frames_recorder: crate::frames_recorder::FramesRecorder,
 }
 
 impl TcpEvents {
@@ -20,8 +25,17 @@ impl TcpEvents {
             app_name,
             subscribers: Subscribers::new(),
             sync_handler,
+            liveness: Arc::new(ConnectionLiveness::new()),
+            connection: Mutex::new(None),
+            #[cfg(feature = "debug_frames")]
+            frames_recorder: crate::frames_recorder::FramesRecorder::new(),
         }
     }
+
+    pub async fn get_connection(&self) -> Option<Arc<TcpConnection>> {
+        let connection = self.connection.lock().await;
+        connection.clone()
+    }
     pub async fn handle_incoming_packet(
         &self,
         _tcp_contract: MyNoSqlTcpContract,
@@ -36,6 +50,8 @@ impl SocketEventCallback<MyNoSqlTcpContract, MyNoSqlReaderTcpSerializer, ()> for
         &self,
         connection: Arc<TcpSocketConnection<MyNoSqlTcpContract, MyNoSqlReaderTcpSerializer, ()>>,
     ) {
+        self.liveness.connected();
+
         let contract = MyNoSqlTcpContract::Greeting {
             name: self.app_name.to_string(),
         };
@@ -50,6 +66,11 @@ impl SocketEventCallback<MyNoSqlTcpContract, MyNoSqlReaderTcpSerializer, ()> for
             connection.send(&contract).await;
         }
 
+        {
+            let mut current_connection = self.connection.lock().await;
+            *current_connection = Some(connection.clone());
+        }
+
         self.sync_handler
             .tcp_events_pusher_new_connection_established(connection);
     }
@@ -58,6 +79,15 @@ impl SocketEventCallback<MyNoSqlTcpContract, MyNoSqlReaderTcpSerializer, ()> for
         &self,
         connection: Arc<TcpSocketConnection<MyNoSqlTcpContract, MyNoSqlReaderTcpSerializer, ()>>,
     ) {
+        {
+            let mut current_connection = self.connection.lock().await;
+            if let Some(current) = current_connection.as_ref() {
+                if Arc::ptr_eq(current, &connection) {
+                    *current_connection = None;
+                }
+            }
+        }
+
         self.sync_handler
             .tcp_events_pusher_connection_disconnected(connection);
     }
@@ -67,6 +97,16 @@ impl SocketEventCallback<MyNoSqlTcpContract, MyNoSqlReaderTcpSerializer, ()> for
         _connection: &Arc<TcpSocketConnection<MyNoSqlTcpContract, MyNoSqlReaderTcpSerializer, ()>>,
         contract: MyNoSqlTcpContract,
     ) {
+        #[cfg(feature = "debug_frames")]
+        self.frames_recorder.record(&contract);
+
+        self.liveness.packet_received();
+        self.apply_contract(contract).await;
+    }
+}
+
+impl TcpEvents {
+    pub async fn apply_contract(&self, contract: MyNoSqlTcpContract) {
         match contract {
             MyNoSqlTcpContract::Ping => {}
             MyNoSqlTcpContract::Pong => {}
@@ -99,6 +139,11 @@ impl SocketEventCallback<MyNoSqlTcpContract, MyNoSqlReaderTcpSerializer, ()> for
                     update_event.as_ref().delete_rows(rows).await;
                 }
             }
+            MyNoSqlTcpContract::ExpireRows { table_name, rows } => {
+                if let Some(update_event) = self.subscribers.get(table_name.as_str()).await {
+                    update_event.as_ref().expire_rows(rows).await;
+                }
+            }
             MyNoSqlTcpContract::Error { message } => {
                 panic!("Server error: {}", message);
             }
@@ -0,0 +1,42 @@
+use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+use serde::{Deserialize, Serialize};
+
+// Entity shared by the unit tests of the crate
+#[derive(Default, Serialize, Deserialize)]
+pub struct TestRow {
+    #[serde(rename = "PartitionKey")]
+    pub partition_key: String,
+    #[serde(rename = "RowKey")]
+    pub row_key: String,
+    #[serde(default)]
+    pub value: u64,
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+impl MyNoSqlEntity for TestRow {
+    const TABLE_NAME: &'static str = "test";
+    const LAZY_DESERIALIZATION: bool = false;
+
+    fn get_partition_key(&self) -> &str {
+        &self.partition_key
+    }
+
+    fn get_row_key(&self) -> &str {
+        &self.row_key
+    }
+
+    fn get_time_stamp(&self) -> i64 {
+        0
+    }
+}
+
+impl MyNoSqlEntitySerializer for TestRow {
+    fn serialize_entity(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap()
+    }
+
+    fn deserialize_entity(src: &[u8]) -> Result<Self, String> {
+        serde_json::from_slice(src).map_err(|err| err.to_string())
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+// Keeps the events in the order they were queued and finds the event to merge an update into by its key
+pub struct IndexedEventsQueue<TKey: Hash + Eq + Clone, TEvent> {
+    events: BTreeMap<i64, (TKey, TEvent)>,
+    index: HashMap<TKey, i64>,
+    next_id: i64,
+}
+
+impl<TKey: Hash + Eq + Clone, TEvent> IndexedEventsQueue<TKey, TEvent> {
+    pub fn new() -> Self {
+        Self {
+            events: BTreeMap::new(),
+            index: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn get_id(&self, key: &TKey) -> Option<i64> {
+        self.index.get(key).copied()
+    }
+
+    pub fn get_mut(&mut self, id: i64) -> Option<&mut TEvent> {
+        self.events.get_mut(&id).map(|(_, event)| event)
+    }
+
+    pub fn push_back(&mut self, key: TKey, event: TEvent) -> i64 {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.index.insert(key.clone(), id);
+        self.events.insert(id, (key, event));
+        id
+    }
+
+    // Event which was on delivery goes back to the head of the queue.
+    // Updates of the same key which came after it stay after it
+    pub fn push_front(&mut self, key: TKey, event: TEvent) -> i64 {
+        let id = match self.events.first_key_value() {
+            Some((first_id, _)) => first_id - 1,
+            None => {
+                let id = self.next_id;
+                self.next_id += 1;
+                id
+            }
+        };
+
+        if !self.index.contains_key(&key) {
+            self.index.insert(key.clone(), id);
+        }
+
+        self.events.insert(id, (key, event));
+        id
+    }
+
+    pub fn pop_front(&mut self) -> Option<(i64, TEvent)> {
+        let (id, (key, event)) = self.events.pop_first()?;
+        self.remove_from_index(&key, id);
+        Some((id, event))
+    }
+
+    pub fn remove(&mut self, id: i64) -> Option<TEvent> {
+        let (key, event) = self.events.remove(&id)?;
+        self.remove_from_index(&key, id);
+        Some(event)
+    }
+
+    fn remove_from_index(&mut self, key: &TKey, id: i64) {
+        if self.index.get(key) == Some(&id) {
+            self.index.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexedEventsQueue;
+
+    #[test]
+    fn test_returned_event_is_delivered_before_newer_event_with_the_same_key() {
+        let mut queue = IndexedEventsQueue::new();
+
+        queue.push_back("table", 1);
+        let (_, event) = queue.pop_front().unwrap();
+
+        let newer_id = queue.push_back("table", 2);
+        queue.push_front("table", event);
+
+        // Updates keep going to the newer event
+        assert_eq!(queue.get_id(&"table"), Some(newer_id));
+
+        assert_eq!(queue.pop_front().unwrap().1, 1);
+        assert_eq!(queue.pop_front().unwrap().1, 2);
+        assert_eq!(queue.get_id(&"table"), None);
+    }
+}
@@ -1,12 +1,14 @@
 mod sync_to_main_node_event;
 mod sync_to_main_node_handler;
 
+mod indexed_events_queue;
 mod sync_to_main_node_handler_inner;
 mod update_entity_statistics_data;
 mod update_partition_expiration_time_queue;
 mod update_partitions_last_read_time_queue;
 mod update_rows_expiration_time_queue;
 mod update_rows_last_read_time_queue;
+use indexed_events_queue::IndexedEventsQueue;
 pub use sync_to_main_node_event::*;
 pub use sync_to_main_node_handler::*;
 pub use update_entity_statistics_data::*;
@@ -17,6 +19,8 @@ pub use update_rows_last_read_time_queue::*;
 
 mod sync_to_main_node_queue;
 pub use sync_to_main_node_queue::*;
+mod sync_to_main_operations;
+pub use sync_to_main_operations::*;
 
 type DataReaderTcpConnection = my_tcp_sockets::tcp_connection::TcpSocketConnection<
     crate::MyNoSqlTcpContract,
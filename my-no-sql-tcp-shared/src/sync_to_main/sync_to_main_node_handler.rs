@@ -1,11 +1,11 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use rust_extensions::{events_loop::EventsLoop, ApplicationStates, Logger};
 use tokio::sync::Mutex;
 
 use super::{
     sync_to_main_node_handler_inner::SyncToMainNodeHandlerInner, DataReaderTcpConnection,
-    SyncToMainNodeEvent, UpdateEntityStatisticsData,
+    SyncToMainNodeEvent, SyncToMainOperationStatus, UpdateEntityStatisticsData,
 };
 
 pub struct SyncToMainNodeHandler {
@@ -15,11 +15,21 @@ pub struct SyncToMainNodeHandler {
 
 impl SyncToMainNodeHandler {
     pub fn new(logger: Arc<dyn Logger + Send + Sync + 'static>) -> Self {
+        Self::new_with_capacity(logger, super::DEFAULT_SYNC_TO_MAIN_QUEUE_CAPACITY)
+    }
+
+    pub fn new_with_capacity(
+        logger: Arc<dyn Logger + Send + Sync + 'static>,
+        queue_capacity: usize,
+    ) -> Self {
         let mut events_loop = EventsLoop::new("SyncToMainNodeQueues".to_string(), logger);
 
         let events_publisher = events_loop.get_publisher();
 
-        let inner = Arc::new(SyncToMainNodeHandlerInner::new(events_publisher));
+        let inner = Arc::new(SyncToMainNodeHandlerInner::new(
+            events_publisher,
+            queue_capacity,
+        ));
 
         events_loop.register_event_loop(inner.clone());
 
@@ -34,6 +44,12 @@ impl SyncToMainNodeHandler {
         events_loop.start(app_states);
     }
 
+    // Updates which are still in the queues are not delivered
+    pub async fn stop(&self) {
+        let events_loop = self.events_loop.lock().await;
+        events_loop.stop();
+    }
+
     pub fn tcp_events_pusher_new_connection_established(
         &self,
         connection: Arc<DataReaderTcpConnection>,
@@ -58,64 +74,82 @@ impl SyncToMainNodeHandler {
             .send(SyncToMainNodeEvent::Delivered(confirmation_id));
     }
 
+    pub async fn get_queue_depth(&self) -> usize {
+        self.inner.queues.lock().await.get_queue_depth()
+    }
+
+    pub async fn get_dropped_updates(&self) -> usize {
+        self.inner.queues.lock().await.get_dropped_updates()
+    }
+
+    pub async fn get_operation_status(&self, operation_id: i64) -> SyncToMainOperationStatus {
+        self.inner
+            .queues
+            .lock()
+            .await
+            .get_operation_status(operation_id)
+    }
+
+    pub async fn is_confirmed(&self, operation_id: i64) -> bool {
+        self.get_operation_status(operation_id).await == SyncToMainOperationStatus::Confirmed
+    }
+
+    // Resolves as soon as the main node confirms the operation or the operation is dropped because the queue is full.
+    // Pending is returned on timeout
+    pub async fn wait_for_confirmation(
+        &self,
+        operation_id: i64,
+        timeout: Duration,
+    ) -> SyncToMainOperationStatus {
+        let wait = async {
+            loop {
+                let notified = self.inner.confirmed.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                let status = self.get_operation_status(operation_id).await;
+
+                if status != SyncToMainOperationStatus::Pending {
+                    return status;
+                }
+
+                notified.await;
+            }
+        };
+
+        match tokio::time::timeout(timeout, wait).await {
+            Ok(status) => status,
+            Err(_) => SyncToMainOperationStatus::Pending,
+        }
+    }
+
+    // Returns the id of the operation to wait the confirmation for. None if there is nothing to update
     pub async fn update<'s, TRowKeys: Iterator<Item = &'s str>>(
         &self,
         table_name: &str,
         partition_key: &str,
         row_keys: impl Fn() -> TRowKeys,
         data: &UpdateEntityStatisticsData,
-    ) {
+    ) -> Option<i64> {
         if !data.has_data_to_update() {
-            return;
+            return None;
         }
 
         let mut inner = self.inner.queues.lock().await;
 
-        if data.partition_last_read_moment {
-            inner
-                .update_partitions_last_read_time_queue
-                .add_partition(table_name, partition_key);
-
-            self.inner
-                .events_publisher
-                .send(SyncToMainNodeEvent::PingToDeliver);
-        }
+        let dropped_before = inner.get_dropped_updates();
 
-        if let Some(partition_expiration) = data.partition_expiration_moment {
-            inner.update_partition_expiration_time_update.add(
-                table_name,
-                partition_key,
-                partition_expiration,
-            );
+        let operation_id = inner.update(table_name, partition_key, row_keys, data);
 
-            self.inner
-                .events_publisher
-                .send(SyncToMainNodeEvent::PingToDeliver);
+        // Operations waiting for the confirmation could be dropped to free the room
+        if inner.get_dropped_updates() != dropped_before {
+            self.inner.confirmed.notify_waiters();
         }
 
-        if data.row_last_read_moment {
-            if data.row_last_read_moment {
-                inner
-                    .update_rows_last_read_time_queue
-                    .add(table_name, partition_key, row_keys());
-
-                self.inner
-                    .events_publisher
-                    .send(SyncToMainNodeEvent::PingToDeliver);
-            }
-        }
+        self.inner
+            .events_publisher
+            .send(SyncToMainNodeEvent::PingToDeliver);
 
-        if let Some(row_expiration) = data.row_expiration_moment {
-            inner.update_rows_expiration_time_queue.add(
-                table_name,
-                partition_key,
-                row_keys(),
-                row_expiration,
-            );
-
-            self.inner
-                .events_publisher
-                .send(SyncToMainNodeEvent::PingToDeliver);
-        }
+        Some(operation_id)
     }
 }
@@ -1,5 +1,5 @@
 use rust_extensions::events_loop::{EventsLoopPublisher, EventsLoopTick};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
 
 use crate::sync_to_main::DeliverToMainNodeEvent;
 
@@ -8,13 +8,18 @@ use super::{SyncToMainNodeEvent, SyncToMainNodeQueue};
 pub struct SyncToMainNodeHandlerInner {
     pub queues: Mutex<SyncToMainNodeQueue>,
     pub events_publisher: EventsLoopPublisher<SyncToMainNodeEvent>,
+    pub confirmed: Notify,
 }
 
 impl SyncToMainNodeHandlerInner {
-    pub fn new(events_publisher: EventsLoopPublisher<SyncToMainNodeEvent>) -> Self {
+    pub fn new(
+        events_publisher: EventsLoopPublisher<SyncToMainNodeEvent>,
+        queue_capacity: usize,
+    ) -> Self {
         Self {
-            queues: Mutex::new(SyncToMainNodeQueue::new()),
+            queues: Mutex::new(SyncToMainNodeQueue::new_with_capacity(queue_capacity)),
             events_publisher,
+            confirmed: Notify::new(),
         }
     }
 }
@@ -40,6 +45,7 @@ impl EventsLoopTick<SyncToMainNodeEvent> for SyncToMainNodeHandlerInner {
             SyncToMainNodeEvent::Delivered(confirmation_id) => {
                 let mut queues = self.queues.lock().await;
                 to_main_node_pusher(&mut queues, Some(confirmation_id)).await;
+                self.confirmed.notify_waiters();
             }
         }
     }
@@ -1,10 +1,11 @@
-use std::sync::Arc;
+use std::{collections::BTreeSet, sync::Arc};
 
 use super::{
-    DataReaderTcpConnection, UpdatePartitionExpirationEvent, UpdatePartitionsExpirationTimeQueue,
-    UpdatePartitionsLastReadTimeEvent, UpdatePartitionsLastReadTimeQueue,
-    UpdateRowsExpirationTimeEvent, UpdateRowsExpirationTimeQueue, UpdateRowsLastReadTimeEvent,
-    UpdateRowsLastReadTimeQueue,
+    DataReaderTcpConnection, SyncToMainOperationStatus, SyncToMainOperations,
+    UpdateEntityStatisticsData, UpdatePartitionExpirationEvent,
+    UpdatePartitionsExpirationTimeQueue, UpdatePartitionsLastReadTimeEvent,
+    UpdatePartitionsLastReadTimeQueue, UpdateRowsExpirationTimeEvent,
+    UpdateRowsExpirationTimeQueue, UpdateRowsLastReadTimeEvent, UpdateRowsLastReadTimeQueue,
 };
 
 #[derive(Debug, Clone)]
@@ -48,8 +49,21 @@ impl DeliverToMainNodeEvent {
             } => *confirmation_id,
         }
     }
+
+    pub fn get_operations(&self) -> &BTreeSet<i64> {
+        match self {
+            DeliverToMainNodeEvent::UpdatePartitionsExpiration { event, .. } => &event.operations,
+            DeliverToMainNodeEvent::UpdatePartitionsLastReadTime { event, .. } => &event.operations,
+            DeliverToMainNodeEvent::UpdateRowsExpirationTime { event, .. } => &event.operations,
+            DeliverToMainNodeEvent::UpdateRowsLastReadTime { event, .. } => &event.operations,
+        }
+    }
 }
 
+// Max amount of row keys (partitions for the partition queues) kept by each queue.
+// When it is reached the oldest events are dropped
+pub const DEFAULT_SYNC_TO_MAIN_QUEUE_CAPACITY: usize = 10_000;
+
 pub struct SyncToMainNodeQueue {
     pub confirmation_id: i64,
     pub update_partition_expiration_time_update: UpdatePartitionsExpirationTimeQueue,
@@ -59,19 +73,103 @@ pub struct SyncToMainNodeQueue {
     pub update_rows_last_read_time_queue: UpdateRowsLastReadTimeQueue,
     pub on_delivery: Option<DeliverToMainNodeEvent>,
     pub connection: Option<Arc<DataReaderTcpConnection>>,
+    operations: SyncToMainOperations,
 }
 
 impl SyncToMainNodeQueue {
     pub fn new() -> Self {
+        Self::new_with_capacity(DEFAULT_SYNC_TO_MAIN_QUEUE_CAPACITY)
+    }
+
+    pub fn new_with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
         Self {
             confirmation_id: 0,
-            update_partition_expiration_time_update: UpdatePartitionsExpirationTimeQueue::new(),
-            update_rows_expiration_time_queue: UpdateRowsExpirationTimeQueue::new(),
-            update_rows_last_read_time_queue: UpdateRowsLastReadTimeQueue::new(),
-            update_partitions_last_read_time_queue: UpdatePartitionsLastReadTimeQueue::new(),
+            update_partition_expiration_time_update: UpdatePartitionsExpirationTimeQueue::new(
+                capacity,
+            ),
+            update_rows_expiration_time_queue: UpdateRowsExpirationTimeQueue::new(capacity),
+            update_rows_last_read_time_queue: UpdateRowsLastReadTimeQueue::new(capacity),
+            update_partitions_last_read_time_queue: UpdatePartitionsLastReadTimeQueue::new(
+                capacity,
+            ),
             on_delivery: None,
             connection: None,
+            operations: SyncToMainOperations::new(),
+        }
+    }
+
+    // Puts the update to the queues and returns the id of the operation to wait the confirmation for
+    pub fn update<'s, TRowKeys: Iterator<Item = &'s str>>(
+        &mut self,
+        table_name: &str,
+        partition_key: &str,
+        row_keys: impl Fn() -> TRowKeys,
+        data: &UpdateEntityStatisticsData,
+    ) -> i64 {
+        let operation_id = self.operations.register();
+
+        if data.partition_last_read_moment {
+            self.update_partitions_last_read_time_queue.add_partition(
+                &mut self.operations,
+                operation_id,
+                table_name,
+                partition_key,
+            );
+        }
+
+        if let Some(partition_expiration) = data.partition_expiration_moment {
+            self.update_partition_expiration_time_update.add(
+                &mut self.operations,
+                operation_id,
+                table_name,
+                partition_key,
+                partition_expiration,
+            );
+        }
+
+        if data.row_last_read_moment {
+            self.update_rows_last_read_time_queue.add(
+                &mut self.operations,
+                operation_id,
+                table_name,
+                partition_key,
+                row_keys(),
+            );
         }
+
+        if let Some(row_expiration) = data.row_expiration_moment {
+            self.update_rows_expiration_time_queue.add(
+                &mut self.operations,
+                operation_id,
+                table_name,
+                partition_key,
+                row_keys(),
+                row_expiration,
+            );
+        }
+
+        operation_id
+    }
+
+    pub fn get_operation_status(&self, operation_id: i64) -> SyncToMainOperationStatus {
+        self.operations.get_status(operation_id)
+    }
+
+    // Amount of row keys and partitions waiting to be delivered to the main node
+    pub fn get_queue_depth(&self) -> usize {
+        self.update_partition_expiration_time_update.len()
+            + self.update_partitions_last_read_time_queue.len()
+            + self.update_rows_expiration_time_queue.len()
+            + self.update_rows_last_read_time_queue.len()
+    }
+
+    // Amount of row keys and partitions dropped because the queue was full
+    pub fn get_dropped_updates(&self) -> usize {
+        self.update_partition_expiration_time_update.get_dropped()
+            + self.update_partitions_last_read_time_queue.get_dropped()
+            + self.update_rows_expiration_time_queue.get_dropped()
+            + self.update_rows_last_read_time_queue.get_dropped()
     }
 
     pub fn new_connection(&mut self, connection: Arc<DataReaderTcpConnection>) {
@@ -92,6 +190,9 @@ impl SyncToMainNodeQueue {
 
                 if on_delivery_confirmation_id != delivery_id {
                     println!("Somehow we are waiting confirmation for delivery with id {}, but we go confirmation id {} which is not the same  as the one we are waiting for. This is a bug.", on_delivery_confirmation_id, delivery_id);
+                    self.operations.event_dropped(event.get_operations());
+                } else {
+                    self.operations.event_released(event.get_operations());
                 }
             }
             None => {
@@ -206,3 +307,106 @@ impl SyncToMainNodeQueue {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{SyncToMainOperationStatus, UpdateEntityStatisticsData},
+        DeliverToMainNodeEvent, SyncToMainNodeQueue,
+    };
+
+    fn rows_last_read() -> UpdateEntityStatisticsData {
+        UpdateEntityStatisticsData {
+            row_last_read_moment: true,
+            ..Default::default()
+        }
+    }
+
+    fn send_next_event(queue: &mut SyncToMainNodeQueue, confirmation_id: i64) {
+        let event = queue.update_rows_last_read_time_queue.dequeue().unwrap();
+        queue.on_delivery = Some(DeliverToMainNodeEvent::UpdateRowsLastReadTime {
+            event,
+            confirmation_id,
+        });
+    }
+
+    #[test]
+    fn test_operation_is_confirmed_after_delivery() {
+        let mut queue = SyncToMainNodeQueue::new();
+
+        let operation_id = queue.update("table", "pk", || ["rk"].into_iter(), &rows_last_read());
+
+        assert!(queue.get_next_event_to_deliver(None).is_none());
+        assert_eq!(
+            queue.get_operation_status(operation_id),
+            SyncToMainOperationStatus::Pending
+        );
+
+        // Event is sent to the main node
+        send_next_event(&mut queue, 1);
+
+        let next_operation_id =
+            queue.update("table", "pk2", || ["rk"].into_iter(), &rows_last_read());
+
+        // Confirmation of the first event comes. The second update is still in the queue
+        queue.get_next_event_to_deliver(Some(1));
+        assert_eq!(
+            queue.get_operation_status(operation_id),
+            SyncToMainOperationStatus::Confirmed
+        );
+        assert_eq!(
+            queue.get_operation_status(next_operation_id),
+            SyncToMainOperationStatus::Pending
+        );
+
+        send_next_event(&mut queue, 2);
+
+        queue.get_next_event_to_deliver(Some(2));
+        assert_eq!(
+            queue.get_operation_status(next_operation_id),
+            SyncToMainOperationStatus::Confirmed
+        );
+    }
+
+    #[test]
+    fn test_operation_merged_into_event_on_delivery_waits_for_next_event() {
+        let mut queue = SyncToMainNodeQueue::new();
+
+        let operation_id = queue.update("table", "pk", || ["rk"].into_iter(), &rows_last_read());
+        send_next_event(&mut queue, 1);
+
+        // Same row is updated while the first event is on delivery
+        let next_operation_id =
+            queue.update("table", "pk", || ["rk"].into_iter(), &rows_last_read());
+
+        queue.get_next_event_to_deliver(Some(1));
+
+        assert_eq!(
+            queue.get_operation_status(operation_id),
+            SyncToMainOperationStatus::Confirmed
+        );
+        assert_eq!(
+            queue.get_operation_status(next_operation_id),
+            SyncToMainOperationStatus::Pending
+        );
+    }
+
+    #[test]
+    fn test_operation_dropped_because_of_capacity_is_reported() {
+        let mut queue = SyncToMainNodeQueue::new_with_capacity(1);
+
+        let operation_id = queue.update("table", "pk", || ["rk"].into_iter(), &rows_last_read());
+        let next_operation_id =
+            queue.update("table", "pk2", || ["rk"].into_iter(), &rows_last_read());
+
+        assert_eq!(queue.get_dropped_updates(), 1);
+        assert_eq!(
+            queue.get_operation_status(operation_id),
+            SyncToMainOperationStatus::Dropped
+        );
+        assert_eq!(
+            queue.get_operation_status(next_operation_id),
+            SyncToMainOperationStatus::Pending
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::{BTreeSet, HashMap};
+
+// Max amount of dropped operations remembered to answer the status requests
+const MAX_DROPPED_OPERATIONS_TO_KEEP: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncToMainOperationStatus {
+    Pending,
+    Confirmed,
+    // Part of the operation was dropped because the queue was full. It is never delivered
+    Dropped,
+}
+
+// Operation is confirmed when every queued event which carries its updates is confirmed by the main node
+pub struct SyncToMainOperations {
+    last_operation_id: i64,
+    // Amount of not confirmed events which carry the operation
+    pending: HashMap<i64, usize>,
+    dropped: BTreeSet<i64>,
+}
+
+impl SyncToMainOperations {
+    pub fn new() -> Self {
+        Self {
+            last_operation_id: 0,
+            pending: HashMap::new(),
+            dropped: BTreeSet::new(),
+        }
+    }
+
+    pub fn register(&mut self) -> i64 {
+        self.last_operation_id += 1;
+        self.last_operation_id
+    }
+
+    pub fn add_to_event(&mut self, event_operations: &mut BTreeSet<i64>, operation_id: i64) {
+        if event_operations.insert(operation_id) {
+            *self.pending.entry(operation_id).or_insert(0) += 1;
+        }
+    }
+
+    // Event is confirmed by the main node or its rows were moved to the other events
+    pub fn event_released(&mut self, event_operations: &BTreeSet<i64>) {
+        for operation_id in event_operations {
+            if let Some(events) = self.pending.get_mut(operation_id) {
+                *events -= 1;
+
+                if *events == 0 {
+                    self.pending.remove(operation_id);
+                }
+            }
+        }
+    }
+
+    pub fn event_dropped(&mut self, event_operations: &BTreeSet<i64>) {
+        self.event_released(event_operations);
+
+        for operation_id in event_operations {
+            self.dropped.insert(*operation_id);
+        }
+
+        while self.dropped.len() > MAX_DROPPED_OPERATIONS_TO_KEEP {
+            self.dropped.pop_first();
+        }
+    }
+
+    pub fn get_status(&self, operation_id: i64) -> SyncToMainOperationStatus {
+        if self.dropped.contains(&operation_id) {
+            return SyncToMainOperationStatus::Dropped;
+        }
+
+        if operation_id > self.last_operation_id || self.pending.contains_key(&operation_id) {
+            return SyncToMainOperationStatus::Pending;
+        }
+
+        SyncToMainOperationStatus::Confirmed
+    }
+}
+
+impl Default for SyncToMainOperations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,53 +1,105 @@
-use rust_extensions::auto_shrink::VecDequeAutoShrink;
 use rust_extensions::date_time::DateTimeAsMicroseconds;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{IndexedEventsQueue, SyncToMainOperations};
 
 #[derive(Debug, Clone)]
 pub struct UpdatePartitionExpirationEvent {
     pub table_name: String,
     pub partitions: BTreeMap<String, Option<DateTimeAsMicroseconds>>,
+    pub operations: BTreeSet<i64>,
 }
 
 pub struct UpdatePartitionsExpirationTimeQueue {
-    queue: VecDequeAutoShrink<UpdatePartitionExpirationEvent>,
+    queue: IndexedEventsQueue<String, UpdatePartitionExpirationEvent>,
+    partitions: usize,
+    capacity: usize,
+    dropped: usize,
 }
 
 impl UpdatePartitionsExpirationTimeQueue {
-    pub fn new() -> Self {
+    pub fn new(capacity: usize) -> Self {
         Self {
-            queue: VecDequeAutoShrink::new(32),
+            queue: IndexedEventsQueue::new(),
+            partitions: 0,
+            capacity,
+            dropped: 0,
         }
     }
 
     pub fn add(
         &mut self,
+        operations: &mut SyncToMainOperations,
+        operation_id: i64,
         table_name: &str,
         partition_key: &str,
         date_time: Option<DateTimeAsMicroseconds>,
     ) {
-        if let Some(item) = self
-            .queue
-            .iter_mut()
-            .find(|itm| itm.table_name == table_name)
+        let key = table_name.to_string();
+
+        let id = match self.queue.get_id(&key) {
+            Some(id) => id,
+            None => self.queue.push_back(
+                key,
+                UpdatePartitionExpirationEvent {
+                    table_name: table_name.to_string(),
+                    partitions: BTreeMap::new(),
+                    operations: BTreeSet::new(),
+                },
+            ),
+        };
+
+        let item = self.queue.get_mut(id).unwrap();
+        operations.add_to_event(&mut item.operations, operation_id);
+
+        if item
+            .partitions
+            .insert(partition_key.to_string(), date_time)
+            .is_none()
         {
-            item.partitions.insert(partition_key.to_string(), date_time);
-            return;
+            self.partitions += 1;
         }
 
-        let mut partitions = BTreeMap::new();
-        partitions.insert(partition_key.to_string(), date_time);
-
-        self.queue.push_back(UpdatePartitionExpirationEvent {
-            table_name: table_name.to_string(),
-            partitions,
-        });
+        while self.partitions > self.capacity {
+            match self.queue.pop_front() {
+                Some((_, event)) => {
+                    // Partitions keep the expiration they had before the update
+                    println!(
+                        "Sync to main node queue is full. Expiration update of {} partitions of table {} is dropped",
+                        event.partitions.len(),
+                        event.table_name
+                    );
+                    self.partitions -= event.partitions.len();
+                    self.dropped += event.partitions.len();
+                    operations.event_dropped(&event.operations);
+                }
+                None => break,
+            }
+        }
     }
 
     pub fn return_event(&mut self, event: UpdatePartitionExpirationEvent) {
-        self.queue.push_back(event);
+        self.partitions += event.partitions.len();
+        self.queue.push_front(event.table_name.clone(), event);
     }
 
     pub fn dequeue(&mut self) -> Option<UpdatePartitionExpirationEvent> {
-        self.queue.pop_front()
+        let (_, event) = self.queue.pop_front()?;
+        self.partitions -= event.partitions.len();
+        Some(event)
+    }
+
+    // Amount of partitions waiting to be delivered
+    pub fn len(&self) -> usize {
+        self.partitions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.partitions == 0
+    }
+
+    // Amount of partitions dropped because the queue was full
+    pub fn get_dropped(&self) -> usize {
+        self.dropped
     }
 }
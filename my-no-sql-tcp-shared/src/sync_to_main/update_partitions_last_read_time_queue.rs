@@ -1,74 +1,130 @@
-use rust_extensions::auto_shrink::VecDequeAutoShrink;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{IndexedEventsQueue, SyncToMainOperations};
 
 #[derive(Clone, Debug)]
 pub struct UpdatePartitionsLastReadTimeEvent {
     pub table_name: String,
     pub partitions: BTreeMap<String, ()>,
+    pub operations: BTreeSet<i64>,
 }
 
 pub struct UpdatePartitionsLastReadTimeQueue {
-    queue: VecDequeAutoShrink<UpdatePartitionsLastReadTimeEvent>,
+    queue: IndexedEventsQueue<String, UpdatePartitionsLastReadTimeEvent>,
+    partitions: usize,
+    capacity: usize,
+    dropped: usize,
 }
 
 impl UpdatePartitionsLastReadTimeQueue {
-    pub fn new() -> Self {
+    pub fn new(capacity: usize) -> Self {
         Self {
-            queue: VecDequeAutoShrink::new(32),
+            queue: IndexedEventsQueue::new(),
+            partitions: 0,
+            capacity,
+            dropped: 0,
         }
     }
 
     pub fn add<'s, TPartitions: Iterator<Item = &'s String>>(
         &mut self,
+        operations: &mut SyncToMainOperations,
+        operation_id: i64,
         table_name: &str,
         partition_keys: TPartitions,
     ) {
-        if let Some(item) = self
-            .queue
-            .iter_mut()
-            .find(|itm| itm.table_name == table_name)
-        {
-            for partition_key in partition_keys {
-                item.partitions.insert(partition_key.to_string(), ());
-            }
-            return;
-        }
+        let item = self.get_event(table_name);
+        operations.add_to_event(&mut item.operations, operation_id);
 
-        let mut partitions = BTreeMap::new();
+        let mut added = 0;
         for partition_key in partition_keys {
-            partitions.insert(partition_key.to_string(), ());
+            if item
+                .partitions
+                .insert(partition_key.to_string(), ())
+                .is_none()
+            {
+                added += 1;
+            }
         }
 
-        self.queue.push_back(UpdatePartitionsLastReadTimeEvent {
-            table_name: table_name.to_string(),
-            partitions,
-        });
+        self.partitions += added;
+        self.drop_oldest_events_over_capacity(operations);
     }
 
-    pub fn add_partition(&mut self, table_name: &str, partition_key: &str) {
-        if let Some(item) = self
-            .queue
-            .iter_mut()
-            .find(|itm| itm.table_name == table_name)
+    pub fn add_partition(
+        &mut self,
+        operations: &mut SyncToMainOperations,
+        operation_id: i64,
+        table_name: &str,
+        partition_key: &str,
+    ) {
+        let item = self.get_event(table_name);
+        operations.add_to_event(&mut item.operations, operation_id);
+
+        if item
+            .partitions
+            .insert(partition_key.to_string(), ())
+            .is_none()
         {
-            item.partitions.insert(partition_key.to_string(), ());
+            self.partitions += 1;
         }
 
-        let mut partitions = BTreeMap::new();
+        self.drop_oldest_events_over_capacity(operations);
+    }
+
+    fn get_event(&mut self, table_name: &str) -> &mut UpdatePartitionsLastReadTimeEvent {
+        let key = table_name.to_string();
 
-        partitions.insert(partition_key.to_string(), ());
+        let id = match self.queue.get_id(&key) {
+            Some(id) => id,
+            None => self.queue.push_back(
+                key,
+                UpdatePartitionsLastReadTimeEvent {
+                    table_name: table_name.to_string(),
+                    partitions: BTreeMap::new(),
+                    operations: BTreeSet::new(),
+                },
+            ),
+        };
 
-        self.queue.push_back(UpdatePartitionsLastReadTimeEvent {
-            table_name: table_name.to_string(),
-            partitions,
-        });
+        self.queue.get_mut(id).unwrap()
+    }
+
+    fn drop_oldest_events_over_capacity(&mut self, operations: &mut SyncToMainOperations) {
+        while self.partitions > self.capacity {
+            match self.queue.pop_front() {
+                Some((_, event)) => {
+                    self.partitions -= event.partitions.len();
+                    self.dropped += event.partitions.len();
+                    operations.event_dropped(&event.operations);
+                }
+                None => break,
+            }
+        }
     }
 
     pub fn return_event(&mut self, event: UpdatePartitionsLastReadTimeEvent) {
-        self.queue.push_back(event);
+        self.partitions += event.partitions.len();
+        self.queue.push_front(event.table_name.clone(), event);
     }
 
     pub fn dequeue(&mut self) -> Option<UpdatePartitionsLastReadTimeEvent> {
-        self.queue.pop_front()
+        let (_, event) = self.queue.pop_front()?;
+        self.partitions -= event.partitions.len();
+        Some(event)
+    }
+
+    // Amount of partitions waiting to be delivered
+    pub fn len(&self) -> usize {
+        self.partitions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.partitions == 0
+    }
+
+    // Amount of partitions dropped because the queue was full
+    pub fn get_dropped(&self) -> usize {
+        self.dropped
     }
 }
@@ -1,60 +1,339 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
-use rust_extensions::auto_shrink::VecDequeAutoShrink;
 use rust_extensions::date_time::DateTimeAsMicroseconds;
 
+use super::{IndexedEventsQueue, SyncToMainOperations};
+
 #[derive(Debug, Clone)]
 pub struct UpdateRowsExpirationTimeEvent {
     pub table_name: String,
     pub partition_key: String,
     pub row_keys: BTreeMap<String, ()>,
     pub expiration_time: Option<DateTimeAsMicroseconds>,
+    pub operations: BTreeSet<i64>,
 }
 
+type RowsExpirationKey = (String, String, Option<i64>);
+
 pub struct UpdateRowsExpirationTimeQueue {
-    queue: VecDequeAutoShrink<UpdateRowsExpirationTimeEvent>,
+    queue: IndexedEventsQueue<RowsExpirationKey, UpdateRowsExpirationTimeEvent>,
+    // Id of the queued event each row is in. Key is (table_name, partition_key)
+    rows_events: HashMap<(String, String), HashMap<String, i64>>,
+    rows: usize,
+    capacity: usize,
+    dropped: usize,
 }
 
 impl UpdateRowsExpirationTimeQueue {
-    pub fn new() -> Self {
+    pub fn new(capacity: usize) -> Self {
         Self {
-            queue: VecDequeAutoShrink::new(32),
+            queue: IndexedEventsQueue::new(),
+            rows_events: HashMap::new(),
+            rows: 0,
+            capacity,
+            dropped: 0,
         }
     }
 
     pub fn add<'s, TRowKeys: Iterator<Item = &'s str>>(
         &mut self,
+        operations: &mut SyncToMainOperations,
+        operation_id: i64,
         table_name: &str,
         partition_key: &str,
         row_keys: TRowKeys,
         date_time: Option<DateTimeAsMicroseconds>,
     ) {
-        if let Some(item) = self
-            .queue
-            .iter_mut()
-            .find(|itm| itm.table_name == table_name && itm.partition_key == partition_key)
-        {
-            for row_key in row_keys {
-                item.row_keys.insert(row_key.to_string(), ());
+        let key = (
+            table_name.to_string(),
+            partition_key.to_string(),
+            date_time.map(|itm| itm.unix_microseconds),
+        );
+
+        let id = match self.queue.get_id(&key) {
+            Some(id) => id,
+            None => self.queue.push_back(
+                key,
+                UpdateRowsExpirationTimeEvent {
+                    table_name: table_name.to_string(),
+                    partition_key: partition_key.to_string(),
+                    row_keys: BTreeMap::new(),
+                    expiration_time: date_time,
+                    operations: BTreeSet::new(),
+                },
+            ),
+        };
+
+        let rows_events = self
+            .rows_events
+            .entry((table_name.to_string(), partition_key.to_string()))
+            .or_default();
+
+        // Operations of the events the rows are taken from wait for the event the rows are moved to
+        let mut carried_operations = BTreeSet::new();
+
+        for row_key in row_keys {
+            if let Some(prev_id) = rows_events.insert(row_key.to_string(), id) {
+                // The latest expiration wins. Row is taken away from the event with other expiration
+                if prev_id != id {
+                    if let Some(prev_event) = self.queue.get_mut(prev_id) {
+                        if prev_event.row_keys.remove(row_key).is_some() {
+                            self.rows -= 1;
+                        }
+
+                        carried_operations.extend(prev_event.operations.iter().copied());
+
+                        if prev_event.row_keys.is_empty() {
+                            let prev_event = self.queue.remove(prev_id).unwrap();
+                            operations.event_released(&prev_event.operations);
+                        }
+                    }
+                }
             }
 
-            return;
+            let item = self.queue.get_mut(id).unwrap();
+            if item.row_keys.insert(row_key.to_string(), ()).is_none() {
+                self.rows += 1;
+            }
         }
 
-        let item = UpdateRowsExpirationTimeEvent {
-            table_name: table_name.to_string(),
-            partition_key: partition_key.to_string(),
-            row_keys: row_keys.map(|k| (k.to_string(), ())).collect(),
-            expiration_time: date_time,
-        };
+        let item = self.queue.get_mut(id).unwrap();
+
+        operations.add_to_event(&mut item.operations, operation_id);
+        for carried_operation_id in carried_operations {
+            operations.add_to_event(&mut item.operations, carried_operation_id);
+        }
+
+        if item.row_keys.is_empty() {
+            let event = self.queue.remove(id).unwrap();
+            self.remove_rows(id, &event);
+            operations.event_released(&event.operations);
+        }
+
+        self.drop_oldest_events_over_capacity(operations);
+    }
 
-        self.queue.push_back(item);
+    fn drop_oldest_events_over_capacity(&mut self, operations: &mut SyncToMainOperations) {
+        while self.rows > self.capacity {
+            match self.queue.pop_front() {
+                Some((id, event)) => {
+                    // Rows keep the expiration they had before the update
+                    println!(
+                        "Sync to main node queue is full. Expiration update of {} rows of table {} partition {} is dropped",
+                        event.row_keys.len(),
+                        event.table_name,
+                        event.partition_key
+                    );
+                    self.remove_rows(id, &event);
+                    self.dropped += event.row_keys.len();
+                    operations.event_dropped(&event.operations);
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn remove_rows(&mut self, id: i64, event: &UpdateRowsExpirationTimeEvent) {
+        self.rows -= event.row_keys.len();
+
+        let key = (event.table_name.clone(), event.partition_key.clone());
+
+        if let Some(rows_events) = self.rows_events.get_mut(&key) {
+            for row_key in event.row_keys.keys() {
+                if rows_events.get(row_key) == Some(&id) {
+                    rows_events.remove(row_key);
+                }
+            }
+
+            if rows_events.is_empty() {
+                self.rows_events.remove(&key);
+            }
+        }
     }
 
     pub fn return_event(&mut self, event: UpdateRowsExpirationTimeEvent) {
-        self.queue.push_back(event);
+        self.rows += event.row_keys.len();
+
+        let key = (
+            event.table_name.clone(),
+            event.partition_key.clone(),
+            event.expiration_time.map(|itm| itm.unix_microseconds),
+        );
+
+        let rows_key = (event.table_name.clone(), event.partition_key.clone());
+        let row_keys: Vec<String> = event.row_keys.keys().cloned().collect();
+
+        let id = self.queue.push_front(key, event);
+
+        // Rows which got a newer expiration while the event was on delivery stay with the newer event
+        let rows_events = self.rows_events.entry(rows_key).or_default();
+        for row_key in row_keys {
+            rows_events.entry(row_key).or_insert(id);
+        }
     }
+
     pub fn dequeue(&mut self) -> Option<UpdateRowsExpirationTimeEvent> {
-        self.queue.pop_front()
+        let (id, event) = self.queue.pop_front()?;
+        self.remove_rows(id, &event);
+        Some(event)
+    }
+
+    // Amount of row keys waiting to be delivered
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    // Amount of row keys dropped because the queue was full
+    pub fn get_dropped(&self) -> usize {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+    use super::{
+        super::{SyncToMainOperationStatus, SyncToMainOperations},
+        UpdateRowsExpirationTimeQueue,
+    };
+
+    #[test]
+    fn test_repeated_updates_of_one_row_are_collapsed() {
+        let mut operations = SyncToMainOperations::new();
+        let mut queue = UpdateRowsExpirationTimeQueue::new(16);
+
+        for i in 0..100 {
+            let operation_id = operations.register();
+            queue.add(
+                &mut operations,
+                operation_id,
+                "table",
+                "pk",
+                ["rk"].into_iter(),
+                Some(DateTimeAsMicroseconds::new(i)),
+            );
+        }
+
+        let event = queue.dequeue().unwrap();
+
+        assert_eq!(event.row_keys.len(), 1);
+        assert_eq!(event.expiration_time.unwrap().unix_microseconds, 99);
+        assert!(queue.dequeue().is_none());
+    }
+
+    #[test]
+    fn test_oldest_event_is_dropped_when_capacity_is_reached() {
+        let mut operations = SyncToMainOperations::new();
+        let mut queue = UpdateRowsExpirationTimeQueue::new(2);
+
+        for partition_key in ["pk1", "pk2", "pk3"] {
+            let operation_id = operations.register();
+            queue.add(
+                &mut operations,
+                operation_id,
+                "table",
+                partition_key,
+                ["rk"].into_iter(),
+                None,
+            );
+        }
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.get_dropped(), 1);
+        assert_eq!(operations.get_status(1), SyncToMainOperationStatus::Dropped);
+        assert_eq!(operations.get_status(2), SyncToMainOperationStatus::Pending);
+        assert_eq!(queue.dequeue().unwrap().partition_key, "pk2");
+    }
+
+    #[test]
+    fn test_event_emptied_by_newer_expiration_is_not_counted() {
+        let mut operations = SyncToMainOperations::new();
+        let mut queue = UpdateRowsExpirationTimeQueue::new(16);
+
+        let operation_id = operations.register();
+        queue.add(
+            &mut operations,
+            operation_id,
+            "table",
+            "pk",
+            ["rk1", "rk2"].into_iter(),
+            Some(DateTimeAsMicroseconds::new(1)),
+        );
+        let operation_id = operations.register();
+        queue.add(
+            &mut operations,
+            operation_id,
+            "table",
+            "pk",
+            ["rk1", "rk2"].into_iter(),
+            Some(DateTimeAsMicroseconds::new(2)),
+        );
+
+        assert_eq!(queue.len(), 2);
+
+        let event = queue.dequeue().unwrap();
+        assert_eq!(event.expiration_time.unwrap().unix_microseconds, 2);
+        assert_eq!(event.row_keys.len(), 2);
+
+        // First operation is confirmed together with the event which took its rows
+        assert_eq!(event.operations.iter().copied().collect::<Vec<_>>(), [1, 2]);
+
+        assert_eq!(queue.len(), 0);
+        assert!(queue.dequeue().is_none());
+    }
+
+    #[test]
+    fn test_returned_event_does_not_override_newer_expiration() {
+        let mut operations = SyncToMainOperations::new();
+        let mut queue = UpdateRowsExpirationTimeQueue::new(16);
+
+        let operation_id = operations.register();
+        queue.add(
+            &mut operations,
+            operation_id,
+            "table",
+            "pk",
+            ["rk"].into_iter(),
+            Some(DateTimeAsMicroseconds::new(1)),
+        );
+
+        let on_delivery = queue.dequeue().unwrap();
+
+        let operation_id = operations.register();
+        queue.add(
+            &mut operations,
+            operation_id,
+            "table",
+            "pk",
+            ["rk"].into_iter(),
+            Some(DateTimeAsMicroseconds::new(2)),
+        );
+
+        queue.return_event(on_delivery);
+
+        assert_eq!(
+            queue
+                .dequeue()
+                .unwrap()
+                .expiration_time
+                .unwrap()
+                .unix_microseconds,
+            1
+        );
+        assert_eq!(
+            queue
+                .dequeue()
+                .unwrap()
+                .expiration_time
+                .unwrap()
+                .unix_microseconds,
+            2
+        );
+        assert_eq!(queue.len(), 0);
     }
 }
@@ -1,9 +1,13 @@
-use std::collections::VecDeque;
+use std::collections::BTreeSet;
+
+use super::{IndexedEventsQueue, SyncToMainOperations};
+
 #[derive(Debug, Clone)]
 pub struct UpdateRowsLastReadTimeEvent {
     pub table_name: String,
     pub partition_key: String,
     pub row_keys: Vec<String>,
+    pub operations: BTreeSet<i64>,
 }
 
 impl UpdateRowsLastReadTimeEvent {
@@ -19,51 +23,159 @@ impl UpdateRowsLastReadTimeEvent {
 }
 
 pub struct UpdateRowsLastReadTimeQueue {
-    queue: VecDeque<UpdateRowsLastReadTimeEvent>,
+    queue: IndexedEventsQueue<(String, String), UpdateRowsLastReadTimeEvent>,
+    rows: usize,
+    capacity: usize,
+    dropped: usize,
 }
 
 impl UpdateRowsLastReadTimeQueue {
-    pub fn new() -> Self {
+    pub fn new(capacity: usize) -> Self {
         Self {
-            queue: VecDeque::new(),
+            queue: IndexedEventsQueue::new(),
+            rows: 0,
+            capacity,
+            dropped: 0,
         }
     }
 
     pub fn add<'s, TRowKeys: Iterator<Item = &'s str>>(
         &mut self,
+        operations: &mut SyncToMainOperations,
+        operation_id: i64,
         table_name: &str,
         partition_key: &str,
         row_keys: TRowKeys,
     ) {
-        if let Some(item) = self
-            .queue
-            .iter_mut()
-            .find(|itm| itm.table_name == table_name && itm.partition_key == partition_key)
-        {
-            for row_key in row_keys {
-                item.row_keys.push(row_key.to_string());
-            }
-            return;
-        }
+        let key = (table_name.to_string(), partition_key.to_string());
 
-        let mut item = UpdateRowsLastReadTimeEvent {
-            table_name: table_name.to_string(),
-            partition_key: partition_key.to_string(),
-            row_keys: Vec::new(),
+        let id = match self.queue.get_id(&key) {
+            Some(id) => id,
+            None => self.queue.push_back(
+                key,
+                UpdateRowsLastReadTimeEvent {
+                    table_name: table_name.to_string(),
+                    partition_key: partition_key.to_string(),
+                    row_keys: Vec::new(),
+                    operations: BTreeSet::new(),
+                },
+            ),
         };
 
+        let item = self.queue.get_mut(id).unwrap();
+        let rows_before = item.row_keys.len();
+
         for row_key in row_keys {
             item.insert_row_key(row_key);
         }
 
-        self.queue.push_back(item);
+        self.rows += item.row_keys.len() - rows_before;
+        operations.add_to_event(&mut item.operations, operation_id);
+
+        self.drop_oldest_events_over_capacity(operations);
+    }
+
+    fn drop_oldest_events_over_capacity(&mut self, operations: &mut SyncToMainOperations) {
+        while self.rows > self.capacity {
+            match self.queue.pop_front() {
+                Some((_, event)) => {
+                    self.rows -= event.row_keys.len();
+                    self.dropped += event.row_keys.len();
+                    operations.event_dropped(&event.operations);
+                }
+                None => break,
+            }
+        }
     }
 
     pub fn return_event(&mut self, event: UpdateRowsLastReadTimeEvent) {
-        self.queue.push_back(event);
+        self.rows += event.row_keys.len();
+        let key = (event.table_name.clone(), event.partition_key.clone());
+        self.queue.push_front(key, event);
     }
 
     pub fn dequeue(&mut self) -> Option<UpdateRowsLastReadTimeEvent> {
-        self.queue.pop_front()
+        let (_, event) = self.queue.pop_front()?;
+        self.rows -= event.row_keys.len();
+        Some(event)
+    }
+
+    // Amount of row keys waiting to be delivered
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    // Amount of row keys dropped because the queue was full
+    pub fn get_dropped(&self) -> usize {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{SyncToMainOperationStatus, SyncToMainOperations},
+        UpdateRowsLastReadTimeQueue,
+    };
+
+    #[test]
+    fn test_repeated_updates_of_one_row_are_collapsed() {
+        let mut operations = SyncToMainOperations::new();
+        let mut queue = UpdateRowsLastReadTimeQueue::new(16);
+
+        for _ in 0..100 {
+            let operation_id = operations.register();
+            queue.add(
+                &mut operations,
+                operation_id,
+                "table",
+                "pk",
+                ["rk"].into_iter(),
+            );
+        }
+
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.dequeue().unwrap().row_keys, vec!["rk".to_string()]);
+    }
+
+    #[test]
+    fn test_capacity_is_counted_in_row_keys() {
+        let mut operations = SyncToMainOperations::new();
+        let mut queue = UpdateRowsLastReadTimeQueue::new(3);
+
+        let first_operation_id = operations.register();
+        queue.add(
+            &mut operations,
+            first_operation_id,
+            "table",
+            "pk1",
+            ["rk1", "rk2"].into_iter(),
+        );
+
+        let second_operation_id = operations.register();
+        queue.add(
+            &mut operations,
+            second_operation_id,
+            "table",
+            "pk2",
+            ["rk1", "rk2"].into_iter(),
+        );
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.get_dropped(), 2);
+        assert_eq!(
+            operations.get_status(first_operation_id),
+            SyncToMainOperationStatus::Dropped
+        );
+        assert_eq!(
+            operations.get_status(second_operation_id),
+            SyncToMainOperationStatus::Pending
+        );
+        assert_eq!(queue.dequeue().unwrap().partition_key, "pk2");
+        assert!(queue.dequeue().is_none());
     }
 }
@@ -72,6 +72,10 @@ pub enum MyNoSqlTcpContract {
     Confirmation {
         confirmation_id: i64,
     },
+    ExpireRows {
+        table_name: String,
+        rows: Vec<DeleteRowTcpContract>,
+    },
 }
 
 impl MyNoSqlTcpContract {
@@ -312,6 +316,22 @@ impl MyNoSqlTcpContract {
                 let confirmation_id = socket_reader.read_i64().await?;
                 Ok(Self::Confirmation { confirmation_id })
             }
+            EXPIRE_ROWS => {
+                let _protocol_version = socket_reader.read_byte().await?;
+                let table_name =
+                    crate::common_deserializes::read_pascal_string(socket_reader).await?;
+
+                let rows_amount = socket_reader.read_i32().await?;
+
+                let mut rows = Vec::new();
+
+                for _ in 0..rows_amount {
+                    let row = DeleteRowTcpContract::deserialize(socket_reader).await?;
+                    rows.push(row);
+                }
+
+                Ok(Self::ExpireRows { table_name, rows })
+            }
             _ => Err(ReadingTcpContractFail::InvalidPacketId(packet_no)),
         };
 
@@ -484,6 +504,17 @@ impl MyNoSqlTcpContract {
                 write_buffer.write_byte(0); // Protocol version
                 write_buffer.write_i64(*confirmation_id);
             }
+
+            Self::ExpireRows { table_name, rows } => {
+                write_buffer.write_byte(EXPIRE_ROWS);
+                write_buffer.write_byte(0); // Protocol version
+                write_buffer.write_pascal_string(table_name);
+                write_buffer.write_i32(rows.len() as i32);
+
+                for row in rows {
+                    row.serialize(write_buffer);
+                }
+            }
         }
     }
 }
@@ -496,3 +527,47 @@ impl my_tcp_sockets::TcpContract for MyNoSqlTcpContract {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use my_tcp_sockets::socket_reader::SocketReaderInMem;
+
+    use super::MyNoSqlTcpContract;
+    use crate::DeleteRowTcpContract;
+
+    #[tokio::test]
+    async fn test_expire_rows_round_trip() {
+        let contract = MyNoSqlTcpContract::ExpireRows {
+            table_name: "test-table".to_string(),
+            rows: vec![
+                DeleteRowTcpContract {
+                    partition_key: "pk".to_string(),
+                    row_key: "rk1".to_string(),
+                },
+                DeleteRowTcpContract {
+                    partition_key: "pk".to_string(),
+                    row_key: "rk2".to_string(),
+                },
+            ],
+        };
+
+        let mut payload = Vec::new();
+        contract.serialize(&mut payload);
+
+        let mut reader = SocketReaderInMem::new(payload);
+
+        match MyNoSqlTcpContract::deserialize(&mut reader).await.unwrap() {
+            MyNoSqlTcpContract::ExpireRows { table_name, rows } => {
+                assert_eq!(table_name, "test-table");
+
+                let rows: Vec<(&str, &str)> = rows
+                    .iter()
+                    .map(|row| (row.partition_key.as_str(), row.row_key.as_str()))
+                    .collect();
+
+                assert_eq!(rows, vec![("pk", "rk1"), ("pk", "rk2")]);
+            }
+            other => panic!("Unexpected contract {:?}", other),
+        }
+    }
+}
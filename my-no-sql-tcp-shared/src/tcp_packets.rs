@@ -17,3 +17,4 @@ pub const UPDATE_ROWS_LAST_READ_TIME: u8 = 15;
 pub const UPDATE_PARTITIONS_EXPIRATION_TIME: u8 = 16;
 pub const UPDATE_ROWS_EXPIRATION_TIME: u8 = 17;
 pub const CONFIRMATION: u8 = 18;
+pub const EXPIRE_ROWS: u8 = 19;